@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 use crate::pagination::Pagination;
 
@@ -7,6 +8,12 @@ pub struct Users {
     pub users: Vec<String>
 }
 
+#[derive(Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct UserMergeReport {
+    pub rewritten: BTreeMap<String, u64>,
+    pub dropped: BTreeMap<String, u64>
+}
+
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
 pub struct Range {
     pub min: Option<i64>,
@@ -23,6 +30,14 @@ pub struct GameData {
     pub length: Range
 }
 
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ExtensionTarget {
+    pub module: String,
+    pub version: Option<String>,
+    pub project: Option<String>,
+    pub release: Option<String>
+}
+
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct FileData {
     pub filename: String,
@@ -31,12 +46,55 @@ pub struct FileData {
     pub sha256: String,
     pub published_at: String,
     pub published_by: String,
-    pub requires: Option<String>
+    pub requires: Option<String>,
+    pub module_name: Option<String>,
+    pub sides: Vec<String>,
+    pub maps: Vec<String>,
+    pub extends: Option<ExtensionTarget>
+}
+
+#[derive(Debug, thiserror::Error, Eq, PartialEq)]
+#[error("release state {0} unknown")]
+pub struct ReleaseStateError(pub String);
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(try_from = "&str", rename_all = "kebab-case")]
+pub enum ReleaseState {
+    Stable,
+    PreRelease,
+    Yanked
+}
+
+impl ReleaseState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReleaseState::Stable => "stable",
+            ReleaseState::PreRelease => "pre-release",
+            ReleaseState::Yanked => "yanked"
+        }
+    }
+}
+
+impl TryFrom<&str> for ReleaseState {
+    type Error = ReleaseStateError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "stable" => Ok(ReleaseState::Stable),
+            "pre-release" => Ok(ReleaseState::PreRelease),
+            "yanked" => Ok(ReleaseState::Yanked),
+            _ => Err(ReleaseStateError(value.into()))
+        }
+    }
 }
 
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct ReleaseData {
     pub version: String,
+    pub changelog: Option<String>,
+    pub released_at: String,
+    pub state: ReleaseState,
+    pub yank_reason: Option<String>,
     pub files: Vec<FileData>
 }
 
@@ -53,7 +111,9 @@ pub struct PackageData {
 pub struct GalleryImage {
     pub id: i64,
     pub filename: String,
-    pub description: String
+    pub description: String,
+    pub width: Option<i64>,
+    pub height: Option<i64>
 }
 
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
@@ -85,10 +145,28 @@ pub struct ProjectSummary {
     pub game: GameData
 }
 
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct LengthBucket {
+    pub min: Option<u32>,
+    pub max: Option<u32>,
+    pub count: i64
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct FacetCounts {
+    pub tags: BTreeMap<String, i64>,
+    pub publishers: BTreeMap<String, i64>,
+    pub years: BTreeMap<String, i64>,
+    pub players: BTreeMap<u32, i64>,
+    pub lengths: Vec<LengthBucket>
+}
+
 #[derive(Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct Projects {
     pub projects: Vec<ProjectSummary>,
-    pub meta: Pagination
+    pub meta: Pagination,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub counts: Option<FacetCounts>
 }
 
 #[derive(Debug, Deserialize, Eq, PartialEq, Serialize)]
@@ -96,11 +174,37 @@ pub struct Publishers {
     pub publishers: Vec<String>
 }
 
+#[derive(Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct PublisherSummary {
+    pub name: String,
+    pub projects: i64
+}
+
+#[derive(Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct PublisherSummaries {
+    pub publishers: Vec<PublisherSummary>
+}
+
 #[derive(Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct Tags {
     pub tags: Vec<String>
 }
 
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SuggestKind {
+    #[default]
+    Title,
+    Publisher,
+    Tag,
+    Owner
+}
+
+#[derive(Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Suggestions {
+    pub suggestions: Vec<String>
+}
+
 #[derive(Debug, thiserror::Error, Eq, PartialEq)]
 #[error("flag tag {0} unknown")]
 pub struct FlagTagError(pub String);
@@ -139,6 +243,45 @@ pub struct FlagData {
     pub message: Option<String>
 }
 
+#[derive(Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct SharedFile {
+    pub project: String,
+    pub slug: String,
+    pub package: String,
+    pub version: String,
+    pub filename: String,
+    pub url: String,
+    pub size: i64,
+    pub published_at: String,
+    pub published_by: String
+}
+
+#[derive(Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct SharedFiles {
+    pub sha256: String,
+    pub files: Vec<SharedFile>
+}
+
+#[derive(Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct OrphanedObject {
+    pub url: String,
+    pub size: u64,
+    pub last_modified: String
+}
+
+#[derive(Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct OrphanedObjects {
+    pub dry_run: bool,
+    pub objects: Vec<OrphanedObject>
+}
+
+#[derive(Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct UploadStatus {
+    pub id: String,
+    pub offset: u64,
+    pub length: Option<u64>
+}
+
 #[derive(Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct Flags {
     pub flags: Vec<FlagData>
@@ -156,4 +299,19 @@ mod test {
         assert_eq!(FlagTag::try_from("other").unwrap(), FlagTag::Other);
         assert_eq!(FlagTag::try_from("bogus").unwrap_err(), FlagTagError("bogus".into()));
     }
+
+    #[test]
+    fn try_from_str_release_state() {
+        assert_eq!(ReleaseState::try_from("stable").unwrap(), ReleaseState::Stable);
+        assert_eq!(ReleaseState::try_from("pre-release").unwrap(), ReleaseState::PreRelease);
+        assert_eq!(ReleaseState::try_from("yanked").unwrap(), ReleaseState::Yanked);
+        assert_eq!(ReleaseState::try_from("bogus").unwrap_err(), ReleaseStateError("bogus".into()));
+    }
+
+    #[test]
+    fn release_state_as_str_round_trip() {
+        for s in [ReleaseState::Stable, ReleaseState::PreRelease, ReleaseState::Yanked] {
+            assert_eq!(ReleaseState::try_from(s.as_str()).unwrap(), s);
+        }
+    }
 }
@@ -185,7 +185,12 @@ pub enum SortBy {
     GameTitle,
     ModificationTime,
     CreationTime,
-    Relevance
+    Relevance,
+    Players,
+    // files are served straight from storage, so only downloads through
+    // the latest-file redirect are counted
+    LatestDownloads,
+    ReleaseTime
 }
 
 impl From<SortBy> for String {
@@ -204,6 +209,9 @@ impl TryFrom<&str> for SortBy {
             "m" => Ok(SortBy::ModificationTime),
             "c" => Ok(SortBy::CreationTime),
             "r" => Ok(SortBy::Relevance),
+            "n" => Ok(SortBy::Players),
+            "d" => Ok(SortBy::LatestDownloads),
+            "l" => Ok(SortBy::ReleaseTime),
             _ => Err(SortByError(value.into()))
         }
     }
@@ -216,7 +224,10 @@ impl fmt::Display for SortBy {
             SortBy::GameTitle => "t",
             SortBy::ModificationTime => "m",
             SortBy::CreationTime => "c",
-            SortBy::Relevance => "r"
+            SortBy::Relevance => "r",
+            SortBy::Players => "n",
+            SortBy::LatestDownloads => "d",
+            SortBy::ReleaseTime => "l"
         };
         write!(f, "{}", s)
     }
@@ -229,7 +240,10 @@ impl SortBy {
             SortBy::GameTitle => Direction::Ascending,
             SortBy::ModificationTime => Direction::Descending,
             SortBy::CreationTime => Direction::Descending,
-            SortBy::Relevance => Direction::Ascending
+            SortBy::Relevance => Direction::Ascending,
+            SortBy::Players => Direction::Descending,
+            SortBy::LatestDownloads => Direction::Descending,
+            SortBy::ReleaseTime => Direction::Descending
         }
     }
 }
@@ -246,7 +260,8 @@ pub enum Facet {
     LengthMax(u32),
     Tag(String),
     Owner(String),
-    Player(String)
+    Player(String),
+    Vassal(String)
 }
 
 impl fmt::Display for Facet {
@@ -262,7 +277,8 @@ impl fmt::Display for Facet {
             Facet::LengthMax(m) => write!(f, "length_max={m}"),
             Facet::Tag(t) => write!(f, "tag={}", Encoded(t)),
             Facet::Owner(o) => write!(f, "owner={}", Encoded(o)),
-            Facet::Player(p) => write!(f, "player={}", Encoded(p))
+            Facet::Player(p) => write!(f, "player={}", Encoded(p)),
+            Facet::Vassal(v) => write!(f, "vassal={}", Encoded(v))
         }
     }
 }
@@ -428,6 +444,9 @@ mod test {
         assert_sort_by_round_trip(SortBy::ModificationTime);
         assert_sort_by_round_trip(SortBy::CreationTime);
         assert_sort_by_round_trip(SortBy::Relevance);
+        assert_sort_by_round_trip(SortBy::Players);
+        assert_sort_by_round_trip(SortBy::LatestDownloads);
+        assert_sort_by_round_trip(SortBy::ReleaseTime);
     }
 
     #[test]
@@ -462,5 +481,17 @@ mod test {
             SortBy::Relevance.default_direction(),
             Direction::Ascending
         );
+        assert_eq!(
+            SortBy::Players.default_direction(),
+            Direction::Descending
+        );
+        assert_eq!(
+            SortBy::LatestDownloads.default_direction(),
+            Direction::Descending
+        );
+        assert_eq!(
+            SortBy::ReleaseTime.default_direction(),
+            Direction::Descending
+        );
     }
 }
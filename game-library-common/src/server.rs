@@ -1,7 +1,8 @@
 use axum::{
     Router,
     body::Body,
-    extract::{ConnectInfo, Request}
+    extract::{ConnectInfo, Request},
+    http::{Extensions, HeaderMap}
 };
 use std::net::{IpAddr, SocketAddr};
 use tokio::net::TcpListener;
@@ -15,17 +16,25 @@ use tracing_subscriber::{
     util::SubscriberInitExt
 };
 
-fn real_addr(request: &Request) -> String {
+pub fn client_addr(
+    headers: &HeaderMap,
+    extensions: &Extensions
+) -> Option<String>
+{
     // If we're behind a proxy, get IP from X-Forwarded-For header
-    match request.headers().get("x-forwarded-for") {
+    match headers.get("x-forwarded-for") {
         Some(addr) => addr.to_str()
             .map(String::from)
             .ok(),
-        None => request.extensions()
+        None => extensions
             .get::<ConnectInfo<SocketAddr>>()
             .map(|info| info.ip().to_string())
     }
-    .unwrap_or_else(|| "<unknown>".into())
+}
+
+fn real_addr(request: &Request) -> String {
+    client_addr(request.headers(), request.extensions())
+        .unwrap_or_else(|| "<unknown>".into())
 }
 
 #[derive(Clone, Debug)]
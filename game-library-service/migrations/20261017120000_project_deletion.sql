@@ -0,0 +1,138 @@
+/* Soft deletion of projects */
+
+ALTER TABLE projects_history ADD COLUMN deleted_at INTEGER;
+
+ALTER TABLE projects_history ADD COLUMN deleted_by INTEGER
+  REFERENCES users(user_id)
+  CHECK(
+    (deleted_at IS NULL AND deleted_by IS NULL) OR
+    (deleted_at >= created_at AND deleted_by IS NOT NULL)
+  );
+
+/* Deleted projects keep their projects row, but are removed from the
+   full-text index; the index must not be touched for them until they
+   are restored. */
+
+DROP TRIGGER IF EXISTS projects_ad;
+
+CREATE TRIGGER IF NOT EXISTS projects_ad AFTER DELETE ON projects
+WHEN NOT EXISTS (
+  SELECT 1
+  FROM projects_history
+  WHERE project_id = OLD.project_id
+    AND deleted_at IS NOT NULL
+)
+BEGIN
+  INSERT INTO projects_fts (
+    projects_fts,
+    rowid,
+    game_title,
+    game_publisher,
+    game_year,
+    description,
+    readme
+  )
+  VALUES (
+    'delete',
+    OLD.project_id,
+    OLD.game_title,
+    OLD.game_publisher,
+    OLD.game_year,
+    OLD.description,
+    OLD.readme
+  );
+END;
+
+DROP TRIGGER IF EXISTS projects_au;
+
+CREATE TRIGGER IF NOT EXISTS projects_au AFTER UPDATE ON projects
+WHEN NOT EXISTS (
+  SELECT 1
+  FROM projects_history
+  WHERE project_id = OLD.project_id
+    AND deleted_at IS NOT NULL
+)
+BEGIN
+  INSERT INTO projects_fts (
+    projects_fts,
+    rowid,
+    game_title,
+    game_publisher,
+    game_year,
+    description,
+    readme
+  )
+  VALUES (
+    'delete',
+    OLD.project_id,
+    OLD.game_title,
+    OLD.game_publisher,
+    OLD.game_year,
+    OLD.description,
+    OLD.readme
+  );
+  INSERT INTO projects_fts (
+    rowid,
+    game_title,
+    game_publisher,
+    game_year,
+    description,
+    readme
+  )
+  VALUES (
+    NEW.project_id,
+    NEW.game_title,
+    NEW.game_publisher,
+    NEW.game_year,
+    NEW.description,
+    NEW.readme
+  );
+END;
+
+CREATE TRIGGER IF NOT EXISTS projects_history_au_end
+AFTER UPDATE OF deleted_at ON projects_history
+WHEN OLD.deleted_at IS NULL AND NEW.deleted_at IS NOT NULL
+BEGIN
+  INSERT INTO projects_fts (
+    projects_fts,
+    rowid,
+    game_title,
+    game_publisher,
+    game_year,
+    description,
+    readme
+  )
+  SELECT
+    'delete',
+    project_id,
+    game_title,
+    game_publisher,
+    game_year,
+    description,
+    readme
+  FROM projects
+  WHERE project_id = OLD.project_id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS projects_history_au_restore
+AFTER UPDATE OF deleted_at ON projects_history
+WHEN OLD.deleted_at IS NOT NULL AND NEW.deleted_at IS NULL
+BEGIN
+  INSERT INTO projects_fts (
+    rowid,
+    game_title,
+    game_publisher,
+    game_year,
+    description,
+    readme
+  )
+  SELECT
+    project_id,
+    game_title,
+    game_publisher,
+    game_year,
+    description,
+    readme
+  FROM projects
+  WHERE project_id = OLD.project_id;
+END;
@@ -0,0 +1,9 @@
+/* Former slugs of renamed projects */
+
+CREATE TABLE IF NOT EXISTS projects_aliases (
+  slug TEXT NOT NULL,
+  project_id INTEGER NOT NULL,
+  created_at INTEGER NOT NULL,
+  FOREIGN KEY(project_id) REFERENCES projects_history(project_id),
+  UNIQUE(slug)
+);
@@ -0,0 +1,18 @@
+/* Metadata extracted from uploaded modules, extensions, saves, and logs */
+
+CREATE TABLE IF NOT EXISTS files_modules (
+  file_id INTEGER PRIMARY KEY NOT NULL,
+  name TEXT,
+  description TEXT,
+  date_saved INTEGER,
+  FOREIGN KEY(file_id) REFERENCES files(file_id)
+);
+
+CREATE TABLE IF NOT EXISTS files_modules_items (
+  file_id INTEGER NOT NULL,
+  kind TEXT NOT NULL CHECK(kind IN ('extension', 'palette', 'map', 'side')),
+  position INTEGER NOT NULL CHECK(position >= 0),
+  value TEXT NOT NULL,
+  FOREIGN KEY(file_id) REFERENCES files_modules(file_id),
+  UNIQUE(file_id, kind, position)
+);
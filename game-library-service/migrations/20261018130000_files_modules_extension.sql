@@ -0,0 +1,5 @@
+/* The module which an extension extends */
+
+ALTER TABLE files_modules ADD COLUMN target_module TEXT;
+
+ALTER TABLE files_modules ADD COLUMN target_module_version TEXT;
@@ -0,0 +1,11 @@
+/* Resized renditions of uploaded images */
+
+CREATE TABLE IF NOT EXISTS image_renditions (
+  project_id INTEGER NOT NULL,
+  filename TEXT NOT NULL,
+  published_at INTEGER NOT NULL,
+  size INTEGER NOT NULL CHECK(size > 0),
+  url TEXT NOT NULL,
+  FOREIGN KEY(project_id, filename, published_at) REFERENCES image_revisions(project_id, filename, published_at),
+  UNIQUE(project_id, filename, published_at, size)
+);
@@ -0,0 +1,36 @@
+/* Pixel dimensions of uploaded images */
+
+ALTER TABLE image_revisions ADD COLUMN width INTEGER CHECK(width > 0);
+
+ALTER TABLE image_revisions ADD COLUMN height INTEGER CHECK(height > 0);
+
+ALTER TABLE images ADD COLUMN width INTEGER CHECK(width > 0);
+
+ALTER TABLE images ADD COLUMN height INTEGER CHECK(height > 0);
+
+DROP TRIGGER IF EXISTS image_revisions_ai_start;
+
+CREATE TRIGGER IF NOT EXISTS image_revisions_ai_start
+AFTER INSERT ON image_revisions
+BEGIN
+  INSERT OR REPLACE INTO images (
+    project_id,
+    filename,
+    url,
+    content_type,
+    published_at,
+    published_by,
+    width,
+    height
+  )
+  VALUES (
+    NEW.project_id,
+    NEW.filename,
+    NEW.url,
+    NEW.content_type,
+    NEW.published_at,
+    NEW.published_by,
+    NEW.width,
+    NEW.height
+  );
+END;
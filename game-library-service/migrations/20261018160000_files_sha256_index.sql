@@ -0,0 +1,3 @@
+/* Look up files by content, to reuse identical uploads */
+
+CREATE INDEX IF NOT EXISTS files_sha256_size ON files(sha256, size);
@@ -0,0 +1,167 @@
+/* Soft deletion of files */
+
+CREATE TABLE IF NOT EXISTS files_history (
+  file_id INTEGER PRIMARY KEY NOT NULL CHECK(file_id >= 0),
+  release_id INTEGER NOT NULL,
+  url TEXT NOT NULL,
+  filename TEXT NOT NULL,
+  size INTEGER NOT NULL CHECK(size >= 0),
+  sha256 TEXT NOT NULL,
+  content_type TEXT NOT NULL,
+  requires TEXT,
+  published_at INTEGER NOT NULL,
+  published_by INTEGER NOT NULL,
+  deleted_at INTEGER,
+  deleted_by INTEGER,
+  FOREIGN KEY(release_id) REFERENCES releases_history(release_id),
+  FOREIGN KEY(published_by) REFERENCES users(user_id),
+  FOREIGN KEY(deleted_by) REFERENCES users(user_id),
+  CHECK(
+    (deleted_at IS NULL AND deleted_by IS NULL) OR
+    (deleted_at >= published_at AND deleted_by IS NOT NULL)
+  )
+);
+
+INSERT INTO files_history (
+  file_id,
+  release_id,
+  url,
+  filename,
+  size,
+  sha256,
+  content_type,
+  requires,
+  published_at,
+  published_by
+)
+SELECT
+  file_id,
+  release_id,
+  url,
+  filename,
+  size,
+  sha256,
+  content_type,
+  requires,
+  published_at,
+  published_by
+FROM files;
+
+/* Module metadata must outlive the files row, so files and files_modules
+   are rebuilt to reference files_history. Foreign keys cannot be altered
+   in place, and the existing rows are parked in temporary tables while
+   the tables are recreated. */
+
+CREATE TEMPORARY TABLE files_tmp AS SELECT * FROM files;
+
+CREATE TEMPORARY TABLE files_modules_tmp AS SELECT * FROM files_modules;
+
+CREATE TEMPORARY TABLE files_modules_items_tmp AS
+  SELECT * FROM files_modules_items;
+
+DROP TABLE files_modules_items;
+
+DROP TABLE files_modules;
+
+DROP TABLE files;
+
+CREATE TABLE IF NOT EXISTS files (
+  file_id INTEGER PRIMARY KEY NOT NULL,
+  release_id INTEGER NOT NULL,
+  url TEXT NOT NULL,
+  filename TEXT NOT NULL,
+  size INTEGER NOT NULL CHECK(size >= 0),
+  sha256 TEXT NOT NULL,
+  content_type TEXT NOT NULL,
+  requires TEXT,
+  published_at INTEGER NOT NULL,
+  published_by INTEGER NOT NULL,
+  UNIQUE(release_id, filename),
+  FOREIGN KEY(file_id) REFERENCES files_history(file_id),
+  FOREIGN KEY(release_id) REFERENCES releases(release_id),
+  FOREIGN KEY(published_by) REFERENCES users(user_id)
+);
+
+CREATE INDEX IF NOT EXISTS files_sha256_size ON files(sha256, size);
+
+CREATE TABLE IF NOT EXISTS files_modules (
+  file_id INTEGER PRIMARY KEY NOT NULL,
+  name TEXT,
+  description TEXT,
+  date_saved INTEGER,
+  target_module TEXT,
+  target_module_version TEXT,
+  FOREIGN KEY(file_id) REFERENCES files_history(file_id)
+);
+
+CREATE TABLE IF NOT EXISTS files_modules_items (
+  file_id INTEGER NOT NULL,
+  kind TEXT NOT NULL CHECK(kind IN ('extension', 'palette', 'map', 'side')),
+  position INTEGER NOT NULL CHECK(position >= 0),
+  value TEXT NOT NULL,
+  FOREIGN KEY(file_id) REFERENCES files_modules(file_id),
+  UNIQUE(file_id, kind, position)
+);
+
+INSERT INTO files SELECT * FROM files_tmp;
+
+INSERT INTO files_modules (
+  file_id,
+  name,
+  description,
+  date_saved,
+  target_module,
+  target_module_version
+)
+SELECT
+  file_id,
+  name,
+  description,
+  date_saved,
+  target_module,
+  target_module_version
+FROM files_modules_tmp;
+
+INSERT INTO files_modules_items SELECT * FROM files_modules_items_tmp;
+
+DROP TABLE files_tmp;
+
+DROP TABLE files_modules_tmp;
+
+DROP TABLE files_modules_items_tmp;
+
+CREATE TRIGGER IF NOT EXISTS files_history_ai_start
+AFTER INSERT ON files_history
+BEGIN
+  INSERT INTO files (
+    file_id,
+    release_id,
+    url,
+    filename,
+    size,
+    sha256,
+    content_type,
+    requires,
+    published_at,
+    published_by
+  )
+  VALUES (
+    NEW.file_id,
+    NEW.release_id,
+    NEW.url,
+    NEW.filename,
+    NEW.size,
+    NEW.sha256,
+    NEW.content_type,
+    NEW.requires,
+    NEW.published_at,
+    NEW.published_by
+  );
+END;
+
+CREATE TRIGGER IF NOT EXISTS files_history_au_end
+AFTER UPDATE OF deleted_at ON files_history
+BEGIN
+  DELETE FROM files
+  WHERE file_id = OLD.file_id;
+END;
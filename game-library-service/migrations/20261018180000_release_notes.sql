@@ -0,0 +1,81 @@
+/* Changelogs and release dates for releases */
+
+CREATE TABLE IF NOT EXISTS releases_revisions (
+  release_id INTEGER NOT NULL,
+  changelog TEXT,
+  released_at INTEGER NOT NULL,
+  modified_at INTEGER NOT NULL,
+  modified_by INTEGER NOT NULL,
+  FOREIGN KEY(release_id) REFERENCES releases_history(release_id),
+  FOREIGN KEY(modified_by) REFERENCES users(user_id)
+);
+
+ALTER TABLE releases ADD COLUMN changelog TEXT;
+
+ALTER TABLE releases ADD COLUMN released_at INTEGER;
+
+UPDATE releases SET released_at = published_at;
+
+INSERT INTO releases_revisions (
+  release_id,
+  changelog,
+  released_at,
+  modified_at,
+  modified_by
+)
+SELECT
+  release_id,
+  NULL,
+  published_at,
+  published_at,
+  published_by
+FROM releases_history;
+
+DROP TRIGGER IF EXISTS releases_history_ai_start;
+
+CREATE TRIGGER IF NOT EXISTS releases_history_ai_start
+AFTER INSERT ON releases_history
+BEGIN
+  INSERT INTO releases (
+    release_id,
+    package_id,
+    version,
+    version_major,
+    version_minor,
+    version_patch,
+    version_pre,
+    version_build,
+    published_at,
+    published_by,
+    changelog,
+    released_at
+  )
+  VALUES (
+    NEW.release_id,
+    NEW.package_id,
+    NEW.version,
+    NEW.version_major,
+    NEW.version_minor,
+    NEW.version_patch,
+    NEW.version_pre,
+    NEW.version_build,
+    NEW.published_at,
+    NEW.published_by,
+    NULL,
+    NEW.published_at
+  );
+  INSERT INTO releases_revisions (
+    release_id,
+    changelog,
+    released_at,
+    modified_at,
+    modified_by
+  )
+  VALUES (
+    NEW.release_id,
+    NULL,
+    NEW.published_at,
+    NEW.published_at,
+    NEW.published_by
+  );
+END;
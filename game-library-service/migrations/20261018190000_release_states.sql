@@ -0,0 +1,80 @@
+/* Stable, pre-release, and yanked states for releases */
+
+ALTER TABLE releases ADD COLUMN state TEXT NOT NULL DEFAULT 'stable'
+  CHECK(state IN ('stable', 'pre-release', 'yanked'));
+
+ALTER TABLE releases ADD COLUMN yank_reason TEXT
+  CHECK(yank_reason IS NULL OR state = 'yanked');
+
+UPDATE releases SET state = 'pre-release' WHERE version_pre != '';
+
+ALTER TABLE releases_revisions ADD COLUMN state TEXT NOT NULL DEFAULT 'stable'
+  CHECK(state IN ('stable', 'pre-release', 'yanked'));
+
+ALTER TABLE releases_revisions ADD COLUMN yank_reason TEXT
+  CHECK(yank_reason IS NULL OR state = 'yanked');
+
+UPDATE releases_revisions
+SET state = 'pre-release'
+WHERE release_id IN (
+  SELECT release_id
+  FROM releases_history
+  WHERE version_pre != ''
+);
+
+DROP TRIGGER IF EXISTS releases_history_ai_start;
+
+CREATE TRIGGER IF NOT EXISTS releases_history_ai_start
+AFTER INSERT ON releases_history
+BEGIN
+  INSERT INTO releases (
+    release_id,
+    package_id,
+    version,
+    version_major,
+    version_minor,
+    version_patch,
+    version_pre,
+    version_build,
+    published_at,
+    published_by,
+    changelog,
+    released_at,
+    state,
+    yank_reason
+  )
+  VALUES (
+    NEW.release_id,
+    NEW.package_id,
+    NEW.version,
+    NEW.version_major,
+    NEW.version_minor,
+    NEW.version_patch,
+    NEW.version_pre,
+    NEW.version_build,
+    NEW.published_at,
+    NEW.published_by,
+    NULL,
+    NEW.published_at,
+    CASE WHEN NEW.version_pre = '' THEN 'stable' ELSE 'pre-release' END,
+    NULL
+  );
+  INSERT INTO releases_revisions (
+    release_id,
+    changelog,
+    released_at,
+    state,
+    yank_reason,
+    modified_at,
+    modified_by
+  )
+  VALUES (
+    NEW.release_id,
+    NULL,
+    NEW.published_at,
+    CASE WHEN NEW.version_pre = '' THEN 'stable' ELSE 'pre-release' END,
+    NULL,
+    NEW.published_at,
+    NEW.published_by
+  );
+END;
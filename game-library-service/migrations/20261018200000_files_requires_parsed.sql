@@ -0,0 +1,90 @@
+/* Parsed minimum Vassal versions for files */
+
+ALTER TABLE files_history ADD COLUMN requires_major INTEGER;
+
+ALTER TABLE files_history ADD COLUMN requires_minor INTEGER;
+
+ALTER TABLE files_history ADD COLUMN requires_patch INTEGER;
+
+ALTER TABLE files ADD COLUMN requires_major INTEGER;
+
+ALTER TABLE files ADD COLUMN requires_minor INTEGER;
+
+ALTER TABLE files ADD COLUMN requires_patch INTEGER;
+
+/* Existing requirements all have the form ">= MAJOR.MINOR.PATCH" */
+
+CREATE TEMPORARY TABLE requires_tmp AS
+  SELECT
+    file_id,
+    substr(v, 1, instr(v, '.') - 1) AS major,
+    substr(r, 1, instr(r, '.') - 1) AS minor,
+    substr(r, instr(r, '.') + 1) AS patch
+  FROM (
+    SELECT
+      file_id,
+      v,
+      substr(v, instr(v, '.') + 1) AS r
+    FROM (
+      SELECT
+        file_id,
+        trim(substr(requires, 3)) AS v
+      FROM files_history
+      WHERE requires LIKE '>=%'
+    )
+  );
+
+UPDATE files_history
+SET
+  requires_major = CAST(requires_tmp.major AS INTEGER),
+  requires_minor = CAST(requires_tmp.minor AS INTEGER),
+  requires_patch = CAST(requires_tmp.patch AS INTEGER)
+FROM requires_tmp
+WHERE files_history.file_id = requires_tmp.file_id;
+
+UPDATE files
+SET
+  requires_major = files_history.requires_major,
+  requires_minor = files_history.requires_minor,
+  requires_patch = files_history.requires_patch
+FROM files_history
+WHERE files.file_id = files_history.file_id;
+
+DROP TABLE requires_tmp;
+
+DROP TRIGGER IF EXISTS files_history_ai_start;
+
+CREATE TRIGGER IF NOT EXISTS files_history_ai_start
+AFTER INSERT ON files_history
+BEGIN
+  INSERT INTO files (
+    file_id,
+    release_id,
+    url,
+    filename,
+    size,
+    sha256,
+    content_type,
+    requires,
+    published_at,
+    published_by,
+    requires_major,
+    requires_minor,
+    requires_patch
+  )
+  VALUES (
+    NEW.file_id,
+    NEW.release_id,
+    NEW.url,
+    NEW.filename,
+    NEW.size,
+    NEW.sha256,
+    NEW.content_type,
+    NEW.requires,
+    NEW.published_at,
+    NEW.published_by,
+    NEW.requires_major,
+    NEW.requires_minor,
+    NEW.requires_patch
+  );
+END;
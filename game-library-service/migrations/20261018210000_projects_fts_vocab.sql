@@ -0,0 +1,6 @@
+/* Vocabulary of the full-text index, for correcting misspelled queries */
+
+CREATE VIRTUAL TABLE IF NOT EXISTS projects_fts_vocab USING fts5vocab(
+  projects_fts,
+  'row'
+);
@@ -0,0 +1,10 @@
+/* Prefix lookups for search suggestions; LIKE is case-insensitive, so
+   can use only indices with NOCASE collation */
+
+CREATE INDEX IF NOT EXISTS projects_game_title_nocase ON projects(game_title COLLATE NOCASE);
+
+CREATE INDEX IF NOT EXISTS publishers_name_nocase ON publishers(name COLLATE NOCASE);
+
+CREATE INDEX IF NOT EXISTS tags_tag_nocase ON tags(tag COLLATE NOCASE);
+
+CREATE INDEX IF NOT EXISTS users_username_nocase ON users(username COLLATE NOCASE);
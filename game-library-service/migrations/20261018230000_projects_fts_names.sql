@@ -0,0 +1,272 @@
+/* Full-text search over project names, tags, and package names
+
+   These are not columns of projects, so the index can no longer use
+   projects as its external content table; it now stores its own copy
+   of the indexed text, taken from projects_fts_source. */
+
+DROP TRIGGER IF EXISTS projects_ai;
+DROP TRIGGER IF EXISTS projects_ad;
+DROP TRIGGER IF EXISTS projects_au;
+DROP TRIGGER IF EXISTS projects_history_au_end;
+DROP TRIGGER IF EXISTS projects_history_au_restore;
+
+DROP TABLE IF EXISTS projects_fts_vocab;
+DROP TABLE IF EXISTS projects_fts;
+
+/* Deleted projects are not indexed */
+CREATE VIEW IF NOT EXISTS projects_fts_source AS
+SELECT
+  projects.project_id,
+  projects.game_title,
+  projects.game_publisher,
+  projects.game_year,
+  projects.description,
+  projects.readme,
+  projects.name,
+  (
+    SELECT IFNULL(group_concat(tags.tag, ' '), '')
+    FROM projects_tags
+    JOIN tags
+    ON projects_tags.tag_id = tags.tag_id
+    WHERE projects_tags.project_id = projects.project_id
+  ) AS tags,
+  (
+    SELECT IFNULL(group_concat(packages.name, ' '), '')
+    FROM packages
+    WHERE packages.project_id = projects.project_id
+  ) AS packages
+FROM projects
+JOIN projects_history
+ON projects.project_id = projects_history.project_id
+WHERE projects_history.deleted_at IS NULL;
+
+CREATE VIRTUAL TABLE IF NOT EXISTS projects_fts USING fts5(
+  game_title,
+  game_publisher,
+  game_year,
+  description,
+  readme,
+  name,
+  tags,
+  packages
+);
+
+/* Set column weights, in column order; these may be retuned at any time
+   by setting rank again, without rebuilding the index */
+INSERT INTO projects_fts(
+  projects_fts,
+  rank
+) VALUES(
+  'rank',
+  'bm25(100.0, 1.0, 1.0, 1.0, 1.0, 50.0, 20.0, 20.0)'
+);
+
+INSERT INTO projects_fts (
+  rowid,
+  game_title,
+  game_publisher,
+  game_year,
+  description,
+  readme,
+  name,
+  tags,
+  packages
+)
+SELECT *
+FROM projects_fts_source;
+
+CREATE VIRTUAL TABLE IF NOT EXISTS projects_fts_vocab USING fts5vocab(
+  projects_fts,
+  'row'
+);
+
+/* Each trigger reindexes the projects it affects */
+
+CREATE TRIGGER IF NOT EXISTS projects_ai AFTER INSERT ON projects
+BEGIN
+  DELETE FROM projects_fts WHERE rowid = NEW.project_id;
+  INSERT INTO projects_fts (
+    rowid,
+    game_title,
+    game_publisher,
+    game_year,
+    description,
+    readme,
+    name,
+    tags,
+    packages
+  )
+  SELECT *
+  FROM projects_fts_source
+  WHERE project_id = NEW.project_id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS projects_ad AFTER DELETE ON projects
+BEGIN
+  DELETE FROM projects_fts WHERE rowid = OLD.project_id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS projects_au AFTER UPDATE ON projects
+BEGIN
+  DELETE FROM projects_fts WHERE rowid = OLD.project_id;
+  INSERT INTO projects_fts (
+    rowid,
+    game_title,
+    game_publisher,
+    game_year,
+    description,
+    readme,
+    name,
+    tags,
+    packages
+  )
+  SELECT *
+  FROM projects_fts_source
+  WHERE project_id = NEW.project_id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS projects_history_au_fts
+AFTER UPDATE OF deleted_at ON projects_history
+BEGIN
+  DELETE FROM projects_fts WHERE rowid = OLD.project_id;
+  INSERT INTO projects_fts (
+    rowid,
+    game_title,
+    game_publisher,
+    game_year,
+    description,
+    readme,
+    name,
+    tags,
+    packages
+  )
+  SELECT *
+  FROM projects_fts_source
+  WHERE project_id = NEW.project_id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS projects_tags_ai_fts
+AFTER INSERT ON projects_tags
+BEGIN
+  DELETE FROM projects_fts WHERE rowid = NEW.project_id;
+  INSERT INTO projects_fts (
+    rowid,
+    game_title,
+    game_publisher,
+    game_year,
+    description,
+    readme,
+    name,
+    tags,
+    packages
+  )
+  SELECT *
+  FROM projects_fts_source
+  WHERE project_id = NEW.project_id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS projects_tags_ad_fts
+AFTER DELETE ON projects_tags
+BEGIN
+  DELETE FROM projects_fts WHERE rowid = OLD.project_id;
+  INSERT INTO projects_fts (
+    rowid,
+    game_title,
+    game_publisher,
+    game_year,
+    description,
+    readme,
+    name,
+    tags,
+    packages
+  )
+  SELECT *
+  FROM projects_fts_source
+  WHERE project_id = OLD.project_id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS tags_au_fts
+AFTER UPDATE OF tag ON tags
+BEGIN
+  DELETE FROM projects_fts WHERE rowid IN (
+    SELECT project_id
+    FROM projects_tags
+    WHERE tag_id = NEW.tag_id
+  );
+  INSERT INTO projects_fts (
+    rowid,
+    game_title,
+    game_publisher,
+    game_year,
+    description,
+    readme,
+    name,
+    tags,
+    packages
+  )
+  SELECT projects_fts_source.*
+  FROM projects_fts_source
+  JOIN projects_tags
+  ON projects_fts_source.project_id = projects_tags.project_id
+  WHERE projects_tags.tag_id = NEW.tag_id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS packages_ai_fts
+AFTER INSERT ON packages
+BEGIN
+  DELETE FROM projects_fts WHERE rowid = NEW.project_id;
+  INSERT INTO projects_fts (
+    rowid,
+    game_title,
+    game_publisher,
+    game_year,
+    description,
+    readme,
+    name,
+    tags,
+    packages
+  )
+  SELECT *
+  FROM projects_fts_source
+  WHERE project_id = NEW.project_id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS packages_au_fts
+AFTER UPDATE OF name ON packages
+BEGIN
+  DELETE FROM projects_fts WHERE rowid = NEW.project_id;
+  INSERT INTO projects_fts (
+    rowid,
+    game_title,
+    game_publisher,
+    game_year,
+    description,
+    readme,
+    name,
+    tags,
+    packages
+  )
+  SELECT *
+  FROM projects_fts_source
+  WHERE project_id = NEW.project_id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS packages_ad_fts
+AFTER DELETE ON packages
+BEGIN
+  DELETE FROM projects_fts WHERE rowid = OLD.project_id;
+  INSERT INTO projects_fts (
+    rowid,
+    game_title,
+    game_publisher,
+    game_year,
+    description,
+    readme,
+    name,
+    tags,
+    packages
+  )
+  SELECT *
+  FROM projects_fts_source
+  WHERE project_id = OLD.project_id;
+END;
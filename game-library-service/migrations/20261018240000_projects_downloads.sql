@@ -0,0 +1,4 @@
+/* Downloads of files through the service, for sorting by popularity */
+
+ALTER TABLE projects_history ADD COLUMN downloads INTEGER NOT NULL DEFAULT 0
+  CHECK(downloads >= 0);
@@ -0,0 +1,97 @@
+/* Player counts and latest release times, kept up to date for sorting */
+
+ALTER TABLE projects_history ADD COLUMN players INTEGER NOT NULL DEFAULT 0
+  CHECK(players >= 0);
+
+/* Projects without unyanked releases sort as if released at the epoch */
+ALTER TABLE projects_history ADD COLUMN released_at INTEGER NOT NULL DEFAULT 0;
+
+UPDATE projects_history
+SET players = (
+  SELECT COUNT(1)
+  FROM players
+  WHERE players.project_id = projects_history.project_id
+);
+
+UPDATE projects_history
+SET released_at = (
+  SELECT IFNULL(MAX(releases.released_at), 0)
+  FROM packages
+  JOIN releases
+  ON packages.package_id = releases.package_id
+  WHERE packages.project_id = projects_history.project_id
+    AND releases.state != 'yanked'
+);
+
+CREATE TRIGGER IF NOT EXISTS players_ai_count
+AFTER INSERT ON players
+BEGIN
+  UPDATE projects_history
+  SET players = players + 1
+  WHERE project_id = NEW.project_id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS players_ad_count
+AFTER DELETE ON players
+BEGIN
+  UPDATE projects_history
+  SET players = players - 1
+  WHERE project_id = OLD.project_id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS releases_ai_released_at
+AFTER INSERT ON releases
+BEGIN
+  UPDATE projects_history
+  SET released_at = (
+    SELECT IFNULL(MAX(releases.released_at), 0)
+    FROM packages
+    JOIN releases
+    ON packages.package_id = releases.package_id
+    WHERE packages.project_id = projects_history.project_id
+      AND releases.state != 'yanked'
+  )
+  WHERE project_id = (
+    SELECT project_id
+    FROM packages
+    WHERE package_id = NEW.package_id
+  );
+END;
+
+CREATE TRIGGER IF NOT EXISTS releases_au_released_at
+AFTER UPDATE OF released_at, state ON releases
+BEGIN
+  UPDATE projects_history
+  SET released_at = (
+    SELECT IFNULL(MAX(releases.released_at), 0)
+    FROM packages
+    JOIN releases
+    ON packages.package_id = releases.package_id
+    WHERE packages.project_id = projects_history.project_id
+      AND releases.state != 'yanked'
+  )
+  WHERE project_id = (
+    SELECT project_id
+    FROM packages
+    WHERE package_id = NEW.package_id
+  );
+END;
+
+CREATE TRIGGER IF NOT EXISTS releases_ad_released_at
+AFTER DELETE ON releases
+BEGIN
+  UPDATE projects_history
+  SET released_at = (
+    SELECT IFNULL(MAX(releases.released_at), 0)
+    FROM packages
+    JOIN releases
+    ON packages.package_id = releases.package_id
+    WHERE packages.project_id = projects_history.project_id
+      AND releases.state != 'yanked'
+  )
+  WHERE project_id = (
+    SELECT project_id
+    FROM packages
+    WHERE package_id = OLD.package_id
+  );
+END;
@@ -0,0 +1,66 @@
+/* The project holding the module an extension extends, and a release of
+   it satisfying the extension's required module version, as resolved
+   when the extension was added */
+
+ALTER TABLE files_modules ADD COLUMN target_project_id INTEGER
+  REFERENCES projects(project_id)
+  CHECK(target_project_id IS NULL OR target_module IS NOT NULL);
+
+ALTER TABLE files_modules ADD COLUMN target_release_id INTEGER
+  REFERENCES releases_history(release_id)
+  CHECK(target_release_id IS NULL OR target_project_id IS NOT NULL);
+
+/* Existing extensions get their target project; target releases are
+   resolved only for extensions added from now on */
+/* prefer a module in the same project, then the oldest other one */
+UPDATE files_modules
+SET target_project_id = COALESCE(
+  (
+    SELECT packages.project_id
+    FROM files_modules AS m
+    JOIN files
+    ON m.file_id = files.file_id
+    JOIN releases
+    ON files.release_id = releases.release_id
+    JOIN packages
+    ON releases.package_id = packages.package_id
+    JOIN projects
+    ON packages.project_id = projects.project_id
+    WHERE m.name = files_modules.target_module
+      AND m.target_module IS NULL
+      AND files.filename LIKE '%.vmod'
+      AND packages.project_id = (
+        SELECT p.project_id
+        FROM files AS f
+        JOIN releases AS r
+        ON f.release_id = r.release_id
+        JOIN packages AS p
+        ON r.package_id = p.package_id
+        WHERE f.file_id = files_modules.file_id
+      )
+    ORDER BY
+      projects.created_at ASC,
+      projects.project_id ASC
+    LIMIT 1
+  ),
+  (
+    SELECT packages.project_id
+    FROM files_modules AS m
+    JOIN files
+    ON m.file_id = files.file_id
+    JOIN releases
+    ON files.release_id = releases.release_id
+    JOIN packages
+    ON releases.package_id = packages.package_id
+    JOIN projects
+    ON packages.project_id = projects.project_id
+    WHERE m.name = files_modules.target_module
+      AND m.target_module IS NULL
+      AND files.filename LIKE '%.vmod'
+    ORDER BY
+      projects.created_at ASC,
+      projects.project_id ASC
+    LIMIT 1
+  )
+)
+WHERE target_module IS NOT NULL;
@@ -0,0 +1,11 @@
+/* One revision of a release per modification time */
+
+DELETE FROM releases_revisions
+WHERE rowid NOT IN (
+  SELECT MAX(rowid)
+  FROM releases_revisions
+  GROUP BY release_id, modified_at
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS releases_revisions_release_id_modified_at
+ON releases_revisions(release_id, modified_at);
@@ -4,7 +4,9 @@ use std::sync::Arc;
 use crate::{
     core::CoreArc,
     jwt::DecodingKey,
-    model::User
+    downloads::RecentDownloads,
+    model::User,
+    resumable::UploadSessions
 };
 
 #[derive(Default)]
@@ -17,5 +19,7 @@ pub struct AppState {
     pub key: Arc<DecodingKey>,
     pub core: CoreArc,
     pub admins: Arc<Vec<User>>,
-    pub discourse_update_config: Arc<DiscourseUpdateConfig>
+    pub discourse_update_config: Arc<DiscourseUpdateConfig>,
+    pub uploads: Arc<UploadSessions>,
+    pub downloads: Arc<RecentDownloads>
 }
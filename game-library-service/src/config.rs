@@ -9,6 +9,8 @@ pub struct Config {
     pub listen_port: u16,
     pub max_file_size: usize,
     pub max_image_size: usize,
+    pub max_image_width: u32,
+    pub max_image_height: u32,
     pub read_only: bool,
     pub bucket_name: String,
     pub bucket_region: String,
@@ -18,6 +20,8 @@ pub struct Config {
     pub bucket_base_url: String,
     pub bucket_base_dir: String,
     pub upload_dir: String,
+    pub local_uploads: bool,
+    pub local_uploads_base_url: String,
     pub upload_timeout: u64,
     pub log_headers: bool,
     pub admins: Vec<String>,
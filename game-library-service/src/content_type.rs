@@ -127,7 +127,6 @@ pub fn infer_image_type(
 ) -> Result<Mime, BadMimeType>
 {
     match ext {
-        Some("svg") => Ok(mime::IMAGE_SVG),
         Some("avif") => check_avif(buf),
         Some("gif") => check_gif(buf),
         Some("jpg") => check_jpeg(buf),
@@ -141,7 +140,6 @@ pub fn supported_image_type(mime: &Mime) -> bool {
     mime == &mime::IMAGE_PNG ||
     mime == &mime::IMAGE_JPEG ||
     mime == &mime::IMAGE_GIF ||
-    mime == &mime::IMAGE_SVG ||
     *mime == *IMAGE_AVIF ||
     *mime == *IMAGE_WEBP
 }
@@ -245,7 +243,8 @@ mod test {
             }
         }
 
-        assert_eq!(infer_image_type(Some("svg"), &[]), Ok(mime::IMAGE_SVG));
+        // SVGs can carry scripts, so are not accepted as images
+        assert_eq!(infer_image_type(Some("svg"), &[]), Err(BadMimeType));
     }
 
     #[test]
@@ -255,7 +254,6 @@ mod test {
             &mime::IMAGE_GIF,
             &mime::IMAGE_JPEG,
             &mime::IMAGE_PNG,
-            &mime::IMAGE_SVG,
             &*IMAGE_WEBP
         ];
 
@@ -267,6 +265,7 @@ mod test {
     #[test]
     fn supported_image_types_no() {
         assert!(!supported_image_type(&mime::TEXT_PLAIN));
+        assert!(!supported_image_type(&mime::IMAGE_SVG));
     }
 
     #[test]
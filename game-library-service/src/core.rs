@@ -1,7 +1,8 @@
 use async_trait::async_trait;
 use glc::{
     discourse::UserUpdateParams,
-    model::{Flags, Projects, ProjectData, Publishers, Tags, Users}
+    model::{Flags, OrphanedObjects, Projects, ProjectData, PublisherSummaries, Publishers, ReleaseData, SharedFiles, SuggestKind, Suggestions, Tags, UserMergeReport, Users},
+    pagination::Limit
 };
 use mime::Mime;
 use std::{
@@ -14,7 +15,7 @@ use thiserror::Error;
 
 use crate::{
     db,
-    input::{FlagPost, GalleryPatch, PackageDataPatch, PackageDataPost, ProjectDataPatch, ProjectDataPost},
+    input::{FlagPost, GalleryPatch, PackageDataPatch, PackageDataPost, ProjectDataPatch, ProjectDataPost, ProjectRenamePost, PublisherRenamePost, ReleaseDataPatch, ReleaseDataPost},
     model::{Admin, Flag, Owner, Package, Project, Release, User},
     module,
     params::ProjectsParams,
@@ -86,6 +87,12 @@ pub enum GetProjectError {
     TimeError(#[from] time::Error)
 }
 
+#[derive(Debug, Error, PartialEq)]
+pub enum AddDownloadError {
+    #[error("{0}")]
+    DatabaseError(#[from] db::DatabaseError)
+}
+
 #[derive(Debug, Error, PartialEq)]
 pub enum CreateProjectError {
     #[error("Already exists")]
@@ -115,6 +122,45 @@ pub enum UpdateProjectError {
     TimeError(#[from] time::Error)
 }
 
+#[derive(Debug, Error, PartialEq)]
+pub enum DeleteProjectError {
+    #[error("{0}")]
+    DatabaseError(#[from] db::DatabaseError),
+    #[error("{0}")]
+    TimeError(#[from] time::Error)
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum RestoreProjectError {
+    #[error("{0}")]
+    DatabaseError(#[from] db::DatabaseError),
+    #[error("Not found")]
+    NotFound,
+    #[error("{0}")]
+    TimeError(#[from] time::Error)
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum RenameProjectError {
+    #[error("Already exists")]
+    AlreadyExists,
+    #[error("{0}")]
+    DatabaseError(db::DatabaseError),
+    #[error("Invalid project name")]
+    InvalidProjectName,
+    #[error("{0}")]
+    TimeError(#[from] time::Error)
+}
+
+impl From<db::DatabaseError> for RenameProjectError {
+    fn from(err: db::DatabaseError) -> Self {
+        match err {
+            db::DatabaseError::AlreadyExists => RenameProjectError::AlreadyExists,
+            e => RenameProjectError::DatabaseError(e)
+        }
+    }
+}
+
 #[derive(Debug, Error, PartialEq)]
 pub enum CreatePackageError {
     #[error("Already exists")]
@@ -186,6 +232,16 @@ impl From<db::DatabaseError> for CreateReleaseError {
     }
 }
 
+#[derive(Debug, Error, PartialEq)]
+pub enum UpdateReleaseError {
+    #[error("{0}")]
+    DatabaseError(#[from] db::DatabaseError),
+    #[error("State contradicts version")]
+    StateContradictsVersion,
+    #[error("{0}")]
+    TimeError(#[from] time::Error)
+}
+
 #[derive(Debug, Error, PartialEq)]
 pub enum DeleteReleaseError {
     #[error("{0}")]
@@ -205,6 +261,25 @@ impl From<db::DatabaseError> for DeleteReleaseError {
     }
 }
 
+#[derive(Debug, Error, PartialEq)]
+pub enum DeleteFileError {
+    #[error("{0}")]
+    DatabaseError(db::DatabaseError),
+    #[error("Not found")]
+    NotFound,
+    #[error("{0}")]
+    TimeError(#[from] time::Error)
+}
+
+impl From<db::DatabaseError> for DeleteFileError {
+    fn from(err: db::DatabaseError) -> Self {
+        match err {
+            db::DatabaseError::NotFound => DeleteFileError::NotFound,
+            e => DeleteFileError::DatabaseError(e)
+        }
+    }
+}
+
 #[derive(Debug, Error)]
 pub enum AddFileError {
     #[error("Bad MIME type")]
@@ -295,6 +370,8 @@ pub enum AddImageError {
     TimeError(#[from] time::Error),
     #[error("File too large")]
     TooLarge,
+    #[error("Image dimensions too large")]
+    TooManyPixels,
     #[error("{0}")]
     UploadError(#[from] upload::UploadError)
 }
@@ -308,7 +385,8 @@ impl PartialEq for AddImageError {
             (Self::UploadError(l), Self::UploadError(r)) => l == r,
             (Self::BadMimeType, Self::BadMimeType) |
             (Self::InvalidFilename, Self::InvalidFilename) |
-            (Self::TooLarge, Self::TooLarge) => true,
+            (Self::TooLarge, Self::TooLarge) |
+            (Self::TooManyPixels, Self::TooManyPixels) => true,
             (_, _) => false
         }
     }
@@ -320,12 +398,57 @@ pub enum GetPublishersError {
     DatabaseError(#[from] db::DatabaseError)
 }
 
+#[derive(Debug, Error, PartialEq)]
+pub enum RenamePublisherError {
+    #[error("Already exists")]
+    AlreadyExists,
+    #[error("{0}")]
+    DatabaseError(db::DatabaseError),
+    #[error("Not found")]
+    NotFound
+}
+
+impl From<db::DatabaseError> for RenamePublisherError {
+    fn from(err: db::DatabaseError) -> Self {
+        match err {
+            db::DatabaseError::AlreadyExists => RenamePublisherError::AlreadyExists,
+            db::DatabaseError::NotFound => RenamePublisherError::NotFound,
+            e => RenamePublisherError::DatabaseError(e)
+        }
+    }
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum MergePublishersError {
+    #[error("Cannot merge into self")]
+    CannotMergeIntoSelf,
+    #[error("{0}")]
+    DatabaseError(db::DatabaseError),
+    #[error("Not found")]
+    NotFound
+}
+
+impl From<db::DatabaseError> for MergePublishersError {
+    fn from(err: db::DatabaseError) -> Self {
+        match err {
+            db::DatabaseError::NotFound => MergePublishersError::NotFound,
+            e => MergePublishersError::DatabaseError(e)
+        }
+    }
+}
+
 #[derive(Debug, Error, PartialEq)]
 pub enum GetTagsError {
     #[error("{0}")]
     DatabaseError(#[from] db::DatabaseError)
 }
 
+#[derive(Debug, Error, PartialEq)]
+pub enum GetSuggestionsError {
+    #[error("{0}")]
+    DatabaseError(#[from] db::DatabaseError)
+}
+
 #[derive(Debug, Error, PartialEq)]
 pub enum AddFlagError {
     #[error("{0}")]
@@ -350,12 +473,47 @@ pub enum GetFlagsError {
     TimeError(#[from] time::Error)
 }
 
+#[derive(Debug, Error, PartialEq)]
+pub enum GetSharedFilesError {
+    #[error("{0}")]
+    DatabaseError(#[from] db::DatabaseError),
+    #[error("{0}")]
+    TimeError(#[from] time::Error)
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum CollectGarbageError {
+    #[error("{0}")]
+    DatabaseError(#[from] db::DatabaseError),
+    #[error("{0}")]
+    UploadError(#[from] upload::UploadError)
+}
+
 #[derive(Debug, Error, PartialEq)]
 pub enum UpdateUserError {
     #[error("{0}")]
     DatabaseError(#[from] db::DatabaseError)
 }
 
+#[derive(Debug, Error, PartialEq)]
+pub enum MergeUsersError {
+    #[error("Cannot merge into self")]
+    CannotMergeIntoSelf,
+    #[error("{0}")]
+    DatabaseError(db::DatabaseError),
+    #[error("Not found")]
+    NotFound
+}
+
+impl From<db::DatabaseError> for MergeUsersError {
+    fn from(err: db::DatabaseError) -> Self {
+        match err {
+            db::DatabaseError::NotFound => MergeUsersError::NotFound,
+            e => MergeUsersError::DatabaseError(e)
+        }
+    }
+}
+
 #[async_trait]
 pub trait Core {
     fn upload_dir(&self) -> &Path {
@@ -495,6 +653,43 @@ pub trait Core {
         unimplemented!();
     }
 
+    async fn delete_project(
+        &self,
+        _requester: User,
+        _proj: Project
+    ) -> Result<(), DeleteProjectError>
+    {
+        unimplemented!();
+    }
+
+    async fn restore_project(
+        &self,
+        _admin: Admin,
+        _proj: &str
+    ) -> Result<(), RestoreProjectError>
+    {
+        unimplemented!();
+    }
+
+    async fn rename_project(
+        &self,
+        _admin: Admin,
+        _proj: Project,
+        _proj_data: &ProjectRenamePost
+    ) -> Result<(), RenameProjectError>
+    {
+        unimplemented!();
+    }
+
+    async fn get_project_compatible(
+        &self,
+        _proj: Project,
+        _vassal: &Version
+    ) -> Result<ProjectData, GetProjectError>
+    {
+        unimplemented!();
+    }
+
     async fn get_project_revision(
         &self,
         _proj: Project,
@@ -531,27 +726,72 @@ pub trait Core {
         _owner: Owner,
         _proj: Project,
         _pkg: Package,
+        _cascade: bool
     ) -> Result<(), DeletePackageError>
     {
         unimplemented!();
     }
 
+    async fn get_latest_release(
+        &self,
+        _proj: Project,
+        _pkg: Package,
+        _pre: bool,
+        _vassal: Option<&Version>
+    ) -> Result<ReleaseData, GetProjectError>
+    {
+        unimplemented!();
+    }
+
+    async fn get_latest_file(
+        &self,
+        _proj: Project,
+        _pkg: Package,
+        _filename: &str,
+        _pre: bool,
+        _vassal: Option<&Version>
+    ) -> Result<String, GetProjectError>
+    {
+        unimplemented!();
+    }
+
+    async fn add_download(
+        &self,
+        _proj: Project
+    ) -> Result<(), AddDownloadError>
+    {
+        unimplemented!();
+    }
+
     async fn create_release(
         &self,
         _owner: Owner,
         _proj: Project,
         _pkg: Package,
-        _version: &str
+        _version: &str,
+        _rel_data: &ReleaseDataPost
     ) -> Result<(), CreateReleaseError>
     {
         unimplemented!();
     }
 
+    async fn update_release(
+        &self,
+        _owner: Owner,
+        _proj: Project,
+        _rel: Release,
+        _rel_data: &ReleaseDataPatch
+    ) -> Result<(), UpdateReleaseError>
+    {
+        unimplemented!();
+    }
+
     async fn delete_release(
         &self,
         _owner: Owner,
         _proj: Project,
-        _rel: Release
+        _rel: Release,
+        _cascade: bool
     ) -> Result<(), DeleteReleaseError>
     {
         unimplemented!();
@@ -574,6 +814,17 @@ pub trait Core {
         unimplemented!();
     }
 
+    async fn delete_file(
+        &self,
+        _owner: Owner,
+        _proj: Project,
+        _release: Release,
+        _filename: &str
+    ) -> Result<(), DeleteFileError>
+    {
+        unimplemented!();
+    }
+
     async fn get_players(
         &self,
         _proj: Project
@@ -628,7 +879,8 @@ pub trait Core {
     async fn get_image(
         &self,
         _proj: Project,
-        _img_name: &str
+        _img_name: &str,
+        _size: Option<u32>
     ) -> Result<String, GetImageError>
     {
         unimplemented!();
@@ -666,6 +918,31 @@ pub trait Core {
         unimplemented!();
     }
 
+    async fn get_publisher_summaries(
+        &self
+    ) -> Result<PublisherSummaries, GetPublishersError>
+    {
+        unimplemented!();
+    }
+
+    async fn rename_publisher(
+        &self,
+        _publisher: &str,
+        _publisher_data: &PublisherRenamePost
+    ) -> Result<(), RenamePublisherError>
+    {
+        unimplemented!();
+    }
+
+    async fn merge_publishers(
+        &self,
+        _src: &str,
+        _dst: &str
+    ) -> Result<(), MergePublishersError>
+    {
+        unimplemented!();
+    }
+
     async fn get_tags(
         &self
     ) -> Result<Tags, GetTagsError>
@@ -673,6 +950,16 @@ pub trait Core {
         unimplemented!();
     }
 
+    async fn get_suggestions(
+        &self,
+        _kind: SuggestKind,
+        _q: &str,
+        _limit: Limit
+    ) -> Result<Suggestions, GetSuggestionsError>
+    {
+        unimplemented!();
+    }
+
     async fn get_flag_id(
          &self,
         _flag: i64
@@ -707,12 +994,37 @@ pub trait Core {
         unimplemented!();
     }
 
+    async fn get_shared_files(
+        &self,
+        _sha256: &str
+    ) -> Result<SharedFiles, GetSharedFilesError>
+    {
+        unimplemented!();
+    }
+
+    async fn collect_garbage(
+        &self,
+        _dry_run: bool,
+        _grace: u64
+    ) -> Result<OrphanedObjects, CollectGarbageError>
+    {
+        unimplemented!();
+    }
+
     async fn update_user(
         &self,
         _params: &UserUpdateParams
     ) -> Result<(), UpdateUserError> {
         unimplemented!();
     }
+
+    async fn merge_users(
+        &self,
+        _src: User,
+        _dst: User
+    ) -> Result<UserMergeReport, MergeUsersError> {
+        unimplemented!();
+    }
 }
 
 pub type CoreArc = Arc<dyn Core + Send + Sync>;
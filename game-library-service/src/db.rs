@@ -1,6 +1,6 @@
 use glc::{
     discourse::UserUpdateParams,
-    model::{FlagTag, GalleryImage, Users},
+    model::{FacetCounts, FlagTag, GalleryImage, SuggestKind, UserMergeReport, Users},
     pagination::{Direction, Facet, SortBy}
 };
 use serde::Deserialize;
@@ -12,8 +12,9 @@ use sqlx::FromRow;
 use thiserror::Error;
 
 use crate::{
-    input::{FlagPost, GalleryPatch, PackageDataPatch, PackageDataPost, ProjectDataPatch, ProjectDataPost},
+    input::{FlagPost, GalleryPatch, PackageDataPatch, PackageDataPost, ProjectDataPatch, ProjectDataPost, ReleaseDataPatch, ReleaseDataPost},
     model::{Admin, Flag, Owner, Package, Project, Release, User},
+    module::ModuleMetadata,
     version::Version
 };
 
@@ -69,7 +70,10 @@ pub struct ProjectSummaryRow {
     pub game_players_max: Option<i64>,
     pub game_length_min: Option<i64>,
     pub game_length_max: Option<i64>,
-    pub image: Option<String>
+    pub image: Option<String>,
+    pub players: i64,
+    pub downloads: i64,
+    pub released_at: i64
 }
 
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
@@ -115,7 +119,11 @@ pub struct ReleaseRow {
     pub version_pre: String,
     pub version_build: String,
     pub published_at: i64,
-    pub published_by: String
+    pub published_by: String,
+    pub changelog: Option<String>,
+    pub released_at: i64,
+    pub state: String,
+    pub yank_reason: Option<String>
 }
 
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
@@ -127,6 +135,38 @@ pub struct FileRow {
     pub sha256: String,
     pub requires: Option<String>,
     pub published_at: i64,
+    pub published_by: String,
+    pub module_name: Option<String>,
+    pub target_module: Option<String>,
+    pub target_module_version: Option<String>,
+    pub target_project: Option<String>,
+    pub target_release: Option<String>
+}
+
+// where the module an extension extends was found when it was added
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ModuleTarget {
+    pub project: Project,
+    pub release: Option<Release>
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+pub struct FileModuleItemRow {
+    pub file_id: i64,
+    pub kind: String,
+    pub value: String
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+pub struct SharedFileRow {
+    pub project: String,
+    pub slug: String,
+    pub package: String,
+    pub version: String,
+    pub filename: String,
+    pub url: String,
+    pub size: i64,
+    pub published_at: i64,
     pub published_by: String
 }
 
@@ -141,8 +181,15 @@ pub struct FlagRow {
     pub message: Option<String>
 }
 
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+pub struct PublisherRow {
+    pub name: String,
+    pub projects: i64
+}
+
 pub enum MidField<'a> {
     Timestamp(i64),
+    Count(i64),
     Weight(f64),
     Text(&'a str)
 }
@@ -153,11 +200,31 @@ pub trait DatabaseClient {
         _projname: &str
     ) -> impl Future<Output = Result<Option<Project>, DatabaseError>> + Send;
 
+    fn get_deleted_project_id(
+        &self,
+        _projname: &str
+    ) -> impl Future<Output = Result<Option<Project>, DatabaseError>> + Send;
+
+    fn get_project_alias_id(
+        &self,
+        _projname: &str
+    ) -> impl Future<Output = Result<Option<Project>, DatabaseError>> + Send;
+
+    fn get_search_facets(
+        &self,
+        _facets: &[Facet]
+    ) -> impl Future<Output = Result<Vec<Facet>, DatabaseError>> + Send;
+
     fn get_projects_count(
         &self,
         _facets: &[Facet]
     ) -> impl Future<Output = Result<i64, DatabaseError>> + Send;
 
+    fn get_facet_counts(
+        &self,
+        _facets: &[Facet]
+    ) -> impl Future<Output = Result<FacetCounts, DatabaseError>> + Send;
+
    fn get_user_id(
         &self,
         _username: &str
@@ -168,6 +235,12 @@ pub trait DatabaseClient {
         _params: &UserUpdateParams
     ) -> impl Future<Output = Result<(), DatabaseError>> + Send;
 
+    fn merge_users(
+        &self,
+        _src: User,
+        _dst: User
+    ) -> impl Future<Output = Result<UserMergeReport, DatabaseError>> + Send;
+
     fn get_owners(
         &self,
         _proj: Project
@@ -242,6 +315,34 @@ pub trait DatabaseClient {
         _now: i64
     ) -> impl Future<Output = Result<(), DatabaseError>> + Send;
 
+    fn delete_project(
+        &self,
+        _user: User,
+        _proj: Project,
+        _now: i64
+    ) -> impl Future<Output = Result<(), DatabaseError>> + Send;
+
+    fn restore_project(
+        &self,
+        _admin: Admin,
+        _proj: Project,
+        _now: i64
+    ) -> impl Future<Output = Result<(), DatabaseError>> + Send;
+
+    fn add_download(
+        &self,
+        _proj: Project
+    ) -> impl Future<Output = Result<(), DatabaseError>> + Send;
+
+    fn rename_project(
+        &self,
+        _admin: Admin,
+        _proj: Project,
+        _name: &str,
+        _slug: &str,
+        _now: i64
+    ) -> impl Future<Output = Result<(), DatabaseError>> + Send;
+
     fn get_project_row(
         &self,
         proj: Project
@@ -290,6 +391,7 @@ pub trait DatabaseClient {
         _owner: Owner,
         _proj: Project,
         _pkg: Package,
+        _cascade: bool,
         _now: i64
     ) -> impl Future<Output = Result<(), DatabaseError>> + Send;
 
@@ -307,6 +409,12 @@ pub trait DatabaseClient {
         _pkg: Package
     ) -> impl Future<Output = Result<Vec<ReleaseRow>, DatabaseError>> + Send;
 
+    fn get_vassal_compatible_releases(
+        &self,
+        _proj: Project,
+        _vassal: &Version
+    ) -> impl Future<Output = Result<Vec<i64>, DatabaseError>> + Send;
+
     fn get_releases_at(
         &self,
         _pkg: Package,
@@ -335,6 +443,16 @@ pub trait DatabaseClient {
         _proj: Project,
         _pkg: Package,
         _version: &Version,
+        _rel_data: &ReleaseDataPost,
+        _now: i64
+    ) -> impl Future<Output = Result<(), DatabaseError>> + Send;
+
+    fn update_release(
+        &self,
+        _owner: Owner,
+        _proj: Project,
+        _rel: Release,
+        _rel_data: &ReleaseDataPatch,
         _now: i64
     ) -> impl Future<Output = Result<(), DatabaseError>> + Send;
 
@@ -343,6 +461,7 @@ pub trait DatabaseClient {
         _owner: Owner,
         _proj: Project,
         _rel: Release,
+        _cascade: bool,
         _now: i64
     ) -> impl Future<Output = Result<(), DatabaseError>> + Send;
 
@@ -372,11 +491,55 @@ pub trait DatabaseClient {
         _size: i64,
         _sha256: &str,
         _content_type: &str,
-        _requires: Option<&str>,
+        _requires: Option<&Version>,
+        _module: Option<&ModuleMetadata>,
+        _target: Option<&ModuleTarget>,
+        _players: Option<u32>,
         _url: &str,
         _now: i64
     ) -> impl Future<Output = Result<(), DatabaseError>> + Send;
 
+    fn delete_file(
+        &self,
+        _owner: Owner,
+        _proj: Project,
+        _release: Release,
+        _filename: &str,
+        _now: i64
+    ) -> impl Future<Output = Result<(), DatabaseError>> + Send;
+
+    fn get_file_url_by_hash(
+        &self,
+        _sha256: &str,
+        _size: i64
+    ) -> impl Future<Output = Result<Option<String>, DatabaseError>> + Send;
+
+    fn get_files_by_hash(
+        &self,
+        _sha256: &str
+    ) -> impl Future<Output = Result<Vec<SharedFileRow>, DatabaseError>> + Send;
+
+    fn get_referenced_urls(
+        &self
+    ) -> impl Future<Output = Result<Vec<String>, DatabaseError>> + Send;
+
+    fn get_release_module_items(
+        &self,
+        _rel: Release
+    ) -> impl Future<Output = Result<Vec<FileModuleItemRow>, DatabaseError>> + Send;
+
+    fn get_module_project(
+        &self,
+        _proj: Project,
+        _module: &str
+    ) -> impl Future<Output = Result<Option<Project>, DatabaseError>> + Send;
+
+    fn get_module_releases(
+        &self,
+        _proj: Project,
+        _module: &str
+    ) -> impl Future<Output = Result<Vec<(Release, Version)>, DatabaseError>> + Send;
+
     fn get_players(
         &self,
         _proj: Project
@@ -400,6 +563,13 @@ pub trait DatabaseClient {
         _img_name: &str
     ) -> impl Future<Output = Result<Option<String>, DatabaseError>> + Send;
 
+    fn get_image_rendition_url(
+        &self,
+        _proj: Project,
+        _img_name: &str,
+        _size: u32
+    ) -> impl Future<Output = Result<Option<String>, DatabaseError>> + Send;
+
     fn get_image_url_at(
         &self,
         _proj: Project,
@@ -407,6 +577,7 @@ pub trait DatabaseClient {
         _date: i64
     ) -> impl Future<Output = Result<Option<String>, DatabaseError>> + Send;
 
+    #[allow(clippy::too_many_arguments)]
     fn add_image_url(
         &self,
         _owner: Owner,
@@ -414,6 +585,8 @@ pub trait DatabaseClient {
         _img_name: &str,
         _url: &str,
         _content_type: &str,
+        _dimensions: Option<(u32, u32)>,
+        _renditions: &[(u32, String)],
         _now: i64
     ) -> impl Future<Output = Result<(), DatabaseError>> + Send;
 
@@ -439,6 +612,7 @@ pub trait DatabaseClient {
         _date: i64
     ) -> impl Future<Output = Result<Vec<GalleryImage>, DatabaseError>> + Send;
 
+    #[allow(clippy::too_many_arguments)]
     fn add_gallery_image(
         &self,
         _owner: Owner,
@@ -446,6 +620,8 @@ pub trait DatabaseClient {
         _img_name: &str,
         _url: &str,
         _content_type: &str,
+        _dimensions: Option<(u32, u32)>,
+        _renditions: &[(u32, String)],
         _now: i64
     ) -> impl Future<Output = Result<(), DatabaseError>> + Send;
 
@@ -465,6 +641,22 @@ pub trait DatabaseClient {
         &self
     ) -> impl Future<Output = Result<Vec<String>, DatabaseError>> + Send;
 
+    fn get_publishers_active_counts(
+        &self
+    ) -> impl Future<Output = Result<Vec<PublisherRow>, DatabaseError>> + Send;
+
+    fn rename_publisher(
+        &self,
+        _publisher: &str,
+        _name: &str
+    ) -> impl Future<Output = Result<(), DatabaseError>> + Send;
+
+    fn merge_publishers(
+        &self,
+        _src: &str,
+        _dst: &str
+    ) -> impl Future<Output = Result<(), DatabaseError>> + Send;
+
     fn get_tags(
         &self
     ) -> impl Future<Output = Result<Vec<String>, DatabaseError>> + Send;
@@ -473,6 +665,13 @@ pub trait DatabaseClient {
         &self
     ) -> impl Future<Output = Result<Vec<String>, DatabaseError>> + Send;
 
+    fn get_suggestions(
+        &self,
+        _kind: SuggestKind,
+        _prefix: &str,
+        _limit: u32
+    ) -> impl Future<Output = Result<Vec<String>, DatabaseError>> + Send;
+
     fn get_flag_id(
         &self,
         _flag: i64
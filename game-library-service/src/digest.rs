@@ -0,0 +1,196 @@
+use base64::{Engine as _};
+use http::header::HeaderMap;
+
+pub const CONTENT_DIGEST: &str = "content-digest";
+pub const REPR_DIGEST: &str = "repr-digest";
+
+#[derive(Debug, Eq, PartialEq, thiserror::Error)]
+pub enum Error {
+    #[error("Malformed digest")]
+    Malformed,
+    #[error("Digest mismatch")]
+    Mismatch
+}
+
+fn parse_digest_field(value: &str) -> Result<Option<String>, Error> {
+    // A digest field is a dictionary of algorithms and byte sequences,
+    // e.g., sha-256=:X48E9qOokqqrvdts8nOJRJN3OWDUoyWxBf7kbu9DBPE=:
+    for member in value.split(',') {
+        let (alg, val) = member.split_once('=')
+            .ok_or(Error::Malformed)?;
+
+        // we compute only sha-256, so other algorithms are ignored
+        if !alg.trim().eq_ignore_ascii_case("sha-256") {
+            continue;
+        }
+
+        let val = val.trim()
+            .strip_prefix(':')
+            .and_then(|v| v.strip_suffix(':'))
+            .ok_or(Error::Malformed)?;
+
+        let bytes = base64::engine::general_purpose::STANDARD.decode(val)
+            .or(Err(Error::Malformed))?;
+
+        if bytes.len() != 32 {
+            return Err(Error::Malformed);
+        }
+
+        return Ok(
+            Some(bytes.iter().map(|b| format!("{b:02x}")).collect())
+        );
+    }
+
+    Ok(None)
+}
+
+fn parse_hex_digest(value: &str) -> Result<String, Error> {
+    if value.len() == 64 && value.bytes().all(|b| b.is_ascii_hexdigit()) {
+        Ok(value.to_ascii_lowercase())
+    }
+    else {
+        Err(Error::Malformed)
+    }
+}
+
+pub fn expected_sha256(
+    headers: &HeaderMap,
+    sha256_param: Option<&str>
+) -> Result<Vec<String>, Error>
+{
+    let mut expected = vec![];
+
+    for name in [CONTENT_DIGEST, REPR_DIGEST] {
+        for value in headers.get_all(name) {
+            let value = value.to_str().or(Err(Error::Malformed))?;
+            if let Some(sha256) = parse_digest_field(value)? {
+                expected.push(sha256);
+            }
+        }
+    }
+
+    if let Some(sha256) = sha256_param {
+        expected.push(parse_hex_digest(sha256)?);
+    }
+
+    Ok(expected)
+}
+
+pub fn check_sha256(expected: &[String], sha256: &str) -> Result<(), Error> {
+    match expected.iter().all(|e| e == sha256) {
+        true => Ok(()),
+        false => Err(Error::Mismatch)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use http::header::HeaderValue;
+
+    // sha256 of "hello"
+    const HELLO_HEX: &str = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+    const HELLO_B64: &str = "LPJNul+wow4m6DsqxbninhsWHlwfp0JecwQzYpOLmCQ=";
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.append(*name, HeaderValue::from_str(value).unwrap());
+        }
+        headers
+    }
+
+    #[test]
+    fn expected_sha256_none() {
+        assert_eq!(
+            expected_sha256(&HeaderMap::new(), None).unwrap(),
+            Vec::<String>::new()
+        );
+    }
+
+    #[test]
+    fn expected_sha256_content_digest() {
+        let h = headers(&[(CONTENT_DIGEST, &format!("sha-256=:{HELLO_B64}:"))]);
+        assert_eq!(expected_sha256(&h, None).unwrap(), [HELLO_HEX]);
+    }
+
+    #[test]
+    fn expected_sha256_repr_digest_multiple_algs() {
+        let h = headers(&[(
+            REPR_DIGEST,
+            &format!("sha-512=:AAAA:, sha-256=:{HELLO_B64}:")
+        )]);
+        assert_eq!(expected_sha256(&h, None).unwrap(), [HELLO_HEX]);
+    }
+
+    #[test]
+    fn expected_sha256_other_alg_only() {
+        let h = headers(&[(CONTENT_DIGEST, "sha-512=:AAAA:")]);
+        assert_eq!(
+            expected_sha256(&h, None).unwrap(),
+            Vec::<String>::new()
+        );
+    }
+
+    #[test]
+    fn expected_sha256_param() {
+        assert_eq!(
+            expected_sha256(
+                &HeaderMap::new(),
+                Some(&HELLO_HEX.to_ascii_uppercase())
+            ).unwrap(),
+            [HELLO_HEX]
+        );
+    }
+
+    #[test]
+    fn expected_sha256_header_and_param() {
+        let h = headers(&[(CONTENT_DIGEST, &format!("sha-256=:{HELLO_B64}:"))]);
+        assert_eq!(
+            expected_sha256(&h, Some(HELLO_HEX)).unwrap(),
+            [HELLO_HEX, HELLO_HEX]
+        );
+    }
+
+    #[test]
+    fn expected_sha256_bad_base64() {
+        let h = headers(&[(CONTENT_DIGEST, "sha-256=:!!!:")]);
+        assert_eq!(expected_sha256(&h, None).unwrap_err(), Error::Malformed);
+    }
+
+    #[test]
+    fn expected_sha256_not_byte_sequence() {
+        let h = headers(&[(CONTENT_DIGEST, &format!("sha-256={HELLO_B64}"))]);
+        assert_eq!(expected_sha256(&h, None).unwrap_err(), Error::Malformed);
+    }
+
+    #[test]
+    fn expected_sha256_wrong_length() {
+        let h = headers(&[(CONTENT_DIGEST, "sha-256=:AAAA:")]);
+        assert_eq!(expected_sha256(&h, None).unwrap_err(), Error::Malformed);
+    }
+
+    #[test]
+    fn expected_sha256_bad_param() {
+        assert_eq!(
+            expected_sha256(&HeaderMap::new(), Some("xyz")).unwrap_err(),
+            Error::Malformed
+        );
+    }
+
+    #[test]
+    fn check_sha256_ok() {
+        assert!(check_sha256(&[HELLO_HEX.into()], HELLO_HEX).is_ok());
+        assert!(check_sha256(&[], HELLO_HEX).is_ok());
+    }
+
+    #[test]
+    fn check_sha256_mismatch() {
+        assert_eq!(
+            check_sha256(&[HELLO_HEX.into(), "0".repeat(64)], HELLO_HEX)
+                .unwrap_err(),
+            Error::Mismatch
+        );
+    }
+}
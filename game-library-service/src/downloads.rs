@@ -0,0 +1,149 @@
+use std::{
+    collections::{HashMap, hash_map::Entry},
+    net::IpAddr,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant}
+};
+
+use crate::model::Project;
+
+// how long repeated downloads of a project by one client count only once
+pub const DOWNLOAD_WINDOW: Duration = Duration::from_secs(60 * 60);
+
+// how many recent downloads are remembered; more are not counted
+pub const MAX_RECENT_DOWNLOADS: usize = 100_000;
+
+const PRUNE_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+#[derive(Default)]
+pub struct RecentDownloads {
+    seen: Mutex<HashMap<(Option<IpAddr>, i64), Instant>>
+}
+
+impl RecentDownloads {
+    pub fn first_in_window(
+        &self,
+        client: Option<IpAddr>,
+        proj: Project,
+        now: Instant
+    ) -> bool
+    {
+        let mut seen = self.seen.lock()
+            .unwrap_or_else(|e| e.into_inner());
+
+        let full = seen.len() >= MAX_RECENT_DOWNLOADS;
+
+        match seen.entry((client, proj.0)) {
+            Entry::Occupied(mut e) => {
+                // the entry may have outlived the window if not yet pruned
+                if now.duration_since(*e.get()) < DOWNLOAD_WINDOW {
+                    false
+                }
+                else {
+                    e.insert(now);
+                    true
+                }
+            },
+            // if flooded, stop counting rather than grow without bound
+            Entry::Vacant(_) if full => false,
+            Entry::Vacant(e) => {
+                e.insert(now);
+                true
+            }
+        }
+    }
+
+    fn prune(&self, now: Instant) {
+        // forget downloads from before the window
+        self.seen.lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|_, t| now.duration_since(*t) < DOWNLOAD_WINDOW);
+    }
+
+    pub async fn prune_periodically(self: Arc<Self>) {
+        let mut interval = tokio::time::interval(PRUNE_INTERVAL);
+        loop {
+            interval.tick().await;
+            self.prune(Instant::now());
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn ip(s: &str) -> Option<IpAddr> {
+        Some(s.parse().unwrap())
+    }
+
+    #[test]
+    fn recent_downloads_same_client() {
+        let rd = RecentDownloads::default();
+        let now = Instant::now();
+        assert!(rd.first_in_window(ip("1.2.3.4"), Project(1), now));
+        assert!(!rd.first_in_window(ip("1.2.3.4"), Project(1), now));
+    }
+
+    #[test]
+    fn recent_downloads_other_client() {
+        let rd = RecentDownloads::default();
+        let now = Instant::now();
+        assert!(rd.first_in_window(ip("1.2.3.4"), Project(1), now));
+        assert!(rd.first_in_window(ip("5.6.7.8"), Project(1), now));
+    }
+
+    #[test]
+    fn recent_downloads_other_project() {
+        let rd = RecentDownloads::default();
+        let now = Instant::now();
+        assert!(rd.first_in_window(ip("1.2.3.4"), Project(1), now));
+        assert!(rd.first_in_window(ip("1.2.3.4"), Project(2), now));
+    }
+
+    #[test]
+    fn recent_downloads_window_passed() {
+        let rd = RecentDownloads::default();
+        let now = Instant::now();
+        assert!(rd.first_in_window(ip("1.2.3.4"), Project(1), now));
+        assert!(
+            rd.first_in_window(
+                ip("1.2.3.4"),
+                Project(1),
+                now + DOWNLOAD_WINDOW
+            )
+        );
+        assert!(
+            !rd.first_in_window(
+                ip("1.2.3.4"),
+                Project(1),
+                now + DOWNLOAD_WINDOW
+            )
+        );
+    }
+
+    #[test]
+    fn recent_downloads_full() {
+        let rd = RecentDownloads::default();
+        let now = Instant::now();
+        for i in 0..MAX_RECENT_DOWNLOADS {
+            assert!(rd.first_in_window(None, Project(i as i64), now));
+        }
+        assert!(!rd.first_in_window(ip("1.2.3.4"), Project(1), now));
+
+        // pruning makes room again
+        rd.prune(now + DOWNLOAD_WINDOW);
+        assert!(rd.first_in_window(ip("1.2.3.4"), Project(1), now));
+    }
+
+    #[test]
+    fn recent_downloads_prune() {
+        let rd = RecentDownloads::default();
+        let now = Instant::now();
+        assert!(rd.first_in_window(ip("1.2.3.4"), Project(1), now));
+        rd.prune(now + DOWNLOAD_WINDOW - Duration::from_secs(1));
+        assert_eq!(rd.seen.lock().unwrap().len(), 1);
+        rd.prune(now + DOWNLOAD_WINDOW);
+        assert!(rd.seen.lock().unwrap().is_empty());
+    }
+}
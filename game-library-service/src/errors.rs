@@ -1,7 +1,8 @@
 use glc::discourse::DiscourseEventError;
 use thiserror::Error;
 
-use crate::core::{AddFlagError, AddImageError, AddFileError, AddOwnersError, AddPlayerError, CloseFlagError, CreatePackageError, CreateProjectError, CreateReleaseError, DeletePackageError, DeleteReleaseError, GetFlagsError, GetIdError, GetImageError, GetOwnersError, GetPlayersError, GetProjectError, GetProjectsError, GetPublishersError, GetTagsError, RemoveOwnersError, RemovePlayerError, UpdateGalleryError, UpdatePackageError, UpdateProjectError, UpdateUserError, UserIsOwnerError};
+use crate::digest;
+use crate::core::{AddDownloadError, AddFlagError, AddImageError, AddFileError, AddOwnersError, AddPlayerError, CloseFlagError, CollectGarbageError, CreatePackageError, CreateProjectError, CreateReleaseError, DeleteFileError, DeletePackageError, DeleteProjectError, DeleteReleaseError, GetFlagsError, GetIdError, GetImageError, GetOwnersError, GetPlayersError, GetProjectError, GetProjectsError, GetPublishersError, GetSharedFilesError, GetSuggestionsError, GetTagsError, MergePublishersError, MergeUsersError, RemoveOwnersError, RemovePlayerError, RenamePublisherError, RenameProjectError, RestoreProjectError, UpdateGalleryError, UpdatePackageError, UpdateProjectError, UpdateReleaseError, UpdateUserError, UserIsOwnerError};
 
 // TODO: better error messsages
 #[derive(Debug, Error, PartialEq)]
@@ -12,8 +13,14 @@ pub enum AppError {
     TooLarge,
     #[error("Cannot remove last project owner")]
     CannotRemoveLastOwner,
+    #[error("Cannot merge into self")]
+    CannotMergeIntoSelf,
+    #[error("Conflict")]
+    Conflict,
     #[error("{0}")]
     DatabaseError(String),
+    #[error("Digest mismatch")]
+    DigestMismatch,
     #[error("Forbidden")]
     Forbidden,
     #[error("{0}")]
@@ -44,10 +51,21 @@ pub enum AppError {
     NotFound,
     #[error("Bad request")]
     NotEmpty,
+    #[error("Too many requests")]
+    TooManyRequests,
     #[error("Unauthorized")]
     Unauthorized
 }
 
+impl From<digest::Error> for AppError {
+    fn from(err: digest::Error) -> Self {
+        match err {
+            digest::Error::Malformed => AppError::MalformedUpload,
+            digest::Error::Mismatch => AppError::DigestMismatch
+        }
+    }
+}
+
 impl From<GetIdError> for AppError {
     fn from(err: GetIdError) -> Self {
         match err {
@@ -110,6 +128,14 @@ impl From<GetProjectError> for AppError {
     }
 }
 
+impl From<AddDownloadError> for AppError {
+    fn from(err: AddDownloadError) -> Self {
+        match err {
+            AddDownloadError::DatabaseError(e) => AppError::DatabaseError(e.to_string())
+        }
+    }
+}
+
 impl From<CreateProjectError> for AppError {
     fn from(err: CreateProjectError) -> Self {
         match err {
@@ -130,6 +156,36 @@ impl From<UpdateProjectError> for AppError {
     }
 }
 
+impl From<DeleteProjectError> for AppError {
+    fn from(err: DeleteProjectError) -> Self {
+        match err {
+            DeleteProjectError::DatabaseError(e) => AppError::DatabaseError(e.to_string()),
+            DeleteProjectError::TimeError(e) => AppError::InternalError(e.to_string())
+        }
+    }
+}
+
+impl From<RestoreProjectError> for AppError {
+    fn from(err: RestoreProjectError) -> Self {
+        match err {
+            RestoreProjectError::DatabaseError(e) => AppError::DatabaseError(e.to_string()),
+            RestoreProjectError::NotFound => AppError::NotFound,
+            RestoreProjectError::TimeError(e) => AppError::InternalError(e.to_string())
+        }
+    }
+}
+
+impl From<RenameProjectError> for AppError {
+    fn from(err: RenameProjectError) -> Self {
+        match err {
+            RenameProjectError::AlreadyExists => AppError::AlreadyExists,
+            RenameProjectError::DatabaseError(e) => AppError::DatabaseError(e.to_string()),
+            RenameProjectError::InvalidProjectName => AppError::InvalidProjectName,
+            RenameProjectError::TimeError(e) => AppError::InternalError(e.to_string())
+        }
+    }
+}
+
 impl From<CreatePackageError> for AppError {
     fn from(err: CreatePackageError) -> Self {
         match err {
@@ -172,6 +228,16 @@ impl From<CreateReleaseError> for AppError {
     }
 }
 
+impl From<UpdateReleaseError> for AppError {
+    fn from(err: UpdateReleaseError) -> Self {
+        match err {
+            UpdateReleaseError::DatabaseError(e) => AppError::DatabaseError(e.to_string()),
+            UpdateReleaseError::StateContradictsVersion => AppError::JsonError,
+            UpdateReleaseError::TimeError(e) => AppError::InternalError(e.to_string())
+        }
+    }
+}
+
 impl From<DeleteReleaseError> for AppError {
     fn from(err: DeleteReleaseError) -> Self {
         match err {
@@ -182,6 +248,16 @@ impl From<DeleteReleaseError> for AppError {
     }
 }
 
+impl From<DeleteFileError> for AppError {
+    fn from(err: DeleteFileError) -> Self {
+        match err {
+            DeleteFileError::DatabaseError(e) => AppError::DatabaseError(e.to_string()),
+            DeleteFileError::NotFound => AppError::NotFound,
+            DeleteFileError::TimeError(e) => AppError::InternalError(e.to_string())
+        }
+    }
+}
+
 impl From<AddFileError> for AppError {
     fn from(err: AddFileError) -> Self {
         match err {
@@ -241,6 +317,7 @@ impl From<AddImageError> for AppError {
             AddImageError::IOError(e) => AppError::InternalError(e.to_string()),
             AddImageError::TimeError(e) => AppError::InternalError(e.to_string()),
             AddImageError::TooLarge => AppError::TooLarge,
+            AddImageError::TooManyPixels => AppError::TooLarge,
             AddImageError::UploadError(e) => AppError::UploadError(e.to_string())
         }
     }
@@ -263,6 +340,26 @@ impl From<GetPublishersError> for AppError {
     }
 }
 
+impl From<RenamePublisherError> for AppError {
+    fn from(err: RenamePublisherError) -> Self {
+        match err {
+            RenamePublisherError::AlreadyExists => AppError::AlreadyExists,
+            RenamePublisherError::DatabaseError(e) => AppError::DatabaseError(e.to_string()),
+            RenamePublisherError::NotFound => AppError::NotFound
+        }
+    }
+}
+
+impl From<MergePublishersError> for AppError {
+    fn from(err: MergePublishersError) -> Self {
+        match err {
+            MergePublishersError::CannotMergeIntoSelf => AppError::CannotMergeIntoSelf,
+            MergePublishersError::DatabaseError(e) => AppError::DatabaseError(e.to_string()),
+            MergePublishersError::NotFound => AppError::NotFound
+        }
+    }
+}
+
 impl From<GetTagsError> for AppError {
     fn from(err: GetTagsError) -> Self {
         match err {
@@ -271,6 +368,14 @@ impl From<GetTagsError> for AppError {
     }
 }
 
+impl From<GetSuggestionsError> for AppError {
+    fn from(err: GetSuggestionsError) -> Self {
+        match err {
+            GetSuggestionsError::DatabaseError(e) => AppError::DatabaseError(e.to_string())
+        }
+    }
+}
+
 impl From<AddFlagError> for AppError {
     fn from(err: AddFlagError) -> Self {
         match err {
@@ -298,6 +403,24 @@ impl From<GetFlagsError> for AppError {
     }
 }
 
+impl From<GetSharedFilesError> for AppError {
+    fn from(err: GetSharedFilesError) -> Self {
+        match err {
+            GetSharedFilesError::DatabaseError(e) => AppError::DatabaseError(e.to_string()),
+            GetSharedFilesError::TimeError(e) => AppError::InternalError(e.to_string())
+        }
+    }
+}
+
+impl From<CollectGarbageError> for AppError {
+    fn from(err: CollectGarbageError) -> Self {
+        match err {
+            CollectGarbageError::DatabaseError(e) => AppError::DatabaseError(e.to_string()),
+            CollectGarbageError::UploadError(e) => AppError::InternalError(e.to_string())
+        }
+    }
+}
+
 impl From<UpdateUserError> for AppError {
     fn from(err: UpdateUserError) -> Self {
         match err {
@@ -306,6 +429,16 @@ impl From<UpdateUserError> for AppError {
     }
 }
 
+impl From<MergeUsersError> for AppError {
+    fn from(err: MergeUsersError) -> Self {
+        match err {
+            MergeUsersError::CannotMergeIntoSelf => AppError::CannotMergeIntoSelf,
+            MergeUsersError::DatabaseError(e) => AppError::DatabaseError(e.to_string()),
+            MergeUsersError::NotFound => AppError::NotFound
+        }
+    }
+}
+
 impl From<DiscourseEventError> for AppError {
     fn from(e: DiscourseEventError) -> Self {
         match e {
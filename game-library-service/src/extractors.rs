@@ -2,7 +2,8 @@ use axum::{
     RequestPartsExt,
     body::Bytes,
     extract::{
-        FromRef, FromRequest, FromRequestParts, Path, Request,
+        ConnectInfo, FromRef, FromRequest, FromRequestParts,
+        OptionalFromRequest, Path, Request,
         rejection::{JsonRejection, QueryRejection}
     },
     http::request::Parts
@@ -18,7 +19,10 @@ use axum_extra::{
 use glc::discourse::parse_event;
 use itertools::Itertools;
 use serde::de::DeserializeOwned;
-use std::sync::Arc;
+use std::{
+    net::{IpAddr, SocketAddr},
+    sync::Arc
+};
 
 use crate::{
     app::DiscourseUpdateConfig,
@@ -259,6 +263,35 @@ where
     }
 }
 
+pub struct ClientAddr(pub Option<IpAddr>);
+
+impl<S> FromRequestParts<S> for ClientAddr
+where
+    S: Send + Sync
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        _state: &S
+    ) -> Result<Self, Self::Rejection>
+    {
+        // Earlier X-Forwarded-For entries are whatever the client sent;
+        // only the last one, appended by our proxy, can be trusted.
+        let addr = match parts.headers.get("x-forwarded-for") {
+            Some(xff) => xff.to_str()
+                .ok()
+                .and_then(|xff| xff.rsplit(',').next())
+                .and_then(|hop| hop.trim().parse::<IpAddr>().ok()),
+            None => parts.extensions
+                .get::<ConnectInfo<SocketAddr>>()
+                .map(|info| info.ip())
+        };
+
+        Ok(ClientAddr(addr))
+    }
+}
+
 pub struct DiscourseEvent<E>(pub E);
 
 impl<S, T> FromRequest<S> for DiscourseEvent<T>
@@ -339,6 +372,27 @@ where
     }
 }
 
+impl<S, T> OptionalFromRequest<S> for Wrapper<T>
+where
+    S: Send + Sync,
+    T: OptionalFromRequest<S>,
+    AppError: From<<T as OptionalFromRequest<S>>::Rejection>
+{
+    type Rejection = AppError;
+
+    async fn from_request(
+        req: Request,
+        state: &S
+    ) -> Result<Option<Self>, Self::Rejection>
+    {
+        Ok(
+            <T as OptionalFromRequest<S>>::from_request(req, state)
+                .await?
+                .map(Wrapper)
+        )
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -584,7 +638,9 @@ mod test {
             key: Arc::new(DecodingKey::from_secret(KEY)),
             core: Arc::new(core) as CoreArc,
             admins: Arc::new(vec![]),
-            discourse_update_config: Default::default()
+            discourse_update_config: Default::default(),
+            uploads: Default::default(),
+            downloads: Default::default()
         }
     }
 
@@ -1133,4 +1189,48 @@ mod test {
 
         assert_eq!(response.status(), StatusCode::NOT_FOUND);
     }
+
+    async fn client_addr(
+        xff: Option<&str>,
+        peer: Option<SocketAddr>
+    ) -> Option<IpAddr>
+    {
+        let mut request = Request::builder()
+            .method(Method::GET)
+            .uri("/");
+
+        if let Some(xff) = xff {
+            request = request.header("x-forwarded-for", xff);
+        }
+
+        let mut parts;
+        (parts, _) = request.body(()).unwrap().into_parts();
+
+        if let Some(peer) = peer {
+            parts.extensions.insert(ConnectInfo(peer));
+        }
+
+        ClientAddr::from_request_parts(&mut parts, &()).await.unwrap().0
+    }
+
+    #[tokio::test]
+    async fn client_addr_from_request_parts_last_hop() {
+        assert_eq!(
+            client_addr(Some("1.2.3.4, 5.6.7.8"), None).await,
+            Some("5.6.7.8".parse().unwrap())
+        );
+    }
+
+    #[tokio::test]
+    async fn client_addr_from_request_parts_bad_hop() {
+        assert_eq!(client_addr(Some("1.2.3.4, bogus"), None).await, None);
+    }
+
+    #[tokio::test]
+    async fn client_addr_from_request_parts_peer() {
+        assert_eq!(
+            client_addr(None, Some("1.2.3.4:5678".parse().unwrap())).await,
+            Some("1.2.3.4".parse().unwrap())
+        );
+    }
 }
@@ -0,0 +1,140 @@
+// A minimal S3-compatible server for exercising BucketUploader in tests.
+// It supports just what BucketUploader uses: PUT, DELETE, and ListObjectsV2.
+
+use axum::{
+    Router,
+    body::Bytes,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode, header::{CONTENT_DISPOSITION, ETAG}},
+    response::IntoResponse,
+    routing::{get, put}
+};
+use chrono::{DateTime, SecondsFormat, Utc};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Write,
+    sync::{Arc, Mutex}
+};
+use tokio::net::TcpListener;
+
+use crate::upload::BucketUploader;
+
+pub const BUCKET: &str = "test-bucket";
+pub const BASE_URL: &str = "https://example.com";
+
+struct FakeObject {
+    size: usize,
+    last_modified: DateTime<Utc>,
+    content_disposition: Option<String>
+}
+
+#[derive(Clone, Default)]
+pub struct FakeS3 {
+    objects: Arc<Mutex<BTreeMap<String, FakeObject>>>
+}
+
+impl FakeS3 {
+    pub async fn start() -> (FakeS3, String) {
+        let s3 = FakeS3::default();
+
+        let app = Router::new()
+            .route("/{bucket}/", get(list_objects))
+            .route("/{bucket}/{*key}", put(put_object).delete(delete_object))
+            .with_state(s3.clone());
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        (s3, endpoint)
+    }
+
+    pub fn uploader(endpoint: &str, base_dir: &str) -> BucketUploader {
+        BucketUploader::new(
+            BUCKET,
+            "us-east-1",
+            endpoint,
+            "access",
+            "secret",
+            BASE_URL,
+            base_dir
+        ).unwrap()
+    }
+
+    pub fn insert(&self, key: &str, size: usize, last_modified: DateTime<Utc>) {
+        self.objects.lock()
+            .unwrap()
+            .insert(
+                key.into(),
+                FakeObject { size, last_modified, content_disposition: None }
+            );
+    }
+
+    pub fn content_disposition(&self, key: &str) -> Option<String> {
+        self.objects.lock()
+            .unwrap()
+            .get(key)
+            .and_then(|o| o.content_disposition.clone())
+    }
+
+    pub fn keys(&self) -> Vec<String> {
+        self.objects.lock().unwrap().keys().cloned().collect()
+    }
+}
+
+async fn list_objects(
+    Path(bucket): Path<String>,
+    Query(params): Query<HashMap<String, String>>,
+    State(s3): State<FakeS3>
+) -> impl IntoResponse
+{
+    let prefix = params.get("prefix").map(String::as_str).unwrap_or("");
+
+    let mut contents = String::new();
+    for (key, obj) in s3.objects.lock().unwrap().iter() {
+        if key.starts_with(prefix) {
+            write!(
+                contents,
+                "<Contents><Key>{key}</Key><LastModified>{}</LastModified><ETag>\"etag\"</ETag><Size>{}</Size><StorageClass>STANDARD</StorageClass></Contents>",
+                obj.last_modified.to_rfc3339_opts(SecondsFormat::Millis, true),
+                obj.size
+            ).unwrap();
+        }
+    }
+
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?><ListBucketResult xmlns=\"http://s3.amazonaws.com/doc/2006-03-01/\"><Name>{bucket}</Name><Prefix>{prefix}</Prefix><MaxKeys>1000</MaxKeys><IsTruncated>false</IsTruncated>{contents}</ListBucketResult>"
+    )
+}
+
+async fn put_object(
+    Path((_, key)): Path<(String, String)>,
+    State(s3): State<FakeS3>,
+    headers: HeaderMap,
+    body: Bytes
+) -> impl IntoResponse
+{
+    let content_disposition = headers.get(CONTENT_DISPOSITION)
+        .and_then(|v| v.to_str().ok())
+        .map(String::from);
+
+    s3.objects.lock().unwrap().insert(
+        key,
+        FakeObject {
+            size: body.len(),
+            last_modified: Utc::now(),
+            content_disposition
+        }
+    );
+    (StatusCode::OK, [(ETAG, "\"etag\"")])
+}
+
+async fn delete_object(
+    Path((_, key)): Path<(String, String)>,
+    State(s3): State<FakeS3>
+) -> StatusCode
+{
+    s3.objects.lock().unwrap().remove(&key);
+    StatusCode::NO_CONTENT
+}
@@ -101,7 +101,7 @@ VALUES
     NULL
   );
 
-INSERT INTO files (
+INSERT INTO files_history (
   file_id,
   release_id,
   url,
@@ -110,6 +110,9 @@ INSERT INTO files (
   sha256,
   content_type,
   requires,
+  requires_major,
+  requires_minor,
+  requires_patch,
   published_at,
   published_by
 )
@@ -123,6 +126,9 @@ VALUES
     "c0e0fa7373a12b45a91e4f4d4e2e186442fc6ee9b346caa2fdc1c09026a2144a",
     "",
     ">= 3.2.17",
+    3,
+    2,
+    17,
     1702137389180282477,
     1
   ),
@@ -135,6 +141,9 @@ VALUES
     "79fdd8fe3128f818e446e919cce5dcfb81815f8f4341c53f4d6b58ded48cebf2",
     "",
     ">= 3.7.12",
+    3,
+    7,
+    12,
     1702223789180282477,
     2
   ),
@@ -147,6 +156,9 @@ VALUES
     "a8f515e9e2de99919d1a987733296aaa951a4ba2aa0f7014c510bdbd60dc0efd",
     "",
     NULL,
+    NULL,
+    NULL,
+    NULL,
     1702655789180282477,
     3
   );
@@ -2,7 +2,8 @@ use async_tempfile::TempFile;
 use axum::{
     body::Bytes,
     extract::{Path, Request, State},
-    response::{Json, Redirect}
+    http::HeaderMap,
+    response::{IntoResponse, Json, Redirect, Response}
 };
 use axum_extra::{
     extract::Query as MultiQuery,
@@ -12,25 +13,33 @@ use axum_extra::{
 use futures::{Stream, TryStreamExt};
 use glc::{
     discourse::UserUpdatePost,
-    model::{Flags, ProjectData, Projects, Publishers, Tags, Users}
+    model::{Flags, OrphanedObjects, ProjectData, Projects, ReleaseData, SharedFiles, Suggestions, Tags, UploadStatus, UserMergeReport, Users}
 };
 use http_body_util::{BodyExt, Limited, LengthLimitError};
 use sha2::{Digest, Sha256};
 use std::{
     error::Error,
-    io
+    io::{self, SeekFrom},
+    sync::Arc,
+    time::Instant
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncSeekExt, AsyncWrite, AsyncWriteExt, BufWriter},
+    sync::OwnedMutexGuard
 };
-use tokio::io::{AsyncReadExt, AsyncWrite, AsyncWriteExt, BufWriter};
 use tokio_util::io::{InspectWriter, StreamReader};
-use tracing::info;
+use tracing::{info, warn};
 
 use crate::{
     core::CoreArc,
+    digest::{check_sha256, expected_sha256},
+    downloads::RecentDownloads,
     errors::AppError,
-    extractors::{DiscourseEvent, ProjectPackage, ProjectPackageRelease, Wrapper},
-    input::{FlagPost, GalleryPatch, PackageDataPatch, PackageDataPost, ProjectDataPatch, ProjectDataPost},
-    model::{Admin, Flag, Owned, Project, User},
-    params::ProjectsParams,
+    extractors::{ClientAddr, DiscourseEvent, ProjectPackage, ProjectPackageRelease, Wrapper},
+    input::{FlagPost, GalleryPatch, PackageDataPatch, PackageDataPost, ProjectDataPatch, ProjectDataPost, ProjectRenamePost, PublisherMergePost, PublisherRenamePost, ReleaseDataPatch, ReleaseDataPost, UserMergePost},
+    model::{Admin, Flag, Owned, Owner, Project, Release, User},
+    params::{DeleteParams, DigestParams, GarbageParams, ImageParams, LatestParams, ProjectParams, ProjectsParams, PublishersParams, SuggestParams},
+    resumable::{UploadSession, UploadSessions},
     upload::safe_filename
 };
 
@@ -58,10 +67,19 @@ pub async fn projects_get(
 
 pub async fn project_get(
     proj: Project,
+    Wrapper(MultiQuery(params)): Wrapper<MultiQuery<ProjectParams>>,
     State(core): State<CoreArc>
 ) -> Result<Json<ProjectData>, AppError>
 {
-    Ok(Json(core.get_project(proj).await?))
+    Ok(
+        Json(
+            match params.vassal {
+                Some(vassal) =>
+                    core.get_project_compatible(proj, &vassal).await?,
+                None => core.get_project(proj).await?
+            }
+        )
+    )
 }
 
 pub async fn project_post(
@@ -83,6 +101,23 @@ pub async fn project_patch(
     Ok(core.update_project(owner, proj, &proj_data).await?)
 }
 
+pub async fn project_delete(
+    requester: User,
+    proj: Project,
+    State(admins): State<Arc<Vec<User>>>,
+    State(core): State<CoreArc>
+) -> Result<(), AppError>
+{
+    // owners and admins may delete projects
+    if admins.binary_search(&requester).is_err() &&
+        !core.user_is_owner(requester, proj).await?
+    {
+        return Err(AppError::Forbidden);
+    }
+
+    Ok(core.delete_project(requester, proj).await?)
+}
+
 pub async fn project_revision_get(
     proj: Project,
     Path((_, revision)): Path<(String, u32)>,
@@ -167,29 +202,109 @@ pub async fn package_patch(
 pub async fn package_delete(
     Owned(owner, proj): Owned,
     ProjectPackage(_, pkg): ProjectPackage,
+    Wrapper(MultiQuery(params)): Wrapper<MultiQuery<DeleteParams>>,
     State(core): State<CoreArc>
 ) -> Result<(), AppError>
 {
-    Ok(core.delete_package(owner, proj, pkg).await?)
+    Ok(core.delete_package(owner, proj, pkg, params.cascade).await?)
+}
+
+pub async fn release_latest_get(
+    ProjectPackage(proj, pkg): ProjectPackage,
+    Wrapper(MultiQuery(params)): Wrapper<MultiQuery<LatestParams>>,
+    State(core): State<CoreArc>
+) -> Result<Json<ReleaseData>, AppError>
+{
+    Ok(
+        Json(
+            core.get_latest_release(
+                proj,
+                pkg,
+                params.pre,
+                params.vassal.as_ref()
+            ).await?
+        )
+    )
+}
+
+pub async fn file_latest_get(
+    ProjectPackage(proj, pkg): ProjectPackage,
+    Path((_, _, filename)): Path<(String, String, String)>,
+    Wrapper(MultiQuery(params)): Wrapper<MultiQuery<LatestParams>>,
+    State(core): State<CoreArc>
+) -> Result<Redirect, AppError>
+{
+    Ok(
+        Redirect::to(
+            &core.get_latest_file(
+                proj,
+                pkg,
+                &filename,
+                params.pre,
+                params.vassal.as_ref()
+            ).await?
+        )
+    )
+}
+
+pub async fn file_latest_download(
+    ProjectPackage(proj, pkg): ProjectPackage,
+    ClientAddr(client): ClientAddr,
+    Path((_, _, filename)): Path<(String, String, String)>,
+    Wrapper(MultiQuery(params)): Wrapper<MultiQuery<LatestParams>>,
+    State(core): State<CoreArc>,
+    State(downloads): State<Arc<RecentDownloads>>
+) -> Result<Redirect, AppError>
+{
+    let url = core.get_latest_file(
+        proj,
+        pkg,
+        &filename,
+        params.pre,
+        params.vassal.as_ref()
+    ).await?;
+
+    // counting is best-effort; failing to count must not fail the download
+    if downloads.first_in_window(client, proj, Instant::now()) &&
+        let Err(e) = core.add_download(proj).await
+    {
+        warn!("failed to count download of project {}: {e}", proj.0);
+    }
+
+    Ok(Redirect::to(&url))
 }
 
 pub async fn release_post(
     Owned(owner, proj): Owned,
     ProjectPackage(_, pkg): ProjectPackage,
     Path((_, _, version)): Path<(String, String, String)>,
-    State(core): State<CoreArc>
+    State(core): State<CoreArc>,
+    rel_data: Option<Wrapper<Json<ReleaseDataPost>>>
 ) -> Result<(), AppError>
 {
-    Ok(core.create_release(owner, proj, pkg, &version).await?)
+    // the changelog and release date may be given at creation
+    let rel_data = rel_data.map(|Wrapper(Json(d))| d).unwrap_or_default();
+    Ok(core.create_release(owner, proj, pkg, &version, &rel_data).await?)
+}
+
+pub async fn release_patch(
+    Owned(owner, proj): Owned,
+    ProjectPackageRelease(_, _, release): ProjectPackageRelease,
+    State(core): State<CoreArc>,
+    Wrapper(Json(rel_data)): Wrapper<Json<ReleaseDataPatch>>
+) -> Result<(), AppError>
+{
+    Ok(core.update_release(owner, proj, release, &rel_data).await?)
 }
 
 pub async fn release_delete(
     Owned(owner, proj): Owned,
     ProjectPackageRelease(_, _, release): ProjectPackageRelease,
+    Wrapper(MultiQuery(params)): Wrapper<MultiQuery<DeleteParams>>,
     State(core): State<CoreArc>
 ) -> Result<(), AppError>
 {
-    Ok(core.delete_release(owner, proj, release).await?)
+    Ok(core.delete_release(owner, proj, release, params.cascade).await?)
 }
 
 fn unpack_limited_error(e: Box<dyn Error + Sync + Send>) -> io::Error {
@@ -406,12 +521,15 @@ where
     Ok((file, size, sha256))
 }
 
+#[allow(clippy::too_many_arguments)]
 pub async fn file_post(
     Owned(owner, proj): Owned,
     ProjectPackageRelease(_, _, release): ProjectPackageRelease,
     Path((_, _, _, filename)): Path<(String, String, String, String)>,
+    Wrapper(MultiQuery(params)): Wrapper<MultiQuery<DigestParams>>,
     content_type: Option<TypedHeader<ContentType>>,
     content_length: Option<TypedHeader<ContentLength>>,
+    headers: HeaderMap,
     State(core): State<CoreArc>,
     request: Request
 ) -> Result<(), AppError>
@@ -421,12 +539,16 @@ pub async fn file_post(
         core.max_file_size()
     )?;
 
+    let expected = expected_sha256(&headers, params.sha256.as_deref())?;
+
     let (mut file, size, sha256) = stream_to_temp_file(
         &filename,
         core.upload_dir(),
         into_limited_stream(request, limit)
     ).await?;
 
+    check_sha256(&expected, &sha256)?;
+
     let path = file.file_path().to_owned();
 
     Ok(
@@ -444,11 +566,256 @@ pub async fn file_post(
     )
 }
 
+pub async fn file_delete(
+    Owned(owner, proj): Owned,
+    ProjectPackageRelease(_, _, release): ProjectPackageRelease,
+    Path((_, _, _, filename)): Path<(String, String, String, String)>,
+    State(core): State<CoreArc>
+) -> Result<(), AppError>
+{
+    Ok(core.delete_file(owner, proj, release, &filename).await?)
+}
+
+const UPLOAD_LENGTH: &str = "upload-length";
+const UPLOAD_OFFSET: &str = "upload-offset";
+
+fn upload_header(
+    headers: &HeaderMap,
+    name: &str
+) -> Result<Option<u64>, AppError>
+{
+    headers.get(name)
+        .map(|v| v.to_str()
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .ok_or(AppError::MalformedUpload)
+        )
+        .transpose()
+}
+
+fn upload_status(id: &str, session: &UploadSession) -> UploadStatus {
+    UploadStatus {
+        id: id.into(),
+        offset: session.offset,
+        length: session.length
+    }
+}
+
+fn lock_upload_session(
+    uploads: &UploadSessions,
+    id: &str,
+    owner: Owner,
+    proj: Project,
+    release: Release,
+    filename: &str
+) -> Result<OwnedMutexGuard<UploadSession>, AppError>
+{
+    let session = uploads.get(id)
+        .ok_or(AppError::NotFound)?
+        // another request is using this session
+        .try_lock_owned()
+        .or(Err(AppError::Conflict))?;
+
+    match session.belongs_to(owner, proj, release, filename) {
+        true => Ok(session),
+        false => Err(AppError::NotFound)
+    }
+}
+
+pub async fn file_upload_post(
+    Owned(owner, proj): Owned,
+    ProjectPackageRelease(_, _, release): ProjectPackageRelease,
+    Path((_, _, _, filename)): Path<(String, String, String, String)>,
+    content_type: Option<TypedHeader<ContentType>>,
+    headers: HeaderMap,
+    State(core): State<CoreArc>,
+    State(uploads): State<Arc<UploadSessions>>
+) -> Result<Json<UploadStatus>, AppError>
+{
+    // ensure the filename is valid
+    safe_filename(&filename).or(Err(AppError::MalformedQuery))?;
+
+    // reject declared lengths which are too large up front
+    let length = upload_header(&headers, UPLOAD_LENGTH)?;
+    if length.is_some() {
+        limit_content_length(length, core.max_file_size())?;
+    }
+
+    let file = TempFile::new_in(core.upload_dir())
+        .await
+        .map_err(|e| AppError::InternalError(e.to_string()))?;
+
+    info!("created upload file {}", file.file_path().display());
+
+    let session = UploadSession::new(
+        owner,
+        proj,
+        release,
+        &filename,
+        content_type.map(|h| h.0.into()),
+        length,
+        file
+    );
+
+    let id = uploads.insert(session).or(Err(AppError::TooManyRequests))?;
+
+    Ok(
+        Json(
+            UploadStatus {
+                id,
+                offset: 0,
+                length
+            }
+        )
+    )
+}
+
+pub async fn file_upload_get(
+    Owned(owner, proj): Owned,
+    ProjectPackageRelease(_, _, release): ProjectPackageRelease,
+    Path((_, _, _, filename, id)): Path<(String, String, String, String, String)>,
+    State(uploads): State<Arc<UploadSessions>>
+) -> Result<Json<UploadStatus>, AppError>
+{
+    let session = lock_upload_session(
+        &uploads, &id, owner, proj, release, &filename
+    )?;
+
+    Ok(Json(upload_status(&id, &session)))
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn file_upload_patch(
+    Owned(owner, proj): Owned,
+    ProjectPackageRelease(_, _, release): ProjectPackageRelease,
+    Path((_, _, _, filename, id)): Path<(String, String, String, String, String)>,
+    content_length: Option<TypedHeader<ContentLength>>,
+    headers: HeaderMap,
+    State(core): State<CoreArc>,
+    State(uploads): State<Arc<UploadSessions>>,
+    request: Request
+) -> Result<Json<UploadStatus>, AppError>
+{
+    let mut session = lock_upload_session(
+        &uploads, &id, owner, proj, release, &filename
+    )?;
+
+    // the client must say where it thinks the chunk goes
+    let offset = upload_header(&headers, UPLOAD_OFFSET)?
+        .ok_or(AppError::MalformedUpload)?;
+
+    if offset != session.offset {
+        return Err(AppError::Conflict);
+    }
+
+    // the chunk may not take the file past the declared or maximum size
+    let max_size = core.max_file_size() as u64;
+    let max_size = session.length.map_or(max_size, |l| l.min(max_size));
+    let remaining = usize::try_from(max_size.saturating_sub(session.offset))
+        .unwrap_or(usize::MAX);
+
+    let limit = limit_content_length(
+        content_length.map(|cl| cl.0.0),
+        remaining
+    )?;
+
+    let (mut chunk, _, _) = stream_to_temp_file(
+        &filename,
+        core.upload_dir(),
+        into_limited_stream(request, limit)
+    ).await?;
+
+    if let Err(e) = session.append(&mut chunk).await {
+        // the session file is in an unknown state now
+        uploads.remove(&id);
+        return Err(AppError::InternalError(e.to_string()));
+    }
+
+    Ok(Json(upload_status(&id, &session)))
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn file_upload_finalize(
+    Owned(owner, proj): Owned,
+    ProjectPackageRelease(_, _, release): ProjectPackageRelease,
+    Path((_, _, _, filename, id)): Path<(String, String, String, String, String)>,
+    Wrapper(MultiQuery(params)): Wrapper<MultiQuery<DigestParams>>,
+    headers: HeaderMap,
+    State(core): State<CoreArc>,
+    State(uploads): State<Arc<UploadSessions>>
+) -> Result<(), AppError>
+{
+    let expected = expected_sha256(&headers, params.sha256.as_deref())?;
+
+    let mut session = lock_upload_session(
+        &uploads, &id, owner, proj, release, &filename
+    )?;
+
+    // the upload must be complete if a length was declared
+    if session.length.is_some_and(|l| l != session.offset) {
+        return Err(AppError::MalformedUpload);
+    }
+
+    // a mismatch leaves the session intact, so the client can inspect it
+    let sha256 = session.sha256();
+    check_sha256(&expected, &sha256)?;
+
+    let size = session.offset;
+    let content_type = session.content_type.clone();
+    let path = session.file.file_path().to_owned();
+
+    session.file.rewind()
+        .await
+        .map_err(|e| AppError::InternalError(e.to_string()))?;
+
+    let added = core.add_file(
+        owner,
+        proj,
+        release,
+        &filename,
+        content_type.as_ref(),
+        size,
+        &sha256,
+        &path,
+        &mut session.file
+    ).await;
+
+    match added {
+        Ok(()) => {
+            uploads.remove(&id);
+            Ok(())
+        },
+        Err(e) => {
+            // keep the session so the client can retry without uploading
+            // again; any further chunks go at the end
+            session.file.seek(SeekFrom::End(0))
+                .await
+                .map_err(|e| AppError::InternalError(e.to_string()))?;
+            Err(e.into())
+        }
+    }
+}
+
+pub async fn file_upload_delete(
+    Owned(owner, proj): Owned,
+    ProjectPackageRelease(_, _, release): ProjectPackageRelease,
+    Path((_, _, _, filename, id)): Path<(String, String, String, String, String)>,
+    State(uploads): State<Arc<UploadSessions>>
+) -> Result<(), AppError>
+{
+    lock_upload_session(&uploads, &id, owner, proj, release, &filename)?;
+    uploads.remove(&id);
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
 pub async fn gallery_post(
     Owned(owner, proj): Owned,
     Path((_, img_name)): Path<(String, String)>,
+    Wrapper(MultiQuery(params)): Wrapper<MultiQuery<DigestParams>>,
     content_type: Option<TypedHeader<ContentType>>,
     content_length: Option<TypedHeader<ContentLength>>,
+    headers: HeaderMap,
     State(core): State<CoreArc>,
     request: Request
 ) -> Result<(), AppError>
@@ -462,12 +829,16 @@ pub async fn gallery_post(
     // have to make it optional and check manually
     let content_type = content_type.ok_or(AppError::BadMimeType)?.0.into();
 
+    let expected = expected_sha256(&headers, params.sha256.as_deref())?;
+
     let (mut file, size, sha256) = stream_to_temp_file(
         &img_name,
         core.upload_dir(),
         into_limited_stream(request, limit)
     ).await?;
 
+    check_sha256(&expected, &sha256)?;
+
     let path = file.file_path().to_owned();
 
     Ok(
@@ -496,10 +867,11 @@ pub async fn gallery_patch(
 pub async fn image_get(
     proj: Project,
     Path((_, img_name)): Path<(String, String)>,
+    Wrapper(MultiQuery(params)): Wrapper<MultiQuery<ImageParams>>,
     State(core): State<CoreArc>
 ) -> Result<Redirect, AppError>
 {
-    Ok(Redirect::to(&core.get_image(proj, &img_name).await?))
+    Ok(Redirect::to(&core.get_image(proj, &img_name, params.size).await?))
 }
 
 pub async fn image_revision_get(
@@ -515,11 +887,14 @@ pub async fn image_revision_get(
     )
 }
 
+#[allow(clippy::too_many_arguments)]
 pub async fn image_post(
     Owned(owner, proj): Owned,
     Path((_, img_name)): Path<(String, String)>,
+    Wrapper(MultiQuery(params)): Wrapper<MultiQuery<DigestParams>>,
     content_type: Option<TypedHeader<ContentType>>,
     content_length: Option<TypedHeader<ContentLength>>,
+    headers: HeaderMap,
     State(core): State<CoreArc>,
     request: Request
 ) -> Result<(), AppError>
@@ -533,12 +908,16 @@ pub async fn image_post(
     // have to make it optional and check manually
     let content_type = content_type.ok_or(AppError::BadMimeType)?.0.into();
 
+    let expected = expected_sha256(&headers, params.sha256.as_deref())?;
+
     let (mut file, size, sha256) = stream_to_temp_file(
         &img_name,
         core.upload_dir(),
         into_limited_stream(request, limit)
     ).await?;
 
+    check_sha256(&expected, &sha256)?;
+
     let path = file.file_path().to_owned();
 
     Ok(
@@ -556,10 +935,16 @@ pub async fn image_post(
 }
 
 pub async fn publishers_get(
+    Wrapper(MultiQuery(params)): Wrapper<MultiQuery<PublishersParams>>,
     State(core): State<CoreArc>
-) -> Result<Json<Publishers>, AppError>
+) -> Result<Response, AppError>
 {
-    Ok(Json(core.get_publishers().await?))
+    Ok(
+        match params.counts {
+            true => Json(core.get_publisher_summaries().await?).into_response(),
+            false => Json(core.get_publishers().await?).into_response()
+        }
+    )
 }
 
 pub async fn tags_get(
@@ -569,6 +954,22 @@ pub async fn tags_get(
     Ok(Json(core.get_tags().await?))
 }
 
+pub async fn suggest_get(
+    Wrapper(MultiQuery(params)): Wrapper<MultiQuery<SuggestParams>>,
+    State(core): State<CoreArc>
+) -> Result<Json<Suggestions>, AppError>
+{
+    Ok(
+        Json(
+            core.get_suggestions(
+                params.kind,
+                &params.q,
+                params.limit.unwrap_or_default()
+            ).await?
+        )
+    )
+}
+
 pub async fn flag_post(
     requester: User,
     proj: Project,
@@ -596,11 +997,97 @@ pub async fn admin_flags_get(
     Ok(Json(core.get_flags().await?))
 }
 
+pub async fn admin_files_get(
+    _admin: Admin,
+    Path(sha256): Path<String>,
+    State(core): State<CoreArc>
+) -> Result<Json<SharedFiles>, AppError>
+{
+    Ok(Json(core.get_shared_files(&sha256).await?))
+}
+
+// the default age, in seconds, an unreferenced object must reach
+// before it is collected
+const DEFAULT_GC_GRACE: u64 = 7 * 24 * 60 * 60;
+
+// Objects referenced by any revision, current or past, are retained, as
+// they are still served for past states of projects; only objects which
+// were never referenced are collected.
+
+pub async fn admin_gc_post(
+    _admin: Admin,
+    Wrapper(MultiQuery(params)): Wrapper<MultiQuery<GarbageParams>>,
+    State(core): State<CoreArc>
+) -> Result<Json<OrphanedObjects>, AppError>
+{
+    Ok(
+        Json(
+            core.collect_garbage(
+                params.dry_run,
+                params.grace.unwrap_or(DEFAULT_GC_GRACE)
+            ).await?
+        )
+    )
+}
+
+pub async fn admin_project_restore(
+    admin: Admin,
+    Path(proj): Path<String>,
+    State(core): State<CoreArc>
+) -> Result<(), AppError>
+{
+    Ok(core.restore_project(admin, &proj).await?)
+}
+
+pub async fn admin_project_rename(
+    admin: Admin,
+    proj: Project,
+    State(core): State<CoreArc>,
+    Wrapper(Json(proj_data)): Wrapper<Json<ProjectRenamePost>>
+) -> Result<(), AppError>
+{
+    Ok(core.rename_project(admin, proj, &proj_data).await?)
+}
+
+pub async fn admin_publisher_rename(
+    _admin: Admin,
+    Path(publisher): Path<String>,
+    State(core): State<CoreArc>,
+    Wrapper(Json(publisher_data)): Wrapper<Json<PublisherRenamePost>>
+) -> Result<(), AppError>
+{
+    Ok(core.rename_publisher(&publisher, &publisher_data).await?)
+}
+
+pub async fn admin_publisher_merge(
+    _admin: Admin,
+    Path(publisher): Path<String>,
+    State(core): State<CoreArc>,
+    Wrapper(Json(merge)): Wrapper<Json<PublisherMergePost>>
+) -> Result<(), AppError>
+{
+    Ok(core.merge_publishers(&publisher, &merge.into).await?)
+}
+
+pub async fn admin_user_merge(
+    _admin: Admin,
+    Path(src): Path<String>,
+    State(core): State<CoreArc>,
+    Wrapper(Json(merge)): Wrapper<Json<UserMergePost>>
+) -> Result<Json<UserMergeReport>, AppError>
+{
+    let src = core.get_user_id(&src).await?;
+    let dst = core.get_user_id(&merge.into).await?;
+    Ok(Json(core.merge_users(src, dst).await?))
+}
+
 pub async fn admin_user_event_post(
     State(core): State<CoreArc>,
     DiscourseEvent(data): DiscourseEvent<UserUpdatePost>
 ) -> Result<(), AppError>
 {
+    // TODO: Discourse user events do not say when accounts are merged,
+    // so merges must be done through /admin/users/{user}/merge for now
     Ok(core.update_user(&data.user).await?)
 }
 
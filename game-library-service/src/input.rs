@@ -1,4 +1,5 @@
-use glc::model::FlagTag;
+use chrono::DateTime;
+use glc::model::{FlagTag, ReleaseState};
 use regex::Regex;
 use serde::{Deserialize, Deserializer, Serialize};
 use std::sync::LazyLock;
@@ -163,6 +164,141 @@ impl TryFrom<MaybePackageDataPatch> for PackageDataPatch {
     }
 }
 
+const CHANGELOG_MAX_LENGTH: usize = 65536;
+const YANK_REASON_MAX_LENGTH: usize = 256;
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct MaybeReleaseDataPatch {
+    #[serde(default, deserialize_with = "double_option")]
+    pub changelog: Option<Option<String>>,
+    pub released_at: Option<String>,
+    pub state: Option<ReleaseState>,
+    pub yank_reason: Option<String>
+}
+
+impl MaybeReleaseDataPatch {
+    fn is_empty(&self) -> bool {
+        matches!(
+            self,
+            MaybeReleaseDataPatch {
+                changelog: None,
+                released_at: None,
+                state: None,
+                yank_reason: None
+            }
+        )
+    }
+
+    fn is_valid(&self) -> bool {
+        !self.is_empty() &&
+        // check changelog
+        match &self.changelog {
+            Some(Some(c)) => c.len() <= CHANGELOG_MAX_LENGTH,
+            _ => true
+        } &&
+        // a yank reason is required for yanking and forbidden otherwise
+        match (&self.state, &self.yank_reason) {
+            (Some(ReleaseState::Yanked), Some(r)) =>
+                !r.is_empty() &&
+                r.len() <= YANK_REASON_MAX_LENGTH &&
+                r == r.trim(),
+            (_, None) => self.state != Some(ReleaseState::Yanked),
+            (_, Some(_)) => false
+        }
+    }
+}
+
+fn rfc3339_to_nanos(s: &str) -> Option<i64> {
+    DateTime::parse_from_rfc3339(s)
+        .ok()
+        .and_then(|dt| dt.timestamp_nanos_opt())
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(try_from = "MaybeReleaseDataPatch")]
+pub struct ReleaseDataPatch {
+    pub changelog: Option<Option<String>>,
+    pub released_at: Option<i64>,
+    pub state: Option<ReleaseState>,
+    pub yank_reason: Option<String>
+}
+
+#[derive(Debug, thiserror::Error, Eq, PartialEq)]
+#[error("invalid data {0:?}")]
+pub struct ReleaseDataPatchError(MaybeReleaseDataPatch);
+
+impl TryFrom<MaybeReleaseDataPatch> for ReleaseDataPatch {
+    type Error = ReleaseDataPatchError;
+
+    fn try_from(m: MaybeReleaseDataPatch) -> Result<Self, Self::Error> {
+        // at least one element must be present to be a valid request,
+        // field lengths must be within bounds, dates must parse, and
+        // yanking must give a reason
+        if !m.is_valid() {
+            return Err(ReleaseDataPatchError(m));
+        }
+
+        let released_at = match &m.released_at {
+            Some(r) => match rfc3339_to_nanos(r) {
+                Some(r) => Some(r),
+                None => return Err(ReleaseDataPatchError(m))
+            },
+            None => None
+        };
+
+        Ok(
+            ReleaseDataPatch {
+                changelog: m.changelog,
+                released_at,
+                state: m.state,
+                yank_reason: m.yank_reason
+            }
+        )
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct MaybeReleaseDataPost {
+    pub changelog: Option<String>,
+    pub released_at: Option<String>
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(try_from = "MaybeReleaseDataPost")]
+pub struct ReleaseDataPost {
+    pub changelog: Option<String>,
+    pub released_at: Option<i64>
+}
+
+#[derive(Debug, thiserror::Error, Eq, PartialEq)]
+#[error("invalid data {0:?}")]
+pub struct ReleaseDataPostError(MaybeReleaseDataPost);
+
+impl TryFrom<MaybeReleaseDataPost> for ReleaseDataPost {
+    type Error = ReleaseDataPostError;
+
+    fn try_from(m: MaybeReleaseDataPost) -> Result<Self, Self::Error> {
+        if m.changelog.as_ref().is_some_and(|c| c.len() > CHANGELOG_MAX_LENGTH) {
+            return Err(ReleaseDataPostError(m));
+        }
+
+        let released_at = match &m.released_at {
+            Some(r) => match rfc3339_to_nanos(r) {
+                Some(r) => Some(r),
+                None => return Err(ReleaseDataPostError(m))
+            },
+            None => None
+        };
+
+        Ok(
+            ReleaseDataPost {
+                changelog: m.changelog,
+                released_at
+            }
+        )
+    }
+}
+
 fn double_option<'de, T, D>(de: D) -> Result<Option<Option<T>>, D::Error>
 where
     T: Deserialize<'de>,
@@ -476,6 +612,56 @@ impl TryFrom<MaybeProjectDataPost> for ProjectDataPost {
     }
 }
 
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ProjectRenamePost {
+    pub name: String
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct MaybePublisherRenamePost {
+    pub name: String
+}
+
+impl MaybePublisherRenamePost {
+    fn is_valid(&self) -> bool {
+        !self.name.is_empty() &&
+        self.name.len() <= GAME_PUBLISHER_MAX_LENGTH &&
+        self.name == self.name.trim() &&
+        !self.name.has_consecutive_whitespace()
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(try_from = "MaybePublisherRenamePost")]
+pub struct PublisherRenamePost {
+    pub name: String
+}
+
+#[derive(Debug, thiserror::Error, Eq, PartialEq)]
+#[error("invalid data {0:?}")]
+pub struct PublisherRenamePostError(MaybePublisherRenamePost);
+
+impl TryFrom<MaybePublisherRenamePost> for PublisherRenamePost {
+    type Error = PublisherRenamePostError;
+
+    fn try_from(m: MaybePublisherRenamePost) -> Result<Self, Self::Error> {
+        match m.is_valid() {
+            true => Ok(PublisherRenamePost { name: m.name }),
+            false => Err(PublisherRenamePostError(m))
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct PublisherMergePost {
+    pub into: String
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct UserMergePost {
+    pub into: String
+}
+
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
 #[serde(tag = "op", rename_all = "lowercase")]
 pub enum MaybeGalleryOp {
@@ -842,6 +1028,232 @@ mod test {
         );
     }
 
+    #[test]
+    fn try_from_release_data_patch_ok() {
+        assert_eq!(
+            ReleaseDataPatch::try_from(
+                MaybeReleaseDataPatch {
+                    changelog: Some(Some("* Fixed the map".into())),
+                    released_at: Some("2023-12-09T15:56:29.180282477Z".into()),
+                    ..Default::default()
+                }
+            ).unwrap(),
+            ReleaseDataPatch {
+                changelog: Some(Some("* Fixed the map".into())),
+                released_at: Some(1702137389180282477),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn try_from_release_data_patch_clear_changelog() {
+        assert_eq!(
+            ReleaseDataPatch::try_from(
+                MaybeReleaseDataPatch {
+                    changelog: Some(None),
+                    ..Default::default()
+                }
+            ).unwrap(),
+            ReleaseDataPatch {
+                changelog: Some(None),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn try_from_release_data_patch_yank() {
+        assert_eq!(
+            ReleaseDataPatch::try_from(
+                MaybeReleaseDataPatch {
+                    state: Some(ReleaseState::Yanked),
+                    yank_reason: Some("corrupts saves".into()),
+                    ..Default::default()
+                }
+            ).unwrap(),
+            ReleaseDataPatch {
+                state: Some(ReleaseState::Yanked),
+                yank_reason: Some("corrupts saves".into()),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn try_from_release_data_patch_pre_release() {
+        assert_eq!(
+            ReleaseDataPatch::try_from(
+                MaybeReleaseDataPatch {
+                    state: Some(ReleaseState::PreRelease),
+                    ..Default::default()
+                }
+            ).unwrap(),
+            ReleaseDataPatch {
+                state: Some(ReleaseState::PreRelease),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn try_from_release_data_patch_yank_no_reason() {
+        let mrdp = MaybeReleaseDataPatch {
+            state: Some(ReleaseState::Yanked),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            ReleaseDataPatch::try_from(mrdp.clone()).unwrap_err(),
+            ReleaseDataPatchError(mrdp)
+        );
+    }
+
+    #[test]
+    fn try_from_release_data_patch_yank_reason_not_trimmed() {
+        let mrdp = MaybeReleaseDataPatch {
+            state: Some(ReleaseState::Yanked),
+            yank_reason: Some(" corrupts saves".into()),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            ReleaseDataPatch::try_from(mrdp.clone()).unwrap_err(),
+            ReleaseDataPatchError(mrdp)
+        );
+    }
+
+    #[test]
+    fn try_from_release_data_patch_reason_not_yanked() {
+        let mrdp = MaybeReleaseDataPatch {
+            state: Some(ReleaseState::Stable),
+            yank_reason: Some("corrupts saves".into()),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            ReleaseDataPatch::try_from(mrdp.clone()).unwrap_err(),
+            ReleaseDataPatchError(mrdp)
+        );
+    }
+
+    #[test]
+    fn try_from_release_data_patch_reason_no_state() {
+        let mrdp = MaybeReleaseDataPatch {
+            yank_reason: Some("corrupts saves".into()),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            ReleaseDataPatch::try_from(mrdp.clone()).unwrap_err(),
+            ReleaseDataPatchError(mrdp)
+        );
+    }
+
+    #[test]
+    fn try_from_release_data_post_ok() {
+        assert_eq!(
+            ReleaseDataPost::try_from(
+                MaybeReleaseDataPost {
+                    changelog: Some("* Fixed the map".into()),
+                    released_at: Some("2023-12-09T15:56:29.180282477Z".into())
+                }
+            ).unwrap(),
+            ReleaseDataPost {
+                changelog: Some("* Fixed the map".into()),
+                released_at: Some(1702137389180282477)
+            }
+        );
+    }
+
+    #[test]
+    fn try_from_release_data_post_empty() {
+        assert_eq!(
+            ReleaseDataPost::try_from(MaybeReleaseDataPost::default()).unwrap(),
+            ReleaseDataPost::default()
+        );
+    }
+
+    #[test]
+    fn try_from_release_data_post_bad_released_at() {
+        let mrdp = MaybeReleaseDataPost {
+            released_at: Some("yesterday".into()),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            ReleaseDataPost::try_from(mrdp.clone()).unwrap_err(),
+            ReleaseDataPostError(mrdp)
+        );
+    }
+
+    #[test]
+    fn try_from_release_data_post_overlong_changelog() {
+        let mrdp = MaybeReleaseDataPost {
+            changelog: Some("x".repeat(CHANGELOG_MAX_LENGTH + 1)),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            ReleaseDataPost::try_from(mrdp.clone()).unwrap_err(),
+            ReleaseDataPostError(mrdp)
+        );
+    }
+
+    #[test]
+    fn maybe_release_data_patch_from_json_bad_state() {
+        let json = r#"{"state": "withdrawn"}"#;
+        assert!(serde_json::from_str::<MaybeReleaseDataPatch>(json).is_err());
+    }
+
+    #[test]
+    fn try_from_release_data_patch_overlong_changelog() {
+        let mrdp = MaybeReleaseDataPatch {
+            changelog: Some(Some("x".repeat(CHANGELOG_MAX_LENGTH + 1))),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            ReleaseDataPatch::try_from(mrdp.clone()).unwrap_err(),
+            ReleaseDataPatchError(mrdp)
+        );
+    }
+
+    #[test]
+    fn try_from_release_data_patch_bad_date() {
+        let mrdp = MaybeReleaseDataPatch {
+            released_at: Some("last Tuesday".into()),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            ReleaseDataPatch::try_from(mrdp.clone()).unwrap_err(),
+            ReleaseDataPatchError(mrdp)
+        );
+    }
+
+    #[test]
+    fn try_from_release_data_patch_empty() {
+        let mrdp = MaybeReleaseDataPatch::default();
+
+        assert_eq!(
+            ReleaseDataPatch::try_from(mrdp.clone()).unwrap_err(),
+            ReleaseDataPatchError(mrdp)
+        );
+    }
+
+    #[test]
+    fn maybe_release_data_patch_from_json_changelog_clear() {
+        let json = r#"{"changelog": null}"#;
+        assert_eq!(
+            serde_json::from_str::<MaybeReleaseDataPatch>(json).unwrap(),
+            MaybeReleaseDataPatch {
+                changelog: Some(None),
+                ..Default::default()
+            }
+        );
+    }
+
     #[test]
     fn try_from_project_data_post_ok() {
         assert_eq!(
@@ -1559,6 +1971,54 @@ mod test {
         );
     }
 
+    #[test]
+    fn try_from_maybe_publisher_rename_post_ok() {
+        assert_eq!(
+            PublisherRenamePost::try_from(
+                MaybePublisherRenamePost { name: "Avalon Hill".into() }
+            ),
+            Ok(PublisherRenamePost { name: "Avalon Hill".into() })
+        );
+    }
+
+    #[test]
+    fn try_from_maybe_publisher_rename_post_empty() {
+        let m = MaybePublisherRenamePost { name: "".into() };
+        assert_eq!(
+            PublisherRenamePost::try_from(m.clone()),
+            Err(PublisherRenamePostError(m))
+        );
+    }
+
+    #[test]
+    fn try_from_maybe_publisher_rename_post_overlong() {
+        let m = MaybePublisherRenamePost {
+            name: "x".repeat(GAME_PUBLISHER_MAX_LENGTH + 1)
+        };
+        assert_eq!(
+            PublisherRenamePost::try_from(m.clone()),
+            Err(PublisherRenamePostError(m))
+        );
+    }
+
+    #[test]
+    fn try_from_maybe_publisher_rename_post_untrimmed() {
+        let m = MaybePublisherRenamePost { name: " x ".into() };
+        assert_eq!(
+            PublisherRenamePost::try_from(m.clone()),
+            Err(PublisherRenamePostError(m))
+        );
+    }
+
+    #[test]
+    fn try_from_maybe_publisher_rename_post_consecutive_whitespace() {
+        let m = MaybePublisherRenamePost { name: "x  x".into() };
+        assert_eq!(
+            PublisherRenamePost::try_from(m.clone()),
+            Err(PublisherRenamePostError(m))
+        );
+    }
+
     #[test]
     fn try_from_maybe_range_post_none_none() {
         assert_eq!(
@@ -15,7 +15,7 @@ use std::{
     fs,
     io,
     net::IpAddr,
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::Arc,
     time::Duration
 };
@@ -23,6 +23,7 @@ use tower::ServiceBuilder;
 use tower_http::{
     compression::CompressionLayer,
     cors::CorsLayer,
+    services::ServeDir,
     timeout::TimeoutLayer,
     trace::{DefaultOnFailure, DefaultOnResponse, TraceLayer}
 };
@@ -33,8 +34,12 @@ mod config;
 mod content_type;
 mod core;
 mod db;
+mod digest;
+mod downloads;
 mod errors;
 mod extractors;
+#[cfg(test)]
+mod fake_s3;
 mod handlers;
 mod input;
 mod jwt;
@@ -42,6 +47,8 @@ mod model;
 mod module;
 mod params;
 mod prod_core;
+mod rendition;
+mod resumable;
 mod sqlite;
 mod time;
 mod upload;
@@ -50,12 +57,15 @@ mod version;
 use crate::{
     app::{AppState, DiscourseUpdateConfig},
     config::Config,
-    core::{Core, CoreArc},
+    core::CoreArc,
+    downloads::RecentDownloads,
     prod_core::ProdCore,
     errors::AppError,
+    rendition::ImageLimits,
     jwt::DecodingKey,
+    resumable::UploadSessions,
     sqlite::SqlxDatabaseClient,
-    upload::{BucketUploader, LocalUploader},
+    upload::{BucketUploader, LocalUploader, Uploader, local_objects_dir},
 };
 
 impl From<&AppError> for StatusCode {
@@ -64,7 +74,10 @@ impl From<&AppError> for StatusCode {
             AppError::BadMimeType => StatusCode::UNSUPPORTED_MEDIA_TYPE,
             AppError::TooLarge => StatusCode::PAYLOAD_TOO_LARGE,
             AppError::CannotRemoveLastOwner => StatusCode::BAD_REQUEST,
+            AppError::CannotMergeIntoSelf => StatusCode::BAD_REQUEST,
+            AppError::Conflict => StatusCode::CONFLICT,
             AppError::DatabaseError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            AppError::DigestMismatch => StatusCode::BAD_REQUEST,
             AppError::UploadError(_) => StatusCode::BAD_REQUEST,
             AppError::ModuleError(_) => StatusCode::BAD_REQUEST,
             AppError::InternalError(_) => StatusCode::INTERNAL_SERVER_ERROR,
@@ -79,6 +92,7 @@ impl From<&AppError> for StatusCode {
             AppError::NotAUser => StatusCode::NOT_FOUND,
             AppError::NotFound => StatusCode::NOT_FOUND,
             AppError::NotEmpty => StatusCode::BAD_REQUEST,
+            AppError::TooManyRequests => StatusCode::TOO_MANY_REQUESTS,
             AppError::Forbidden => StatusCode::FORBIDDEN,
             AppError::Unauthorized => StatusCode::UNAUTHORIZED
         }
@@ -117,7 +131,8 @@ fn routes(
     api: &str,
     read_only: bool,
     log_headers: bool,
-    upload_timeout: u64
+    upload_timeout: u64,
+    local_uploads: Option<&Path>
 ) -> Router<AppState> {
     // set up our routes under api
     let api_router = Router::new()
@@ -130,10 +145,12 @@ fn routes(
             if read_only {
                 post(handlers::forbidden)
                 .patch(handlers::forbidden)
+                .delete(handlers::forbidden)
             }
             else {
                 post(handlers::project_post)
                 .patch(handlers::project_patch)
+                .delete(handlers::project_delete)
             }
             .get(handlers::project_get)
         )
@@ -178,15 +195,31 @@ fn routes(
                 .delete(handlers::package_delete)
             }
         )
+        .route(
+            "/projects/{proj}/packages/{pkg_name}/latest",
+            get(handlers::release_latest_get)
+        )
+        .route(
+            "/projects/{proj}/packages/{pkg_name}/latest/{file}",
+            if read_only {
+                get(handlers::file_latest_get)
+            }
+            else {
+                // downloads are counted only when writable
+                get(handlers::file_latest_download)
+            }
+        )
         .route(
             "/projects/{proj}/packages/{pkg_name}/{version}",
             if read_only {
                 post(handlers::forbidden)
+                .patch(handlers::forbidden)
                 .delete(handlers::forbidden)
             }
             else {
 // FIXME: release_version_post?
                 post(handlers::release_post)
+                .patch(handlers::release_patch)
                 .delete(handlers::release_delete)
             }
         )
@@ -239,10 +272,27 @@ fn routes(
             "/tags",
              get(handlers::tags_get)
         )
+        .route(
+            "/suggest",
+            get(handlers::suggest_get)
+        )
         .route(
             "/admin/flags",
             get(handlers::admin_flags_get)
         )
+        .route(
+            "/admin/files/{sha256}",
+            get(handlers::admin_files_get)
+        )
+        .route(
+            "/admin/gc",
+            if read_only {
+                post(handlers::forbidden)
+            }
+            else {
+                post(handlers::admin_gc_post)
+            }
+        )
         .route(
             "/admin/flags/{flag}",
             if read_only {
@@ -252,6 +302,51 @@ fn routes(
                 patch(handlers::admin_flag_close)
             }
         )
+        .route(
+            "/admin/projects/{proj}/restore",
+            if read_only {
+                post(handlers::forbidden)
+            }
+            else {
+                post(handlers::admin_project_restore)
+            }
+        )
+        .route(
+            "/admin/projects/{proj}/rename",
+            if read_only {
+                post(handlers::forbidden)
+            }
+            else {
+                post(handlers::admin_project_rename)
+            }
+        )
+        .route(
+            "/admin/publishers/{publisher}/rename",
+            if read_only {
+                post(handlers::forbidden)
+            }
+            else {
+                post(handlers::admin_publisher_rename)
+            }
+        )
+        .route(
+            "/admin/publishers/{publisher}/merge",
+            if read_only {
+                post(handlers::forbidden)
+            }
+            else {
+                post(handlers::admin_publisher_merge)
+            }
+        )
+        .route(
+            "/admin/users/{user}/merge",
+            if read_only {
+                post(handlers::forbidden)
+            }
+            else {
+                post(handlers::admin_user_merge)
+            }
+        )
         .route(
             "/admin/userEvent",
             if read_only {
@@ -269,6 +364,7 @@ fn routes(
             "/projects/{proj}/packages/{pkg_name}/{version}/{file}",
             if read_only {
                 post(handlers::forbidden)
+                .delete(handlers::forbidden)
             }
             else {
                 post(handlers::file_post)
@@ -276,9 +372,46 @@ fn routes(
                         StatusCode::REQUEST_TIMEOUT,
                         Duration::from_secs(upload_timeout))
                     )
+                .delete(handlers::file_delete)
+            }
+        )
+        .route(
+            "/projects/{proj}/packages/{pkg_name}/{version}/{file}/uploads",
+            if read_only {
+                post(handlers::forbidden)
+            }
+            else {
+                post(handlers::file_upload_post)
+            }
+        )
+        .route(
+            "/projects/{proj}/packages/{pkg_name}/{version}/{file}/uploads/{upload_id}",
+            if read_only {
+                get(handlers::forbidden)
+                .patch(handlers::forbidden)
+                .post(handlers::forbidden)
+                .delete(handlers::forbidden)
+            }
+            else {
+                get(handlers::file_upload_get)
+                .patch(handlers::file_upload_patch)
+                .post(handlers::file_upload_finalize)
+                .delete(handlers::file_upload_delete)
             }
+            .layer(TimeoutLayer::with_status_code(
+                StatusCode::REQUEST_TIMEOUT,
+                Duration::from_secs(upload_timeout))
+            )
         );
 
+    // serve uploaded files ourselves if they are not in a bucket; note that
+    // the Content-Type is guessed from the file extension, not the type
+    // recorded when the file was uploaded
+    let api_router = match local_uploads {
+        Some(dir) => api_router.nest_service("/uploads", ServeDir::new(dir)),
+        None => api_router
+    };
+
     // set up things wrapped around our routes
     Router::new()
         .route(
@@ -318,11 +451,36 @@ enum StartupError {
     NoUploadsDirectory
 }
 
+fn make_core<U>(
+    db: SqlxDatabaseClient<sqlx::sqlite::Sqlite>,
+    uploader: U,
+    config: &Config,
+    upload_dir: PathBuf
+) -> CoreArc
+where
+    U: Uploader + Send + Sync + 'static
+{
+    Arc::new(
+        ProdCore {
+            db,
+            uploader,
+            now: Utc::now,
+            max_image_size: config.max_image_size << 20, // MB to bytes
+            max_file_size: config.max_file_size << 20,   // MB to bytes
+            image_limits: ImageLimits {
+                max_width: config.max_image_width,
+                max_height: config.max_image_height
+            },
+            upload_dir
+        }
+    )
+}
+
 async fn run() -> Result<(), StartupError> {
     info!("Reading config.toml");
     let config: Config = toml::from_str(&fs::read_to_string("config.toml")?)?;
 
-    let upload_dir = PathBuf::from(config.upload_dir);
+    let upload_dir = PathBuf::from(&config.upload_dir);
     if !upload_dir.is_dir() {
         return Err(StartupError::NoUploadsDirectory);
     }
@@ -333,22 +491,35 @@ async fn run() -> Result<(), StartupError> {
         .connect(&format!("sqlite://{}", &config.db_path))
         .await?;
 
-    let core = ProdCore {
-        db: SqlxDatabaseClient(db_pool),
-//        uploader: LocalUploader { uploads_directory: "uploads".into() },
-        uploader: BucketUploader::new(
-            &config.bucket_name,
-            &config.bucket_region,
-            &config.bucket_endpoint,
-            &config.bucket_access_key,
-            &config.bucket_secret_key,
-            &config.bucket_base_url,
-            &config.bucket_base_dir
-        )?,
-        now: Utc::now,
-        max_image_size: config.max_image_size << 20, // MB to bytes
-        max_file_size: config.max_file_size << 20,   // MB to bytes
-        upload_dir
+    let db = SqlxDatabaseClient(db_pool);
+
+    let core = if config.local_uploads {
+        info!("Storing uploads in {}", upload_dir.display());
+        make_core(
+            db,
+            LocalUploader {
+                uploads_directory: upload_dir.clone(),
+                base_url: config.local_uploads_base_url.clone()
+            },
+            &config,
+            upload_dir.clone()
+        )
+    }
+    else {
+        make_core(
+            db,
+            BucketUploader::new(
+                &config.bucket_name,
+                &config.bucket_region,
+                &config.bucket_endpoint,
+                &config.bucket_access_key,
+                &config.bucket_secret_key,
+                &config.bucket_base_url,
+                &config.bucket_base_dir
+            )?,
+            &config,
+            upload_dir.clone()
+        )
     };
 
     let mut admins = try_join_all(
@@ -361,18 +532,29 @@ async fn run() -> Result<(), StartupError> {
         secret: config.discourse_update_secret.into_bytes()
     };
 
+    let uploads = Arc::new(UploadSessions::default());
+    tokio::spawn(uploads.clone().prune_periodically());
+
+    let downloads = Arc::new(RecentDownloads::default());
+    tokio::spawn(downloads.clone().prune_periodically());
+
     let state = AppState {
         key: Arc::new(DecodingKey::from_secret(config.jwt_key.as_bytes())),
-        core: Arc::new(core) as CoreArc,
+        core,
         admins: Arc::new(admins),
-        discourse_update_config: Arc::new(duc)
+        discourse_update_config: Arc::new(duc),
+        uploads,
+        downloads
     };
 
+    let objects_dir = local_objects_dir(&upload_dir);
+
     let app: Router = routes(
         &config.api_base_path,
         config.read_only,
         config.log_headers,
         config.upload_timeout,
+        config.local_uploads.then_some(objects_dir.as_path())
     ).with_state(state);
 
     let ip: IpAddr = config.listen_ip.parse()?;
@@ -409,7 +591,7 @@ mod test {
     };
     use futures::Stream;
     use glc::{
-        model::{FlagData, Flags, FlagTag, GameData, FileData, PackageData, ProjectData, Projects, ProjectSummary, Range, ReleaseData, Users},
+        model::{FacetCounts, FlagData, Flags, FlagTag, GameData, FileData, PackageData, ProjectData, Projects, ProjectSummary, PublisherSummaries, PublisherSummary, Publishers, OrphanedObject, OrphanedObjects, Range, ReleaseData, ReleaseState, SharedFile, SharedFiles, SuggestKind, Suggestions, UploadStatus, UserMergeReport, Users},
         pagination::{Anchor, Direction, Limit, SortBy, Pagination, Seek, SeekLink}
     };
     use mime::{APPLICATION_JSON, IMAGE_PNG, TEXT_PLAIN, Mime};
@@ -422,11 +604,14 @@ mod test {
     use tower::ServiceExt; // for oneshot
 
     use crate::{
-        core::{AddFileError, AddFlagError, AddImageError, AddOwnersError, AddPlayerError, CloseFlagError, Core, CreatePackageError, CreateProjectError, CreateReleaseError, DeletePackageError, DeleteReleaseError, GetFlagsError, GetIdError, GetImageError, GetOwnersError, GetPlayersError, GetProjectError, GetProjectsError, RemoveOwnersError, RemovePlayerError, UpdateGalleryError, UpdatePackageError, UpdateProjectError, UserIsOwnerError},
-        input::{FlagPost, GalleryPatch, GalleryOp, GameDataPost, PackageDataPatch, PackageDataPost, ProjectDataPatch, ProjectDataPost, RangePost},
+        core::{AddDownloadError, AddFileError, AddFlagError, AddImageError, AddOwnersError, AddPlayerError, CloseFlagError, CollectGarbageError, Core, CreatePackageError, CreateProjectError, CreateReleaseError, DeleteFileError, DeletePackageError, DeleteProjectError, DeleteReleaseError, GetFlagsError, GetIdError, GetImageError, GetOwnersError, GetPlayersError, GetProjectError, GetProjectsError, GetPublishersError, GetSharedFilesError, GetSuggestionsError, MergePublishersError, MergeUsersError, RemoveOwnersError, RemovePlayerError, RenameProjectError, RenamePublisherError, RestoreProjectError, UpdateGalleryError, UpdatePackageError, UpdateProjectError, UpdateReleaseError, UserIsOwnerError},
+        db::DatabaseError,
+        input::{FlagPost, GalleryPatch, GalleryOp, GameDataPost, PackageDataPatch, PackageDataPost, ProjectDataPatch, ProjectDataPost, ProjectRenamePost, PublisherMergePost, PublisherRenamePost, RangePost, ReleaseDataPatch, ReleaseDataPost, UserMergePost},
         jwt::{self, EncodingKey},
         model::{Admin, Flag, Owner, Package, Project, Release, User},
-        params::ProjectsParams
+        params::ProjectsParams,
+        resumable::MAX_SESSIONS_PER_OWNER,
+        version::Version
     };
 
     const API_V1: &str = "/api/v1";
@@ -515,6 +700,10 @@ mod test {
                     releases: vec![
                         ReleaseData {
                             version: "1.2.3".into(),
+                            changelog: Some("* Initial release".into()),
+                            released_at: "2023-10-30T18:53:53.056386142Z".into(),
+                            state: ReleaseState::Stable,
+                            yank_reason: None,
                             files: vec![
                                 FileData {
                                     filename: "eia.vmod".into(),
@@ -523,7 +712,11 @@ mod test {
                                     sha256: "deadbeef".into(),
                                     published_at: "2023-10-30T18:53:53.056386142Z".into(),
                                     published_by: "alice".into(),
-                                    requires: None
+                                    requires: None,
+                                    module_name: None,
+                                    sides: vec![],
+                                    maps: vec![],
+                                    extends: None
                                 }
                             ]
                         }
@@ -705,7 +898,11 @@ mod test {
                             )
                         ),
                         total: 1234
-                    }
+                    },
+                    counts: params.counts.then(|| FacetCounts {
+                        tags: [("WWII".into(), 142)].into(),
+                        ..Default::default()
+                    })
                 }
             )
         }
@@ -718,6 +915,17 @@ mod test {
             Ok(EIA_PROJECT_DATA.clone())
         }
 
+        async fn get_project_compatible(
+            &self,
+            _proj: Project,
+            _vassal: &Version
+        ) -> Result<ProjectData, GetProjectError>
+        {
+            let mut proj_data = EIA_PROJECT_DATA.clone();
+            proj_data.packages[0].releases.clear();
+            Ok(proj_data)
+        }
+
         async fn create_project(
             &self,
             _user: User,
@@ -738,6 +946,40 @@ mod test {
             Ok(())
         }
 
+        async fn delete_project(
+            &self,
+            _requester: User,
+            _proj: Project
+        ) -> Result<(), DeleteProjectError>
+        {
+            Ok(())
+        }
+
+        async fn restore_project(
+            &self,
+            _admin: Admin,
+            proj: &str
+        ) -> Result<(), RestoreProjectError>
+        {
+            match proj {
+                "deleted_project" => Ok(()),
+                _ => Err(RestoreProjectError::NotFound)
+            }
+        }
+
+        async fn rename_project(
+            &self,
+            _admin: Admin,
+            _proj: Project,
+            proj_data: &ProjectRenamePost
+        ) -> Result<(), RenameProjectError>
+        {
+            match proj_data.name.as_str() {
+                "taken_project" => Err(RenameProjectError::AlreadyExists),
+                _ => Ok(())
+            }
+        }
+
         async fn get_project_revision(
             &self,
             proj: Project,
@@ -780,20 +1022,55 @@ mod test {
             _owner: Owner,
             _proj: Project,
             pkg: Package,
+            cascade: bool
         ) -> Result<(), DeletePackageError>
         {
-            match pkg {
-                Package(1) => Ok(()),
+            match (pkg, cascade) {
+                (Package(1), _) | (_, true) => Ok(()),
                 _ => Err(DeletePackageError::NotEmpty)
             }
         }
 
+        async fn get_latest_release(
+            &self,
+            _proj: Project,
+            pkg: Package,
+            _pre: bool,
+            _vassal: Option<&Version>
+        ) -> Result<ReleaseData, GetProjectError>
+        {
+            match pkg {
+                Package(1) => Ok(
+                    EIA_PROJECT_DATA.packages[0].releases[0].clone()
+                ),
+                _ => Err(GetProjectError::NotFound)
+            }
+        }
+
+        async fn get_latest_file(
+            &self,
+            _proj: Project,
+            pkg: Package,
+            filename: &str,
+            _pre: bool,
+            _vassal: Option<&Version>
+        ) -> Result<String, GetProjectError>
+        {
+            match (pkg, filename) {
+                (Package(1), "eia.vmod") => Ok(
+                    "https://example.com/eia.vmod".into()
+                ),
+                _ => Err(GetProjectError::NotFound)
+            }
+        }
+
         async fn create_release(
             &self,
             _owner: Owner,
             proj: Project,
             pkg: Package,
             version: &str,
+            _rel_data: &ReleaseDataPost
         ) -> Result<(), CreateReleaseError>
         {
             match (proj, pkg, version) {
@@ -802,15 +1079,27 @@ mod test {
             }
         }
 
+        async fn update_release(
+            &self,
+            _owner: Owner,
+            _proj: Project,
+            _rel: Release,
+            _rel_data: &ReleaseDataPatch
+        ) -> Result<(), UpdateReleaseError>
+        {
+            Ok(())
+        }
+
         async fn delete_release(
             &self,
             _owner: Owner,
             _proj: Project,
-            rel: Release
+            rel: Release,
+            cascade: bool
         ) -> Result<(), DeleteReleaseError>
         {
-            match rel {
-                Release(1) => Ok(()),
+            match (rel, cascade) {
+                (Release(1), _) | (_, true) => Ok(()),
                 _ => Err(DeleteReleaseError::NotEmpty)
             }
         }
@@ -851,11 +1140,15 @@ mod test {
         async fn get_image(
             &self,
             proj: Project,
-            img_name: &str
+            img_name: &str,
+            size: Option<u32>
         ) -> Result<String, GetImageError>
         {
             if proj == Project(1) && img_name == "img.png" {
-                Ok("https://example.com/img.png".into())
+                match size {
+                    Some(256) => Ok("https://example.com/img.png.256.webp".into()),
+                    _ => Ok("https://example.com/img.png".into())
+                }
             }
             else {
                 Err(GetImageError::NotFound)
@@ -917,7 +1210,7 @@ mod test {
             _owner: Owner,
             _proj: Project,
             _release: Release,
-            _filename: &str,
+            filename: &str,
             _content_type: Option<&Mime>,
             _size: u64,
             _sha256: &str,
@@ -925,7 +1218,24 @@ mod test {
             mut _file: &mut File
         ) -> Result<(), AddFileError>
         {
-            Ok(())
+            match filename {
+                "bad.txt" => Err(AddFileError::BadMimeType),
+                _ => Ok(())
+            }
+        }
+
+        async fn delete_file(
+            &self,
+            _owner: Owner,
+            _proj: Project,
+            _release: Release,
+            filename: &str
+        ) -> Result<(), DeleteFileError>
+        {
+            match filename {
+                "war_and_peace.txt" => Ok(()),
+                _ => Err(DeleteFileError::NotFound)
+            }
         }
 
         async fn get_flag_id(
@@ -978,6 +1288,170 @@ mod test {
                 }
             )
         }
+
+        async fn get_shared_files(
+            &self,
+            sha256: &str
+        ) -> Result<SharedFiles, GetSharedFilesError>
+        {
+            Ok(
+                SharedFiles {
+                    sha256: sha256.into(),
+                    files: vec![
+                        SharedFile {
+                            project: "a_project".into(),
+                            slug: "a_project".into(),
+                            package: "a_package".into(),
+                            version: "1.2.3".into(),
+                            filename: "a_package-1.2.3.vmod".into(),
+                            url: "https://example.com/a_package-1.2.3.vmod".into(),
+                            size: 1234,
+                            published_at: "2023-10-30T18:53:53.056386142Z".into(),
+                            published_by: "bob".into()
+                        }
+                    ]
+                }
+            )
+        }
+
+        async fn collect_garbage(
+            &self,
+            dry_run: bool,
+            grace: u64
+        ) -> Result<OrphanedObjects, CollectGarbageError>
+        {
+            // pretend there is one orphan, a week old
+            Ok(
+                OrphanedObjects {
+                    dry_run,
+                    objects: match grace {
+                        0..=604800 => vec![
+                            OrphanedObject {
+                                url: "https://example.com/orphan.png".into(),
+                                size: 1234,
+                                last_modified: "2023-10-30T18:53:53.056386142Z".into()
+                            }
+                        ],
+                        _ => vec![]
+                    }
+                }
+            )
+        }
+
+        async fn get_publishers(
+            &self
+        ) -> Result<Publishers, GetPublishersError>
+        {
+            Ok(
+                Publishers {
+                    publishers: vec!["A Company".into(), "XYZ".into()]
+                }
+            )
+        }
+
+        async fn get_publisher_summaries(
+            &self
+        ) -> Result<PublisherSummaries, GetPublishersError>
+        {
+            Ok(
+                PublisherSummaries {
+                    publishers: vec![
+                        PublisherSummary {
+                            name: "A Company".into(),
+                            projects: 3
+                        },
+                        PublisherSummary {
+                            name: "XYZ".into(),
+                            projects: 1
+                        }
+                    ]
+                }
+            )
+        }
+
+        async fn add_download(
+            &self,
+            _proj: Project
+        ) -> Result<(), AddDownloadError>
+        {
+            Err(AddDownloadError::DatabaseError(DatabaseError::NotFound))
+        }
+
+        async fn get_suggestions(
+            &self,
+            kind: SuggestKind,
+            q: &str,
+            limit: Limit
+        ) -> Result<Suggestions, GetSuggestionsError>
+        {
+            Ok(
+                Suggestions {
+                    suggestions: match kind {
+                        SuggestKind::Tag => ["WWII", "WWI"].into_iter()
+                            .filter(|t| t.starts_with(q))
+                            .take(limit.get() as usize)
+                            .map(String::from)
+                            .collect(),
+                        _ => vec![]
+                    }
+                }
+            )
+        }
+
+        async fn rename_publisher(
+            &self,
+            publisher: &str,
+            publisher_data: &PublisherRenamePost
+        ) -> Result<(), RenamePublisherError>
+        {
+            match (publisher, publisher_data.name.as_str()) {
+                ("XYZ", "A Company") => Err(RenamePublisherError::AlreadyExists),
+                ("XYZ", _) => Ok(()),
+                _ => Err(RenamePublisherError::NotFound)
+            }
+        }
+
+        async fn merge_publishers(
+            &self,
+            src: &str,
+            dst: &str
+        ) -> Result<(), MergePublishersError>
+        {
+            match (src, dst) {
+                _ if src == dst => Err(MergePublishersError::CannotMergeIntoSelf),
+                ("XYZ", "A Company") => Ok(()),
+                _ => Err(MergePublishersError::NotFound)
+            }
+        }
+
+        async fn get_user_id(
+            &self,
+            username: &str
+        ) -> Result<User, GetIdError>
+        {
+            match username {
+                "bob" => Ok(User(1)),
+                "alice" => Ok(User(2)),
+                _ => Err(GetIdError::NotFound)
+            }
+        }
+
+        async fn merge_users(
+            &self,
+            src: User,
+            dst: User
+        ) -> Result<UserMergeReport, MergeUsersError>
+        {
+            match src == dst {
+                true => Err(MergeUsersError::CannotMergeIntoSelf),
+                false => Ok(
+                    UserMergeReport {
+                        rewritten: [("owners.user_id".into(), 1)].into(),
+                        dropped: [("owners.user_id".into(), 0)].into()
+                    }
+                )
+            }
+        }
     }
 
     fn test_state() -> AppState {
@@ -985,7 +1459,9 @@ mod test {
             key: Arc::new(DecodingKey::from_secret(KEY)),
             core: Arc::new(TestCore {}) as CoreArc,
             admins: Arc::new(vec![User(5)]),
-            discourse_update_config: Default::default()
+            discourse_update_config: Default::default(),
+            uploads: Default::default(),
+            downloads: Default::default()
         }
     }
 
@@ -996,7 +1472,7 @@ mod test {
     }
 
     async fn try_request(request: Request<Body>, rw: bool) -> Response {
-        routes(API_V1, !rw, false, 10)
+        routes(API_V1, !rw, false, 10, None)
             .with_state(test_state())
             .oneshot(request)
             .await
@@ -1053,6 +1529,15 @@ mod test {
         );
     }
 
+    #[track_caller]
+    async fn assert_cannot_merge_into_self(response: Response) {
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(
+            body_as::<HttpError>(response).await,
+            HttpError::from(AppError::CannotMergeIntoSelf)
+        );
+    }
+
     #[track_caller]
     async fn assert_limit_out_of_range(response: Response) {
         assert_eq!(response.status(), StatusCode::BAD_REQUEST);
@@ -1265,7 +1750,8 @@ mod test {
                         )
                     ),
                     total: 1234
-                }
+                },
+                counts: None
             }
         );
     }
@@ -1282,11 +1768,11 @@ mod test {
         assert_get_projects_no_params_ok(response).await;
     }
 
-    async fn get_projects_limit_ok(rw: bool) -> Response {
+    async fn get_projects_counts_ok(rw: bool) -> Response {
         try_request(
             Request::builder()
                 .method(Method::GET)
-                .uri(format!("{API_V1}/projects?limit=5"))
+                .uri(format!("{API_V1}/projects?counts=true"))
                 .body(Body::empty())
                 .unwrap(),
             rw
@@ -1295,40 +1781,93 @@ mod test {
     }
 
     #[track_caller]
-    async fn assert_projects_limit_ok(response: Response) {
+    async fn assert_projects_counts_ok(response: Response) {
         assert_eq!(response.status(), StatusCode::OK);
         assert_eq!(
-            body_as::<Projects>(response).await,
-            Projects {
-                projects: vec![
-                    PROJECT_SUMMARY_A.clone(),
-                    PROJECT_SUMMARY_B.clone()
-                ],
-                meta: Pagination {
-                    prev_page: Some(
-                        SeekLink::new(
-                            &Seek {
-                                anchor: Anchor::Before("project_a".into(), 0),
-                                sort_by: SortBy::ProjectName,
-                                dir: Direction::Ascending,
-                                facets: vec![]
-                            },
-                            Limit::new(5)
-                        )
-                    ),
-                    next_page: Some(
-                        SeekLink::new(
-                            &Seek {
-                                anchor: Anchor::After("project_b".into(), 0),
-                                sort_by: SortBy::ProjectName,
-                                dir: Direction::Ascending,
-                                facets: vec![]
+            body_as::<Projects>(response).await.counts,
+            Some(
+                FacetCounts {
+                    tags: [("WWII".into(), 142)].into(),
+                    ..Default::default()
+                }
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn get_projects_counts_ok_rw() {
+        let response = get_projects_counts_ok(true).await;
+        assert_projects_counts_ok(response).await;
+    }
+
+    #[tokio::test]
+    async fn get_projects_counts_ok_ro() {
+        let response = get_projects_counts_ok(false).await;
+        assert_projects_counts_ok(response).await;
+    }
+
+    #[tokio::test]
+    async fn get_projects_counts_bad_rw() {
+        let response = try_request(
+            Request::builder()
+                .method(Method::GET)
+                .uri(format!("{API_V1}/projects?counts=maybe"))
+                .body(Body::empty())
+                .unwrap(),
+            true
+        )
+        .await;
+        assert_malformed_query(response).await;
+    }
+
+    async fn get_projects_limit_ok(rw: bool) -> Response {
+        try_request(
+            Request::builder()
+                .method(Method::GET)
+                .uri(format!("{API_V1}/projects?limit=5"))
+                .body(Body::empty())
+                .unwrap(),
+            rw
+        )
+        .await
+    }
+
+    #[track_caller]
+    async fn assert_projects_limit_ok(response: Response) {
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            body_as::<Projects>(response).await,
+            Projects {
+                projects: vec![
+                    PROJECT_SUMMARY_A.clone(),
+                    PROJECT_SUMMARY_B.clone()
+                ],
+                meta: Pagination {
+                    prev_page: Some(
+                        SeekLink::new(
+                            &Seek {
+                                anchor: Anchor::Before("project_a".into(), 0),
+                                sort_by: SortBy::ProjectName,
+                                dir: Direction::Ascending,
+                                facets: vec![]
+                            },
+                            Limit::new(5)
+                        )
+                    ),
+                    next_page: Some(
+                        SeekLink::new(
+                            &Seek {
+                                anchor: Anchor::After("project_b".into(), 0),
+                                sort_by: SortBy::ProjectName,
+                                dir: Direction::Ascending,
+                                facets: vec![]
                             },
                             Limit::new(5)
                         )
                     ),
                     total: 1234
-                }
+                },
+                counts: None
             }
         );
     }
@@ -1497,7 +2036,8 @@ mod test {
                         )
                     ),
                     total: 1234
-                }
+                },
+                counts: None
             }
         );
     }
@@ -1570,7 +2110,8 @@ mod test {
                         )
                     ),
                     total: 1234
-                }
+                },
+                counts: None
             }
         );
     }
@@ -1643,7 +2184,8 @@ mod test {
                         )
                     ),
                     total: 1234
-                }
+                },
+                counts: None
             }
         );
     }
@@ -1716,7 +2258,8 @@ mod test {
                         )
                     ),
                     total: 1234
-                }
+                },
+                counts: None
             }
         );
     }
@@ -1789,7 +2332,8 @@ mod test {
                         )
                     ),
                     total: 1234
-                }
+                },
+                counts: None
             }
         );
     }
@@ -1862,7 +2406,8 @@ mod test {
                         )
                     ),
                     total: 1234
-                }
+                },
+                counts: None
             }
         );
     }
@@ -1936,6 +2481,41 @@ mod test {
         assert_not_found(response).await;
     }
 
+    async fn get_project_vassal(rw: bool, vassal: &str) -> Response {
+        try_request(
+            Request::builder()
+                .method(Method::GET)
+                .uri(format!("{API_V1}/projects/a_project?vassal={vassal}"))
+                .body(Body::empty())
+                .unwrap(),
+            rw
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn get_project_vassal_ok_rw() {
+        let response = get_project_vassal(true, "3.7.12").await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(
+            body_as::<ProjectData>(response).await.packages[0]
+                .releases
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn get_project_vassal_ok_ro() {
+        let response = get_project_vassal(false, "3.7.12").await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn get_project_vassal_bad_rw() {
+        let response = get_project_vassal(true, "3.7").await;
+        assert_malformed_query(response).await;
+    }
+
     async fn post_project_ok(rw: bool) -> Response {
         let proj_data = ProjectDataPost {
             name: "name".into(),
@@ -2322,6 +2902,130 @@ mod test {
         assert_forbidden(response).await;
     }
 
+    async fn delete_project_ok(rw: bool) -> Response {
+        try_request(
+            Request::builder()
+                .method(Method::DELETE)
+                .uri(format!("{API_V1}/projects/a_project"))
+                .header(AUTHORIZATION, token(BOB_UID))
+                .body(Body::empty())
+                .unwrap(),
+            rw
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn delete_project_ok_rw() {
+        let response = delete_project_ok(true).await;
+        assert_ok(response).await;
+    }
+
+    #[tokio::test]
+    async fn delete_project_ok_ro() {
+        let response = delete_project_ok(false).await;
+        assert_forbidden(response).await;
+    }
+
+    async fn delete_project_admin_ok(rw: bool) -> Response {
+        try_request(
+            Request::builder()
+                .method(Method::DELETE)
+                .uri(format!("{API_V1}/projects/a_project"))
+                .header(AUTHORIZATION, token(5))
+                .body(Body::empty())
+                .unwrap(),
+            rw
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn delete_project_admin_ok_rw() {
+        let response = delete_project_admin_ok(true).await;
+        assert_ok(response).await;
+    }
+
+    #[tokio::test]
+    async fn delete_project_admin_ok_ro() {
+        let response = delete_project_admin_ok(false).await;
+        assert_forbidden(response).await;
+    }
+
+    async fn delete_project_not_owner(rw: bool) -> Response {
+        try_request(
+            Request::builder()
+                .method(Method::DELETE)
+                .uri(format!("{API_V1}/projects/a_project"))
+                .header(AUTHORIZATION, token(3))
+                .body(Body::empty())
+                .unwrap(),
+            rw
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn delete_project_not_owner_rw() {
+        let response = delete_project_not_owner(true).await;
+        assert_forbidden(response).await;
+    }
+
+    #[tokio::test]
+    async fn delete_project_not_owner_ro() {
+        let response = delete_project_not_owner(false).await;
+        assert_forbidden(response).await;
+    }
+
+    async fn delete_project_not_a_project(rw: bool) -> Response {
+        try_request(
+            Request::builder()
+                .method(Method::DELETE)
+                .uri(format!("{API_V1}/projects/not_a_project"))
+                .header(AUTHORIZATION, token(BOB_UID))
+                .body(Body::empty())
+                .unwrap(),
+            rw
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn delete_project_not_a_project_rw() {
+        let response = delete_project_not_a_project(true).await;
+        assert_not_found(response).await;
+    }
+
+    #[tokio::test]
+    async fn delete_project_not_a_project_ro() {
+        let response = delete_project_not_a_project(false).await;
+        assert_forbidden(response).await;
+    }
+
+    async fn delete_project_unauth(rw: bool) -> Response {
+        try_request(
+            Request::builder()
+                .method(Method::DELETE)
+                .uri(format!("{API_V1}/projects/a_project"))
+                .body(Body::empty())
+                .unwrap(),
+            rw
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn delete_project_unauth_rw() {
+        let response = delete_project_unauth(true).await;
+        assert_unauthorized(response).await;
+    }
+
+    #[tokio::test]
+    async fn delete_project_unauth_ro() {
+        let response = delete_project_unauth(false).await;
+        assert_forbidden(response).await;
+    }
+
     async fn get_project_revision_ok(rw: bool) -> Response {
         try_request(
             Request::builder()
@@ -3034,6 +3738,69 @@ mod test {
         assert_forbidden(response).await;
     }
 
+    async fn get_local_upload(path: &str, rw: bool) -> Response {
+        let dir = std::env::temp_dir().join(
+            format!(
+                "gls-local-uploads-{}-{rw}-{}",
+                std::process::id(),
+                path.replace('/', "_")
+            )
+        );
+        fs::create_dir_all(dir.join("a/b")).unwrap();
+        fs::write(dir.join("a/b/img.png"), b"not really a png").unwrap();
+
+        let response = routes(API_V1, !rw, false, 10, Some(&dir))
+            .with_state(test_state())
+            .oneshot(
+                Request::builder()
+                    .method(Method::GET)
+                    .uri(format!("{API_V1}/uploads/{path}"))
+                    .body(Body::empty())
+                    .unwrap()
+            )
+            .await
+            .unwrap();
+
+        fs::remove_dir_all(&dir).unwrap();
+        response
+    }
+
+    #[tokio::test]
+    async fn get_local_upload_ok_rw() {
+        let response = get_local_upload("a/b/img.png", true).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers().get(CONTENT_TYPE).unwrap(),
+            IMAGE_PNG.as_ref()
+        );
+        assert_eq!(
+            body_bytes(response).await,
+            b"not really a png".as_slice()
+        );
+    }
+
+    #[tokio::test]
+    async fn get_local_upload_ok_ro() {
+        let response = get_local_upload("a/b/img.png", false).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers().get(CONTENT_TYPE).unwrap(),
+            IMAGE_PNG.as_ref()
+        );
+    }
+
+    #[tokio::test]
+    async fn get_local_upload_not_found() {
+        let response = get_local_upload("a/b/bogus.png", true).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn get_local_upload_parent_dir() {
+        let response = get_local_upload("../a/b/img.png", true).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
     async fn get_image_ok(rw: bool) -> Response {
         try_request(
             Request::builder()
@@ -3068,11 +3835,11 @@ mod test {
         );
     }
 
-    async fn get_image_not_a_project(rw: bool) -> Response {
-         try_request(
+    async fn get_image_size_ok(rw: bool) -> Response {
+        try_request(
             Request::builder()
                 .method(Method::GET)
-                .uri(format!("{API_V1}/projects/not_a_project/images/img.png"))
+                .uri(format!("{API_V1}/projects/a_project/images/img.png?size=256"))
                 .body(Body::empty())
                 .unwrap(),
             rw
@@ -3081,22 +3848,32 @@ mod test {
     }
 
     #[tokio::test]
-    async fn get_image_not_a_project_rw() {
-        let response = get_image_not_a_project(true).await;
-        assert_not_found(response).await;
+    async fn get_image_size_ok_rw() {
+        let response = get_image_size_ok(true).await;
+
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+        assert_eq!(
+            response.headers().get(LOCATION).unwrap(),
+            "https://example.com/img.png.256.webp"
+        );
     }
 
     #[tokio::test]
-    async fn get_image_not_a_project_ro() {
-        let response = get_image_not_a_project(false).await;
-        assert_not_found(response).await;
+    async fn get_image_size_ok_ro() {
+        let response = get_image_size_ok(false).await;
+
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+        assert_eq!(
+            response.headers().get(LOCATION).unwrap(),
+            "https://example.com/img.png.256.webp"
+        );
     }
 
-    async fn get_image_not_an_image(rw: bool) -> Response {
+    async fn get_image_size_bad(rw: bool) -> Response {
         try_request(
             Request::builder()
                 .method(Method::GET)
-                .uri(format!("{API_V1}/projects/a_project/images/not_a.png"))
+                .uri(format!("{API_V1}/projects/a_project/images/img.png?size=x"))
                 .body(Body::empty())
                 .unwrap(),
             rw
@@ -3105,20 +3882,68 @@ mod test {
     }
 
     #[tokio::test]
-    async fn get_image_not_an_image_rw() {
-        let response = get_image_not_an_image(true).await;
-        assert_not_found(response).await;
+    async fn get_image_size_bad_rw() {
+        let response = get_image_size_bad(true).await;
+        assert_malformed_query(response).await;
     }
 
     #[tokio::test]
-    async fn get_image_not_an_image_ro() {
-        let response = get_image_not_an_image(false).await;
-        assert_not_found(response).await;
+    async fn get_image_size_bad_ro() {
+        let response = get_image_size_bad(false).await;
+        assert_malformed_query(response).await;
     }
 
-    async fn post_image_ok(rw: bool) -> Response {
-        try_request(
-            Request::builder()
+    async fn get_image_not_a_project(rw: bool) -> Response {
+         try_request(
+            Request::builder()
+                .method(Method::GET)
+                .uri(format!("{API_V1}/projects/not_a_project/images/img.png"))
+                .body(Body::empty())
+                .unwrap(),
+            rw
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn get_image_not_a_project_rw() {
+        let response = get_image_not_a_project(true).await;
+        assert_not_found(response).await;
+    }
+
+    #[tokio::test]
+    async fn get_image_not_a_project_ro() {
+        let response = get_image_not_a_project(false).await;
+        assert_not_found(response).await;
+    }
+
+    async fn get_image_not_an_image(rw: bool) -> Response {
+        try_request(
+            Request::builder()
+                .method(Method::GET)
+                .uri(format!("{API_V1}/projects/a_project/images/not_a.png"))
+                .body(Body::empty())
+                .unwrap(),
+            rw
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn get_image_not_an_image_rw() {
+        let response = get_image_not_an_image(true).await;
+        assert_not_found(response).await;
+    }
+
+    #[tokio::test]
+    async fn get_image_not_an_image_ro() {
+        let response = get_image_not_an_image(false).await;
+        assert_not_found(response).await;
+    }
+
+    async fn post_image_ok(rw: bool) -> Response {
+        try_request(
+            Request::builder()
                 .method(Method::POST)
                 .uri(format!("{API_V1}/projects/a_project/images/img.png"))
                 .header(AUTHORIZATION, token(BOB_UID))
@@ -3958,205 +4783,516 @@ mod test {
         assert_forbidden(response).await;
     }
 
-    async fn post_package_ok(rw: bool) -> Response {
-        let pd = PackageDataPost {
-            name: "pkg".into(),
-            sort_key: 4,
-            description: "".into()
-        };
+    const X_SHA256: &str = "2d711642b726b04401627ca9fbac32f5c8530fb1903cc4db02258717921a4881";
+    const X_SHA256_B64: &str = "LXEWQrcmsEQBYnyp+6wy9chTD7GQPMTbAiWHF5IaSIE=";
 
-        try_request(
-            Request::builder()
-                .method(Method::POST)
-                .uri(format!("{API_V1}/projects/a_project/packages/newpkg"))
-                .header(AUTHORIZATION, token(BOB_UID))
-                .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
-                .body(Body::from(serde_json::to_vec(&pd).unwrap()))
-                .unwrap(),
-            rw
-        )
-        .await
+    async fn post_file_digest(
+        rw: bool,
+        query: &str,
+        header: Option<(&str, String)>
+    ) -> Response
+    {
+        let mut builder = Request::builder()
+            .method(Method::POST)
+            .uri(format!("{API_V1}/projects/a_project/packages/a_package/1.2.3/war_and_peace.txt{query}"))
+            .header(AUTHORIZATION, token(BOB_UID))
+            .header(CONTENT_TYPE, TEXT_PLAIN.as_ref())
+            .header(CONTENT_LENGTH, 1);
+
+        if let Some((name, value)) = header {
+            builder = builder.header(name, value);
+        }
+
+        try_request(builder.body(Body::from("x")).unwrap(), rw).await
+    }
+
+    #[track_caller]
+    async fn assert_digest_mismatch(response: Response) {
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(
+            body_as::<HttpError>(response).await,
+            HttpError::from(AppError::DigestMismatch)
+        );
+    }
+
+    #[track_caller]
+    async fn assert_malformed_upload(response: Response) {
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(
+            body_as::<HttpError>(response).await,
+            HttpError::from(AppError::MalformedUpload)
+        );
     }
 
     #[tokio::test]
-    async fn post_package_ok_rw() {
-        let response = post_package_ok(true).await;
+    async fn post_file_content_digest_ok_rw() {
+        let response = post_file_digest(
+            true,
+            "",
+            Some(("content-digest", format!("sha-256=:{X_SHA256_B64}:")))
+        ).await;
         assert_ok(response).await;
     }
 
     #[tokio::test]
-    async fn post_package_ok_ro() {
-        let response = post_package_ok(false).await;
+    async fn post_file_content_digest_ok_ro() {
+        let response = post_file_digest(
+            false,
+            "",
+            Some(("content-digest", format!("sha-256=:{X_SHA256_B64}:")))
+        ).await;
         assert_forbidden(response).await;
     }
 
-    async fn post_package_not_a_project(rw: bool) -> Response {
-        let pd = PackageDataPost {
-            name: "pkg".into(),
-            sort_key: 4,
-            description: "".into()
-        };
+    #[tokio::test]
+    async fn post_file_repr_digest_ok_rw() {
+        let response = post_file_digest(
+            true,
+            "",
+            Some(("repr-digest", format!("sha-256=:{X_SHA256_B64}:")))
+        ).await;
+        assert_ok(response).await;
+    }
 
-        try_request(
-            Request::builder()
-                .method(Method::POST)
-                .uri(format!("{API_V1}/projects/not_a_project/packages/a_package"))
-                .header(AUTHORIZATION, token(BOB_UID))
-                .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
-                .body(Body::from(serde_json::to_vec(&pd).unwrap()))
-                .unwrap(),
-            rw
-        )
-        .await
+    #[tokio::test]
+    async fn post_file_sha256_param_ok_rw() {
+        let response = post_file_digest(
+            true,
+            &format!("?sha256={X_SHA256}"),
+            None
+        ).await;
+        assert_ok(response).await;
     }
 
     #[tokio::test]
-    async fn post_package_not_a_project_rw() {
-        let response = post_package_not_a_project(true).await;
-        assert_not_found(response).await;
+    async fn post_file_content_digest_mismatch_rw() {
+        let response = post_file_digest(
+            true,
+            "",
+            Some((
+                "content-digest",
+                "sha-256=:LPJNul+wow4m6DsqxbninhsWHlwfp0JecwQzYpOLmCQ=:".into()
+            ))
+        ).await;
+        assert_digest_mismatch(response).await;
     }
 
     #[tokio::test]
-    async fn post_package_not_a_project_ro() {
-        let response = post_package_not_a_project(false).await;
-        assert_forbidden(response).await;
+    async fn post_file_sha256_param_mismatch_rw() {
+        let response = post_file_digest(
+            true,
+            &format!("?sha256={}", "0".repeat(64)),
+            Some(("content-digest", format!("sha-256=:{X_SHA256_B64}:")))
+        ).await;
+        assert_digest_mismatch(response).await;
     }
 
-    async fn post_package_unauth(rw: bool) -> Response {
-        let pd = PackageDataPost {
-            name: "pkg".into(),
-            sort_key: 4,
-            description: "".into()
-        };
+    #[tokio::test]
+    async fn post_file_content_digest_malformed_rw() {
+        let response = post_file_digest(
+            true,
+            "",
+            Some(("content-digest", "sha-256=bogus".into()))
+        ).await;
+        assert_malformed_upload(response).await;
+    }
 
-        try_request(
+    #[tokio::test]
+    async fn post_image_content_digest_mismatch_rw() {
+        let response = try_request(
             Request::builder()
                 .method(Method::POST)
-                .uri(format!("{API_V1}/projects/a_project/packages/a_package"))
-                .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
-                .body(Body::from(serde_json::to_vec(&pd).unwrap()))
+                .uri(format!("{API_V1}/projects/a_project/images/img.png?sha256={}", "0".repeat(64)))
+                .header(AUTHORIZATION, token(BOB_UID))
+                .header(CONTENT_LENGTH, 1)
+                .header(CONTENT_TYPE, IMAGE_PNG.as_ref())
+                .body(Body::from("x"))
                 .unwrap(),
-            rw
-        )
-        .await
+            true
+        ).await;
+        assert_digest_mismatch(response).await;
     }
 
     #[tokio::test]
-    async fn post_package_unauth_rw() {
-        let response = post_package_unauth(true).await;
-        assert_unauthorized(response).await;
+    async fn post_image_content_digest_ok_rw() {
+        let response = try_request(
+            Request::builder()
+                .method(Method::POST)
+                .uri(format!("{API_V1}/projects/a_project/images/img.png"))
+                .header(AUTHORIZATION, token(BOB_UID))
+                .header(CONTENT_LENGTH, 1)
+                .header(CONTENT_TYPE, IMAGE_PNG.as_ref())
+                .header("content-digest", format!("sha-256=:{X_SHA256_B64}:"))
+                .body(Body::from("x"))
+                .unwrap(),
+            true
+        ).await;
+        assert_ok(response).await;
     }
 
-    #[tokio::test]
-    async fn post_package_unauth_ro() {
-        let response = post_package_unauth(false).await;
-        assert_forbidden(response).await;
+    const UPLOADS: &str = "projects/a_project/packages/a_package/1.2.3/war_and_peace.txt/uploads";
+
+    fn upload_app(rw: bool) -> Router {
+        routes(API_V1, !rw, false, 10, None).with_state(test_state())
     }
 
-    async fn post_package_already_exists(rw: bool) -> Response {
-        let pd = PackageDataPost {
-            name: "pkg".into(),
-            sort_key: 4,
-            description: "".into()
-        };
+    async fn create_upload(
+        app: &Router,
+        uid: i64,
+        length: Option<usize>
+    ) -> Response
+    {
+        let mut builder = Request::builder()
+            .method(Method::POST)
+            .uri(format!("{API_V1}/{UPLOADS}"))
+            .header(AUTHORIZATION, token(uid))
+            .header(CONTENT_TYPE, TEXT_PLAIN.as_ref());
+
+        if let Some(length) = length {
+            builder = builder.header("upload-length", length);
+        }
 
-        try_request(
-            Request::builder()
-                .method(Method::POST)
-                .uri(format!("{API_V1}/projects/a_project/packages/a_package"))
-                .header(AUTHORIZATION, token(BOB_UID))
-                .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
-                .body(Body::from(serde_json::to_vec(&pd).unwrap()))
-                .unwrap(),
-            rw
-        )
-        .await
+        app.clone()
+            .oneshot(builder.body(Body::empty()).unwrap())
+            .await
+            .unwrap()
     }
 
-    #[tokio::test]
-    async fn post_package_already_exists_rw() {
-        let response = post_package_already_exists(true).await;
-        assert_malformed_query(response).await;
+    async fn create_upload_id(app: &Router, length: Option<usize>) -> String {
+        let response = create_upload(app, BOB_UID, length).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        body_as::<UploadStatus>(response).await.id
     }
 
-    #[tokio::test]
-    async fn post_package_already_exists_ro() {
-        let response = post_package_already_exists(false).await;
-        assert_forbidden(response).await;
+    async fn patch_upload(
+        app: &Router,
+        id: &str,
+        offset: Option<u64>,
+        data: &'static str
+    ) -> Response
+    {
+        let mut builder = Request::builder()
+            .method(Method::PATCH)
+            .uri(format!("{API_V1}/{UPLOADS}/{id}"))
+            .header(AUTHORIZATION, token(BOB_UID))
+            .header(CONTENT_LENGTH, data.len());
+
+        if let Some(offset) = offset {
+            builder = builder.header("upload-offset", offset);
+        }
+
+        app.clone()
+            .oneshot(builder.body(Body::from(data)).unwrap())
+            .await
+            .unwrap()
     }
 
-    async fn update_package_ok(rw: bool) -> Response {
-        let pd = PackageDataPatch {
-            sort_key: Some(4),
-            ..Default::default()
-        };
+    async fn upload_request(
+        app: &Router,
+        method: Method,
+        id: &str
+    ) -> Response
+    {
+        app.clone()
+            .oneshot(
+                Request::builder()
+                    .method(method)
+                    .uri(format!("{API_V1}/{UPLOADS}/{id}"))
+                    .header(AUTHORIZATION, token(BOB_UID))
+                    .body(Body::empty())
+                    .unwrap()
+            )
+            .await
+            .unwrap()
+    }
 
-        try_request(
-            Request::builder()
-                .method(Method::PATCH)
-                .uri(format!("{API_V1}/projects/a_project/packages/a_package"))
-                .header(AUTHORIZATION, token(BOB_UID))
-                .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
-                .body(Body::from(serde_json::to_vec(&pd).unwrap()))
-                .unwrap(),
-            rw
-        )
-        .await
+    #[track_caller]
+    async fn assert_upload_status(
+        response: Response,
+        id: &str,
+        offset: u64,
+        length: Option<u64>
+    )
+    {
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            body_as::<UploadStatus>(response).await,
+            UploadStatus { id: id.into(), offset, length }
+        );
     }
 
     #[tokio::test]
-    async fn update_package_ok_rw() {
-        let response = update_package_ok(true).await;
-        assert_ok(response).await;
+    async fn post_file_upload_ok_rw() {
+        let app = upload_app(true);
+        let response = create_upload(&app, BOB_UID, Some(6)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let status = body_as::<UploadStatus>(response).await;
+        assert_eq!(status.id.len(), 32);
+        assert_eq!(status.offset, 0);
+        assert_eq!(status.length, Some(6));
     }
 
     #[tokio::test]
-    async fn update_package_ok_ro() {
-        let response = update_package_ok(false).await;
+    async fn post_file_upload_ok_ro() {
+        let app = upload_app(false);
+        let response = create_upload(&app, BOB_UID, None).await;
         assert_forbidden(response).await;
     }
 
-    async fn update_package_not_a_project(rw: bool) -> Response {
-        let pd = PackageDataPatch {
-            sort_key: Some(4),
-            ..Default::default()
-        };
-
-        try_request(
-            Request::builder()
-                .method(Method::PATCH)
-                .uri(format!("{API_V1}/projects/not_a_project/packages/a_package"))
-                .header(AUTHORIZATION, token(BOB_UID))
-                .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
-                .body(Body::from(serde_json::to_vec(&pd).unwrap()))
-                .unwrap(),
-            rw
-        )
-        .await
+    #[tokio::test]
+    async fn post_file_upload_not_owner_rw() {
+        let app = upload_app(true);
+        let response = create_upload(&app, 0, None).await;
+        assert_forbidden(response).await;
     }
 
     #[tokio::test]
-    async fn update_package_not_a_project_rw() {
-        let response = update_package_not_a_project(true).await;
-        assert_not_found(response).await;
+    async fn post_file_upload_too_large_rw() {
+        let app = upload_app(true);
+        let response = create_upload(&app, BOB_UID, Some(MAX_FILE_SIZE + 1)).await;
+        assert_payload_too_large(response).await;
     }
 
     #[tokio::test]
-    async fn update_package_not_a_project_ro() {
-        let response = update_package_not_a_project(false).await;
-        assert_forbidden(response).await;
-    }
+    async fn file_upload_resume_rw() {
+        let app = upload_app(true);
+        let id = create_upload_id(&app, Some(6)).await;
 
-    async fn update_package_not_a_package(rw: bool) -> Response {
-        let pd = PackageDataPatch {
-            sort_key: Some(4),
-            ..Default::default()
-        };
+        let response = patch_upload(&app, &id, Some(0), "abc").await;
+        assert_upload_status(response, &id, 3, Some(6)).await;
+
+        let response = upload_request(&app, Method::GET, &id).await;
+        assert_upload_status(response, &id, 3, Some(6)).await;
+
+        let response = patch_upload(&app, &id, Some(3), "def").await;
+        assert_upload_status(response, &id, 6, Some(6)).await;
+
+        let response = upload_request(&app, Method::POST, &id).await;
+        assert_ok(response).await;
+
+        // the session is gone once finalized
+        let response = upload_request(&app, Method::GET, &id).await;
+        assert_not_found(response).await;
+    }
+
+    #[tokio::test]
+    async fn patch_file_upload_wrong_offset_rw() {
+        let app = upload_app(true);
+        let id = create_upload_id(&app, None).await;
+
+        let response = patch_upload(&app, &id, Some(0), "abc").await;
+        assert_upload_status(response, &id, 3, None).await;
+
+        let response = patch_upload(&app, &id, Some(0), "abc").await;
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        assert_eq!(
+            body_as::<HttpError>(response).await,
+            HttpError::from(AppError::Conflict)
+        );
+
+        let response = upload_request(&app, Method::GET, &id).await;
+        assert_upload_status(response, &id, 3, None).await;
+    }
+
+    #[tokio::test]
+    async fn patch_file_upload_no_offset_rw() {
+        let app = upload_app(true);
+        let id = create_upload_id(&app, None).await;
+        let response = patch_upload(&app, &id, None, "abc").await;
+        assert_malformed_upload(response).await;
+    }
+
+    #[tokio::test]
+    async fn patch_file_upload_exceeds_length_rw() {
+        let app = upload_app(true);
+        let id = create_upload_id(&app, Some(3)).await;
+
+        let response = patch_upload(&app, &id, Some(0), "abcd").await;
+        assert_payload_too_large(response).await;
+
+        // the failed chunk is not kept
+        let response = upload_request(&app, Method::GET, &id).await;
+        assert_upload_status(response, &id, 0, Some(3)).await;
+    }
+
+    #[tokio::test]
+    async fn patch_file_upload_too_large_rw() {
+        let app = upload_app(true);
+        let id = create_upload_id(&app, None).await;
+        let long: &'static str = "x".repeat(MAX_FILE_SIZE).leak();
+
+        let response = patch_upload(&app, &id, Some(0), long).await;
+        assert_upload_status(response, &id, MAX_FILE_SIZE as u64, None).await;
+
+        let response = patch_upload(&app, &id, Some(MAX_FILE_SIZE as u64), "x").await;
+        assert_payload_too_large(response).await;
+    }
+
+    #[tokio::test]
+    async fn finalize_file_upload_incomplete_rw() {
+        let app = upload_app(true);
+        let id = create_upload_id(&app, Some(6)).await;
+
+        let response = patch_upload(&app, &id, Some(0), "abc").await;
+        assert_upload_status(response, &id, 3, Some(6)).await;
+
+        let response = upload_request(&app, Method::POST, &id).await;
+        assert_malformed_upload(response).await;
+    }
+
+    #[tokio::test]
+    async fn finalize_file_upload_digest_mismatch_rw() {
+        let app = upload_app(true);
+        let id = create_upload_id(&app, None).await;
+
+        let response = patch_upload(&app, &id, Some(0), "x").await;
+        assert_upload_status(response, &id, 1, None).await;
+
+        let response = app.clone()
+            .oneshot(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri(format!("{API_V1}/{UPLOADS}/{id}?sha256={}", "0".repeat(64)))
+                    .header(AUTHORIZATION, token(BOB_UID))
+                    .body(Body::empty())
+                    .unwrap()
+            )
+            .await
+            .unwrap();
+        assert_digest_mismatch(response).await;
+
+        let response = app.clone()
+            .oneshot(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri(format!("{API_V1}/{UPLOADS}/{id}?sha256={X_SHA256}"))
+                    .header(AUTHORIZATION, token(BOB_UID))
+                    .body(Body::empty())
+                    .unwrap()
+            )
+            .await
+            .unwrap();
+        assert_ok(response).await;
+    }
+
+    #[tokio::test]
+    async fn finalize_file_upload_add_fails_rw() {
+        let app = upload_app(true);
+        let uploads = UPLOADS.replace("war_and_peace.txt", "bad.txt");
+
+        let request = |method: Method, uri: String, body: &'static str| {
+            app.clone().oneshot(
+                Request::builder()
+                    .method(method)
+                    .uri(uri)
+                    .header(AUTHORIZATION, token(BOB_UID))
+                    .header("upload-offset", 0)
+                    .header(CONTENT_LENGTH, body.len())
+                    .body(Body::from(body))
+                    .unwrap()
+            )
+        };
+
+        let response = request(Method::POST, format!("{API_V1}/{uploads}"), "")
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let id = body_as::<UploadStatus>(response).await.id;
+
+        let uri = format!("{API_V1}/{uploads}/{id}");
+
+        let response = request(Method::PATCH, uri.clone(), "abc")
+            .await
+            .unwrap();
+        assert_upload_status(response, &id, 3, None).await;
+
+        let response = request(Method::POST, uri.clone(), "").await.unwrap();
+        assert_unsupported_media_type(response).await;
+
+        // the session survives a failed finalization
+        let response = request(Method::GET, uri, "").await.unwrap();
+        assert_upload_status(response, &id, 3, None).await;
+    }
+
+    #[tokio::test]
+    async fn post_file_upload_too_many_rw() {
+        let app = upload_app(true);
+
+        for _ in 0..MAX_SESSIONS_PER_OWNER {
+            create_upload_id(&app, None).await;
+        }
+
+        let response = create_upload(&app, BOB_UID, None).await;
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(
+            body_as::<HttpError>(response).await,
+            HttpError::from(AppError::TooManyRequests)
+        );
+    }
+
+    #[tokio::test]
+    async fn delete_file_upload_rw() {
+        let app = upload_app(true);
+        let id = create_upload_id(&app, None).await;
+
+        let response = upload_request(&app, Method::DELETE, &id).await;
+        assert_ok(response).await;
+
+        let response = upload_request(&app, Method::GET, &id).await;
+        assert_not_found(response).await;
+    }
+
+    #[tokio::test]
+    async fn get_file_upload_not_found_rw() {
+        let app = upload_app(true);
+        let response = upload_request(&app, Method::GET, "bogus").await;
+        assert_not_found(response).await;
+    }
+
+    #[tokio::test]
+    async fn get_file_upload_other_file_rw() {
+        let app = upload_app(true);
+        let id = create_upload_id(&app, None).await;
+
+        let response = app.clone()
+            .oneshot(
+                Request::builder()
+                    .method(Method::GET)
+                    .uri(format!("{API_V1}/projects/a_project/packages/a_package/1.2.3/other.txt/uploads/{id}"))
+                    .header(AUTHORIZATION, token(BOB_UID))
+                    .body(Body::empty())
+                    .unwrap()
+            )
+            .await
+            .unwrap();
+        assert_not_found(response).await;
+    }
+
+    #[tokio::test]
+    async fn get_file_upload_ro() {
+        let app = upload_app(false);
+        let response = upload_request(&app, Method::GET, "bogus").await;
+        assert_forbidden(response).await;
+    }
+
+    #[tokio::test]
+    async fn patch_file_upload_ro() {
+        let app = upload_app(false);
+        let response = patch_upload(&app, "bogus", Some(0), "abc").await;
+        assert_forbidden(response).await;
+    }
+
+    async fn post_package_ok(rw: bool) -> Response {
+        let pd = PackageDataPost {
+            name: "pkg".into(),
+            sort_key: 4,
+            description: "".into()
+        };
 
         try_request(
             Request::builder()
-                .method(Method::PATCH)
-                .uri(format!("{API_V1}/projects/a_project/packages/not_a_package"))
+                .method(Method::POST)
+                .uri(format!("{API_V1}/projects/a_project/packages/newpkg"))
                 .header(AUTHORIZATION, token(BOB_UID))
                 .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
                 .body(Body::from(serde_json::to_vec(&pd).unwrap()))
@@ -4167,18 +5303,113 @@ mod test {
     }
 
     #[tokio::test]
-    async fn update_package_not_a_package_rw() {
-        let response = update_package_not_a_package(true).await;
+    async fn post_package_ok_rw() {
+        let response = post_package_ok(true).await;
+        assert_ok(response).await;
+    }
+
+    #[tokio::test]
+    async fn post_package_ok_ro() {
+        let response = post_package_ok(false).await;
+        assert_forbidden(response).await;
+    }
+
+    async fn post_package_not_a_project(rw: bool) -> Response {
+        let pd = PackageDataPost {
+            name: "pkg".into(),
+            sort_key: 4,
+            description: "".into()
+        };
+
+        try_request(
+            Request::builder()
+                .method(Method::POST)
+                .uri(format!("{API_V1}/projects/not_a_project/packages/a_package"))
+                .header(AUTHORIZATION, token(BOB_UID))
+                .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+                .body(Body::from(serde_json::to_vec(&pd).unwrap()))
+                .unwrap(),
+            rw
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn post_package_not_a_project_rw() {
+        let response = post_package_not_a_project(true).await;
         assert_not_found(response).await;
     }
 
     #[tokio::test]
-    async fn update_package_not_a_packaget_ro() {
-        let response = update_package_not_a_package(false).await;
+    async fn post_package_not_a_project_ro() {
+        let response = post_package_not_a_project(false).await;
         assert_forbidden(response).await;
     }
 
-    async fn update_package_unauth(rw: bool) -> Response {
+    async fn post_package_unauth(rw: bool) -> Response {
+        let pd = PackageDataPost {
+            name: "pkg".into(),
+            sort_key: 4,
+            description: "".into()
+        };
+
+        try_request(
+            Request::builder()
+                .method(Method::POST)
+                .uri(format!("{API_V1}/projects/a_project/packages/a_package"))
+                .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+                .body(Body::from(serde_json::to_vec(&pd).unwrap()))
+                .unwrap(),
+            rw
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn post_package_unauth_rw() {
+        let response = post_package_unauth(true).await;
+        assert_unauthorized(response).await;
+    }
+
+    #[tokio::test]
+    async fn post_package_unauth_ro() {
+        let response = post_package_unauth(false).await;
+        assert_forbidden(response).await;
+    }
+
+    async fn post_package_already_exists(rw: bool) -> Response {
+        let pd = PackageDataPost {
+            name: "pkg".into(),
+            sort_key: 4,
+            description: "".into()
+        };
+
+        try_request(
+            Request::builder()
+                .method(Method::POST)
+                .uri(format!("{API_V1}/projects/a_project/packages/a_package"))
+                .header(AUTHORIZATION, token(BOB_UID))
+                .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+                .body(Body::from(serde_json::to_vec(&pd).unwrap()))
+                .unwrap(),
+            rw
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn post_package_already_exists_rw() {
+        let response = post_package_already_exists(true).await;
+        assert_malformed_query(response).await;
+    }
+
+    #[tokio::test]
+    async fn post_package_already_exists_ro() {
+        let response = post_package_already_exists(false).await;
+        assert_forbidden(response).await;
+    }
+
+    async fn update_package_ok(rw: bool) -> Response {
         let pd = PackageDataPatch {
             sort_key: Some(4),
             ..Default::default()
@@ -4188,6 +5419,7 @@ mod test {
             Request::builder()
                 .method(Method::PATCH)
                 .uri(format!("{API_V1}/projects/a_project/packages/a_package"))
+                .header(AUTHORIZATION, token(BOB_UID))
                 .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
                 .body(Body::from(serde_json::to_vec(&pd).unwrap()))
                 .unwrap(),
@@ -4197,24 +5429,30 @@ mod test {
     }
 
     #[tokio::test]
-    async fn update_package_unauth_rw() {
-        let response = update_package_unauth(true).await;
-        assert_unauthorized(response).await;
+    async fn update_package_ok_rw() {
+        let response = update_package_ok(true).await;
+        assert_ok(response).await;
     }
 
     #[tokio::test]
-    async fn update_package_unauth_ro() {
-        let response = update_package_unauth(false).await;
+    async fn update_package_ok_ro() {
+        let response = update_package_ok(false).await;
         assert_forbidden(response).await;
     }
 
-    async fn delete_package_ok(rw: bool) -> Response {
+    async fn update_package_not_a_project(rw: bool) -> Response {
+        let pd = PackageDataPatch {
+            sort_key: Some(4),
+            ..Default::default()
+        };
+
         try_request(
             Request::builder()
-                .method(Method::DELETE)
-                .uri(format!("{API_V1}/projects/a_project/packages/a_package"))
+                .method(Method::PATCH)
+                .uri(format!("{API_V1}/projects/not_a_project/packages/a_package"))
                 .header(AUTHORIZATION, token(BOB_UID))
-                .body(Body::empty())
+                .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+                .body(Body::from(serde_json::to_vec(&pd).unwrap()))
                 .unwrap(),
             rw
         )
@@ -4222,23 +5460,1607 @@ mod test {
     }
 
     #[tokio::test]
-    async fn delete_package_ok_rw() {
-        let response = delete_package_ok(true).await;
-        assert_ok(response).await;
+    async fn update_package_not_a_project_rw() {
+        let response = update_package_not_a_project(true).await;
+        assert_not_found(response).await;
     }
 
     #[tokio::test]
-    async fn delete_package_ok_ro() {
-        let response = delete_package_ok(false).await;
+    async fn update_package_not_a_project_ro() {
+        let response = update_package_not_a_project(false).await;
+        assert_forbidden(response).await;
+    }
+
+    async fn update_package_not_a_package(rw: bool) -> Response {
+        let pd = PackageDataPatch {
+            sort_key: Some(4),
+            ..Default::default()
+        };
+
+        try_request(
+            Request::builder()
+                .method(Method::PATCH)
+                .uri(format!("{API_V1}/projects/a_project/packages/not_a_package"))
+                .header(AUTHORIZATION, token(BOB_UID))
+                .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+                .body(Body::from(serde_json::to_vec(&pd).unwrap()))
+                .unwrap(),
+            rw
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn update_package_not_a_package_rw() {
+        let response = update_package_not_a_package(true).await;
+        assert_not_found(response).await;
+    }
+
+    #[tokio::test]
+    async fn update_package_not_a_packaget_ro() {
+        let response = update_package_not_a_package(false).await;
+        assert_forbidden(response).await;
+    }
+
+    async fn update_package_unauth(rw: bool) -> Response {
+        let pd = PackageDataPatch {
+            sort_key: Some(4),
+            ..Default::default()
+        };
+
+        try_request(
+            Request::builder()
+                .method(Method::PATCH)
+                .uri(format!("{API_V1}/projects/a_project/packages/a_package"))
+                .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+                .body(Body::from(serde_json::to_vec(&pd).unwrap()))
+                .unwrap(),
+            rw
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn update_package_unauth_rw() {
+        let response = update_package_unauth(true).await;
+        assert_unauthorized(response).await;
+    }
+
+    #[tokio::test]
+    async fn update_package_unauth_ro() {
+        let response = update_package_unauth(false).await;
+        assert_forbidden(response).await;
+    }
+
+    async fn get_latest_release(rw: bool, pkg: &str, query: &str) -> Response {
+        try_request(
+            Request::builder()
+                .method(Method::GET)
+                .uri(format!("{API_V1}/projects/a_project/packages/{pkg}/latest{query}"))
+                .body(Body::empty())
+                .unwrap(),
+            rw
+        )
+        .await
+    }
+
+    #[track_caller]
+    async fn assert_latest_release_ok(response: Response) {
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            body_as::<ReleaseData>(response).await,
+            EIA_PROJECT_DATA.packages[0].releases[0]
+        );
+    }
+
+    #[tokio::test]
+    async fn get_latest_release_ok_rw() {
+        let response = get_latest_release(true, "a_package", "").await;
+        assert_latest_release_ok(response).await;
+    }
+
+    #[tokio::test]
+    async fn get_latest_release_ok_ro() {
+        let response = get_latest_release(false, "a_package", "").await;
+        assert_latest_release_ok(response).await;
+    }
+
+    #[tokio::test]
+    async fn get_latest_release_params_rw() {
+        let response = get_latest_release(
+            true, "a_package", "?pre=true&vassal=3.7.12"
+        ).await;
+        assert_latest_release_ok(response).await;
+    }
+
+    #[tokio::test]
+    async fn get_latest_release_bad_vassal_rw() {
+        let response = get_latest_release(
+            true, "a_package", "?vassal=bogus"
+        ).await;
+        assert_malformed_query(response).await;
+    }
+
+    #[tokio::test]
+    async fn get_latest_release_none_rw() {
+        let response = get_latest_release(true, "nonempty", "").await;
+        assert_not_found(response).await;
+    }
+
+    #[tokio::test]
+    async fn get_latest_release_not_a_package_rw() {
+        let response = get_latest_release(true, "bogus", "").await;
+        assert_not_found(response).await;
+    }
+
+    async fn get_latest_file(rw: bool, file: &str) -> Response {
+        try_request(
+            Request::builder()
+                .method(Method::GET)
+                .uri(format!("{API_V1}/projects/a_project/packages/a_package/latest/{file}"))
+                .body(Body::empty())
+                .unwrap(),
+            rw
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn get_latest_file_ok_rw() {
+        // failing to count the download does not fail the redirect
+        let response = get_latest_file(true, "eia.vmod").await;
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+        assert_eq!(
+            response.headers().get(LOCATION).unwrap(),
+            "https://example.com/eia.vmod"
+        );
+    }
+
+    #[tokio::test]
+    async fn get_latest_file_ok_ro() {
+        let response = get_latest_file(false, "eia.vmod").await;
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+        assert_eq!(
+            response.headers().get(LOCATION).unwrap(),
+            "https://example.com/eia.vmod"
+        );
+    }
+
+    #[tokio::test]
+    async fn get_latest_file_not_found_rw() {
+        let response = get_latest_file(true, "bogus.vmod").await;
+        assert_not_found(response).await;
+    }
+
+    async fn update_release(
+        rw: bool,
+        version: &str,
+        uid: Option<i64>,
+        body: &'static str
+    ) -> Response
+    {
+        let mut builder = Request::builder()
+            .method(Method::PATCH)
+            .uri(format!("{API_V1}/projects/a_project/packages/a_package/{version}"))
+            .header(CONTENT_TYPE, APPLICATION_JSON.as_ref());
+
+        if let Some(uid) = uid {
+            builder = builder.header(AUTHORIZATION, token(uid));
+        }
+
+        try_request(builder.body(Body::from(body)).unwrap(), rw).await
+    }
+
+    const RELEASE_PATCH: &str = r#"{"changelog":"* Fixed the map","released_at":"2023-12-09T15:56:29Z"}"#;
+
+    #[tokio::test]
+    async fn update_release_ok_rw() {
+        let response = update_release(
+            true, "1.2.3", Some(BOB_UID), RELEASE_PATCH
+        ).await;
+        assert_ok(response).await;
+    }
+
+    #[tokio::test]
+    async fn update_release_ok_ro() {
+        let response = update_release(
+            false, "1.2.3", Some(BOB_UID), RELEASE_PATCH
+        ).await;
+        assert_forbidden(response).await;
+    }
+
+    #[tokio::test]
+    async fn update_release_clear_changelog_rw() {
+        let response = update_release(
+            true, "1.2.3", Some(BOB_UID), r#"{"changelog":null}"#
+        ).await;
+        assert_ok(response).await;
+    }
+
+    #[tokio::test]
+    async fn update_release_not_a_release_rw() {
+        let response = update_release(
+            true, "0.0.0", Some(BOB_UID), RELEASE_PATCH
+        ).await;
+        assert_not_found(response).await;
+    }
+
+    #[tokio::test]
+    async fn update_release_bad_date_rw() {
+        let response = update_release(
+            true, "1.2.3", Some(BOB_UID), r#"{"released_at":"yesterday"}"#
+        ).await;
+        assert_unprocessable_entity(response).await;
+    }
+
+    #[tokio::test]
+    async fn update_release_empty_rw() {
+        let response = update_release(
+            true, "1.2.3", Some(BOB_UID), "{}"
+        ).await;
+        assert_unprocessable_entity(response).await;
+    }
+
+    #[tokio::test]
+    async fn update_release_yank_rw() {
+        let response = update_release(
+            true,
+            "1.2.3",
+            Some(BOB_UID),
+            r#"{"state":"yanked","yank_reason":"corrupts saves"}"#
+        ).await;
+        assert_ok(response).await;
+    }
+
+    #[tokio::test]
+    async fn update_release_pre_release_rw() {
+        let response = update_release(
+            true, "1.2.3", Some(BOB_UID), r#"{"state":"pre-release"}"#
+        ).await;
+        assert_ok(response).await;
+    }
+
+    #[tokio::test]
+    async fn update_release_yank_no_reason_rw() {
+        let response = update_release(
+            true, "1.2.3", Some(BOB_UID), r#"{"state":"yanked"}"#
+        ).await;
+        assert_unprocessable_entity(response).await;
+    }
+
+    #[tokio::test]
+    async fn update_release_bad_state_rw() {
+        let response = update_release(
+            true, "1.2.3", Some(BOB_UID), r#"{"state":"withdrawn"}"#
+        ).await;
+        assert_unprocessable_entity(response).await;
+    }
+
+    #[tokio::test]
+    async fn update_release_not_owner_rw() {
+        let response = update_release(
+            true, "1.2.3", Some(3), RELEASE_PATCH
+        ).await;
+        assert_forbidden(response).await;
+    }
+
+    #[tokio::test]
+    async fn update_release_unauth_rw() {
+        let response = update_release(
+            true, "1.2.3", None, RELEASE_PATCH
+        ).await;
+        assert_unauthorized(response).await;
+    }
+
+    #[tokio::test]
+    async fn update_release_unauth_ro() {
+        let response = update_release(
+            false, "1.2.3", None, RELEASE_PATCH
+        ).await;
+        assert_forbidden(response).await;
+    }
+
+    async fn delete_package_ok(rw: bool) -> Response {
+        try_request(
+            Request::builder()
+                .method(Method::DELETE)
+                .uri(format!("{API_V1}/projects/a_project/packages/a_package"))
+                .header(AUTHORIZATION, token(BOB_UID))
+                .body(Body::empty())
+                .unwrap(),
+            rw
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn delete_package_ok_rw() {
+        let response = delete_package_ok(true).await;
+        assert_ok(response).await;
+    }
+
+    #[tokio::test]
+    async fn delete_package_ok_ro() {
+        let response = delete_package_ok(false).await;
+        assert_forbidden(response).await;
+    }
+
+    async fn delete_package_not_a_project(rw: bool) -> Response {
+        try_request(
+            Request::builder()
+                .method(Method::DELETE)
+                .uri(format!("{API_V1}/projects/not_a_project/packages/a_package"))
+                .header(AUTHORIZATION, token(BOB_UID))
+                .body(Body::empty())
+                .unwrap(),
+            rw
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn delete_package_not_a_project_rw() {
+        let response = delete_package_not_a_project(true).await;
+        assert_not_found(response).await;
+    }
+
+    #[tokio::test]
+    async fn delete_package_not_a_project_ro() {
+        let response = delete_package_not_a_project(false).await;
+        assert_forbidden(response).await;
+    }
+
+    async fn delete_package_not_a_package(rw: bool) -> Response {
+        try_request(
+            Request::builder()
+                .method(Method::DELETE)
+                .uri(format!("{API_V1}/projects/a_project/packages/not_a_package"))
+                .header(AUTHORIZATION, token(BOB_UID))
+                .body(Body::empty())
+                .unwrap(),
+            rw
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn delete_package_not_a_package_rw() {
+        let response = delete_package_not_a_package(true).await;
+        assert_not_found(response).await;
+    }
+
+    #[tokio::test]
+    async fn delete_package_not_a_packaget_ro() {
+        let response = delete_package_not_a_package(false).await;
+        assert_forbidden(response).await;
+    }
+
+    async fn delete_package_unauth(rw: bool) -> Response {
+        try_request(
+            Request::builder()
+                .method(Method::DELETE)
+                .uri(format!("{API_V1}/projects/a_project/packages/a_package"))
+                .body(Body::empty())
+                .unwrap(),
+            rw
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn delete_package_unauth_rw() {
+        let response = delete_package_unauth(true).await;
+        assert_unauthorized(response).await;
+    }
+
+    #[tokio::test]
+    async fn delete_package_unauth_ro() {
+        let response = delete_package_unauth(false).await;
+        assert_forbidden(response).await;
+    }
+
+
+    async fn delete_package_not_empty(rw: bool) -> Response {
+        try_request(
+            Request::builder()
+                .method(Method::DELETE)
+                .uri(format!("{API_V1}/projects/a_project/packages/nonempty"))
+                .header(AUTHORIZATION, token(BOB_UID))
+                .body(Body::empty())
+                .unwrap(),
+            rw
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn delete_package_not_empty_rw() {
+        let response = delete_package_not_empty(true).await;
+        assert_malformed_query(response).await;
+    }
+
+    #[tokio::test]
+    async fn delete_package_not_empty_ro() {
+        let response = delete_package_not_empty(false).await;
+        assert_forbidden(response).await;
+    }
+
+    async fn delete_package_cascade(rw: bool) -> Response {
+        try_request(
+            Request::builder()
+                .method(Method::DELETE)
+                .uri(format!("{API_V1}/projects/a_project/packages/nonempty?cascade=true"))
+                .header(AUTHORIZATION, token(BOB_UID))
+                .body(Body::empty())
+                .unwrap(),
+            rw
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn delete_package_cascade_rw() {
+        let response = delete_package_cascade(true).await;
+        assert_ok(response).await;
+    }
+
+    #[tokio::test]
+    async fn delete_package_cascade_ro() {
+        let response = delete_package_cascade(false).await;
+        assert_forbidden(response).await;
+    }
+
+    async fn delete_package_bad_cascade(rw: bool) -> Response {
+        try_request(
+            Request::builder()
+                .method(Method::DELETE)
+                .uri(format!("{API_V1}/projects/a_project/packages/nonempty?cascade=maybe"))
+                .header(AUTHORIZATION, token(BOB_UID))
+                .body(Body::empty())
+                .unwrap(),
+            rw
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn delete_package_bad_cascade_rw() {
+        let response = delete_package_bad_cascade(true).await;
+        assert_malformed_query(response).await;
+    }
+
+    #[tokio::test]
+    async fn delete_package_bad_cascade_ro() {
+        let response = delete_package_bad_cascade(false).await;
+        assert_forbidden(response).await;
+    }
+
+    async fn post_release_ok(rw: bool) -> Response {
+        try_request(
+            Request::builder()
+                .method(Method::POST)
+                .uri(format!("{API_V1}/projects/a_project/packages/a_package/1.2.4"))
+                .header(AUTHORIZATION, token(BOB_UID))
+                .body(Body::empty())
+                .unwrap(),
+            rw
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn post_release_ok_rw() {
+        let response = post_release_ok(true).await;
+        assert_ok(response).await;
+    }
+
+    #[tokio::test]
+    async fn post_release_ok_ro() {
+        let response = post_release_ok(false).await;
+        assert_forbidden(response).await;
+    }
+
+    async fn post_release_not_a_project(rw: bool) -> Response {
+        try_request(
+            Request::builder()
+                .method(Method::POST)
+                .uri(format!("{API_V1}/projects/not_a_project/packages/a_package/1.2.4"))
+                .header(AUTHORIZATION, token(BOB_UID))
+                .body(Body::empty())
+                .unwrap(),
+            rw
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn post_release_not_a_project_rw() {
+        let response = post_release_not_a_project(true).await;
+        assert_not_found(response).await;
+    }
+
+    #[tokio::test]
+    async fn post_release_not_a_project_ro() {
+        let response = post_release_not_a_project(false).await;
+        assert_forbidden(response).await;
+    }
+
+    async fn post_release_not_a_package(rw: bool) -> Response {
+        try_request(
+            Request::builder()
+                .method(Method::POST)
+                .uri(format!("{API_V1}/projects/a_project/packages/not_a_package/1.2.4"))
+                .header(AUTHORIZATION, token(BOB_UID))
+                .body(Body::empty())
+                .unwrap(),
+            rw
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn post_release_not_a_package_rw() {
+        let response = post_release_not_a_package(true).await;
+        assert_not_found(response).await;
+    }
+
+    #[tokio::test]
+    async fn post_release_not_a_package_ro() {
+        let response = post_release_not_a_package(false).await;
+        assert_forbidden(response).await;
+    }
+
+    async fn post_release_already_exists(rw: bool) -> Response {
+        try_request(
+            Request::builder()
+                .method(Method::POST)
+                .uri(format!("{API_V1}/projects/a_project/packages/a_package/1.2.3"))
+                .header(AUTHORIZATION, token(BOB_UID))
+                .body(Body::empty())
+                .unwrap(),
+            rw
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn post_release_already_exists_rw() {
+        let response = post_release_already_exists(true).await;
+        assert_malformed_query(response).await;
+    }
+
+    #[tokio::test]
+    async fn post_release_already_exists_ro() {
+        let response = post_release_already_exists(false).await;
+        assert_forbidden(response).await;
+    }
+
+    async fn post_release_unauth(rw: bool) -> Response {
+        try_request(
+            Request::builder()
+                .method(Method::POST)
+                .uri(format!("{API_V1}/projects/a_project/packages/a_package/1.2.4"))
+                .body(Body::empty())
+                .unwrap(),
+            rw
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn post_release_unauth_rw() {
+        let response = post_release_unauth(true).await;
+        assert_unauthorized(response).await;
+    }
+
+    #[tokio::test]
+    async fn post_release_unauth_ro() {
+        let response = post_release_unauth(false).await;
+        assert_forbidden(response).await;
+    }
+
+    async fn post_release_with_data(rw: bool) -> Response {
+        try_request(
+            Request::builder()
+                .method(Method::POST)
+                .uri(format!("{API_V1}/projects/a_project/packages/a_package/1.2.4"))
+                .header(AUTHORIZATION, token(BOB_UID))
+                .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+                .body(Body::from(r#"{ "changelog": "* Fixed the map", "released_at": "2023-12-09T15:56:29.180282477Z" }"#))
+                .unwrap(),
+            rw
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn post_release_with_data_rw() {
+        let response = post_release_with_data(true).await;
+        assert_ok(response).await;
+    }
+
+    #[tokio::test]
+    async fn post_release_with_data_ro() {
+        let response = post_release_with_data(false).await;
+        assert_forbidden(response).await;
+    }
+
+    async fn post_release_bad_data(rw: bool) -> Response {
+        try_request(
+            Request::builder()
+                .method(Method::POST)
+                .uri(format!("{API_V1}/projects/a_project/packages/a_package/1.2.4"))
+                .header(AUTHORIZATION, token(BOB_UID))
+                .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+                .body(Body::from(r#"{ "released_at": "yesterday" }"#))
+                .unwrap(),
+            rw
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn post_release_bad_data_rw() {
+        let response = post_release_bad_data(true).await;
+        assert_unprocessable_entity(response).await;
+    }
+
+    #[tokio::test]
+    async fn post_release_bad_data_ro() {
+        let response = post_release_bad_data(false).await;
+        assert_forbidden(response).await;
+    }
+
+    async fn delete_release_ok(rw: bool) -> Response {
+        try_request(
+            Request::builder()
+                .method(Method::DELETE)
+                .uri(format!("{API_V1}/projects/a_project/packages/a_package/1.2.3"))
+                .header(AUTHORIZATION, token(BOB_UID))
+                .body(Body::empty())
+                .unwrap(),
+            rw
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn delete_release_ok_rw() {
+        let response = delete_release_ok(true).await;
+        assert_ok(response).await;
+    }
+
+    #[tokio::test]
+    async fn delete_release_ok_ro() {
+        let response = delete_release_ok(false).await;
+        assert_forbidden(response).await;
+    }
+
+    async fn delete_release_not_a_project(rw: bool) -> Response {
+        try_request(
+            Request::builder()
+                .method(Method::DELETE)
+                .uri(format!("{API_V1}/projects/not_a_project/packages/a_package/1.2.3"))
+                .header(AUTHORIZATION, token(BOB_UID))
+                .body(Body::empty())
+                .unwrap(),
+            rw
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn delete_release_not_a_project_rw() {
+        let response = delete_release_not_a_project(true).await;
+        assert_not_found(response).await;
+    }
+
+    #[tokio::test]
+    async fn delete_release_not_a_project_ro() {
+        let response = delete_release_not_a_project(false).await;
+        assert_forbidden(response).await;
+    }
+
+    async fn delete_release_not_a_package(rw: bool) -> Response {
+        try_request(
+            Request::builder()
+                .method(Method::DELETE)
+                .uri(format!("{API_V1}/projects/a_project/packages/not_a_package"))
+                .header(AUTHORIZATION, token(BOB_UID))
+                .body(Body::empty())
+                .unwrap(),
+            rw
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn delete_release_not_a_package_rw() {
+        let response = delete_release_not_a_package(true).await;
+        assert_not_found(response).await;
+    }
+
+    #[tokio::test]
+    async fn delete_release_not_a_packaget_ro() {
+        let response = delete_release_not_a_package(false).await;
+        assert_forbidden(response).await;
+    }
+
+    async fn delete_release_unauth(rw: bool) -> Response {
+        try_request(
+            Request::builder()
+                .method(Method::DELETE)
+                .uri(format!("{API_V1}/projects/a_project/packages/a_package/1.2.3"))
+                .body(Body::empty())
+                .unwrap(),
+            rw
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn delete_release_unauth_rw() {
+        let response = delete_release_unauth(true).await;
+        assert_unauthorized(response).await;
+    }
+
+    #[tokio::test]
+    async fn delete_release_unauth_ro() {
+        let response = delete_release_unauth(false).await;
+        assert_forbidden(response).await;
+    }
+
+    async fn delete_release_not_empty(rw: bool) -> Response {
+        try_request(
+            Request::builder()
+                .method(Method::DELETE)
+                .uri(format!("{API_V1}/projects/a_project/packages/a_package/1.2.4"))
+                .header(AUTHORIZATION, token(BOB_UID))
+                .body(Body::empty())
+                .unwrap(),
+            rw
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn delete_release_not_empty_rw() {
+        let response = delete_release_not_empty(true).await;
+        assert_malformed_query(response).await;
+    }
+
+    #[tokio::test]
+    async fn delete_release_not_empty_ro() {
+        let response = delete_release_not_empty(false).await;
+        assert_forbidden(response).await;
+    }
+
+    async fn delete_release_cascade(rw: bool) -> Response {
+        try_request(
+            Request::builder()
+                .method(Method::DELETE)
+                .uri(format!("{API_V1}/projects/a_project/packages/a_package/1.2.4?cascade=true"))
+                .header(AUTHORIZATION, token(BOB_UID))
+                .body(Body::empty())
+                .unwrap(),
+            rw
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn delete_release_cascade_rw() {
+        let response = delete_release_cascade(true).await;
+        assert_ok(response).await;
+    }
+
+    #[tokio::test]
+    async fn delete_release_cascade_ro() {
+        let response = delete_release_cascade(false).await;
+        assert_forbidden(response).await;
+    }
+
+    async fn delete_file_ok(rw: bool) -> Response {
+        try_request(
+            Request::builder()
+                .method(Method::DELETE)
+                .uri(format!("{API_V1}/projects/a_project/packages/a_package/1.2.3/war_and_peace.txt"))
+                .header(AUTHORIZATION, token(BOB_UID))
+                .body(Body::empty())
+                .unwrap(),
+            rw
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn delete_file_ok_rw() {
+        let response = delete_file_ok(true).await;
+        assert_ok(response).await;
+    }
+
+    #[tokio::test]
+    async fn delete_file_ok_ro() {
+        let response = delete_file_ok(false).await;
+        assert_forbidden(response).await;
+    }
+
+    async fn delete_file_not_a_file(rw: bool) -> Response {
+        try_request(
+            Request::builder()
+                .method(Method::DELETE)
+                .uri(format!("{API_V1}/projects/a_project/packages/a_package/1.2.3/not_a_file"))
+                .header(AUTHORIZATION, token(BOB_UID))
+                .body(Body::empty())
+                .unwrap(),
+            rw
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn delete_file_not_a_file_rw() {
+        let response = delete_file_not_a_file(true).await;
+        assert_not_found(response).await;
+    }
+
+    #[tokio::test]
+    async fn delete_file_not_a_file_ro() {
+        let response = delete_file_not_a_file(false).await;
+        assert_forbidden(response).await;
+    }
+
+    async fn delete_file_not_a_release(rw: bool) -> Response {
+        try_request(
+            Request::builder()
+                .method(Method::DELETE)
+                .uri(format!("{API_V1}/projects/a_project/packages/a_package/0.0.0/war_and_peace.txt"))
+                .header(AUTHORIZATION, token(BOB_UID))
+                .body(Body::empty())
+                .unwrap(),
+            rw
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn delete_file_not_a_release_rw() {
+        let response = delete_file_not_a_release(true).await;
+        assert_not_found(response).await;
+    }
+
+    #[tokio::test]
+    async fn delete_file_not_a_release_ro() {
+        let response = delete_file_not_a_release(false).await;
+        assert_forbidden(response).await;
+    }
+
+    async fn delete_file_not_owner(rw: bool) -> Response {
+        try_request(
+            Request::builder()
+                .method(Method::DELETE)
+                .uri(format!("{API_V1}/projects/a_project/packages/a_package/1.2.3/war_and_peace.txt"))
+                .header(AUTHORIZATION, token(3))
+                .body(Body::empty())
+                .unwrap(),
+            rw
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn delete_file_not_owner_rw() {
+        let response = delete_file_not_owner(true).await;
+        assert_forbidden(response).await;
+    }
+
+    #[tokio::test]
+    async fn delete_file_not_owner_ro() {
+        let response = delete_file_not_owner(false).await;
+        assert_forbidden(response).await;
+    }
+
+    async fn delete_file_unauth(rw: bool) -> Response {
+        try_request(
+            Request::builder()
+                .method(Method::DELETE)
+                .uri(format!("{API_V1}/projects/a_project/packages/a_package/1.2.3/war_and_peace.txt"))
+                .body(Body::empty())
+                .unwrap(),
+            rw
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn delete_file_unauth_rw() {
+        let response = delete_file_unauth(true).await;
+        assert_unauthorized(response).await;
+    }
+
+    #[tokio::test]
+    async fn delete_file_unauth_ro() {
+        let response = delete_file_unauth(false).await;
+        assert_forbidden(response).await;
+    }
+
+    async fn patch_gallery_ok(rw: bool) -> Response {
+        let gallery_patch = GalleryPatch {
+            ops: vec![GalleryOp::Update { id: 0, description: "".into() }]
+        };
+
+        try_request(
+            Request::builder()
+                .method(Method::PATCH)
+                .uri(format!("{API_V1}/projects/a_project/gallery"))
+                .header(AUTHORIZATION, token(BOB_UID))
+                .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+                .body(Body::from(serde_json::to_vec(&gallery_patch).unwrap()))
+                .unwrap(),
+            rw
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn patch_gallery_ok_rw() {
+        let response = patch_gallery_ok(true).await;
+        assert_ok(response).await;
+    }
+
+    #[tokio::test]
+    async fn patch_gallery_ok_ro() {
+        let response = patch_gallery_ok(false).await;
+        assert_forbidden(response).await;
+    }
+
+    async fn patch_gallery_empty_ops(rw: bool) -> Response {
+        let gallery_patch = GalleryPatch {
+            ops: vec![]
+        };
+
+        try_request(
+            Request::builder()
+                .method(Method::PATCH)
+                .uri(format!("{API_V1}/projects/a_project/gallery"))
+                .header(AUTHORIZATION, token(BOB_UID))
+                .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+                .body(Body::from(serde_json::to_vec(&gallery_patch).unwrap()))
+                .unwrap(),
+            rw
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn patch_gallery_empty_ops_rw() {
+        let response = patch_gallery_empty_ops(true).await;
+        assert_unprocessable_entity(response).await;
+    }
+
+    #[tokio::test]
+    async fn patch_gallery_empty_ops_ro() {
+        let response = patch_gallery_empty_ops(false).await;
+        assert_forbidden(response).await;
+    }
+
+    async fn patch_gallery_no_data(rw: bool) -> Response {
+        try_request(
+            Request::builder()
+                .method(Method::PATCH)
+                .uri(format!("{API_V1}/projects/a_project/gallery"))
+                .header(AUTHORIZATION, token(BOB_UID))
+                .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+                .body(Body::from("{}"))
+                .unwrap(),
+            rw
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn patch_gallery_no_data_rw() {
+        let response = patch_gallery_no_data(true).await;
+        assert_unprocessable_entity(response).await;
+    }
+
+    #[tokio::test]
+    async fn patch_gallery_no_data_ro() {
+        let response = patch_gallery_no_data(false).await;
+        assert_forbidden(response).await;
+    }
+
+    async fn patch_gallery_unauth(rw: bool) -> Response {
+        let proj_data = ProjectDataPatch {
+            description: Some("A module for Empires in Arms".into()),
+            ..Default::default()
+        };
+
+        try_request(
+            Request::builder()
+                .method(Method::PATCH)
+                .uri(format!("{API_V1}/projects/a_project/gallery"))
+                .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+                .body(Body::from(serde_json::to_vec(&proj_data).unwrap()))
+                .unwrap(),
+            rw
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn patch_gallery_unauth_rw() {
+        let response = patch_gallery_unauth(true).await;
+        assert_unauthorized(response).await;
+    }
+
+    #[tokio::test]
+    async fn patch_gallery_unauth_ro() {
+        let response = patch_gallery_unauth(false).await;
+        assert_forbidden(response).await;
+    }
+
+    async fn patch_gallery_not_owner(rw: bool) -> Response {
+        let gallery_patch = GalleryPatch {
+            ops: vec![GalleryOp::Update { id: 0, description: "".into() }]
+        };
+
+        try_request(
+            Request::builder()
+                .method(Method::PATCH)
+                .uri(format!("{API_V1}/projects/a_project/gallery"))
+                .header(AUTHORIZATION, token(0))
+                .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+                .body(Body::from(serde_json::to_vec(&gallery_patch).unwrap()))
+                .unwrap(),
+            rw
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn patch_gallery_not_owner_rw() {
+        let response = patch_gallery_not_owner(true).await;
+        assert_forbidden(response).await;
+    }
+
+    #[tokio::test]
+    async fn patch_gallery_not_owner_ro() {
+        let response = patch_gallery_not_owner(false).await;
+        assert_forbidden(response).await;
+    }
+
+    async fn patch_gallery_wrong_json(rw: bool) -> Response {
+        try_request(
+            Request::builder()
+                .method(Method::PATCH)
+                .uri(format!("{API_V1}/projects/a_project/gallery"))
+                .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+                .header(AUTHORIZATION, token(BOB_UID))
+                .body(Body::from(r#"{ "garbage": "whatever" }"#))
+                .unwrap(),
+            rw
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn patch_gallery_wrong_json_rw() {
+        let response = patch_gallery_wrong_json(true).await;
+        assert_unprocessable_entity(response).await;
+    }
+
+    #[tokio::test]
+    async fn patch_gallery_wrong_json_ro() {
+        let response = patch_gallery_wrong_json(false).await;
+        assert_forbidden(response).await;
+    }
+
+    async fn patch_gallery_wrong_mime_type(rw: bool) -> Response {
+        try_request(
+            Request::builder()
+                .method(Method::PATCH)
+                .uri(format!("{API_V1}/projects/a_project/gallery"))
+                .header(CONTENT_TYPE, TEXT_PLAIN.as_ref())
+                .header(AUTHORIZATION, token(BOB_UID))
+                .body(Body::from("stuff"))
+                .unwrap(),
+            rw
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn patch_gallery_wrong_mime_type_rw() {
+        let response = patch_gallery_wrong_mime_type(true).await;
+        assert_unsupported_media_type(response).await;
+    }
+
+    #[tokio::test]
+    async fn patch_gallery_wrong_mime_type_ro() {
+        let response = patch_gallery_wrong_mime_type(false).await;
+        assert_forbidden(response).await;
+    }
+
+    async fn patch_gallery_no_mime_type(rw: bool) -> Response {
+        try_request(
+            Request::builder()
+                .method(Method::PATCH)
+                .uri(format!("{API_V1}/projects/a_project/gallery"))
+                .header(AUTHORIZATION, token(BOB_UID))
+                .body(Body::from("stuff"))
+                .unwrap(),
+            rw
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn patch_gallery_no_mime_type_rw() {
+        let response = patch_gallery_no_mime_type(true).await;
+        assert_unsupported_media_type(response).await;
+    }
+
+    #[tokio::test]
+    async fn patch_gallery_no_mime_type_ro() {
+        let response = patch_gallery_no_mime_type(false).await;
+        assert_forbidden(response).await;
+    }
+
+    async fn post_flag_ok(rw: bool) -> Response {
+        let fp = FlagPost::Spam;
+
+        try_request(
+            Request::builder()
+                .method(Method::POST)
+                .uri(format!("{API_V1}/projects/a_project/flag"))
+                .header(AUTHORIZATION, token(BOB_UID))
+                .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+                .body(Body::from(serde_json::to_vec(&fp).unwrap()))
+                .unwrap(),
+            rw
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn post_flag_ok_rw() {
+        let response = post_flag_ok(true).await;
+        assert_ok(response).await;
+    }
+
+    #[tokio::test]
+    async fn post_flag_ok_ro() {
+        let response = post_flag_ok(false).await;
+        assert_forbidden(response).await;
+    }
+
+    async fn post_flag_not_a_project(rw: bool) -> Response {
+        let fp = FlagPost::Spam;
+
+        try_request(
+            Request::builder()
+                .method(Method::POST)
+                .uri(format!("{API_V1}/projects/not_a_project/flag"))
+                .header(AUTHORIZATION, token(BOB_UID))
+                .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+                .body(Body::from(serde_json::to_vec(&fp).unwrap()))
+                .unwrap(),
+            rw
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn post_flag_not_a_project_rw() {
+        let response = post_flag_not_a_project(true).await;
+        assert_not_found(response).await;
+    }
+
+    #[tokio::test]
+    async fn post_flag_not_a_project_ro() {
+        let response = post_flag_not_a_project(false).await;
+        assert_forbidden(response).await;
+    }
+
+    async fn post_flag_unauth(rw: bool) -> Response {
+        let fp = FlagPost::Spam;
+
+        try_request(
+            Request::builder()
+                .method(Method::POST)
+                .uri(format!("{API_V1}/projects/a_project/flag"))
+                .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+                .body(Body::from(serde_json::to_vec(&fp).unwrap()))
+                .unwrap(),
+            rw
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn post_flag_unauth_rw() {
+        let response = post_flag_unauth(true).await;
+        assert_unauthorized(response).await;
+    }
+
+    #[tokio::test]
+    async fn post_flag_unauth_ro() {
+        let response = post_flag_unauth(false).await;
+        assert_forbidden(response).await;
+    }
+
+    async fn post_flag_wrong_mime_type(rw: bool) -> Response {
+        try_request(
+            Request::builder()
+                .method(Method::POST)
+                .uri(format!("{API_V1}/projects/a_project/flag"))
+                .header(CONTENT_TYPE, TEXT_PLAIN.as_ref())
+                .header(AUTHORIZATION, token(BOB_UID))
+                .body(Body::from("stuff"))
+                .unwrap(),
+            rw
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn post_flag_wrong_mime_type_rw() {
+        let response = post_flag_wrong_mime_type(true).await;
+        assert_unsupported_media_type(response).await;
+    }
+
+    #[tokio::test]
+    async fn post_flag_wrong_mime_type_ro() {
+        let response = post_flag_wrong_mime_type(false).await;
+        assert_forbidden(response).await;
+    }
+
+    async fn post_flag_no_mime_type(rw: bool) -> Response {
+        try_request(
+            Request::builder()
+                .method(Method::POST)
+                .uri(format!("{API_V1}/projects/a_project/flag"))
+                .header(AUTHORIZATION, token(BOB_UID))
+                .body(Body::from("stuff"))
+                .unwrap(),
+            rw
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn post_flag_no_mime_type_rw() {
+        let response = post_flag_no_mime_type(true).await;
+        assert_unsupported_media_type(response).await;
+    }
+
+    #[tokio::test]
+    async fn post_flag_no_mime_type_ro() {
+        let response = post_flag_no_mime_type(false).await;
+        assert_forbidden(response).await;
+    }
+
+    async fn get_admin_flags_ok(rw: bool) -> Response {
+        try_request(
+            Request::builder()
+                .method(Method::GET)
+                .uri(format!("{API_V1}/admin/flags"))
+                .header(AUTHORIZATION, token(5))
+                .body(Body::empty())
+                .unwrap(),
+            rw
+        )
+        .await
+    }
+
+    #[track_caller]
+    async fn assert_flags_ok(response: Response) {
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            body_as::<Flags>(response).await,
+            Flags {
+                flags: vec![
+                    FlagData {
+                        flag_id: 1,
+                        project: "a_project".into(),
+                        slug: "a_project".into(),
+                        flagged_by: "bob".into(),
+                        flagged_at: "2023-10-30T18:53:53.056386142Z".into(),
+                        flag: FlagTag::Spam,
+                        message: None
+                    }
+                ]
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn get_admin_flags_ok_rw() {
+        let response = get_admin_flags_ok(true).await;
+        assert_flags_ok(response).await;
+    }
+
+    #[tokio::test]
+    async fn get_admin_flags_ok_ro() {
+        let response = get_admin_flags_ok(false).await;
+        assert_flags_ok(response).await;
+    }
+
+    async fn get_admin_flags_unauth(rw: bool) -> Response {
+        try_request(
+            Request::builder()
+                .method(Method::GET)
+                .uri(format!("{API_V1}/admin/flags"))
+                .body(Body::empty())
+                .unwrap(),
+            rw
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn get_admin_flags_unauth_rw() {
+        let response = get_admin_flags_unauth(true).await;
+        assert_unauthorized(response).await;
+    }
+
+    #[tokio::test]
+    async fn get_admin_flags_unauth_ro() {
+        let response = get_admin_flags_unauth(false).await;
+        assert_unauthorized(response).await;
+    }
+
+    async fn get_admin_files_ok(rw: bool) -> Response {
+        try_request(
+            Request::builder()
+                .method(Method::GET)
+                .uri(format!("{API_V1}/admin/files/deadbeef"))
+                .header(AUTHORIZATION, token(5))
+                .body(Body::empty())
+                .unwrap(),
+            rw
+        )
+        .await
+    }
+
+    #[track_caller]
+    async fn assert_files_ok(response: Response) {
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            body_as::<SharedFiles>(response).await,
+            SharedFiles {
+                sha256: "deadbeef".into(),
+                files: vec![
+                    SharedFile {
+                        project: "a_project".into(),
+                        slug: "a_project".into(),
+                        package: "a_package".into(),
+                        version: "1.2.3".into(),
+                        filename: "a_package-1.2.3.vmod".into(),
+                        url: "https://example.com/a_package-1.2.3.vmod".into(),
+                        size: 1234,
+                        published_at: "2023-10-30T18:53:53.056386142Z".into(),
+                        published_by: "bob".into()
+                    }
+                ]
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn get_admin_files_ok_rw() {
+        let response = get_admin_files_ok(true).await;
+        assert_files_ok(response).await;
+    }
+
+    #[tokio::test]
+    async fn get_admin_files_ok_ro() {
+        let response = get_admin_files_ok(false).await;
+        assert_files_ok(response).await;
+    }
+
+    async fn get_admin_files_not_admin(rw: bool) -> Response {
+        try_request(
+            Request::builder()
+                .method(Method::GET)
+                .uri(format!("{API_V1}/admin/files/deadbeef"))
+                .header(AUTHORIZATION, token(BOB_UID))
+                .body(Body::empty())
+                .unwrap(),
+            rw
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn get_admin_files_not_admin_rw() {
+        let response = get_admin_files_not_admin(true).await;
+        assert_unauthorized(response).await;
+    }
+
+    #[tokio::test]
+    async fn get_admin_files_not_admin_ro() {
+        let response = get_admin_files_not_admin(false).await;
+        assert_unauthorized(response).await;
+    }
+
+    async fn post_admin_gc(rw: bool, uid: i64, query: &str) -> Response {
+        try_request(
+            Request::builder()
+                .method(Method::POST)
+                .uri(format!("{API_V1}/admin/gc{query}"))
+                .header(AUTHORIZATION, token(uid))
+                .body(Body::empty())
+                .unwrap(),
+            rw
+        )
+        .await
+    }
+
+    #[track_caller]
+    async fn assert_orphans_ok(response: Response, dry_run: bool) {
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            body_as::<OrphanedObjects>(response).await,
+            OrphanedObjects {
+                dry_run,
+                objects: vec![
+                    OrphanedObject {
+                        url: "https://example.com/orphan.png".into(),
+                        size: 1234,
+                        last_modified: "2023-10-30T18:53:53.056386142Z".into()
+                    }
+                ]
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn post_admin_gc_ok_rw() {
+        let response = post_admin_gc(true, 5, "").await;
+        assert_orphans_ok(response, false).await;
+    }
+
+    #[tokio::test]
+    async fn post_admin_gc_ok_ro() {
+        let response = post_admin_gc(false, 5, "").await;
+        assert_forbidden(response).await;
+    }
+
+    #[tokio::test]
+    async fn post_admin_gc_dry_run_rw() {
+        let response = post_admin_gc(true, 5, "?dry_run=true").await;
+        assert_orphans_ok(response, true).await;
+    }
+
+    #[tokio::test]
+    async fn post_admin_gc_grace_rw() {
+        let response = post_admin_gc(true, 5, "?dry_run=true&grace=999999999").await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            body_as::<OrphanedObjects>(response).await,
+            OrphanedObjects { dry_run: true, objects: vec![] }
+        );
+    }
+
+    #[tokio::test]
+    async fn post_admin_gc_bad_grace_rw() {
+        let response = post_admin_gc(true, 5, "?grace=-1").await;
+        assert_malformed_query(response).await;
+    }
+
+    #[tokio::test]
+    async fn post_admin_gc_not_admin_rw() {
+        let response = post_admin_gc(true, BOB_UID, "").await;
+        assert_unauthorized(response).await;
+    }
+
+    async fn patch_admin_flags_ok(rw: bool) -> Response {
+        try_request(
+            Request::builder()
+                .method(Method::PATCH)
+                .uri(format!("{API_V1}/admin/flags/1"))
+                .header(AUTHORIZATION, token(5))
+                .body(Body::empty())
+                .unwrap(),
+            rw
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn patch_admin_flags_ok_rw() {
+        let response = patch_admin_flags_ok(true).await;
+        assert_ok(response).await;
+    }
+
+    #[tokio::test]
+    async fn patch_admin_flags_ok_ro() {
+        let response = patch_admin_flags_ok(false).await;
+        assert_forbidden(response).await;
+    }
+
+    async fn patch_admin_flags_not_a_flag(rw: bool) -> Response {
+        try_request(
+            Request::builder()
+                .method(Method::PATCH)
+                .uri(format!("{API_V1}/admin/flags/2"))
+                .header(AUTHORIZATION, token(5))
+                .body(Body::empty())
+                .unwrap(),
+            rw
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn patch_admin_flags_not_a_flag_rw() {
+        let response = patch_admin_flags_not_a_flag(true).await;
+        assert_not_found(response).await;
+    }
+
+    #[tokio::test]
+    async fn patch_admin_flags_not_a_flag_ro() {
+        let response = patch_admin_flags_not_a_flag(false).await;
+        assert_forbidden(response).await;
+    }
+
+    async fn patch_admin_flags_bad_id(rw: bool) -> Response {
+        try_request(
+            Request::builder()
+                .method(Method::PATCH)
+                .uri(format!("{API_V1}/admin/flags/not_an_id"))
+                .header(AUTHORIZATION, token(5))
+                .body(Body::empty())
+                .unwrap(),
+            rw
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn patch_admin_flags_bad_id_rw() {
+        let response = patch_admin_flags_bad_id(true).await;
+        assert_not_found(response).await;
+    }
+
+    #[tokio::test]
+    async fn patch_admin_flags_bad_id_ro() {
+        let response = patch_admin_flags_bad_id(false).await;
+        assert_forbidden(response).await;
+    }
+
+    async fn patch_admin_flags_unauth(rw: bool) -> Response {
+        try_request(
+            Request::builder()
+                .method(Method::PATCH)
+                .uri(format!("{API_V1}/admin/flags/1"))
+                .body(Body::empty())
+                .unwrap(),
+            rw
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn patch_admin_flags_unauth_rw() {
+        let response = patch_admin_flags_unauth(true).await;
+        assert_unauthorized(response).await;
+    }
+
+    #[tokio::test]
+    async fn patch_admin_flags_unauth_ro() {
+        let response = patch_admin_flags_unauth(false).await;
         assert_forbidden(response).await;
     }
 
-    async fn delete_package_not_a_project(rw: bool) -> Response {
+    async fn post_admin_project_restore_ok(rw: bool) -> Response {
         try_request(
             Request::builder()
-                .method(Method::DELETE)
-                .uri(format!("{API_V1}/projects/not_a_project/packages/a_package"))
-                .header(AUTHORIZATION, token(BOB_UID))
+                .method(Method::POST)
+                .uri(format!("{API_V1}/admin/projects/deleted_project/restore"))
+                .header(AUTHORIZATION, token(5))
                 .body(Body::empty())
                 .unwrap(),
             rw
@@ -4247,23 +7069,23 @@ mod test {
     }
 
     #[tokio::test]
-    async fn delete_package_not_a_project_rw() {
-        let response = delete_package_not_a_project(true).await;
-        assert_not_found(response).await;
+    async fn post_admin_project_restore_ok_rw() {
+        let response = post_admin_project_restore_ok(true).await;
+        assert_ok(response).await;
     }
 
     #[tokio::test]
-    async fn delete_package_not_a_project_ro() {
-        let response = delete_package_not_a_project(false).await;
+    async fn post_admin_project_restore_ok_ro() {
+        let response = post_admin_project_restore_ok(false).await;
         assert_forbidden(response).await;
     }
 
-    async fn delete_package_not_a_package(rw: bool) -> Response {
+    async fn post_admin_project_restore_not_deleted(rw: bool) -> Response {
         try_request(
             Request::builder()
-                .method(Method::DELETE)
-                .uri(format!("{API_V1}/projects/a_project/packages/not_a_package"))
-                .header(AUTHORIZATION, token(BOB_UID))
+                .method(Method::POST)
+                .uri(format!("{API_V1}/admin/projects/a_project/restore"))
+                .header(AUTHORIZATION, token(5))
                 .body(Body::empty())
                 .unwrap(),
             rw
@@ -4272,22 +7094,23 @@ mod test {
     }
 
     #[tokio::test]
-    async fn delete_package_not_a_package_rw() {
-        let response = delete_package_not_a_package(true).await;
+    async fn post_admin_project_restore_not_deleted_rw() {
+        let response = post_admin_project_restore_not_deleted(true).await;
         assert_not_found(response).await;
     }
 
     #[tokio::test]
-    async fn delete_package_not_a_packaget_ro() {
-        let response = delete_package_not_a_package(false).await;
+    async fn post_admin_project_restore_not_deleted_ro() {
+        let response = post_admin_project_restore_not_deleted(false).await;
         assert_forbidden(response).await;
     }
 
-    async fn delete_package_unauth(rw: bool) -> Response {
+    async fn post_admin_project_restore_not_admin(rw: bool) -> Response {
         try_request(
             Request::builder()
-                .method(Method::DELETE)
-                .uri(format!("{API_V1}/projects/a_project/packages/a_package"))
+                .method(Method::POST)
+                .uri(format!("{API_V1}/admin/projects/deleted_project/restore"))
+                .header(AUTHORIZATION, token(BOB_UID))
                 .body(Body::empty())
                 .unwrap(),
             rw
@@ -4296,24 +7119,22 @@ mod test {
     }
 
     #[tokio::test]
-    async fn delete_package_unauth_rw() {
-        let response = delete_package_unauth(true).await;
+    async fn post_admin_project_restore_not_admin_rw() {
+        let response = post_admin_project_restore_not_admin(true).await;
         assert_unauthorized(response).await;
     }
 
     #[tokio::test]
-    async fn delete_package_unauth_ro() {
-        let response = delete_package_unauth(false).await;
+    async fn post_admin_project_restore_not_admin_ro() {
+        let response = post_admin_project_restore_not_admin(false).await;
         assert_forbidden(response).await;
     }
 
-
-    async fn delete_package_not_empty(rw: bool) -> Response {
+    async fn post_admin_project_restore_unauth(rw: bool) -> Response {
         try_request(
             Request::builder()
-                .method(Method::DELETE)
-                .uri(format!("{API_V1}/projects/a_project/packages/nonempty"))
-                .header(AUTHORIZATION, token(BOB_UID))
+                .method(Method::POST)
+                .uri(format!("{API_V1}/admin/projects/deleted_project/restore"))
                 .body(Body::empty())
                 .unwrap(),
             rw
@@ -4322,24 +7143,29 @@ mod test {
     }
 
     #[tokio::test]
-    async fn delete_package_not_empty_rw() {
-        let response = delete_package_not_empty(true).await;
-        assert_malformed_query(response).await;
+    async fn post_admin_project_restore_unauth_rw() {
+        let response = post_admin_project_restore_unauth(true).await;
+        assert_unauthorized(response).await;
     }
 
     #[tokio::test]
-    async fn delete_package_not_empty_ro() {
-        let response = delete_package_not_empty(false).await;
+    async fn post_admin_project_restore_unauth_ro() {
+        let response = post_admin_project_restore_unauth(false).await;
         assert_forbidden(response).await;
     }
 
-    async fn post_release_ok(rw: bool) -> Response {
+    async fn post_admin_project_rename_ok(rw: bool) -> Response {
+        let pd = ProjectRenamePost {
+            name: "new_project".into()
+        };
+
         try_request(
             Request::builder()
                 .method(Method::POST)
-                .uri(format!("{API_V1}/projects/a_project/packages/a_package/1.2.4"))
-                .header(AUTHORIZATION, token(BOB_UID))
-                .body(Body::empty())
+                .uri(format!("{API_V1}/admin/projects/a_project/rename"))
+                .header(AUTHORIZATION, token(5))
+                .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+                .body(Body::from(serde_json::to_vec(&pd).unwrap()))
                 .unwrap(),
             rw
         )
@@ -4347,24 +7173,29 @@ mod test {
     }
 
     #[tokio::test]
-    async fn post_release_ok_rw() {
-        let response = post_release_ok(true).await;
+    async fn post_admin_project_rename_ok_rw() {
+        let response = post_admin_project_rename_ok(true).await;
         assert_ok(response).await;
     }
 
     #[tokio::test]
-    async fn post_release_ok_ro() {
-        let response = post_release_ok(false).await;
+    async fn post_admin_project_rename_ok_ro() {
+        let response = post_admin_project_rename_ok(false).await;
         assert_forbidden(response).await;
     }
 
-    async fn post_release_not_a_project(rw: bool) -> Response {
+    async fn post_admin_project_rename_already_exists(rw: bool) -> Response {
+        let pd = ProjectRenamePost {
+            name: "taken_project".into()
+        };
+
         try_request(
             Request::builder()
                 .method(Method::POST)
-                .uri(format!("{API_V1}/projects/not_a_project/packages/a_package/1.2.4"))
-                .header(AUTHORIZATION, token(BOB_UID))
-                .body(Body::empty())
+                .uri(format!("{API_V1}/admin/projects/a_project/rename"))
+                .header(AUTHORIZATION, token(5))
+                .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+                .body(Body::from(serde_json::to_vec(&pd).unwrap()))
                 .unwrap(),
             rw
         )
@@ -4372,24 +7203,29 @@ mod test {
     }
 
     #[tokio::test]
-    async fn post_release_not_a_project_rw() {
-        let response = post_release_not_a_project(true).await;
-        assert_not_found(response).await;
+    async fn post_admin_project_rename_already_exists_rw() {
+        let response = post_admin_project_rename_already_exists(true).await;
+        assert_malformed_query(response).await;
     }
 
     #[tokio::test]
-    async fn post_release_not_a_project_ro() {
-        let response = post_release_not_a_project(false).await;
+    async fn post_admin_project_rename_already_exists_ro() {
+        let response = post_admin_project_rename_already_exists(false).await;
         assert_forbidden(response).await;
     }
 
-    async fn post_release_not_a_package(rw: bool) -> Response {
+    async fn post_admin_project_rename_not_a_project(rw: bool) -> Response {
+        let pd = ProjectRenamePost {
+            name: "new_project".into()
+        };
+
         try_request(
             Request::builder()
                 .method(Method::POST)
-                .uri(format!("{API_V1}/projects/a_project/packages/not_a_package/1.2.4"))
-                .header(AUTHORIZATION, token(BOB_UID))
-                .body(Body::empty())
+                .uri(format!("{API_V1}/admin/projects/not_a_project/rename"))
+                .header(AUTHORIZATION, token(5))
+                .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+                .body(Body::from(serde_json::to_vec(&pd).unwrap()))
                 .unwrap(),
             rw
         )
@@ -4397,24 +7233,29 @@ mod test {
     }
 
     #[tokio::test]
-    async fn post_release_not_a_package_rw() {
-        let response = post_release_not_a_package(true).await;
+    async fn post_admin_project_rename_not_a_project_rw() {
+        let response = post_admin_project_rename_not_a_project(true).await;
         assert_not_found(response).await;
     }
 
     #[tokio::test]
-    async fn post_release_not_a_package_ro() {
-        let response = post_release_not_a_package(false).await;
+    async fn post_admin_project_rename_not_a_project_ro() {
+        let response = post_admin_project_rename_not_a_project(false).await;
         assert_forbidden(response).await;
     }
 
-    async fn post_release_already_exists(rw: bool) -> Response {
+    async fn post_admin_project_rename_not_admin(rw: bool) -> Response {
+        let pd = ProjectRenamePost {
+            name: "new_project".into()
+        };
+
         try_request(
             Request::builder()
                 .method(Method::POST)
-                .uri(format!("{API_V1}/projects/a_project/packages/a_package/1.2.3"))
+                .uri(format!("{API_V1}/admin/projects/a_project/rename"))
                 .header(AUTHORIZATION, token(BOB_UID))
-                .body(Body::empty())
+                .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+                .body(Body::from(serde_json::to_vec(&pd).unwrap()))
                 .unwrap(),
             rw
         )
@@ -4422,23 +7263,28 @@ mod test {
     }
 
     #[tokio::test]
-    async fn post_release_already_exists_rw() {
-        let response = post_release_already_exists(true).await;
-        assert_malformed_query(response).await;
+    async fn post_admin_project_rename_not_admin_rw() {
+        let response = post_admin_project_rename_not_admin(true).await;
+        assert_unauthorized(response).await;
     }
 
     #[tokio::test]
-    async fn post_release_already_exists_ro() {
-        let response = post_release_already_exists(false).await;
+    async fn post_admin_project_rename_not_admin_ro() {
+        let response = post_admin_project_rename_not_admin(false).await;
         assert_forbidden(response).await;
     }
 
-    async fn post_release_unauth(rw: bool) -> Response {
+    async fn post_admin_project_rename_unauth(rw: bool) -> Response {
+        let pd = ProjectRenamePost {
+            name: "new_project".into()
+        };
+
         try_request(
             Request::builder()
                 .method(Method::POST)
-                .uri(format!("{API_V1}/projects/a_project/packages/a_package/1.2.4"))
-                .body(Body::empty())
+                .uri(format!("{API_V1}/admin/projects/a_project/rename"))
+                .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+                .body(Body::from(serde_json::to_vec(&pd).unwrap()))
                 .unwrap(),
             rw
         )
@@ -4446,23 +7292,22 @@ mod test {
     }
 
     #[tokio::test]
-    async fn post_release_unauth_rw() {
-        let response = post_release_unauth(true).await;
+    async fn post_admin_project_rename_unauth_rw() {
+        let response = post_admin_project_rename_unauth(true).await;
         assert_unauthorized(response).await;
     }
 
     #[tokio::test]
-    async fn post_release_unauth_ro() {
-        let response = post_release_unauth(false).await;
+    async fn post_admin_project_rename_unauth_ro() {
+        let response = post_admin_project_rename_unauth(false).await;
         assert_forbidden(response).await;
     }
 
-    async fn delete_release_ok(rw: bool) -> Response {
+    async fn get_publishers_ok(rw: bool) -> Response {
         try_request(
             Request::builder()
-                .method(Method::DELETE)
-                .uri(format!("{API_V1}/projects/a_project/packages/a_package/1.2.3"))
-                .header(AUTHORIZATION, token(BOB_UID))
+                .method(Method::GET)
+                .uri(format!("{API_V1}/publishers"))
                 .body(Body::empty())
                 .unwrap(),
             rw
@@ -4470,24 +7315,34 @@ mod test {
         .await
     }
 
+    #[track_caller]
+    async fn assert_publishers_ok(response: Response) {
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            body_as::<Publishers>(response).await,
+            Publishers {
+                publishers: vec!["A Company".into(), "XYZ".into()]
+            }
+        );
+    }
+
     #[tokio::test]
-    async fn delete_release_ok_rw() {
-        let response = delete_release_ok(true).await;
-        assert_ok(response).await;
+    async fn get_publishers_ok_rw() {
+        let response = get_publishers_ok(true).await;
+        assert_publishers_ok(response).await;
     }
 
     #[tokio::test]
-    async fn delete_release_ok_ro() {
-        let response = delete_release_ok(false).await;
-        assert_forbidden(response).await;
+    async fn get_publishers_ok_ro() {
+        let response = get_publishers_ok(false).await;
+        assert_publishers_ok(response).await;
     }
 
-    async fn delete_release_not_a_project(rw: bool) -> Response {
+    async fn get_publishers_counts_ok(rw: bool) -> Response {
         try_request(
             Request::builder()
-                .method(Method::DELETE)
-                .uri(format!("{API_V1}/projects/not_a_project/packages/a_package/1.2.3"))
-                .header(AUTHORIZATION, token(BOB_UID))
+                .method(Method::GET)
+                .uri(format!("{API_V1}/publishers?counts=true"))
                 .body(Body::empty())
                 .unwrap(),
             rw
@@ -4495,24 +7350,43 @@ mod test {
         .await
     }
 
+    #[track_caller]
+    async fn assert_publisher_summaries_ok(response: Response) {
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            body_as::<PublisherSummaries>(response).await,
+            PublisherSummaries {
+                publishers: vec![
+                    PublisherSummary {
+                        name: "A Company".into(),
+                        projects: 3
+                    },
+                    PublisherSummary {
+                        name: "XYZ".into(),
+                        projects: 1
+                    }
+                ]
+            }
+        );
+    }
+
     #[tokio::test]
-    async fn delete_release_not_a_project_rw() {
-        let response = delete_release_not_a_project(true).await;
-        assert_not_found(response).await;
+    async fn get_publishers_counts_ok_rw() {
+        let response = get_publishers_counts_ok(true).await;
+        assert_publisher_summaries_ok(response).await;
     }
 
     #[tokio::test]
-    async fn delete_release_not_a_project_ro() {
-        let response = delete_release_not_a_project(false).await;
-        assert_forbidden(response).await;
+    async fn get_publishers_counts_ok_ro() {
+        let response = get_publishers_counts_ok(false).await;
+        assert_publisher_summaries_ok(response).await;
     }
 
-    async fn delete_release_not_a_package(rw: bool) -> Response {
+    async fn get_publishers_counts_bad(rw: bool) -> Response {
         try_request(
             Request::builder()
-                .method(Method::DELETE)
-                .uri(format!("{API_V1}/projects/a_project/packages/not_a_package"))
-                .header(AUTHORIZATION, token(BOB_UID))
+                .method(Method::GET)
+                .uri(format!("{API_V1}/publishers?counts=bogus"))
                 .body(Body::empty())
                 .unwrap(),
             rw
@@ -4521,22 +7395,22 @@ mod test {
     }
 
     #[tokio::test]
-    async fn delete_release_not_a_package_rw() {
-        let response = delete_release_not_a_package(true).await;
-        assert_not_found(response).await;
+    async fn get_publishers_counts_bad_rw() {
+        let response = get_publishers_counts_bad(true).await;
+        assert_malformed_query(response).await;
     }
 
     #[tokio::test]
-    async fn delete_release_not_a_packaget_ro() {
-        let response = delete_release_not_a_package(false).await;
-        assert_forbidden(response).await;
+    async fn get_publishers_counts_bad_ro() {
+        let response = get_publishers_counts_bad(false).await;
+        assert_malformed_query(response).await;
     }
 
-    async fn delete_release_unauth(rw: bool) -> Response {
+    async fn get_suggest(query: &str, rw: bool) -> Response {
         try_request(
             Request::builder()
-                .method(Method::DELETE)
-                .uri(format!("{API_V1}/projects/a_project/packages/a_package/1.2.3"))
+                .method(Method::GET)
+                .uri(format!("{API_V1}/suggest?{query}"))
                 .body(Body::empty())
                 .unwrap(),
             rw
@@ -4544,111 +7418,101 @@ mod test {
         .await
     }
 
-    #[tokio::test]
-    async fn delete_release_unauth_rw() {
-        let response = delete_release_unauth(true).await;
-        assert_unauthorized(response).await;
+    #[track_caller]
+    async fn assert_suggestions(response: Response, exp: &[&str]) {
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            body_as::<Suggestions>(response).await,
+            Suggestions {
+                suggestions: exp.iter().map(|s| s.to_string()).collect()
+            }
+        );
     }
 
     #[tokio::test]
-    async fn delete_release_unauth_ro() {
-        let response = delete_release_unauth(false).await;
-        assert_forbidden(response).await;
+    async fn get_suggest_ok_rw() {
+        let response = get_suggest("q=WW&kind=tag", true).await;
+        assert_suggestions(response, &["WWII", "WWI"]).await;
     }
 
-    async fn delete_release_not_empty(rw: bool) -> Response {
-        try_request(
-            Request::builder()
-                .method(Method::DELETE)
-                .uri(format!("{API_V1}/projects/a_project/packages/a_package/1.2.4"))
-                .header(AUTHORIZATION, token(BOB_UID))
-                .body(Body::empty())
-                .unwrap(),
-            rw
-        )
-        .await
+    #[tokio::test]
+    async fn get_suggest_ok_ro() {
+        let response = get_suggest("q=WW&kind=tag", false).await;
+        assert_suggestions(response, &["WWII", "WWI"]).await;
     }
 
     #[tokio::test]
-    async fn delete_release_not_empty_rw() {
-        let response = delete_release_not_empty(true).await;
-        assert_malformed_query(response).await;
+    async fn get_suggest_limit_rw() {
+        let response = get_suggest("q=WW&kind=tag&limit=1", true).await;
+        assert_suggestions(response, &["WWII"]).await;
     }
 
     #[tokio::test]
-    async fn delete_release_not_empty_ro() {
-        let response = delete_release_not_empty(false).await;
-        assert_forbidden(response).await;
+    async fn get_suggest_limit_ro() {
+        let response = get_suggest("q=WW&kind=tag&limit=1", false).await;
+        assert_suggestions(response, &["WWII"]).await;
     }
 
-    async fn patch_gallery_ok(rw: bool) -> Response {
-        let gallery_patch = GalleryPatch {
-            ops: vec![GalleryOp::Update { id: 0, description: "".into() }]
-        };
+    #[tokio::test]
+    async fn get_suggest_default_kind_rw() {
+        let response = get_suggest("q=WW", true).await;
+        assert_suggestions(response, &[]).await;
+    }
 
-        try_request(
-            Request::builder()
-                .method(Method::PATCH)
-                .uri(format!("{API_V1}/projects/a_project/gallery"))
-                .header(AUTHORIZATION, token(BOB_UID))
-                .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
-                .body(Body::from(serde_json::to_vec(&gallery_patch).unwrap()))
-                .unwrap(),
-            rw
-        )
-        .await
+    #[tokio::test]
+    async fn get_suggest_default_kind_ro() {
+        let response = get_suggest("q=WW", false).await;
+        assert_suggestions(response, &[]).await;
     }
 
     #[tokio::test]
-    async fn patch_gallery_ok_rw() {
-        let response = patch_gallery_ok(true).await;
-        assert_ok(response).await;
+    async fn get_suggest_no_q_rw() {
+        let response = get_suggest("kind=tag", true).await;
+        assert_malformed_query(response).await;
     }
 
     #[tokio::test]
-    async fn patch_gallery_ok_ro() {
-        let response = patch_gallery_ok(false).await;
-        assert_forbidden(response).await;
+    async fn get_suggest_no_q_ro() {
+        let response = get_suggest("kind=tag", false).await;
+        assert_malformed_query(response).await;
     }
 
-    async fn patch_gallery_empty_ops(rw: bool) -> Response {
-        let gallery_patch = GalleryPatch {
-            ops: vec![]
-        };
+    #[tokio::test]
+    async fn get_suggest_bad_kind_rw() {
+        let response = get_suggest("q=WW&kind=bogus", true).await;
+        assert_malformed_query(response).await;
+    }
 
-        try_request(
-            Request::builder()
-                .method(Method::PATCH)
-                .uri(format!("{API_V1}/projects/a_project/gallery"))
-                .header(AUTHORIZATION, token(BOB_UID))
-                .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
-                .body(Body::from(serde_json::to_vec(&gallery_patch).unwrap()))
-                .unwrap(),
-            rw
-        )
-        .await
+    #[tokio::test]
+    async fn get_suggest_bad_kind_ro() {
+        let response = get_suggest("q=WW&kind=bogus", false).await;
+        assert_malformed_query(response).await;
     }
 
     #[tokio::test]
-    async fn patch_gallery_empty_ops_rw() {
-        let response = patch_gallery_empty_ops(true).await;
-        assert_unprocessable_entity(response).await;
+    async fn get_suggest_bad_limit_rw() {
+        let response = get_suggest("q=WW&kind=tag&limit=0", true).await;
+        assert_malformed_query(response).await;
     }
 
     #[tokio::test]
-    async fn patch_gallery_empty_ops_ro() {
-        let response = patch_gallery_empty_ops(false).await;
-        assert_forbidden(response).await;
+    async fn get_suggest_bad_limit_ro() {
+        let response = get_suggest("q=WW&kind=tag&limit=0", false).await;
+        assert_malformed_query(response).await;
     }
 
-    async fn patch_gallery_no_data(rw: bool) -> Response {
+    async fn post_admin_publisher_rename_ok(rw: bool) -> Response {
+        let pd = PublisherRenamePost {
+            name: "XYZ Games".into()
+        };
+
         try_request(
             Request::builder()
-                .method(Method::PATCH)
-                .uri(format!("{API_V1}/projects/a_project/gallery"))
-                .header(AUTHORIZATION, token(BOB_UID))
+                .method(Method::POST)
+                .uri(format!("{API_V1}/admin/publishers/XYZ/rename"))
+                .header(AUTHORIZATION, token(5))
                 .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
-                .body(Body::from("{}"))
+                .body(Body::from(serde_json::to_vec(&pd).unwrap()))
                 .unwrap(),
             rw
         )
@@ -4656,29 +7520,29 @@ mod test {
     }
 
     #[tokio::test]
-    async fn patch_gallery_no_data_rw() {
-        let response = patch_gallery_no_data(true).await;
-        assert_unprocessable_entity(response).await;
+    async fn post_admin_publisher_rename_ok_rw() {
+        let response = post_admin_publisher_rename_ok(true).await;
+        assert_ok(response).await;
     }
 
     #[tokio::test]
-    async fn patch_gallery_no_data_ro() {
-        let response = patch_gallery_no_data(false).await;
+    async fn post_admin_publisher_rename_ok_ro() {
+        let response = post_admin_publisher_rename_ok(false).await;
         assert_forbidden(response).await;
     }
 
-    async fn patch_gallery_unauth(rw: bool) -> Response {
-        let proj_data = ProjectDataPatch {
-            description: Some("A module for Empires in Arms".into()),
-            ..Default::default()
+    async fn post_admin_publisher_rename_already_exists(rw: bool) -> Response {
+        let pd = PublisherRenamePost {
+            name: "A Company".into()
         };
 
         try_request(
             Request::builder()
-                .method(Method::PATCH)
-                .uri(format!("{API_V1}/projects/a_project/gallery"))
+                .method(Method::POST)
+                .uri(format!("{API_V1}/admin/publishers/XYZ/rename"))
+                .header(AUTHORIZATION, token(5))
                 .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
-                .body(Body::from(serde_json::to_vec(&proj_data).unwrap()))
+                .body(Body::from(serde_json::to_vec(&pd).unwrap()))
                 .unwrap(),
             rw
         )
@@ -4686,29 +7550,29 @@ mod test {
     }
 
     #[tokio::test]
-    async fn patch_gallery_unauth_rw() {
-        let response = patch_gallery_unauth(true).await;
-        assert_unauthorized(response).await;
+    async fn post_admin_publisher_rename_already_exists_rw() {
+        let response = post_admin_publisher_rename_already_exists(true).await;
+        assert_malformed_query(response).await;
     }
 
     #[tokio::test]
-    async fn patch_gallery_unauth_ro() {
-        let response = patch_gallery_unauth(false).await;
+    async fn post_admin_publisher_rename_already_exists_ro() {
+        let response = post_admin_publisher_rename_already_exists(false).await;
         assert_forbidden(response).await;
     }
 
-    async fn patch_gallery_not_owner(rw: bool) -> Response {
-        let gallery_patch = GalleryPatch {
-            ops: vec![GalleryOp::Update { id: 0, description: "".into() }]
+    async fn post_admin_publisher_rename_not_found(rw: bool) -> Response {
+        let pd = PublisherRenamePost {
+            name: "XYZ Games".into()
         };
 
         try_request(
             Request::builder()
-                .method(Method::PATCH)
-                .uri(format!("{API_V1}/projects/a_project/gallery"))
-                .header(AUTHORIZATION, token(0))
+                .method(Method::POST)
+                .uri(format!("{API_V1}/admin/publishers/bogus/rename"))
+                .header(AUTHORIZATION, token(5))
                 .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
-                .body(Body::from(serde_json::to_vec(&gallery_patch).unwrap()))
+                .body(Body::from(serde_json::to_vec(&pd).unwrap()))
                 .unwrap(),
             rw
         )
@@ -4716,25 +7580,29 @@ mod test {
     }
 
     #[tokio::test]
-    async fn patch_gallery_not_owner_rw() {
-        let response = patch_gallery_not_owner(true).await;
-        assert_forbidden(response).await;
+    async fn post_admin_publisher_rename_not_found_rw() {
+        let response = post_admin_publisher_rename_not_found(true).await;
+        assert_not_found(response).await;
     }
 
     #[tokio::test]
-    async fn patch_gallery_not_owner_ro() {
-        let response = patch_gallery_not_owner(false).await;
+    async fn post_admin_publisher_rename_not_found_ro() {
+        let response = post_admin_publisher_rename_not_found(false).await;
         assert_forbidden(response).await;
     }
 
-    async fn patch_gallery_wrong_json(rw: bool) -> Response {
+    async fn post_admin_publisher_rename_invalid(rw: bool) -> Response {
+        let pd = PublisherRenamePost {
+            name: " XYZ Games ".into()
+        };
+
         try_request(
             Request::builder()
-                .method(Method::PATCH)
-                .uri(format!("{API_V1}/projects/a_project/gallery"))
+                .method(Method::POST)
+                .uri(format!("{API_V1}/admin/publishers/XYZ/rename"))
+                .header(AUTHORIZATION, token(5))
                 .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
-                .header(AUTHORIZATION, token(BOB_UID))
-                .body(Body::from(r#"{ "garbage": "whatever" }"#))
+                .body(Body::from(serde_json::to_vec(&pd).unwrap()))
                 .unwrap(),
             rw
         )
@@ -4742,25 +7610,29 @@ mod test {
     }
 
     #[tokio::test]
-    async fn patch_gallery_wrong_json_rw() {
-        let response = patch_gallery_wrong_json(true).await;
+    async fn post_admin_publisher_rename_invalid_rw() {
+        let response = post_admin_publisher_rename_invalid(true).await;
         assert_unprocessable_entity(response).await;
     }
 
     #[tokio::test]
-    async fn patch_gallery_wrong_json_ro() {
-        let response = patch_gallery_wrong_json(false).await;
+    async fn post_admin_publisher_rename_invalid_ro() {
+        let response = post_admin_publisher_rename_invalid(false).await;
         assert_forbidden(response).await;
     }
 
-    async fn patch_gallery_wrong_mime_type(rw: bool) -> Response {
+    async fn post_admin_publisher_rename_not_admin(rw: bool) -> Response {
+        let pd = PublisherRenamePost {
+            name: "XYZ Games".into()
+        };
+
         try_request(
             Request::builder()
-                .method(Method::PATCH)
-                .uri(format!("{API_V1}/projects/a_project/gallery"))
-                .header(CONTENT_TYPE, TEXT_PLAIN.as_ref())
+                .method(Method::POST)
+                .uri(format!("{API_V1}/admin/publishers/XYZ/rename"))
                 .header(AUTHORIZATION, token(BOB_UID))
-                .body(Body::from("stuff"))
+                .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+                .body(Body::from(serde_json::to_vec(&pd).unwrap()))
                 .unwrap(),
             rw
         )
@@ -4768,24 +7640,28 @@ mod test {
     }
 
     #[tokio::test]
-    async fn patch_gallery_wrong_mime_type_rw() {
-        let response = patch_gallery_wrong_mime_type(true).await;
-        assert_unsupported_media_type(response).await;
+    async fn post_admin_publisher_rename_not_admin_rw() {
+        let response = post_admin_publisher_rename_not_admin(true).await;
+        assert_unauthorized(response).await;
     }
 
     #[tokio::test]
-    async fn patch_gallery_wrong_mime_type_ro() {
-        let response = patch_gallery_wrong_mime_type(false).await;
+    async fn post_admin_publisher_rename_not_admin_ro() {
+        let response = post_admin_publisher_rename_not_admin(false).await;
         assert_forbidden(response).await;
     }
 
-    async fn patch_gallery_no_mime_type(rw: bool) -> Response {
+    async fn post_admin_publisher_rename_unauth(rw: bool) -> Response {
+        let pd = PublisherRenamePost {
+            name: "XYZ Games".into()
+        };
+
         try_request(
             Request::builder()
-                .method(Method::PATCH)
-                .uri(format!("{API_V1}/projects/a_project/gallery"))
-                .header(AUTHORIZATION, token(BOB_UID))
-                .body(Body::from("stuff"))
+                .method(Method::POST)
+                .uri(format!("{API_V1}/admin/publishers/XYZ/rename"))
+                .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+                .body(Body::from(serde_json::to_vec(&pd).unwrap()))
                 .unwrap(),
             rw
         )
@@ -4793,27 +7669,29 @@ mod test {
     }
 
     #[tokio::test]
-    async fn patch_gallery_no_mime_type_rw() {
-        let response = patch_gallery_no_mime_type(true).await;
-        assert_unsupported_media_type(response).await;
+    async fn post_admin_publisher_rename_unauth_rw() {
+        let response = post_admin_publisher_rename_unauth(true).await;
+        assert_unauthorized(response).await;
     }
 
     #[tokio::test]
-    async fn patch_gallery_no_mime_type_ro() {
-        let response = patch_gallery_no_mime_type(false).await;
+    async fn post_admin_publisher_rename_unauth_ro() {
+        let response = post_admin_publisher_rename_unauth(false).await;
         assert_forbidden(response).await;
     }
 
-    async fn post_flag_ok(rw: bool) -> Response {
-        let fp = FlagPost::Spam;
+    async fn post_admin_publisher_merge_ok(rw: bool) -> Response {
+        let pd = PublisherMergePost {
+            into: "A Company".into()
+        };
 
         try_request(
             Request::builder()
                 .method(Method::POST)
-                .uri(format!("{API_V1}/projects/a_project/flag"))
-                .header(AUTHORIZATION, token(BOB_UID))
+                .uri(format!("{API_V1}/admin/publishers/XYZ/merge"))
+                .header(AUTHORIZATION, token(5))
                 .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
-                .body(Body::from(serde_json::to_vec(&fp).unwrap()))
+                .body(Body::from(serde_json::to_vec(&pd).unwrap()))
                 .unwrap(),
             rw
         )
@@ -4821,27 +7699,29 @@ mod test {
     }
 
     #[tokio::test]
-    async fn post_flag_ok_rw() {
-        let response = post_flag_ok(true).await;
+    async fn post_admin_publisher_merge_ok_rw() {
+        let response = post_admin_publisher_merge_ok(true).await;
         assert_ok(response).await;
     }
 
     #[tokio::test]
-    async fn post_flag_ok_ro() {
-        let response = post_flag_ok(false).await;
+    async fn post_admin_publisher_merge_ok_ro() {
+        let response = post_admin_publisher_merge_ok(false).await;
         assert_forbidden(response).await;
     }
 
-    async fn post_flag_not_a_project(rw: bool) -> Response {
-        let fp = FlagPost::Spam;
+    async fn post_admin_publisher_merge_into_self(rw: bool) -> Response {
+        let pd = PublisherMergePost {
+            into: "XYZ".into()
+        };
 
         try_request(
             Request::builder()
                 .method(Method::POST)
-                .uri(format!("{API_V1}/projects/not_a_project/flag"))
-                .header(AUTHORIZATION, token(BOB_UID))
+                .uri(format!("{API_V1}/admin/publishers/XYZ/merge"))
+                .header(AUTHORIZATION, token(5))
                 .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
-                .body(Body::from(serde_json::to_vec(&fp).unwrap()))
+                .body(Body::from(serde_json::to_vec(&pd).unwrap()))
                 .unwrap(),
             rw
         )
@@ -4849,26 +7729,29 @@ mod test {
     }
 
     #[tokio::test]
-    async fn post_flag_not_a_project_rw() {
-        let response = post_flag_not_a_project(true).await;
-        assert_not_found(response).await;
+    async fn post_admin_publisher_merge_into_self_rw() {
+        let response = post_admin_publisher_merge_into_self(true).await;
+        assert_cannot_merge_into_self(response).await;
     }
 
     #[tokio::test]
-    async fn post_flag_not_a_project_ro() {
-        let response = post_flag_not_a_project(false).await;
+    async fn post_admin_publisher_merge_into_self_ro() {
+        let response = post_admin_publisher_merge_into_self(false).await;
         assert_forbidden(response).await;
     }
 
-    async fn post_flag_unauth(rw: bool) -> Response {
-        let fp = FlagPost::Spam;
+    async fn post_admin_publisher_merge_not_found(rw: bool) -> Response {
+        let pd = PublisherMergePost {
+            into: "A Company".into()
+        };
 
         try_request(
             Request::builder()
                 .method(Method::POST)
-                .uri(format!("{API_V1}/projects/a_project/flag"))
+                .uri(format!("{API_V1}/admin/publishers/bogus/merge"))
+                .header(AUTHORIZATION, token(5))
                 .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
-                .body(Body::from(serde_json::to_vec(&fp).unwrap()))
+                .body(Body::from(serde_json::to_vec(&pd).unwrap()))
                 .unwrap(),
             rw
         )
@@ -4876,25 +7759,29 @@ mod test {
     }
 
     #[tokio::test]
-    async fn post_flag_unauth_rw() {
-        let response = post_flag_unauth(true).await;
-        assert_unauthorized(response).await;
+    async fn post_admin_publisher_merge_not_found_rw() {
+        let response = post_admin_publisher_merge_not_found(true).await;
+        assert_not_found(response).await;
     }
 
     #[tokio::test]
-    async fn post_flag_unauth_ro() {
-        let response = post_flag_unauth(false).await;
+    async fn post_admin_publisher_merge_not_found_ro() {
+        let response = post_admin_publisher_merge_not_found(false).await;
         assert_forbidden(response).await;
     }
 
-    async fn post_flag_wrong_mime_type(rw: bool) -> Response {
+    async fn post_admin_publisher_merge_not_admin(rw: bool) -> Response {
+        let pd = PublisherMergePost {
+            into: "A Company".into()
+        };
+
         try_request(
             Request::builder()
                 .method(Method::POST)
-                .uri(format!("{API_V1}/projects/a_project/flag"))
-                .header(CONTENT_TYPE, TEXT_PLAIN.as_ref())
+                .uri(format!("{API_V1}/admin/publishers/XYZ/merge"))
                 .header(AUTHORIZATION, token(BOB_UID))
-                .body(Body::from("stuff"))
+                .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+                .body(Body::from(serde_json::to_vec(&pd).unwrap()))
                 .unwrap(),
             rw
         )
@@ -4902,24 +7789,28 @@ mod test {
     }
 
     #[tokio::test]
-    async fn post_flag_wrong_mime_type_rw() {
-        let response = post_flag_wrong_mime_type(true).await;
-        assert_unsupported_media_type(response).await;
+    async fn post_admin_publisher_merge_not_admin_rw() {
+        let response = post_admin_publisher_merge_not_admin(true).await;
+        assert_unauthorized(response).await;
     }
 
     #[tokio::test]
-    async fn post_flag_wrong_mime_type_ro() {
-        let response = post_flag_wrong_mime_type(false).await;
+    async fn post_admin_publisher_merge_not_admin_ro() {
+        let response = post_admin_publisher_merge_not_admin(false).await;
         assert_forbidden(response).await;
     }
 
-    async fn post_flag_no_mime_type(rw: bool) -> Response {
+    async fn post_admin_publisher_merge_unauth(rw: bool) -> Response {
+        let pd = PublisherMergePost {
+            into: "A Company".into()
+        };
+
         try_request(
             Request::builder()
                 .method(Method::POST)
-                .uri(format!("{API_V1}/projects/a_project/flag"))
-                .header(AUTHORIZATION, token(BOB_UID))
-                .body(Body::from("stuff"))
+                .uri(format!("{API_V1}/admin/publishers/XYZ/merge"))
+                .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+                .body(Body::from(serde_json::to_vec(&pd).unwrap()))
                 .unwrap(),
             rw
         )
@@ -4927,24 +7818,29 @@ mod test {
     }
 
     #[tokio::test]
-    async fn post_flag_no_mime_type_rw() {
-        let response = post_flag_no_mime_type(true).await;
-        assert_unsupported_media_type(response).await;
+    async fn post_admin_publisher_merge_unauth_rw() {
+        let response = post_admin_publisher_merge_unauth(true).await;
+        assert_unauthorized(response).await;
     }
 
     #[tokio::test]
-    async fn post_flag_no_mime_type_ro() {
-        let response = post_flag_no_mime_type(false).await;
+    async fn post_admin_publisher_merge_unauth_ro() {
+        let response = post_admin_publisher_merge_unauth(false).await;
         assert_forbidden(response).await;
     }
 
-    async fn get_admin_flags_ok(rw: bool) -> Response {
+    async fn post_admin_user_merge_ok(rw: bool) -> Response {
+        let pd = UserMergePost {
+            into: "bob".into()
+        };
+
         try_request(
             Request::builder()
-                .method(Method::GET)
-                .uri(format!("{API_V1}/admin/flags"))
+                .method(Method::POST)
+                .uri(format!("{API_V1}/admin/users/alice/merge"))
                 .header(AUTHORIZATION, token(5))
-                .body(Body::empty())
+                .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+                .body(Body::from(serde_json::to_vec(&pd).unwrap()))
                 .unwrap(),
             rw
         )
@@ -4952,44 +7848,41 @@ mod test {
     }
 
     #[track_caller]
-    async fn assert_flags_ok(response: Response) {
+    async fn assert_user_merge_ok(response: Response) {
         assert_eq!(response.status(), StatusCode::OK);
         assert_eq!(
-            body_as::<Flags>(response).await,
-            Flags {
-                flags: vec![
-                    FlagData {
-                        flag_id: 1,
-                        project: "a_project".into(),
-                        slug: "a_project".into(),
-                        flagged_by: "bob".into(),
-                        flagged_at: "2023-10-30T18:53:53.056386142Z".into(),
-                        flag: FlagTag::Spam,
-                        message: None
-                    }
-                ]
+            body_as::<UserMergeReport>(response).await,
+            UserMergeReport {
+                rewritten: [("owners.user_id".into(), 1)].into(),
+                dropped: [("owners.user_id".into(), 0)].into()
             }
         );
     }
 
     #[tokio::test]
-    async fn get_admin_flags_ok_rw() {
-        let response = get_admin_flags_ok(true).await;
-        assert_flags_ok(response).await;
+    async fn post_admin_user_merge_ok_rw() {
+        let response = post_admin_user_merge_ok(true).await;
+        assert_user_merge_ok(response).await;
     }
 
     #[tokio::test]
-    async fn get_admin_flags_ok_ro() {
-        let response = get_admin_flags_ok(false).await;
-        assert_flags_ok(response).await;
+    async fn post_admin_user_merge_ok_ro() {
+        let response = post_admin_user_merge_ok(false).await;
+        assert_forbidden(response).await;
     }
 
-    async fn get_admin_flags_unauth(rw: bool) -> Response {
+    async fn post_admin_user_merge_into_self(rw: bool) -> Response {
+        let pd = UserMergePost {
+            into: "bob".into()
+        };
+
         try_request(
             Request::builder()
-                .method(Method::GET)
-                .uri(format!("{API_V1}/admin/flags"))
-                .body(Body::empty())
+                .method(Method::POST)
+                .uri(format!("{API_V1}/admin/users/bob/merge"))
+                .header(AUTHORIZATION, token(5))
+                .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+                .body(Body::from(serde_json::to_vec(&pd).unwrap()))
                 .unwrap(),
             rw
         )
@@ -4997,24 +7890,29 @@ mod test {
     }
 
     #[tokio::test]
-    async fn get_admin_flags_unauth_rw() {
-        let response = get_admin_flags_unauth(true).await;
-        assert_unauthorized(response).await;
+    async fn post_admin_user_merge_into_self_rw() {
+        let response = post_admin_user_merge_into_self(true).await;
+        assert_cannot_merge_into_self(response).await;
     }
 
     #[tokio::test]
-    async fn get_admin_flags_unauth_ro() {
-        let response = get_admin_flags_unauth(false).await;
-        assert_unauthorized(response).await;
+    async fn post_admin_user_merge_into_self_ro() {
+        let response = post_admin_user_merge_into_self(false).await;
+        assert_forbidden(response).await;
     }
 
-    async fn patch_admin_flags_ok(rw: bool) -> Response {
+    async fn post_admin_user_merge_src_not_a_user(rw: bool) -> Response {
+        let pd = UserMergePost {
+            into: "bob".into()
+        };
+
         try_request(
             Request::builder()
-                .method(Method::PATCH)
-                .uri(format!("{API_V1}/admin/flags/1"))
+                .method(Method::POST)
+                .uri(format!("{API_V1}/admin/users/nobody/merge"))
                 .header(AUTHORIZATION, token(5))
-                .body(Body::empty())
+                .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+                .body(Body::from(serde_json::to_vec(&pd).unwrap()))
                 .unwrap(),
             rw
         )
@@ -5022,24 +7920,29 @@ mod test {
     }
 
     #[tokio::test]
-    async fn patch_admin_flags_ok_rw() {
-        let response = patch_admin_flags_ok(true).await;
-        assert_ok(response).await;
+    async fn post_admin_user_merge_src_not_a_user_rw() {
+        let response = post_admin_user_merge_src_not_a_user(true).await;
+        assert_not_found(response).await;
     }
 
     #[tokio::test]
-    async fn patch_admin_flags_ok_ro() {
-        let response = patch_admin_flags_ok(false).await;
+    async fn post_admin_user_merge_src_not_a_user_ro() {
+        let response = post_admin_user_merge_src_not_a_user(false).await;
         assert_forbidden(response).await;
     }
 
-    async fn patch_admin_flags_not_a_flag(rw: bool) -> Response {
+    async fn post_admin_user_merge_dst_not_a_user(rw: bool) -> Response {
+        let pd = UserMergePost {
+            into: "nobody".into()
+        };
+
         try_request(
             Request::builder()
-                .method(Method::PATCH)
-                .uri(format!("{API_V1}/admin/flags/2"))
+                .method(Method::POST)
+                .uri(format!("{API_V1}/admin/users/alice/merge"))
                 .header(AUTHORIZATION, token(5))
-                .body(Body::empty())
+                .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+                .body(Body::from(serde_json::to_vec(&pd).unwrap()))
                 .unwrap(),
             rw
         )
@@ -5047,24 +7950,29 @@ mod test {
     }
 
     #[tokio::test]
-    async fn patch_admin_flags_not_a_flag_rw() {
-        let response = patch_admin_flags_not_a_flag(true).await;
+    async fn post_admin_user_merge_dst_not_a_user_rw() {
+        let response = post_admin_user_merge_dst_not_a_user(true).await;
         assert_not_found(response).await;
     }
 
     #[tokio::test]
-    async fn patch_admin_flags_not_a_flag_ro() {
-        let response = patch_admin_flags_not_a_flag(false).await;
+    async fn post_admin_user_merge_dst_not_a_user_ro() {
+        let response = post_admin_user_merge_dst_not_a_user(false).await;
         assert_forbidden(response).await;
     }
 
-    async fn patch_admin_flags_bad_id(rw: bool) -> Response {
+    async fn post_admin_user_merge_not_admin(rw: bool) -> Response {
+        let pd = UserMergePost {
+            into: "bob".into()
+        };
+
         try_request(
             Request::builder()
-                .method(Method::PATCH)
-                .uri(format!("{API_V1}/admin/flags/not_an_id"))
-                .header(AUTHORIZATION, token(5))
-                .body(Body::empty())
+                .method(Method::POST)
+                .uri(format!("{API_V1}/admin/users/alice/merge"))
+                .header(AUTHORIZATION, token(BOB_UID))
+                .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+                .body(Body::from(serde_json::to_vec(&pd).unwrap()))
                 .unwrap(),
             rw
         )
@@ -5072,23 +7980,28 @@ mod test {
     }
 
     #[tokio::test]
-    async fn patch_admin_flags_bad_id_rw() {
-        let response = patch_admin_flags_bad_id(true).await;
-        assert_not_found(response).await;
+    async fn post_admin_user_merge_not_admin_rw() {
+        let response = post_admin_user_merge_not_admin(true).await;
+        assert_unauthorized(response).await;
     }
 
     #[tokio::test]
-    async fn patch_admin_flags_bad_id_ro() {
-        let response = patch_admin_flags_bad_id(false).await;
+    async fn post_admin_user_merge_not_admin_ro() {
+        let response = post_admin_user_merge_not_admin(false).await;
         assert_forbidden(response).await;
     }
 
-    async fn patch_admin_flags_unauth(rw: bool) -> Response {
+    async fn post_admin_user_merge_unauth(rw: bool) -> Response {
+        let pd = UserMergePost {
+            into: "bob".into()
+        };
+
         try_request(
             Request::builder()
-                .method(Method::PATCH)
-                .uri(format!("{API_V1}/admin/flags/1"))
-                .body(Body::empty())
+                .method(Method::POST)
+                .uri(format!("{API_V1}/admin/users/alice/merge"))
+                .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+                .body(Body::from(serde_json::to_vec(&pd).unwrap()))
                 .unwrap(),
             rw
         )
@@ -5096,14 +8009,14 @@ mod test {
     }
 
     #[tokio::test]
-    async fn patch_admin_flags_unauth_rw() {
-        let response = patch_admin_flags_unauth(true).await;
+    async fn post_admin_user_merge_unauth_rw() {
+        let response = post_admin_user_merge_unauth(true).await;
         assert_unauthorized(response).await;
     }
 
     #[tokio::test]
-    async fn patch_admin_flags_unauth_ro() {
-        let response = patch_admin_flags_unauth(false).await;
+    async fn post_admin_user_merge_unauth_ro() {
+        let response = post_admin_user_merge_unauth(false).await;
         assert_forbidden(response).await;
     }
 }
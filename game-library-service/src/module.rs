@@ -48,6 +48,22 @@ fn dump_file<P: AsRef<Path>>(
     Ok(data)
 }
 
+fn dump_buildfile_sync<P: AsRef<Path>>(
+    zippath: P
+) -> Result<Option<String>, Error>
+{
+    // older modules have buildFile instead of buildFile.xml
+    for filepath in ["buildFile.xml", "buildFile"] {
+        match dump_file(&zippath, filepath) {
+            Ok(data) => return Ok(Some(data)),
+            Err(Error::Zip(ZipError::FileNotFound)) => continue,
+            Err(e) => return Err(e)
+        }
+    }
+
+    Ok(None)
+}
+
 pub async fn dump_moduledata<P: AsRef<Path>>(
     path: P
 ) -> Result<String, Error>
@@ -60,6 +76,18 @@ pub async fn dump_moduledata<P: AsRef<Path>>(
     }
 }
 
+pub async fn dump_buildfile<P: AsRef<Path>>(
+    path: P
+) -> Result<Option<String>, Error>
+{
+    let path = path.as_ref().to_path_buf();
+    match tokio::task::spawn_blocking(move || dump_buildfile_sync(path)).await {
+        Ok(Ok(v)) => Ok(v),
+        Ok(Err(e)) => Err(e),
+        Err(e) => Err(Error::Io(io::Error::from(e)))
+    }
+}
+
 pub fn versions_in_moduledata(
     md: &str
 ) -> Result<(Option<String>, Option<String>), Error>
@@ -81,6 +109,116 @@ pub fn versions_in_moduledata(
     ))
 }
 
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ModuleMetadata {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub date_saved: Option<i64>,
+    pub extensions: Vec<String>,
+    pub palettes: Vec<String>,
+    pub maps: Vec<String>,
+    pub sides: Vec<String>,
+    pub target_module: Option<String>,
+    pub target_module_version: Option<String>
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct BuildFileMetadata {
+    pub palettes: Vec<String>,
+    pub maps: Vec<String>,
+    pub sides: Vec<String>,
+    pub target_module: Option<String>,
+    pub target_module_version: Option<String>
+}
+
+fn string_at(
+    document: &sxd_document::dom::Document,
+    xpath: &str
+) -> Option<String>
+{
+    sxd_xpath::evaluate_xpath(document, xpath)
+        .ok()
+        .map(Value::into_string)
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+}
+
+fn strings_at(
+    document: &sxd_document::dom::Document,
+    xpath: &str
+) -> Result<Vec<String>, Error>
+{
+    Ok(
+        match sxd_xpath::evaluate_xpath(document, xpath)? {
+            Value::Nodeset(ns) => ns.document_order()
+                .into_iter()
+                .map(|n| n.string_value().trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect(),
+            _ => vec![]
+        }
+    )
+}
+
+pub fn metadata_in_moduledata(
+    md: &str
+) -> Result<ModuleMetadata, Error>
+{
+    let package = sxd_document::parser::parse(md)?;
+    let document = package.as_document();
+
+    Ok(
+        ModuleMetadata {
+            name: string_at(&document, "/data/name"),
+            description: string_at(&document, "/data/description"),
+            // <dateSaved> is in milliseconds since the epoch
+            date_saved: string_at(&document, "/data/dateSaved")
+                .and_then(|d| d.parse::<i64>().ok())
+                .and_then(|d| d.checked_mul(1_000_000)),
+            extensions: strings_at(&document, "/data/extensions/*")?,
+            // extensions declare the module they extend
+            target_module: string_at(&document, "/data/module"),
+            target_module_version: string_at(&document, "/data/moduleVersion"),
+            ..Default::default()
+        }
+    )
+}
+
+pub fn metadata_in_buildfile(
+    bf: &str
+) -> Result<BuildFileMetadata, Error>
+{
+    let package = sxd_document::parser::parse(bf)?;
+    let document = package.as_document();
+
+    Ok(
+        BuildFileMetadata {
+            palettes: strings_at(
+                &document,
+                "//VASSAL.build.module.PieceWindow/@name"
+            )?,
+            maps: strings_at(
+                &document,
+                "//VASSAL.build.module.Map/@mapName |
+                 //VASSAL.build.module.PrivateMap/@mapName |
+                 //VASSAL.build.module.PlayerHand/@mapName"
+            )?,
+            sides: strings_at(
+                &document,
+                "//VASSAL.build.module.PlayerRoster/entry"
+            )?,
+            target_module: string_at(
+                &document,
+                "/VASSAL.build.module.ModuleExtension/@module"
+            ),
+            target_module_version: string_at(
+                &document,
+                "/VASSAL.build.module.ModuleExtension/@moduleVersion"
+            )
+        }
+    )
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -159,4 +297,121 @@ mod test {
             (None, None)
         );
     }
+
+    #[test]
+    fn dump_buildfile_sync_none() {
+        assert_eq!(dump_buildfile_sync("test/test.vmod").unwrap(), None);
+    }
+
+    #[test]
+    fn dump_buildfile_sync_not_a_zip() {
+        assert!(
+            matches!(
+                dump_buildfile_sync("test/empty").unwrap_err(),
+                Error::Zip(_)
+            )
+        );
+    }
+
+    #[test]
+    fn metadata_in_moduledata_ok() {
+        let md = "<data><version>0.1</version><name>Test Game</name><description>A test</description><dateSaved>1690000000000</dateSaved><extensions><extension>a.vmdx</extension><extension>b.vmdx</extension></extensions></data>";
+        assert_eq!(
+            metadata_in_moduledata(md).unwrap(),
+            ModuleMetadata {
+                name: Some("Test Game".into()),
+                description: Some("A test".into()),
+                date_saved: Some(1690000000000000000),
+                extensions: vec!["a.vmdx".into(), "b.vmdx".into()],
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn metadata_in_moduledata_empty() {
+        let md = "<data><name></name><dateSaved>bogus</dateSaved></data>";
+        assert_eq!(
+            metadata_in_moduledata(md).unwrap(),
+            ModuleMetadata::default()
+        );
+    }
+
+    #[test]
+    fn metadata_in_buildfile_ok() {
+        let bf = r#"<?xml version="1.0" encoding="UTF-8" standalone="no"?>
+<VASSAL.build.GameModule name="Test Game" version="0.1">
+  <VASSAL.build.module.PlayerRoster buttonText="Retire">
+    <entry>Allies</entry>
+    <entry>Axis</entry>
+  </VASSAL.build.module.PlayerRoster>
+  <VASSAL.build.module.PieceWindow name="Counters">
+  </VASSAL.build.module.PieceWindow>
+  <VASSAL.build.module.Map mapName="Main Map">
+  </VASSAL.build.module.Map>
+  <VASSAL.build.module.PrivateMap mapName="Allied Hand">
+  </VASSAL.build.module.PrivateMap>
+  <VASSAL.build.module.PlayerHand mapName="Axis Hand">
+  </VASSAL.build.module.PlayerHand>
+</VASSAL.build.GameModule>"#;
+
+        assert_eq!(
+            metadata_in_buildfile(bf).unwrap(),
+            BuildFileMetadata {
+                palettes: vec!["Counters".into()],
+                maps: vec![
+                    "Main Map".into(),
+                    "Allied Hand".into(),
+                    "Axis Hand".into()
+                ],
+                sides: vec!["Allies".into(), "Axis".into()],
+                target_module: None,
+                target_module_version: None
+            }
+        );
+    }
+
+    #[test]
+    fn metadata_in_moduledata_extension() {
+        let md = "<data><version>1.0</version><module>Test Game</module><moduleVersion>2.1</moduleVersion></data>";
+        assert_eq!(
+            metadata_in_moduledata(md).unwrap(),
+            ModuleMetadata {
+                target_module: Some("Test Game".into()),
+                target_module_version: Some("2.1".into()),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn metadata_in_buildfile_extension() {
+        let bf = r#"<?xml version="1.0" encoding="UTF-8" standalone="no"?>
+<VASSAL.build.module.ModuleExtension module="Test Game" moduleVersion="2.1" version="1.0">
+  <VASSAL.build.module.ExtensionElement target="">
+    <VASSAL.build.module.Map mapName="Extra Map">
+    </VASSAL.build.module.Map>
+  </VASSAL.build.module.ExtensionElement>
+</VASSAL.build.module.ModuleExtension>"#;
+
+        assert_eq!(
+            metadata_in_buildfile(bf).unwrap(),
+            BuildFileMetadata {
+                maps: vec!["Extra Map".into()],
+                target_module: Some("Test Game".into()),
+                target_module_version: Some("2.1".into()),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn metadata_in_buildfile_bad_xml() {
+        assert!(
+            matches!(
+                metadata_in_buildfile("<VASSAL.build.GameModule>").unwrap_err(),
+                Error::Xml(_)
+            )
+        );
+    }
 }
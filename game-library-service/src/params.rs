@@ -1,6 +1,11 @@
-use glc::pagination::{Anchor, Facet, Limit, Direction, SortBy, Seek};
+use glc::{
+    model::SuggestKind,
+    pagination::{Anchor, Facet, Limit, Direction, SortBy, Seek}
+};
 use serde::Deserialize;
 
+use crate::version::Version;
+
 #[derive(Debug, Default, Deserialize, Eq, PartialEq)]
 pub struct MaybeProjectsParams {
     pub q: Option<String>,
@@ -23,7 +28,10 @@ pub struct MaybeProjectsParams {
     #[serde(default)]
     pub owner: Vec<String>,
     #[serde(default)]
-    pub player: Vec<String>
+    pub player: Vec<String>,
+    pub vassal: Option<Version>,
+    #[serde(default)]
+    pub counts: bool
 }
 
 #[derive(Debug, Default, Deserialize, Eq, PartialEq)]
@@ -35,7 +43,8 @@ pub struct ProjectsParams {
     pub anchor: Option<Anchor>,
 */
     pub seek: Seek,
-    pub limit: Option<Limit>
+    pub limit: Option<Limit>,
+    pub counts: bool
 }
 
 #[derive(Debug, thiserror::Error, PartialEq)]
@@ -64,7 +73,9 @@ impl TryFrom<MaybeProjectsParams> for ProjectsParams {
             length_max,
             tag,
             owner,
-            player
+            player,
+            vassal,
+            counts
         } = m;
 
         if sort_by == Some(SortBy::Relevance) && q.is_none() {
@@ -81,6 +92,7 @@ impl TryFrom<MaybeProjectsParams> for ProjectsParams {
             (players_max.is_some() as usize) +
             (length_min.is_some() as usize) +
             (length_max.is_some() as usize) +
+            (vassal.is_some() as usize) +
             players_inc.len() +
             tag.len() +
             owner.len() +
@@ -124,6 +136,10 @@ impl TryFrom<MaybeProjectsParams> for ProjectsParams {
         facets.extend(owner.into_iter().map(Facet::Owner));
         facets.extend(player.into_iter().map(Facet::Player));
 
+        if let Some(vassal) = vassal {
+            facets.push(Facet::Vassal(vassal.to_string()));
+        }
+
         // assemble the Seek
         let seek = match (has_query, from, sort_by, dir, anchor) {
             // sort_by, dir, anchor
@@ -163,10 +179,59 @@ impl TryFrom<MaybeProjectsParams> for ProjectsParams {
             _ => return Err(Error::InvalidCombination)
         };
 
-        Ok(ProjectsParams { seek, limit })
+        Ok(ProjectsParams { seek, limit, counts })
     }
 }
 
+#[derive(Debug, Default, Deserialize, Eq, PartialEq)]
+pub struct ProjectParams {
+    pub vassal: Option<Version>
+}
+
+#[derive(Debug, Default, Deserialize, Eq, PartialEq)]
+pub struct PublishersParams {
+    #[serde(default)]
+    pub counts: bool
+}
+
+#[derive(Debug, Default, Deserialize, Eq, PartialEq)]
+pub struct ImageParams {
+    pub size: Option<u32>
+}
+
+#[derive(Debug, Default, Deserialize, Eq, PartialEq)]
+pub struct DigestParams {
+    pub sha256: Option<String>
+}
+
+#[derive(Debug, Default, Deserialize, Eq, PartialEq)]
+pub struct GarbageParams {
+    #[serde(default)]
+    pub dry_run: bool,
+    pub grace: Option<u64>
+}
+
+#[derive(Debug, Default, Deserialize, Eq, PartialEq)]
+pub struct DeleteParams {
+    #[serde(default)]
+    pub cascade: bool
+}
+
+#[derive(Debug, Default, Deserialize, Eq, PartialEq)]
+pub struct LatestParams {
+    #[serde(default)]
+    pub pre: bool,
+    pub vassal: Option<Version>
+}
+
+#[derive(Debug, Default, Deserialize, Eq, PartialEq)]
+pub struct SuggestParams {
+    pub q: String,
+    #[serde(default)]
+    pub kind: SuggestKind,
+    pub limit: Option<Limit>
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -185,7 +250,8 @@ mod test {
                 anchor: Anchor::Start,
                 facets: vec![]
             },
-            limit: None
+            limit: None,
+            counts: false
         };
 
         assert_eq!(ProjectsParams::try_from(mpp).unwrap(), pp);
@@ -221,7 +287,8 @@ mod test {
                 anchor: Anchor::Start,
                 facets: vec![]
             },
-            limit: None
+            limit: None,
+            counts: false
         };
 
         assert_eq!(ProjectsParams::try_from(mpp).unwrap(), pp);
@@ -272,7 +339,8 @@ mod test {
                 anchor: Anchor::Start,
                 facets: vec![]
             },
-            limit: None
+            limit: None,
+            counts: false
         };
 
         assert_eq!(ProjectsParams::try_from(mpp).unwrap(), pp);
@@ -292,7 +360,8 @@ mod test {
                 anchor: Anchor::After("whatever".into(), 0),
                 facets: vec![]
             },
-            limit: None
+            limit: None,
+            counts: false
         };
 
         assert_eq!(ProjectsParams::try_from(mpp).unwrap(), pp);
@@ -312,7 +381,8 @@ mod test {
                 anchor: Anchor::Start,
                 facets: vec![]
             },
-            limit: Limit::new(50)
+            limit: Limit::new(50),
+            counts: false
         };
 
         assert_eq!(ProjectsParams::try_from(mpp).unwrap(), pp);
@@ -332,7 +402,8 @@ mod test {
                 anchor: Anchor::Start,
                 facets: vec![ Facet::Publisher("abc".into()) ]
             },
-            limit: None
+            limit: None,
+            counts: false
         };
 
         assert_eq!(ProjectsParams::try_from(mpp).unwrap(), pp);
@@ -352,7 +423,8 @@ mod test {
                 anchor: Anchor::Start,
                 facets: vec![ Facet::Year("1979".into()) ]
             },
-            limit: None
+            limit: None,
+            counts: false
         };
 
         assert_eq!(ProjectsParams::try_from(mpp).unwrap(), pp);
@@ -375,7 +447,8 @@ mod test {
                     Facet::Tag("y".into())
                 ]
             },
-            limit: None
+            limit: None,
+            counts: false
         };
 
         assert_eq!(ProjectsParams::try_from(mpp).unwrap(), pp);
@@ -398,7 +471,8 @@ mod test {
                     Facet::Owner("y".into())
                 ]
             },
-            limit: None
+            limit: None,
+            counts: false
         };
 
         assert_eq!(ProjectsParams::try_from(mpp).unwrap(), pp);
@@ -421,7 +495,8 @@ mod test {
                     Facet::Player("y".into())
                 ]
             },
-            limit: None
+            limit: None,
+            counts: false
         };
 
         assert_eq!(ProjectsParams::try_from(mpp).unwrap(), pp);
@@ -438,7 +513,8 @@ mod test {
                 anchor: Anchor::After("battle for fallujah: april 2004".into(), 446),
                 facets: vec![Facet::Query("Battle".into())]
             },
-            limit: Limit::new(50)
+            limit: Limit::new(50),
+            counts: false
         };
 
         let Query(act): Query<ProjectsParams> = Query::try_from_uri(&uri)
@@ -446,4 +522,106 @@ mod test {
 
         assert_eq!(act, exp);
     }
+
+    #[test]
+    fn maybe_projects_params_vassal_query_ok() {
+        let uri: Uri = "http://example.com?vassal=3.7.12".parse().unwrap();
+
+        let exp = ProjectsParams {
+            seek: Seek {
+                sort_by: SortBy::default(),
+                dir: SortBy::default().default_direction(),
+                anchor: Anchor::Start,
+                facets: vec![Facet::Vassal("3.7.12".into())]
+            },
+            limit: None,
+            counts: false
+        };
+
+        let Query(act): Query<ProjectsParams> = Query::try_from_uri(&uri)
+            .unwrap();
+
+        assert_eq!(act, exp);
+    }
+
+    #[test]
+    fn maybe_projects_params_bad_vassal_query() {
+        let uri: Uri = "http://example.com?vassal=bogus".parse().unwrap();
+        assert!(Query::<ProjectsParams>::try_from_uri(&uri).is_err());
+    }
+
+    #[test]
+    fn latest_params_query_ok() {
+        let uri: Uri = "http://example.com?pre=true&vassal=3.7.12".parse().unwrap();
+
+        let exp = LatestParams {
+            pre: true,
+            vassal: Some("3.7.12".parse().unwrap())
+        };
+
+        let Query(act): Query<LatestParams> = Query::try_from_uri(&uri)
+            .unwrap();
+
+        assert_eq!(act, exp);
+    }
+
+    #[test]
+    fn latest_params_query_default() {
+        let uri: Uri = "http://example.com".parse().unwrap();
+
+        let Query(act): Query<LatestParams> = Query::try_from_uri(&uri)
+            .unwrap();
+
+        assert_eq!(act, LatestParams::default());
+    }
+
+    #[test]
+    fn latest_params_query_bad_vassal() {
+        let uri: Uri = "http://example.com?vassal=3.7".parse().unwrap();
+        assert!(Query::<LatestParams>::try_from_uri(&uri).is_err());
+    }
+
+    #[test]
+    fn suggest_params_query_ok() {
+        let uri: Uri = "http://example.com?q=Str&kind=publisher&limit=5".parse().unwrap();
+
+        let exp = SuggestParams {
+            q: "Str".into(),
+            kind: SuggestKind::Publisher,
+            limit: Limit::new(5)
+        };
+
+        let Query(act): Query<SuggestParams> = Query::try_from_uri(&uri)
+            .unwrap();
+
+        assert_eq!(act, exp);
+    }
+
+    #[test]
+    fn suggest_params_query_default_kind() {
+        let uri: Uri = "http://example.com?q=Str".parse().unwrap();
+
+        let exp = SuggestParams {
+            q: "Str".into(),
+            kind: SuggestKind::Title,
+            limit: None
+        };
+
+        let Query(act): Query<SuggestParams> = Query::try_from_uri(&uri)
+            .unwrap();
+
+        assert_eq!(act, exp);
+    }
+
+    #[test]
+    fn suggest_params_query_no_q() {
+        let uri: Uri = "http://example.com?kind=tag".parse().unwrap();
+        assert!(Query::<SuggestParams>::try_from_uri(&uri).is_err());
+    }
+
+    #[test]
+    fn suggest_params_query_bad_kind() {
+        let uri: Uri = "http://example.com?q=Str&kind=bogus".parse().unwrap();
+        assert!(Query::<SuggestParams>::try_from_uri(&uri).is_err());
+    }
 }
@@ -1,13 +1,15 @@
 use async_trait::async_trait;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, SecondsFormat, Utc};
 use futures_util::future::try_join_all;
 use glc::{
     discourse::UserUpdateParams,
-    model::{FileData, FlagData, Flags, GalleryImage, GameData, PackageData, ProjectData, Projects, ProjectSummary, Publishers, Range, ReleaseData, Tags, Users},
+    model::{ExtensionTarget, FileData, FlagData, Flags, GalleryImage, GameData, OrphanedObject, OrphanedObjects, PackageData, ProjectData, Projects, ProjectSummary, PublisherSummaries, PublisherSummary, Publishers, Range, ReleaseData, ReleaseState, SharedFile, SharedFiles, SuggestKind, Suggestions, Tags, UserMergeReport, Users},
     pagination::{Anchor, Direction, Facet, Limit, SortBy, Pagination, Seek, SeekLink}
 };
 use mime::Mime;
 use std::{
+    cmp::Reverse,
+    collections::HashSet,
     future::Future,
     io,
     path::{Path, PathBuf}
@@ -16,19 +18,20 @@ use tokio::{
     fs::File,
     io::{AsyncRead, AsyncReadExt, AsyncSeek, AsyncSeekExt}
 };
-use tracing::info;
+use tracing::{info, warn};
 use unicode_ccc::{CanonicalCombiningClass, get_canonical_combining_class};
 use unicode_normalization::UnicodeNormalization;
 use unicode_properties::{GeneralCategoryGroup, UnicodeGeneralCategory};
 
 use crate::{
     content_type::{infer_image_type, infer_file_type, supported_image_type},
-    core::{AddImageError, AddFileError, AddFlagError, AddOwnersError, AddPlayerError, CloseFlagError, Core, CreatePackageError, CreateProjectError, CreateReleaseError, DeletePackageError, DeleteReleaseError, GetFlagsError, GetIdError, GetImageError, GetPlayersError, GetProjectError, GetProjectsError, GetPublishersError, GetOwnersError, GetTagsError, RemoveOwnersError, RemovePlayerError, UpdateGalleryError, UpdatePackageError, UpdateProjectError, UpdateUserError, UserIsOwnerError},
-    db::{DatabaseClient, DatabaseError, FileRow, FlagRow, MidField, PackageRow, ProjectRow, ProjectSummaryRow, ReleaseRow},
-    input::{is_valid_package_name, slug_for, ConsecutiveWhitespace, FlagPost, GameDataPatch, GameDataPost, GalleryPatch, PackageDataPatch, PackageDataPost, ProjectDataPatch, ProjectDataPost},
+    core::{AddDownloadError, AddImageError, AddFileError, AddFlagError, AddOwnersError, AddPlayerError, CloseFlagError, CollectGarbageError, Core, CreatePackageError, CreateProjectError, CreateReleaseError, DeleteFileError, DeletePackageError, DeleteProjectError, DeleteReleaseError, GetFlagsError, GetIdError, GetImageError, GetPlayersError, GetProjectError, GetProjectsError, GetPublishersError, GetOwnersError, GetSharedFilesError, GetSuggestionsError, GetTagsError, MergePublishersError, MergeUsersError, RemoveOwnersError, RemovePlayerError, RenameProjectError, RenamePublisherError, RestoreProjectError, UpdateGalleryError, UpdatePackageError, UpdateProjectError, UpdateReleaseError, UpdateUserError, UserIsOwnerError},
+    db::{DatabaseClient, DatabaseError, FileModuleItemRow, FileRow, FlagRow, MidField, ModuleTarget, PackageRow, ProjectRow, ProjectSummaryRow, ReleaseRow, SharedFileRow},
+    input::{is_valid_package_name, slug_for, ConsecutiveWhitespace, FlagPost, GameDataPatch, GameDataPost, GalleryPatch, PackageDataPatch, PackageDataPost, ProjectDataPatch, ProjectDataPost, ProjectRenamePost, PublisherRenamePost, ReleaseDataPatch, ReleaseDataPost},
     model::{Admin, Flag, Owner, Package, Project, Release, User},
-    module::{dump_moduledata, versions_in_moduledata},
+    module::{ModuleMetadata, dump_buildfile, dump_moduledata, metadata_in_buildfile, metadata_in_moduledata, versions_in_moduledata},
     params::ProjectsParams,
+    rendition::{self, ImageLimits, Rendition, image_format, process_image, rendition_filename},
     time::{self, nanos_to_rfc3339, rfc3339_to_nanos},
     upload::Uploader,
     version::Version
@@ -41,6 +44,7 @@ pub struct ProdCore<C: DatabaseClient, U: Uploader> {
     pub now: fn() -> DateTime<Utc>,
     pub max_file_size: usize,
     pub max_image_size: usize,
+    pub image_limits: ImageLimits,
     pub upload_dir: PathBuf
 }
 
@@ -77,9 +81,13 @@ where
         proj: &str
     ) -> Result<Project, GetIdError>
     {
-        self.db.get_project_id(proj)
-            .await?
-            .ok_or(GetIdError::NotFound)
+        match self.db.get_project_id(proj).await? {
+            Some(proj) => Ok(proj),
+            // fall back to former slugs of renamed projects
+            None => self.db.get_project_alias_id(proj)
+                .await?
+                .ok_or(GetIdError::NotFound)
+        }
     }
 
     async fn get_owners(
@@ -122,10 +130,18 @@ where
         params: ProjectsParams
     ) -> Result<Projects, GetProjectsError>
     {
-        let ProjectsParams { seek, limit } = params;
+        let ProjectsParams { seek, limit, counts } = params;
+
+        // resolve the query expression once for all the queries below
+        let search = self.db.get_search_facets(&seek.facets).await?;
+
+        let counts = match counts {
+            true => Some(self.db.get_facet_counts(&search).await?),
+            false => None
+        };
 
-        let (prev, next, projects, total) = self.get_projects_from(
-            seek, limit.unwrap_or_default()
+        let (prev, next, projects, total) = self.get_projects_matching(
+            seek, &search, limit.unwrap_or_default()
         ).await?;
 
         let prev_page = prev.map(|prev| SeekLink::new(&prev, limit));
@@ -138,7 +154,8 @@ where
                     prev_page,
                     next_page,
                     total
-                }
+                },
+                counts
             },
         )
     }
@@ -209,6 +226,75 @@ where
         Ok(self.db.update_project(owner, proj, proj_data, now).await?)
     }
 
+    async fn delete_project(
+        &self,
+        requester: User,
+        proj: Project
+    ) -> Result<(), DeleteProjectError>
+    {
+        let now = self.now_nanos()?;
+        Ok(self.db.delete_project(requester, proj, now).await?)
+    }
+
+    async fn restore_project(
+        &self,
+        admin: Admin,
+        proj: &str
+    ) -> Result<(), RestoreProjectError>
+    {
+        let proj = self.db.get_deleted_project_id(proj)
+            .await?
+            .ok_or(RestoreProjectError::NotFound)?;
+
+        let now = self.now_nanos()?;
+        Ok(self.db.restore_project(admin, proj, now).await?)
+    }
+
+    async fn rename_project(
+        &self,
+        admin: Admin,
+        proj: Project,
+        proj_data: &ProjectRenamePost
+    ) -> Result<(), RenameProjectError>
+    {
+        let now = self.now_nanos()?;
+
+        check_project_name(&proj_data.name)?;
+        let slug = slug_for(&proj_data.name);
+
+        Ok(
+            self.db.rename_project(admin, proj, &proj_data.name, &slug, now)
+                .await?
+        )
+    }
+
+    async fn get_project_compatible(
+        &self,
+        proj: Project,
+        vassal: &Version
+    ) -> Result<ProjectData, GetProjectError>
+    {
+        let compatible = self.db.get_vassal_compatible_releases(proj, vassal)
+            .await?;
+
+        self.get_project_impl(
+            proj,
+            self.db.get_project_row(proj).await?,
+            self.db.get_project_tags(proj).await?,
+            self.db.get_gallery(proj).await?,
+            self.db.get_packages(proj).await?,
+            |pc, pkg| {
+                let compatible = &compatible;
+                async move {
+                    let mut rows = pc.db.get_releases(pkg).await?;
+                    rows.retain(|rr| compatible.contains(&rr.release_id));
+                    Ok(rows)
+                }
+            },
+            |pc, rel| pc.db.get_files(rel)
+        ).await
+    }
+
     async fn get_project_revision(
         &self,
         proj: Project,
@@ -251,9 +337,19 @@ where
         pkg: &str
     ) -> Result<(Project, Package), GetIdError>
     {
-        self.db.get_project_package_ids(proj, pkg)
-            .await?
-            .ok_or(GetIdError::NotFound)
+        match self.db.get_project_package_ids(proj, pkg).await? {
+            Some(ids) => Ok(ids),
+            // fall back to former slugs of renamed projects
+            None => {
+                let proj = self.db.get_project_alias_id(proj)
+                    .await?
+                    .ok_or(GetIdError::NotFound)?;
+
+                let pkg = self.get_package_id(proj, pkg).await?;
+
+                Ok((proj, pkg))
+            }
+        }
     }
 
     async fn create_package(
@@ -292,10 +388,11 @@ where
         owner: Owner,
         proj: Project,
         pkg: Package,
+        cascade: bool
     ) -> Result<(), DeletePackageError>
     {
         let now = self.now_nanos()?;
-        Ok(self.db.delete_package(owner, proj, pkg, now).await?)
+        Ok(self.db.delete_package(owner, proj, pkg, cascade, now).await?)
     }
 
     async fn get_release_id(
@@ -317,9 +414,87 @@ where
         release: &str
     ) -> Result<(Project, Package, Release), GetIdError>
     {
-        self.db.get_project_package_release_ids(proj, pkg, release)
+        match self.db.get_project_package_release_ids(proj, pkg, release).await? {
+            Some(ids) => Ok(ids),
+            // fall back to former slugs of renamed projects
+            None => {
+                let proj = self.db.get_project_alias_id(proj)
+                    .await?
+                    .ok_or(GetIdError::NotFound)?;
+
+                let pkg = self.get_package_id(proj, pkg).await?;
+                let release = self.get_release_id(proj, pkg, release).await?;
+
+                Ok((proj, pkg, release))
+            }
+        }
+    }
+
+    async fn get_latest_release(
+        &self,
+        proj: Project,
+        pkg: Package,
+        pre: bool,
+        vassal: Option<&Version>
+    ) -> Result<ReleaseData, GetProjectError>
+    {
+        let compatible = match vassal {
+            Some(v) => Some(
+                self.db.get_vassal_compatible_releases(proj, v).await?
+            ),
+            None => None
+        };
+
+        let mut rows = self.db.get_releases(pkg)
             .await?
-            .ok_or(GetIdError::NotFound)
+            .into_iter()
+            .filter(|rr| is_latest_candidate(rr, pre))
+            .filter(|rr| compatible.as_ref()
+                .is_none_or(|c| c.contains(&rr.release_id))
+            )
+            .collect::<Vec<_>>();
+
+        rows.sort_by_key(|rr| Reverse(Version::from(rr)));
+
+        // the latest is the greatest candidate which has files
+        for rr in rows {
+            let rd = self.make_release_data(
+                rr,
+                &|pc: &Self, rel| pc.db.get_files(rel)
+            ).await?;
+
+            if !rd.files.is_empty() {
+                return Ok(rd);
+            }
+        }
+
+        Err(GetProjectError::NotFound)
+    }
+
+    async fn get_latest_file(
+        &self,
+        proj: Project,
+        pkg: Package,
+        filename: &str,
+        pre: bool,
+        vassal: Option<&Version>
+    ) -> Result<String, GetProjectError>
+    {
+        self.get_latest_release(proj, pkg, pre, vassal)
+            .await?
+            .files
+            .into_iter()
+            .find(|f| f.filename == filename)
+            .map(|f| f.url)
+            .ok_or(GetProjectError::NotFound)
+    }
+
+    async fn add_download(
+        &self,
+        proj: Project
+    ) -> Result<(), AddDownloadError>
+    {
+        Ok(self.db.add_download(proj).await?)
     }
 
     async fn create_release(
@@ -327,23 +502,57 @@ where
         owner: Owner,
         proj: Project,
         pkg: Package,
-        version: &str
+        version: &str,
+        rel_data: &ReleaseDataPost
     ) -> Result<(), CreateReleaseError>
     {
         let now = self.now_nanos()?;
         let version = version.parse::<Version>()?;
-        Ok(self.db.create_release(owner, proj, pkg, &version, now).await?)
+        Ok(
+            self.db.create_release(
+                owner,
+                proj,
+                pkg,
+                &version,
+                rel_data,
+                now
+            ).await?
+        )
+    }
+
+    async fn update_release(
+        &self,
+        owner: Owner,
+        proj: Project,
+        rel: Release,
+        rel_data: &ReleaseDataPatch
+    ) -> Result<(), UpdateReleaseError>
+    {
+        // only versions with a pre-release tag are pre-releases, so that
+        // un-yanking restores the state the version implies
+        if let Some(state) = &rel_data.state
+            && *state != ReleaseState::Yanked
+        {
+            let version = self.db.get_release_version(rel).await?;
+            if version.pre.is_some() != (*state == ReleaseState::PreRelease) {
+                return Err(UpdateReleaseError::StateContradictsVersion);
+            }
+        }
+
+        let now = self.now_nanos()?;
+        Ok(self.db.update_release(owner, proj, rel, rel_data, now).await?)
     }
 
     async fn delete_release(
         &self,
         owner: Owner,
         proj: Project,
-        rel: Release
+        rel: Release,
+        cascade: bool
     ) -> Result<(), DeleteReleaseError>
     {
         let now = self.now_nanos()?;
-        Ok(self.db.delete_release(owner, proj, rel, now).await?)
+        Ok(self.db.delete_release(owner, proj, rel, cascade, now).await?)
     }
 
     async fn add_file(
@@ -385,7 +594,7 @@ where
         // has one of those extensions or zip.
 
         // check uploaded file for moduledata
-        let requires = match ext {
+        let (requires, module) = match ext {
             Some(ext @ "vlog") |
             Some(ext @ "vmdx") |
             Some(ext @ "vmod") |
@@ -396,47 +605,80 @@ where
                     Ok(md) => {
                         let (mver, vver) = check_moduledata(&md, ext).await?;
 
-                        if ext == "vmdx" {
+                        let requires = if ext == "vmdx" {
                             // extensions need only a valid version
-                            Ok(None)
+                            None
                         }
                         else {
                             // modules, saves, logs must match release version
                             let rver = self.db.get_release_version(release)
                                 .await?;
-                            check_versions(mver, rver, vver, ext)
-                        }
+                            check_versions(mver, rver, vver, ext)?
+                        };
+
+                        let module = read_module_metadata(&md, path, ext)
+                            .await?;
+
+                        Ok((requires, Some(module)))
                     },
                     // modules, extensions, saves, logs must have moduledata
                     Err(e) => Err(AddFileError::ModuleError(e))
                 }?
             },
-            _ => None
+            _ => (None, None)
         };
 
 //        info!("checked version of temp file {}", file.file_path().display());
 
-        // add hash prefix to file upload path
+        // extensions record where the module they extend was found
+        let target = match &module {
+            Some(module) => self.resolve_module_target(proj, module).await?,
+            None => None
+        };
+
+        // modules propose one player per side
+        let players = match (ext, &module) {
+            (Some("vmod"), Some(module)) => player_side_count(&module.sides),
+            _ => None
+        };
+
+        // files are stored under their hash, so identical files share an
+        // object whatever their filenames
         let bucket_path = format!(
-            "{0}/{1}/{filename}",
+            "{0}/{1}/{sha256}",
             &sha256[0..1],
             &sha256[1..2]
         );
 
 //        info!("going to rewind temp file {}", file.file_path().display());
 
-        file.rewind().await?;
+        // reuse an existing object with the same content
+        let existing = self.db.get_file_url_by_hash(
+            sha256,
+            size as i64
+        ).await?;
+
+        let url = match existing {
+            Some(url) => {
+                info!("reusing {url} for {filename}");
+                url
+            },
+            None => {
+                file.rewind().await?;
 
-//        info!("starting to upload temp file {}", file.file_path().display());
+//                info!("starting to upload temp file {}", file.file_path().display());
 
-        let url = self.uploader.upload_with_content_type(
-            &bucket_path,
-            &mut file,
-            content_type.as_ref()
-        )
-        .await?;
+                self.uploader.upload_with_filename(
+                    &bucket_path,
+                    &mut file,
+                    content_type.as_ref(),
+                    filename
+                )
+                .await?
 
-//        info!("finished upload of temp file {}", file.file_path().display());
+//                info!("finished upload of temp file {}", file.file_path().display());
+            }
+        };
 
         // update record
         self.db.add_file_url(
@@ -447,7 +689,10 @@ where
             size as i64,
             sha256,
             content_type.as_ref(),
-            requires.as_deref(),
+            requires.as_ref(),
+            module.as_ref(),
+            target.as_ref(),
+            players,
             &url,
             now
         ).await?;
@@ -455,6 +700,20 @@ where
         Ok(())
     }
 
+    async fn delete_file(
+        &self,
+        owner: Owner,
+        proj: Project,
+        release: Release,
+        filename: &str
+    ) -> Result<(), DeleteFileError>
+    {
+        let now = self.now_nanos()?;
+        Ok(
+            self.db.delete_file(owner, proj, release, filename, now).await?
+        )
+    }
+
     async fn get_players(
         &self,
         proj: Project
@@ -496,7 +755,7 @@ where
         let now = self.now_nanos()?;
 
         // do the upload
-        let url = self.upload_image(
+        let img = self.upload_image(
             filename,
             content_type,
             size,
@@ -510,8 +769,10 @@ where
             owner,
             proj,
             filename,
-            &url,
+            &img.url,
             content_type.as_ref(),
+            img.dimensions,
+            &img.renditions,
             now
         ).await?;
 
@@ -532,9 +793,20 @@ where
     async fn get_image(
         &self,
         proj: Project,
-        img_name: &str
+        img_name: &str,
+        size: Option<u32>
     ) -> Result<String, GetImageError>
     {
+        if let Some(size) = size {
+            let url = self.db.get_image_rendition_url(proj, img_name, size)
+                .await?;
+
+            if let Some(url) = url {
+                return Ok(url);
+            }
+            // otherwise, no rendition is large enough; use the original
+        }
+
         self.db.get_image_url(proj, img_name)
             .await?
             .ok_or(GetImageError::NotFound)
@@ -570,7 +842,7 @@ where
         let now = self.now_nanos()?;
 
         // do the upload
-        let url = self.upload_image(
+        let img = self.upload_image(
             filename,
             content_type,
             size,
@@ -584,8 +856,10 @@ where
             owner,
             proj,
             filename,
-            &url,
+            &img.url,
             content_type.as_ref(),
+            img.dimensions,
+            &img.renditions,
             now
         ).await?;
 
@@ -596,10 +870,71 @@ where
         Ok(Publishers { publishers: self.db.get_publishers_active().await? })
     }
 
+    async fn get_publisher_summaries(
+        &self
+    ) -> Result<PublisherSummaries, GetPublishersError>
+    {
+        Ok(
+            PublisherSummaries {
+                publishers: self.db.get_publishers_active_counts()
+                    .await?
+                    .into_iter()
+                    .map(|r| PublisherSummary {
+                        name: r.name,
+                        projects: r.projects
+                    })
+                    .collect()
+            }
+        )
+    }
+
+    async fn rename_publisher(
+        &self,
+        publisher: &str,
+        publisher_data: &PublisherRenamePost
+    ) -> Result<(), RenamePublisherError>
+    {
+        Ok(self.db.rename_publisher(publisher, &publisher_data.name).await?)
+    }
+
+    async fn merge_publishers(
+        &self,
+        src: &str,
+        dst: &str
+    ) -> Result<(), MergePublishersError>
+    {
+        if src == dst {
+            return Err(MergePublishersError::CannotMergeIntoSelf);
+        }
+
+        Ok(self.db.merge_publishers(src, dst).await?)
+    }
+
     async fn get_tags(&self) -> Result<Tags, GetTagsError> {
         Ok(Tags { tags: self.db.get_tags_active().await? })
     }
 
+    async fn get_suggestions(
+        &self,
+        kind: SuggestKind,
+        q: &str,
+        limit: Limit
+    ) -> Result<Suggestions, GetSuggestionsError>
+    {
+        // everything is a completion of nothing, so suggest nothing
+        let q = q.trim();
+        let suggestions = match q.is_empty() {
+            true => vec![],
+            false => self.db.get_suggestions(
+                kind,
+                q,
+                limit.get() as u32
+            ).await?
+        };
+
+        Ok(Suggestions { suggestions })
+    }
+
     async fn get_flag_id(
          &self,
         flag: i64
@@ -646,12 +981,87 @@ where
         )
     }
 
+    async fn get_shared_files(
+        &self,
+        sha256: &str
+    ) -> Result<SharedFiles, GetSharedFilesError>
+    {
+        Ok(
+            SharedFiles {
+                sha256: sha256.into(),
+                files: self.db.get_files_by_hash(sha256)
+                    .await?
+                    .into_iter()
+                    .map(SharedFile::try_from)
+                    .collect::<Result<Vec<_>, _>>()?
+            }
+        )
+    }
+
+    async fn collect_garbage(
+        &self,
+        dry_run: bool,
+        grace: u64
+    ) -> Result<OrphanedObjects, CollectGarbageError>
+    {
+        // list the objects before getting the references, so that an object
+        // stored in between is either referenced or within the grace period
+        let stored = self.uploader.list().await?;
+
+        let referenced = self.db.get_referenced_urls()
+            .await?
+            .into_iter()
+            .collect::<HashSet<_>>();
+
+        let now = (self.now)();
+        let mut objects = vec![];
+
+        for obj in stored {
+            let age = now.signed_duration_since(obj.last_modified).num_seconds();
+
+            // skip referenced objects and those too new to judge
+            if referenced.contains(&obj.url) ||
+                !u64::try_from(age).is_ok_and(|age| age >= grace)
+            {
+                continue;
+            }
+
+            if !dry_run {
+                info!("deleting orphaned object {}", obj.path);
+                self.uploader.delete(&obj.path).await?;
+            }
+
+            objects.push(
+                OrphanedObject {
+                    url: obj.url,
+                    size: obj.size,
+                    last_modified: obj.last_modified
+                        .to_rfc3339_opts(SecondsFormat::AutoSi, true)
+                }
+            );
+        }
+
+        Ok(OrphanedObjects { dry_run, objects })
+    }
+
     async fn update_user(
         &self,
         params: &UserUpdateParams
     ) -> Result<(), UpdateUserError> {
         Ok(self.db.update_user(params).await?)
     }
+
+    async fn merge_users(
+        &self,
+        src: User,
+        dst: User
+    ) -> Result<UserMergeReport, MergeUsersError> {
+        if src == dst {
+            return Err(MergeUsersError::CannotMergeIntoSelf);
+        }
+
+        Ok(self.db.merge_users(src, dst).await?)
+    }
 }
 
 impl<C, U> ProdCore<C, U>
@@ -665,11 +1075,61 @@ where
             .ok_or(time::Error::OutOfRangeDateTime(dt))
     }
 
-    async fn make_file_data(
+    async fn resolve_module_target(
+        &self,
+        proj: Project,
+        module: &ModuleMetadata
+    ) -> Result<Option<ModuleTarget>, DatabaseError>
+    {
+        let Some(target) = &module.target_module else {
+            return Ok(None);
+        };
+
+        let Some(tproj) = self.db.get_module_project(proj, target).await?
+        else {
+            return Ok(None);
+        };
+
+        // an unreadable module version cannot be satisfied
+        let min = match module.target_module_version.as_deref() {
+            Some(v) => match minimum_module_version(v) {
+                Some(min) => Some(min),
+                None => return Ok(
+                    Some(ModuleTarget { project: tproj, release: None })
+                )
+            },
+            None => None
+        };
+
+        // find the newest release of the module which satisfies the version
+        let release = self.db.get_module_releases(tproj, target)
+            .await?
+            .into_iter()
+            .filter(|(_, v)| min.as_ref().is_none_or(|min| v >= min))
+            .max_by(|(_, a), (_, b)| a.cmp(b))
+            .map(|(r, _)| r);
+
+        Ok(Some(ModuleTarget { project: tproj, release }))
+    }
+
+    fn make_file_data(
         &self,
-        r: FileRow
+        r: FileRow,
+        items: &[FileModuleItemRow]
     ) -> Result<FileData, GetProjectError>
     {
+        let items_of = |kind: &str| items.iter()
+            .filter(|i| i.file_id == r.id && i.kind == kind)
+            .map(|i| i.value.clone())
+            .collect::<Vec<_>>();
+
+        let extends = r.target_module.map(|module| ExtensionTarget {
+            module,
+            version: r.target_module_version,
+            project: r.target_project,
+            release: r.target_release
+        });
+
         Ok(
             FileData {
                 filename: r.filename,
@@ -679,6 +1139,10 @@ where
                 published_at: nanos_to_rfc3339(r.published_at)?,
                 published_by: r.published_by,
                 requires: r.requires,
+                module_name: r.module_name,
+                sides: items_of("side"),
+                maps: items_of("map"),
+                extends
             }
         )
     }
@@ -692,16 +1156,27 @@ where
         FF: Fn(&'s Self, Release) -> FR,
         FR: Future<Output = Result<Vec<FileRow>, DatabaseError>>
     {
-        let files = try_join_all(
-            get_files_rows(self, Release(rr.release_id))
-                .await?
-                .into_iter()
-                .map(|fr| self.make_file_data(fr))
-        ).await?;
+        let rel = Release(rr.release_id);
+
+        // load the module items for all files at once
+        let items = self.db.get_release_module_items(rel).await?;
+
+        let files = get_files_rows(self, rel)
+            .await?
+            .into_iter()
+            .map(|fr| self.make_file_data(fr, &items))
+            .collect::<Result<Vec<_>, _>>()?;
 
         Ok(
             ReleaseData {
                 version: rr.version,
+                changelog: rr.changelog,
+                released_at: nanos_to_rfc3339(rr.released_at)?,
+                state: ReleaseState::try_from(rr.state.as_str())
+                    .map_err(|e| DatabaseError::from(
+                        sqlx::Error::Decode(e.into())
+                    ))?,
+                yank_reason: rr.yank_reason,
                 files
             }
         )
@@ -815,10 +1290,16 @@ where
     {
         let mf = match sort_by {
             SortBy::CreationTime |
-            SortBy::ModificationTime => MidField::Timestamp(
+            SortBy::ModificationTime |
+            SortBy::ReleaseTime => MidField::Timestamp(
                 rfc3339_to_nanos(field)
                     .map_err(|_| GetProjectsError::MalformedQuery)?
             ),
+            SortBy::Players |
+            SortBy::LatestDownloads => MidField::Count(
+                field.parse::<i64>()
+                    .map_err(|_| GetProjectsError::MalformedQuery)?
+            ),
             SortBy::Relevance => MidField::Weight(
                 field.parse::<f64>()
                     .map_err(|_| GetProjectsError::MalformedQuery)?
@@ -877,6 +1358,7 @@ where
         }
     }
 
+    #[cfg(test)]
     async fn get_projects_from(
         &self,
         seek: Seek,
@@ -890,19 +1372,38 @@ where
         ),
         GetProjectsError
     >
+    {
+        let search = self.db.get_search_facets(&seek.facets).await?;
+        self.get_projects_matching(seek, &search, limit).await
+    }
+
+    async fn get_projects_matching(
+        &self,
+        seek: Seek,
+        search: &[Facet],
+        limit: Limit
+    ) -> Result<
+        (
+            Option<Seek>,
+            Option<Seek>,
+            Vec<ProjectSummary>,
+            i64
+        ),
+        GetProjectsError
+    >
     {
         // unpack the seek
         let Seek { sort_by, dir, anchor, facets } = seek;
 
         // get the total number of responsive items
-        let total = self.db.get_projects_count(&facets).await?;
+        let total = self.db.get_projects_count(search).await?;
 
         // try to get one extra so we can tell if we're at an endpoint
         let limit_extra = limit.get() as u32 + 1;
 
         // get the window
         let mut projects = self.get_projects_window(
-            &facets,
+            search,
             &anchor,
             sort_by,
             dir,
@@ -946,7 +1447,7 @@ where
         sha256: &str,
         mut file: &mut File,
         now: i64
-    ) -> Result<String, AddImageError>
+    ) -> Result<UploadedImage, AddImageError>
     {
         // MIME type should be an image
         if !supported_image_type(content_type) {
@@ -968,21 +1469,74 @@ where
         let content_type = infer_image_type(ext, &buf)
             .map_err(|_| AddImageError::BadMimeType)?;
 
-// TODO: check dimensions? resize?
-
         file.rewind().await?;
 
-        let url = self.uploader.upload_with_content_type(
-            &bucket_path,
-            &mut file,
+        let format = image_format(&content_type)
+            .ok_or(AddImageError::BadMimeType)?;
+
+        // decode, check dimensions, strip metadata
+        let mut buf = vec![];
+        file.read_to_end(&mut buf).await?;
+
+        let img = process_image(buf, format, self.image_limits)
+            .await
+            .map_err(|e| match e {
+                rendition::Error::Io(e) => AddImageError::IOError(e),
+                rendition::Error::Image(_) |
+                rendition::Error::Malformed |
+                rendition::Error::Metadata => AddImageError::BadMimeType,
+                rendition::Error::TooLarge(..) => AddImageError::TooManyPixels
+            })?;
+
+        let url = self.uploader.upload_with_content_type(
+            &bucket_path,
+            img.data.as_slice(),
             content_type.as_ref()
         )
         .await?;
 
-        Ok(url)
+        let renditions = self.upload_renditions(
+            &bucket_path,
+            img.renditions
+        ).await?;
+
+        Ok(
+            UploadedImage {
+                url,
+                dimensions: Some((img.width, img.height)),
+                renditions
+            }
+        )
+    }
+
+    async fn upload_renditions(
+        &self,
+        bucket_path: &str,
+        renditions: Vec<Rendition>
+    ) -> Result<Vec<(u32, String)>, AddImageError>
+    {
+        let mut urls = Vec::with_capacity(renditions.len());
+        for r in renditions {
+            let url = self.uploader.upload_with_content_type(
+                &rendition_filename(bucket_path, r.size),
+                r.data.as_slice(),
+                "image/webp"
+            )
+            .await?;
+
+            urls.push((r.size, url));
+        }
+
+        Ok(urls)
     }
 }
 
+struct UploadedImage {
+    url: String,
+    dimensions: Option<(u32, u32)>,
+    renditions: Vec<(u32, String)>
+}
+
 fn get_prev_for_before(
     facets: &[Facet],
     anchor: &Anchor,
@@ -1231,6 +1785,12 @@ impl From<InvalidProjectName> for CreateProjectError {
     }
 }
 
+impl From<InvalidProjectName> for RenameProjectError {
+    fn from(_: InvalidProjectName) -> Self {
+        RenameProjectError::InvalidProjectName
+    }
+}
+
 fn is_valid_project_name(name: &str) -> bool {
     // project names must be 5 to 64 characters long
     // project names must contain only L, M, N, P, Z category characters
@@ -1315,7 +1875,10 @@ impl ProjectSummaryRow {
                 SortBy::GameTitle => self.game_title_sort.clone(),
                 SortBy::ModificationTime => nanos_to_rfc3339(self.modified_at)?,
                 SortBy::CreationTime => nanos_to_rfc3339(self.created_at)?,
-                SortBy::Relevance => self.rank.to_string()
+                SortBy::Relevance => self.rank.to_string(),
+                SortBy::Players => self.players.to_string(),
+                SortBy::LatestDownloads => self.downloads.to_string(),
+                SortBy::ReleaseTime => nanos_to_rfc3339(self.released_at)?
             }
         )
     }
@@ -1353,6 +1916,26 @@ fn make_project_summary(
     )
 }
 
+impl TryFrom<SharedFileRow> for SharedFile {
+    type Error = GetSharedFilesError;
+
+    fn try_from(r: SharedFileRow) -> Result<Self, Self::Error> {
+        Ok(
+            SharedFile {
+                project: r.project,
+                slug: urlencoding::encode(&r.slug).into(),
+                package: r.package,
+                version: r.version,
+                filename: r.filename,
+                url: r.url,
+                size: r.size,
+                published_at: nanos_to_rfc3339(r.published_at)?,
+                published_by: r.published_by
+            }
+        )
+    }
+}
+
 impl TryFrom<FlagRow> for FlagData {
     type Error = GetFlagsError;
 
@@ -1381,10 +1964,48 @@ where
     Ok(buf)
 }
 
-fn format_minimum_vassal_version(v_vstr: &str) -> Option<String> {
+fn minimum_vassal_version(v_vstr: &str) -> Option<Version> {
     v_vstr.parse::<Version>()
         .ok()
-        .map(|v| format!(">= {}.{}.{}", v.major, v.minor, v.patch))
+        .map(|v| Version { pre: None, build: None, ..v })
+}
+
+fn minimum_module_version(v_mstr: &str) -> Option<Version> {
+    // extensions often give the module version as only major.minor
+    [v_mstr.to_string(), format!("{v_mstr}.0"), format!("{v_mstr}.0.0")]
+        .iter()
+        .find_map(|v| v.parse::<Version>().ok())
+}
+
+fn is_latest_candidate(rr: &ReleaseRow, pre: bool) -> bool {
+    match ReleaseState::try_from(rr.state.as_str()) {
+        Ok(ReleaseState::Stable) => true,
+        Ok(ReleaseState::PreRelease) => pre,
+        _ => false
+    }
+}
+
+// sides which modules commonly declare but which no player takes
+const NON_PLAYER_SIDES: &[&str] = &[
+    "game master",
+    "gm",
+    "kibitzer",
+    "moderator",
+    "observer",
+    "observers",
+    "referee",
+    "solitaire",
+    "solo",
+    "spectator",
+    "umpire"
+];
+
+fn player_side_count(sides: &[String]) -> Option<u32> {
+    let count = sides.iter()
+        .filter(|s| !NON_PLAYER_SIDES.contains(&s.trim().to_lowercase().as_str()))
+        .count() as u32;
+
+    Some(count).filter(|&c| c > 0)
 }
 
 async fn check_moduledata(
@@ -1400,18 +2021,56 @@ async fn check_moduledata(
     ))
 }
 
+async fn read_module_metadata(
+    md: &str,
+    path: &Path,
+    ext: &str
+) -> Result<ModuleMetadata, AddFileError>
+{
+    let mut module = metadata_in_moduledata(md)?;
+
+    // only modules and extensions have a buildFile
+    if ext == "vmod" || ext == "vmdx" {
+        let bfm = dump_buildfile(path).await
+            .and_then(|bf| bf.map(|bf| metadata_in_buildfile(&bf)).transpose());
+
+        // buildFile metadata is extra; a bad buildFile is not fatal
+        match bfm {
+            Ok(Some(bfm)) => {
+                module.palettes = bfm.palettes;
+                module.maps = bfm.maps;
+                module.sides = bfm.sides;
+                module.target_module = module.target_module
+                    .or(bfm.target_module);
+                module.target_module_version = module.target_module_version
+                    .or(bfm.target_module_version);
+            },
+            Ok(None) => {},
+            Err(e) => warn!("ignoring unreadable buildFile: {e}")
+        }
+    }
+
+    // only extensions extend a module
+    if ext != "vmdx" {
+        module.target_module = None;
+        module.target_module_version = None;
+    }
+
+    Ok(module)
+}
+
 fn check_versions(
     mod_version: Version,
     rel_version: Version,
     vas_version: Option<String>,
     ext: &str
-) -> Result<Option<String>, AddFileError>
+) -> Result<Option<Version>, AddFileError>
 {
     match mod_version == rel_version {
         true => Ok(match ext == "vmod" {
             // set minimum required Vassal version
             true => vas_version.as_deref()
-                .and_then(format_minimum_vassal_version),
+                .and_then(minimum_vassal_version),
                 // not a module
             false => None
         }),
@@ -1430,8 +2089,9 @@ mod test {
 
     use crate::{
         input::{GameDataPatch, GameDataPost, RangePatch, RangePost},
+        fake_s3::FakeS3,
         sqlite::{Pool, SqlxDatabaseClient},
-        upload::UploadError
+        upload::{BucketUploader, StoredObject, UploadError}
     };
 
     const NOW: &str = "2023-11-12T15:50:06.419538067Z";
@@ -1471,6 +2131,27 @@ mod test {
         {
             unreachable!();
         }
+
+        async fn upload_with_filename<R>(
+            &self,
+            _path: &str,
+            _reader: R,
+            _content_type: &str,
+            _filename: &str
+        ) -> Result<String, UploadError>
+        where
+            R: AsyncRead + Unpin + Send
+        {
+            unreachable!();
+        }
+
+        async fn list(&self) -> Result<Vec<StoredObject>, UploadError> {
+            unreachable!();
+        }
+
+        async fn delete(&self, _path: &str) -> Result<(), UploadError> {
+            unreachable!();
+        }
     }
 
     fn make_core(
@@ -1484,6 +2165,10 @@ mod test {
             now,
             max_file_size: 256,
             max_image_size: 256,
+            image_limits: ImageLimits {
+                max_width: 4096,
+                max_height: 4096
+            },
             upload_dir: "uploads".into()
         }
     }
@@ -1587,6 +2272,63 @@ mod test {
         );
     }
 
+    #[sqlx::test(fixtures("users", "projects"))]
+    async fn get_projects_counts(pool: Pool) {
+        let core = make_core(pool, fake_now);
+
+        let projects = core.get_projects(
+            ProjectsParams {
+                counts: true,
+                ..Default::default()
+            }
+        ).await.unwrap();
+
+        let counts = projects.counts.unwrap();
+        assert_eq!(
+            counts.publishers.values().sum::<i64>(),
+            projects.meta.total
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "ten_projects"))]
+    async fn get_projects_no_counts(pool: Pool) {
+        let core = make_core(pool, fake_now);
+
+        let projects = core.get_projects(ProjectsParams::default())
+            .await
+            .unwrap();
+
+        assert_eq!(projects.counts, None);
+    }
+
+    #[sqlx::test(fixtures("users", "projects"))]
+    async fn get_suggestions_ok(pool: Pool) {
+        let core = make_core(pool, fake_now);
+
+        assert_eq!(
+            core.get_suggestions(
+                SuggestKind::Title,
+                " some ",
+                Limit::default()
+            ).await.unwrap(),
+            Suggestions { suggestions: vec!["Some Other Game".into()] }
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects"))]
+    async fn get_suggestions_empty(pool: Pool) {
+        let core = make_core(pool, fake_now);
+
+        assert_eq!(
+            core.get_suggestions(
+                SuggestKind::Title,
+                "  ",
+                Limit::default()
+            ).await.unwrap(),
+            Suggestions { suggestions: vec![] }
+        );
+    }
+
     #[sqlx::test(fixtures("users", "ten_projects"))]
     async fn get_projects_pname_start_ok(pool: Pool) {
         let core = make_core(pool, fake_now);
@@ -2345,135 +3087,385 @@ mod test {
         );
     }
 
-    #[sqlx::test(fixtures("users", "projects", "two_owners", "packages"))]
-    async fn get_project_ok(pool: Pool) {
+    #[sqlx::test(fixtures("users", "ten_projects", "proj_sort_window"))]
+    async fn get_projects_players_start_ok(pool: Pool) {
         let core = make_core(pool, fake_now);
+
+        let (prev, next, summaries, total) = core.get_projects_from(
+            Seek {
+                sort_by: SortBy::Players,
+                dir: Direction::Descending,
+                anchor: Anchor::Start,
+                facets: vec![]
+            },
+            Limit::new(3).unwrap()
+        ).await.unwrap();
+
         assert_eq!(
-            core.get_project(Project(42)).await.unwrap(),
-            ProjectData {
-                name: "test_game".into(),
-                slug: "test_game".into(),
-                description: "Brian's Trademarked Game of Being a Test Case".into(),
-                revision: 3,
-                created_at: "2023-11-12T15:50:06.419538067Z".into(),
-                modified_at: "2023-12-14T15:50:06.419538067Z".into(),
-                tags: vec![],
-                game: GameData {
-                    title: "A Game of Tests".into(),
-                    title_sort_key: "game of tests, a".into(),
-                    publisher: "Test Game Company".into(),
-                    year: "1979".into(),
-                    players: Range { min: None, max: Some(3) },
-                    length: Range::default()
-                },
-                readme: "".into(),
-                image: None,
-                owners: vec!["alice".into(), "bob".into()],
-                packages: vec![
-                    PackageData {
-                        name: "a_package".into(),
-                        slug: "a_package".into(),
-                        sort_key: 0,
-                        description: "".into(),
-                        releases: vec![
-                            ReleaseData {
-                                version: "1.2.4".into(),
-                                files: vec![
-                                    FileData {
-                                        filename: "a_package-1.2.4".into(),
-                                        url: "https://example.com/a_package-1.2.4".into(),
-                                        size: 5678,
-                                        sha256: "79fdd8fe3128f818e446e919cce5dcfb81815f8f4341c53f4d6b58ded48cebf2".into(),
-                                        published_at: "2023-12-10T15:56:29.180282477Z".into(),
-                                        published_by: "alice".into(),
-                                        requires: Some(">= 3.7.12".into())
-                                    },
-                                ],
-                            },
-                            ReleaseData {
-                                version: "1.2.3".into(),
-                                files: vec![
-                                    FileData {
-                                        filename: "a_package-1.2.3".into(),
-                                        url: "https://example.com/a_package-1.2.3".into(),
-                                        size: 1234,
-                                        sha256: "c0e0fa7373a12b45a91e4f4d4e2e186442fc6ee9b346caa2fdc1c09026a2144a".into(),
-                                        published_at: "2023-12-09T15:56:29.180282477Z".into(),
-                                        published_by: "bob".into(),
-                                        requires: Some(">= 3.2.17".into())
-                                    }
-                                ]
-                            }
-                        ]
-                    },
-                    PackageData {
-                        name: "b_package".into(),
-                        slug: "b_package".into(),
-                        sort_key: 1,
-                        description: "".into(),
-                        releases: vec![]
-                    },
-                    PackageData {
-                        name: "c_package".into(),
-                        slug: "c_package".into(),
-                        sort_key: 2,
-                        description: "".into(),
-                        releases: vec![
-                            ReleaseData {
-                                version: "0.1.0".into(),
-                                files: vec![
-                                    FileData {
-                                        filename: "c_package-0.1.0".into(),
-                                        url: "https://example.com/c_package-0.1.0".into(),
-                                        size: 123456,
-                                        sha256: "a8f515e9e2de99919d1a987733296aaa951a4ba2aa0f7014c510bdbd60dc0efd".into(),
-                                        published_at: "2023-12-15T15:56:29.180282477Z".into(),
-                                        published_by: "chuck".into(),
-                                        requires: None
-                                    }
-                                ]
-                            }
-                        ]
-                    }
-                ],
-                gallery: vec![]
-            }
+            summaries,
+            [
+                fake_project_summary("b"),
+                fake_project_summary("c"),
+                fake_project_summary("a")
+            ]
+        );
+
+        assert_eq!(total, 10);
+
+        assert_eq!(prev, None);
+
+        assert_eq!(
+            next,
+            Some(
+                Seek {
+                    anchor: Anchor::After("1".into(), 1),
+                    sort_by: SortBy::Players,
+                    dir: Direction::Descending,
+                    facets: vec![]
+                }
+            )
         );
     }
 
-    #[sqlx::test(fixtures("users", "projects", "two_owners", "packages"))]
-    async fn get_project_revision_ok_current(pool: Pool) {
+    #[sqlx::test(fixtures("users", "ten_projects", "proj_sort_window"))]
+    async fn get_projects_players_after_desc_ok(pool: Pool) {
         let core = make_core(pool, fake_now);
+
+        // projects tied on players are ordered by id
+        let (prev, next, summaries, total) = core.get_projects_from(
+            Seek {
+                sort_by: SortBy::Players,
+                dir: Direction::Descending,
+                anchor: Anchor::After("1".into(), 3),
+                facets: vec![]
+            },
+            Limit::new(3).unwrap()
+        ).await.unwrap();
+
         assert_eq!(
-            core.get_project_revision(Project(42), 3).await.unwrap(),
-            ProjectData {
-                name: "test_game".into(),
-                slug: "test_game".into(),
-                description: "Brian's Trademarked Game of Being a Test Case".into(),
-                revision: 3,
-                created_at: "2023-11-12T15:50:06.419538067Z".into(),
-                modified_at: "2023-12-14T15:50:06.419538067Z".into(),
-                tags: vec![],
-                game: GameData {
-                    title: "A Game of Tests".into(),
-                    title_sort_key: "game of tests, a".into(),
-                    publisher: "Test Game Company".into(),
-                    year: "1979".into(),
-                    players: Range { min: None, max: Some(3) },
-                    length: Range::default()
-                },
-                readme: "".into(),
-                image: None,
-                owners: vec!["alice".into(), "bob".into()],
-                packages: vec![
-                    PackageData {
-                        name: "a_package".into(),
-                        slug: "a_package".into(),
-                        sort_key: 0,
-                        description: "".into(),
-                        releases: vec![
+            summaries,
+            [
+                fake_project_summary("a"),
+                fake_project_summary("j"),
+                fake_project_summary("i")
+            ]
+        );
+
+        assert_eq!(total, 10);
+
+        assert_eq!(
+            prev,
+            Some(
+                Seek {
+                    anchor: Anchor::Before("1".into(), 1),
+                    sort_by: SortBy::Players,
+                    dir: Direction::Descending,
+                    facets: vec![]
+                }
+            )
+        );
+
+        assert_eq!(
+            next,
+            Some(
+                Seek {
+                    anchor: Anchor::After("0".into(), 9),
+                    sort_by: SortBy::Players,
+                    dir: Direction::Descending,
+                    facets: vec![]
+                }
+            )
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "ten_projects", "proj_sort_window"))]
+    async fn get_projects_downloads_before_desc_ok(pool: Pool) {
+        let core = make_core(pool, fake_now);
+
+        let (prev, next, summaries, total) = core.get_projects_from(
+            Seek {
+                sort_by: SortBy::LatestDownloads,
+                dir: Direction::Descending,
+                anchor: Anchor::Before("0".into(), 10),
+                facets: vec![]
+            },
+            Limit::new(2).unwrap()
+        ).await.unwrap();
+
+        assert_eq!(
+            summaries,
+            [
+                fake_project_summary("c"),
+                fake_project_summary("a")
+            ]
+        );
+
+        assert_eq!(total, 10);
+
+        assert_eq!(
+            prev,
+            Some(
+                Seek {
+                    anchor: Anchor::Before("5".into(), 3),
+                    sort_by: SortBy::LatestDownloads,
+                    dir: Direction::Descending,
+                    facets: vec![]
+                }
+            )
+        );
+
+        assert_eq!(
+            next,
+            Some(
+                Seek {
+                    anchor: Anchor::After("5".into(), 1),
+                    sort_by: SortBy::LatestDownloads,
+                    dir: Direction::Descending,
+                    facets: vec![]
+                }
+            )
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "ten_projects", "proj_sort_window"))]
+    async fn get_projects_release_time_after_desc_ok(pool: Pool) {
+        let core = make_core(pool, fake_now);
+
+        let (prev, next, summaries, total) = core.get_projects_from(
+            Seek {
+                sort_by: SortBy::ReleaseTime,
+                dir: Direction::Descending,
+                anchor: Anchor::After(
+                    "1970-01-01T00:00:00.000000300Z".into(),
+                    4
+                ),
+                facets: vec![]
+            },
+            Limit::new(2).unwrap()
+        ).await.unwrap();
+
+        assert_eq!(
+            summaries,
+            [
+                fake_project_summary("b"),
+                fake_project_summary("a")
+            ]
+        );
+
+        assert_eq!(total, 10);
+
+        assert_eq!(
+            prev,
+            Some(
+                Seek {
+                    anchor: Anchor::Before(
+                        "1970-01-01T00:00:00.000000300Z".into(),
+                        2
+                    ),
+                    sort_by: SortBy::ReleaseTime,
+                    dir: Direction::Descending,
+                    facets: vec![]
+                }
+            )
+        );
+
+        assert_eq!(
+            next,
+            Some(
+                Seek {
+                    anchor: Anchor::After(
+                        "1970-01-01T00:00:00.000000100Z".into(),
+                        1
+                    ),
+                    sort_by: SortBy::ReleaseTime,
+                    dir: Direction::Descending,
+                    facets: vec![]
+                }
+            )
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "ten_projects", "proj_sort_window"))]
+    async fn get_projects_downloads_bad_anchor(pool: Pool) {
+        let core = make_core(pool, fake_now);
+
+        assert_eq!(
+            core.get_projects_from(
+                Seek {
+                    sort_by: SortBy::LatestDownloads,
+                    dir: Direction::Descending,
+                    anchor: Anchor::After("x".into(), 1),
+                    facets: vec![]
+                },
+                Limit::new(2).unwrap()
+            ).await.unwrap_err(),
+            GetProjectsError::MalformedQuery
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects"))]
+    async fn add_download_ok(pool: Pool) {
+        let core = make_core(pool, fake_now);
+        core.add_download(Project(42)).await.unwrap();
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "two_owners", "packages"))]
+    async fn get_project_ok(pool: Pool) {
+        let core = make_core(pool, fake_now);
+        assert_eq!(
+            core.get_project(Project(42)).await.unwrap(),
+            ProjectData {
+                name: "test_game".into(),
+                slug: "test_game".into(),
+                description: "Brian's Trademarked Game of Being a Test Case".into(),
+                revision: 3,
+                created_at: "2023-11-12T15:50:06.419538067Z".into(),
+                modified_at: "2023-12-14T15:50:06.419538067Z".into(),
+                tags: vec![],
+                game: GameData {
+                    title: "A Game of Tests".into(),
+                    title_sort_key: "game of tests, a".into(),
+                    publisher: "Test Game Company".into(),
+                    year: "1979".into(),
+                    players: Range { min: None, max: Some(3) },
+                    length: Range::default()
+                },
+                readme: "".into(),
+                image: None,
+                owners: vec!["alice".into(), "bob".into()],
+                packages: vec![
+                    PackageData {
+                        name: "a_package".into(),
+                        slug: "a_package".into(),
+                        sort_key: 0,
+                        description: "".into(),
+                        releases: vec![
+                            ReleaseData {
+                                version: "1.2.4".into(),
+                                changelog: None,
+                                released_at: "2023-12-10T15:56:29.180282477Z".into(),
+                                state: ReleaseState::Stable,
+                                yank_reason: None,
+                                files: vec![
+                                    FileData {
+                                        filename: "a_package-1.2.4".into(),
+                                        url: "https://example.com/a_package-1.2.4".into(),
+                                        size: 5678,
+                                        sha256: "79fdd8fe3128f818e446e919cce5dcfb81815f8f4341c53f4d6b58ded48cebf2".into(),
+                                        published_at: "2023-12-10T15:56:29.180282477Z".into(),
+                                        published_by: "alice".into(),
+                                        requires: Some(">= 3.7.12".into()),
+                                        module_name: None,
+                                        sides: vec![],
+                                        maps: vec![],
+                                        extends: None
+                                    },
+                                ],
+                            },
+                            ReleaseData {
+                                version: "1.2.3".into(),
+                                changelog: None,
+                                released_at: "2023-12-09T15:56:29.180282477Z".into(),
+                                state: ReleaseState::Stable,
+                                yank_reason: None,
+                                files: vec![
+                                    FileData {
+                                        filename: "a_package-1.2.3".into(),
+                                        url: "https://example.com/a_package-1.2.3".into(),
+                                        size: 1234,
+                                        sha256: "c0e0fa7373a12b45a91e4f4d4e2e186442fc6ee9b346caa2fdc1c09026a2144a".into(),
+                                        published_at: "2023-12-09T15:56:29.180282477Z".into(),
+                                        published_by: "bob".into(),
+                                        requires: Some(">= 3.2.17".into()),
+                                        module_name: None,
+                                        sides: vec![],
+                                        maps: vec![],
+                                        extends: None
+                                    }
+                                ]
+                            }
+                        ]
+                    },
+                    PackageData {
+                        name: "b_package".into(),
+                        slug: "b_package".into(),
+                        sort_key: 1,
+                        description: "".into(),
+                        releases: vec![]
+                    },
+                    PackageData {
+                        name: "c_package".into(),
+                        slug: "c_package".into(),
+                        sort_key: 2,
+                        description: "".into(),
+                        releases: vec![
+                            ReleaseData {
+                                version: "0.1.0".into(),
+                                changelog: None,
+                                released_at: "2023-12-15T15:56:29.180282477Z".into(),
+                                state: ReleaseState::Stable,
+                                yank_reason: None,
+                                files: vec![
+                                    FileData {
+                                        filename: "c_package-0.1.0".into(),
+                                        url: "https://example.com/c_package-0.1.0".into(),
+                                        size: 123456,
+                                        sha256: "a8f515e9e2de99919d1a987733296aaa951a4ba2aa0f7014c510bdbd60dc0efd".into(),
+                                        published_at: "2023-12-15T15:56:29.180282477Z".into(),
+                                        published_by: "chuck".into(),
+                                        requires: None,
+                                        module_name: None,
+                                        sides: vec![],
+                                        maps: vec![],
+                                        extends: None
+                                    }
+                                ]
+                            }
+                        ]
+                    }
+                ],
+                gallery: vec![]
+            }
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "two_owners", "packages"))]
+    async fn get_project_revision_ok_current(pool: Pool) {
+        let core = make_core(pool, fake_now);
+        assert_eq!(
+            core.get_project_revision(Project(42), 3).await.unwrap(),
+            ProjectData {
+                name: "test_game".into(),
+                slug: "test_game".into(),
+                description: "Brian's Trademarked Game of Being a Test Case".into(),
+                revision: 3,
+                created_at: "2023-11-12T15:50:06.419538067Z".into(),
+                modified_at: "2023-12-14T15:50:06.419538067Z".into(),
+                tags: vec![],
+                game: GameData {
+                    title: "A Game of Tests".into(),
+                    title_sort_key: "game of tests, a".into(),
+                    publisher: "Test Game Company".into(),
+                    year: "1979".into(),
+                    players: Range { min: None, max: Some(3) },
+                    length: Range::default()
+                },
+                readme: "".into(),
+                image: None,
+                owners: vec!["alice".into(), "bob".into()],
+                packages: vec![
+                    PackageData {
+                        name: "a_package".into(),
+                        slug: "a_package".into(),
+                        sort_key: 0,
+                        description: "".into(),
+                        releases: vec![
                             ReleaseData {
                                 version: "1.2.4".into(),
+                                changelog: None,
+                                released_at: "2023-12-10T15:56:29.180282477Z".into(),
+                                state: ReleaseState::Stable,
+                                yank_reason: None,
                                 files: vec![
                                     FileData {
                                         filename: "a_package-1.2.4".into(),
@@ -2482,12 +3474,20 @@ mod test {
                                         sha256: "79fdd8fe3128f818e446e919cce5dcfb81815f8f4341c53f4d6b58ded48cebf2".into(),
                                         published_at: "2023-12-10T15:56:29.180282477Z".into(),
                                         published_by: "alice".into(),
-                                        requires: Some(">= 3.7.12".into())
+                                        requires: Some(">= 3.7.12".into()),
+                                        module_name: None,
+                                        sides: vec![],
+                                        maps: vec![],
+                                        extends: None
                                     },
                                 ]
                             },
                             ReleaseData {
                                 version: "1.2.3".into(),
+                                changelog: None,
+                                released_at: "2023-12-09T15:56:29.180282477Z".into(),
+                                state: ReleaseState::Stable,
+                                yank_reason: None,
                                 files: vec![
                                     FileData {
                                         filename: "a_package-1.2.3".into(),
@@ -2496,7 +3496,11 @@ mod test {
                                         sha256: "c0e0fa7373a12b45a91e4f4d4e2e186442fc6ee9b346caa2fdc1c09026a2144a".into(),
                                         published_at: "2023-12-09T15:56:29.180282477Z".into(),
                                         published_by: "bob".into(),
-                                        requires: Some(">= 3.2.17".into())
+                                        requires: Some(">= 3.2.17".into()),
+                                        module_name: None,
+                                        sides: vec![],
+                                        maps: vec![],
+                                        extends: None
                                     }
                                 ]
                             }
@@ -2724,8 +3728,622 @@ mod test {
         );
     }
 
-    #[sqlx::test(fixtures("users", "projects", "packages"))]
-    async fn create_package_ok(pool: Pool) {
+    #[sqlx::test(fixtures("users", "projects"))]
+    async fn delete_project_ok(pool: Pool) {
+        let core = make_core(pool, fake_now);
+
+        let proj = core.get_project_id("test_game").await.unwrap();
+        core.delete_project(User(1), proj).await.unwrap();
+        assert_eq!(
+            core.get_project_id("test_game").await.unwrap_err(),
+            GetIdError::NotFound
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects"))]
+    async fn restore_project_ok(pool: Pool) {
+        let core = make_core(pool, fake_now);
+
+        let proj = core.get_project_id("test_game").await.unwrap();
+        core.delete_project(User(1), proj).await.unwrap();
+        core.restore_project(Admin(1), "test_game").await.unwrap();
+        assert_eq!(core.get_project_id("test_game").await.unwrap(), proj);
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn delete_file_ok(pool: Pool) {
+        // the fixture files are published after fake_now
+        let core = make_core(pool, Utc::now);
+
+        let proj = Project(42);
+        let old_data = core.get_project(proj).await.unwrap();
+        let files = |d: &ProjectData| d.packages.iter()
+            .flat_map(|p| &p.releases)
+            .flat_map(|r| &r.files)
+            .map(|f| f.filename.clone())
+            .collect::<Vec<_>>();
+        assert!(files(&old_data).contains(&"a_package-1.2.3".into()));
+
+        core.delete_file(Owner(1), proj, Release(1), "a_package-1.2.3")
+            .await
+            .unwrap();
+
+        // project no longer has the file
+        let new_data = core.get_project(proj).await.unwrap();
+        assert_eq!(new_data.revision, old_data.revision + 1);
+        assert!(!files(&new_data).contains(&"a_package-1.2.3".into()));
+
+        // old revision still has the file
+        let rev_data = core.get_project_revision(proj, old_data.revision)
+            .await
+            .unwrap();
+        assert!(files(&rev_data).contains(&"a_package-1.2.3".into()));
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn delete_file_not_found(pool: Pool) {
+        let core = make_core(pool, fake_now);
+
+        assert_eq!(
+            core.delete_file(Owner(1), Project(42), Release(1), "bogus")
+                .await
+                .unwrap_err(),
+            DeleteFileError::NotFound
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn update_release_ok(pool: Pool) {
+        // the fixture releases are published after fake_now
+        let core = make_core(pool, Utc::now);
+
+        let proj = Project(42);
+        let old_data = core.get_project(proj).await.unwrap();
+
+        let rd = ReleaseDataPatch {
+            changelog: Some(Some("* Fixed the map".into())),
+            released_at: Some(1702137389180282477),
+            ..Default::default()
+        };
+
+        core.update_release(Owner(1), proj, Release(2), &rd).await.unwrap();
+
+        let release = |d: &ProjectData| d.packages[0].releases.iter()
+            .find(|r| r.version == "1.2.4")
+            .cloned()
+            .unwrap();
+
+        // project has the new changelog and date
+        let new_data = core.get_project(proj).await.unwrap();
+        assert_eq!(new_data.revision, old_data.revision + 1);
+        let rel = release(&new_data);
+        assert_eq!(rel.changelog, Some("* Fixed the map".into()));
+        assert_eq!(rel.released_at, "2023-12-09T15:56:29.180282477Z");
+
+        // old revision has the old changelog and date
+        let rev_data = core.get_project_revision(proj, old_data.revision)
+            .await
+            .unwrap();
+        assert_eq!(release(&rev_data), release(&old_data));
+        assert_eq!(release(&rev_data).changelog, None);
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn update_release_yank(pool: Pool) {
+        // the fixture releases are published after fake_now
+        let core = make_core(pool, Utc::now);
+
+        let proj = Project(42);
+        let old_data = core.get_project(proj).await.unwrap();
+
+        let rd = ReleaseDataPatch {
+            state: Some(ReleaseState::Yanked),
+            yank_reason: Some("corrupts saves".into()),
+            ..Default::default()
+        };
+
+        core.update_release(Owner(1), proj, Release(2), &rd).await.unwrap();
+
+        let release = |d: &ProjectData| d.packages[0].releases.iter()
+            .find(|r| r.version == "1.2.4")
+            .cloned()
+            .unwrap();
+
+        // project has the yanked release
+        let new_data = core.get_project(proj).await.unwrap();
+        let rel = release(&new_data);
+        assert_eq!(rel.state, ReleaseState::Yanked);
+        assert_eq!(rel.yank_reason, Some("corrupts saves".into()));
+
+        // old revision has the stable release
+        let rev_data = core.get_project_revision(proj, old_data.revision)
+            .await
+            .unwrap();
+        assert_eq!(release(&rev_data).state, ReleaseState::Stable);
+        assert_eq!(release(&rev_data).yank_reason, None);
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn update_release_unyank(pool: Pool) {
+        // the fixture releases are published after fake_now
+        let core = make_core(pool, Utc::now);
+
+        let rd = ReleaseDataPatch {
+            state: Some(ReleaseState::Yanked),
+            yank_reason: Some("corrupts saves".into()),
+            ..Default::default()
+        };
+
+        core.update_release(Owner(1), Project(42), Release(2), &rd)
+            .await
+            .unwrap();
+
+        let rd = ReleaseDataPatch {
+            state: Some(ReleaseState::Stable),
+            ..Default::default()
+        };
+
+        core.update_release(Owner(1), Project(42), Release(2), &rd)
+            .await
+            .unwrap();
+
+        let proj_data = core.get_project(Project(42)).await.unwrap();
+        let rel = proj_data.packages[0].releases.iter()
+            .find(|r| r.version == "1.2.4")
+            .unwrap();
+        assert_eq!(rel.state, ReleaseState::Stable);
+        assert_eq!(rel.yank_reason, None);
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn update_release_pre_release_contradicts_version(pool: Pool) {
+        let core = make_core(pool, Utc::now);
+
+        let rd = ReleaseDataPatch {
+            state: Some(ReleaseState::PreRelease),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            core.update_release(Owner(1), Project(42), Release(2), &rd)
+                .await
+                .unwrap_err(),
+            UpdateReleaseError::StateContradictsVersion
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn update_release_stable_contradicts_version(pool: Pool) {
+        let core = make_core(pool, Utc::now);
+
+        core.create_release(
+            Owner(1),
+            Project(42),
+            Package(1),
+            "1.3.0-beta",
+            &ReleaseDataPost::default()
+        ).await.unwrap();
+
+        let rel = core.db.get_releases(Package(1))
+            .await
+            .unwrap()
+            .into_iter()
+            .find(|rr| rr.version == "1.3.0-beta")
+            .unwrap();
+
+        let rd = ReleaseDataPatch {
+            state: Some(ReleaseState::Stable),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            core.update_release(Owner(1), Project(42), Release(rel.release_id), &rd)
+                .await
+                .unwrap_err(),
+            UpdateReleaseError::StateContradictsVersion
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn get_project_compatible_ok(pool: Pool) {
+        let core = make_core(pool, fake_now);
+        let vassal = "3.6.0".parse::<Version>().unwrap();
+
+        let proj_data = core.get_project_compatible(Project(42), &vassal)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            proj_data.packages[0].releases.iter()
+                .map(|r| r.version.as_str())
+                .collect::<Vec<_>>(),
+            ["1.2.3"]
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn get_project_compatible_none(pool: Pool) {
+        let core = make_core(pool, fake_now);
+        let vassal = "3.1.0".parse::<Version>().unwrap();
+
+        let proj_data = core.get_project_compatible(Project(42), &vassal)
+            .await
+            .unwrap();
+
+        assert!(
+            proj_data.packages[0].releases.is_empty()
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn get_project_compatible_yanked(pool: Pool) {
+        // the fixture releases are published after fake_now
+        let core = make_core(pool, Utc::now);
+        let vassal = "3.6.0".parse::<Version>().unwrap();
+
+        let rd = ReleaseDataPatch {
+            state: Some(ReleaseState::Yanked),
+            yank_reason: Some("corrupts saves".into()),
+            ..Default::default()
+        };
+
+        core.update_release(Owner(1), Project(42), Release(1), &rd)
+            .await
+            .unwrap();
+
+        let proj_data = core.get_project_compatible(Project(42), &vassal)
+            .await
+            .unwrap();
+
+        assert!(
+            proj_data.packages[0].releases.is_empty()
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn get_latest_release_ok(pool: Pool) {
+        let core = make_core(pool, fake_now);
+        assert_eq!(
+            core.get_latest_release(Project(42), Package(1), false, None)
+                .await
+                .unwrap()
+                .version,
+            "1.2.4"
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn get_latest_release_vassal(pool: Pool) {
+        let core = make_core(pool, fake_now);
+        let vassal = "3.6.0".parse::<Version>().unwrap();
+        assert_eq!(
+            core.get_latest_release(Project(42), Package(1), false, Some(&vassal))
+                .await
+                .unwrap()
+                .version,
+            "1.2.3"
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn get_latest_release_vassal_too_old(pool: Pool) {
+        let core = make_core(pool, fake_now);
+        let vassal = "3.1.0".parse::<Version>().unwrap();
+        assert_eq!(
+            core.get_latest_release(Project(42), Package(1), false, Some(&vassal))
+                .await
+                .unwrap_err(),
+            GetProjectError::NotFound
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn get_latest_release_skips_yanked(pool: Pool) {
+        // the fixture releases are published after fake_now
+        let core = make_core(pool, Utc::now);
+
+        let rd = ReleaseDataPatch {
+            state: Some(ReleaseState::Yanked),
+            yank_reason: Some("corrupts saves".into()),
+            ..Default::default()
+        };
+
+        core.update_release(Owner(1), Project(42), Release(2), &rd)
+            .await
+            .unwrap();
+
+        for pre in [false, true] {
+            assert_eq!(
+                core.get_latest_release(Project(42), Package(1), pre, None)
+                    .await
+                    .unwrap()
+                    .version,
+                "1.2.3"
+            );
+        }
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn get_latest_release_pre_release(pool: Pool) {
+        // the fixture releases are published after fake_now
+        let core = make_core(pool, Utc::now);
+
+        core.create_release(
+            Owner(1),
+            Project(42),
+            Package(1),
+            "1.3.0-beta",
+            &ReleaseDataPost::default()
+        ).await.unwrap();
+
+        let rel = core.db.get_releases(Package(1))
+            .await
+            .unwrap()
+            .into_iter()
+            .find(|rr| rr.version == "1.3.0-beta")
+            .unwrap();
+
+        core.db.add_file_url(
+            Owner(1),
+            Project(42),
+            Release(rel.release_id),
+            "a_package-1.3.0-beta",
+            1234,
+            "deadbeef",
+            "application/octet-stream",
+            None,
+            None,
+            None,
+            None,
+            "https://example.com/a_package-1.3.0-beta",
+            0
+        ).await.unwrap();
+
+        assert_eq!(
+            core.get_latest_release(Project(42), Package(1), false, None)
+                .await
+                .unwrap()
+                .version,
+            "1.2.4"
+        );
+
+        assert_eq!(
+            core.get_latest_release(Project(42), Package(1), true, None)
+                .await
+                .unwrap()
+                .version,
+            "1.3.0-beta"
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn get_latest_release_pre_release_order(pool: Pool) {
+        let core = make_core(pool, Utc::now);
+
+        // create the greater pre-release first
+        for v in ["2.0.0-beta", "2.0.0-alpha"] {
+            core.create_release(
+                Owner(1),
+                Project(42),
+                Package(1),
+                v,
+                &ReleaseDataPost::default()
+            ).await.unwrap();
+
+            let rel = core.db.get_releases(Package(1))
+                .await
+                .unwrap()
+                .into_iter()
+                .find(|rr| rr.version == v)
+                .unwrap();
+
+            core.db.add_file_url(
+                Owner(1),
+                Project(42),
+                Release(rel.release_id),
+                &format!("a_package-{v}"),
+                1234,
+                "deadbeef",
+                "application/octet-stream",
+                None,
+                None,
+                None,
+                None,
+                &format!("https://example.com/a_package-{v}"),
+                0
+            ).await.unwrap();
+        }
+
+        assert_eq!(
+            core.get_latest_release(Project(42), Package(1), true, None)
+                .await
+                .unwrap()
+                .version,
+            "2.0.0-beta"
+        );
+
+        assert_eq!(
+            core.get_latest_release(Project(42), Package(1), false, None)
+                .await
+                .unwrap()
+                .version,
+            "1.2.4"
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn get_latest_release_no_releases(pool: Pool) {
+        let core = make_core(pool, fake_now);
+        assert_eq!(
+            core.get_latest_release(Project(42), Package(2), false, None)
+                .await
+                .unwrap_err(),
+            GetProjectError::NotFound
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn get_latest_file_ok(pool: Pool) {
+        let core = make_core(pool, fake_now);
+        assert_eq!(
+            core.get_latest_file(
+                Project(42),
+                Package(1),
+                "a_package-1.2.4",
+                false,
+                None
+            ).await.unwrap(),
+            "https://example.com/a_package-1.2.4"
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn get_latest_file_not_in_latest(pool: Pool) {
+        let core = make_core(pool, fake_now);
+        assert_eq!(
+            core.get_latest_file(
+                Project(42),
+                Package(1),
+                "a_package-1.2.3",
+                false,
+                None
+            ).await.unwrap_err(),
+            GetProjectError::NotFound
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn rename_project_ok(pool: Pool) {
+        let core = make_core(pool, fake_now);
+
+        let proj = core.get_project_id("test_game").await.unwrap();
+        let pd = ProjectRenamePost { name: "Some New Name".into() };
+        core.rename_project(Admin(1), proj, &pd).await.unwrap();
+
+        assert_eq!(core.get_project_id("Some-New-Name").await.unwrap(), proj);
+        // old slug still resolves
+        assert_eq!(core.get_project_id("test_game").await.unwrap(), proj);
+        assert_eq!(
+            core.get_project_package_ids("test_game", "a_package")
+                .await
+                .unwrap(),
+            (proj, Package(1))
+        );
+        assert_eq!(
+            core.get_project_package_release_ids(
+                "test_game",
+                "a_package",
+                "1.2.3"
+            ).await.unwrap(),
+            (proj, Package(1), Release(1))
+        );
+
+        let data = core.get_project(proj).await.unwrap();
+        assert_eq!(data.name, "Some New Name");
+        assert_eq!(data.slug, "Some-New-Name");
+    }
+
+    #[sqlx::test(fixtures("users", "projects"))]
+    async fn rename_project_invalid_name(pool: Pool) {
+        let core = make_core(pool, fake_now);
+
+        let pd = ProjectRenamePost { name: "💩".into() };
+        assert_eq!(
+            core.rename_project(Admin(1), Project(42), &pd)
+                .await
+                .unwrap_err(),
+            RenameProjectError::InvalidProjectName
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects"))]
+    async fn get_publisher_summaries_ok(pool: Pool) {
+        let core = make_core(pool, fake_now);
+        assert_eq!(
+            core.get_publisher_summaries().await.unwrap(),
+            PublisherSummaries {
+                publishers: vec![
+                    PublisherSummary {
+                        name: "Test Game Company".into(),
+                        projects: 1
+                    },
+                    PublisherSummary {
+                        name: "XYZ".into(),
+                        projects: 1
+                    }
+                ]
+            }
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects"))]
+    async fn rename_publisher_ok(pool: Pool) {
+        let core = make_core(pool, fake_now);
+
+        let pd = PublisherRenamePost { name: "XYZ Games".into() };
+        core.rename_publisher("XYZ", &pd).await.unwrap();
+
+        assert_eq!(
+            core.get_publishers().await.unwrap(),
+            Publishers {
+                publishers: vec![
+                    "Test Game Company".into(),
+                    "XYZ Games".into()
+                ]
+            }
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects"))]
+    async fn rename_publisher_not_found(pool: Pool) {
+        let core = make_core(pool, fake_now);
+
+        let pd = PublisherRenamePost { name: "XYZ Games".into() };
+        assert_eq!(
+            core.rename_publisher("bogus", &pd).await.unwrap_err(),
+            RenamePublisherError::NotFound
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects"))]
+    async fn merge_publishers_ok(pool: Pool) {
+        let core = make_core(pool, fake_now);
+
+        core.merge_publishers("XYZ", "Test Game Company").await.unwrap();
+
+        assert_eq!(
+            core.get_publisher_summaries().await.unwrap(),
+            PublisherSummaries {
+                publishers: vec![
+                    PublisherSummary {
+                        name: "Test Game Company".into(),
+                        projects: 2
+                    }
+                ]
+            }
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects"))]
+    async fn merge_publishers_into_self(pool: Pool) {
+        let core = make_core(pool, fake_now);
+        assert_eq!(
+            core.merge_publishers("XYZ", "XYZ").await.unwrap_err(),
+            MergePublishersError::CannotMergeIntoSelf
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects"))]
+    async fn restore_project_not_deleted(pool: Pool) {
+        let core = make_core(pool, fake_now);
+        assert_eq!(
+            core.restore_project(Admin(1), "test_game").await.unwrap_err(),
+            RestoreProjectError::NotFound
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn create_package_ok(pool: Pool) {
         let core = make_core(pool, fake_now);
 
         let owner = Owner(1);
@@ -2787,6 +4405,32 @@ mod test {
         );
     }
 
+    #[sqlx::test(fixtures("users", "projects", "two_owners"))]
+    async fn merge_users_ok(pool: Pool) {
+        let core = make_core(pool, fake_now);
+
+        let report = core.merge_users(User(2), User(1)).await.unwrap();
+        assert_eq!(report.dropped["owners.user_id"], 2);
+
+        assert_eq!(
+            core.get_owners(Project(42)).await.unwrap(),
+            Users { users: vec!["bob".into()] }
+        );
+        assert_eq!(
+            core.get_user_id("alice").await.unwrap_err(),
+            GetIdError::NotFound
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "one_owner"))]
+    async fn merge_users_into_self(pool: Pool) {
+        let core = make_core(pool, fake_now);
+        assert_eq!(
+            core.merge_users(User(1), User(1)).await.unwrap_err(),
+            MergeUsersError::CannotMergeIntoSelf
+        );
+    }
+
     #[sqlx::test(fixtures("users", "projects", "one_owner"))]
     async fn user_is_owner_true(pool: Pool) {
         let core = make_core(pool, fake_now);
@@ -2876,20 +4520,275 @@ mod test {
         );
     }
 
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn add_file_reuse_existing(pool: Pool) {
+        let core = make_core(pool, fake_now);
+
+        let path = std::env::temp_dir().join(
+            format!("gls-add-file-reuse-{}.txt", std::process::id())
+        );
+        tokio::fs::write(&path, "x".repeat(100)).await.unwrap();
+        let mut file = File::open(&path).await.unwrap();
+
+        // FakeUploader panics if we try to upload
+        core.add_file(
+            Owner(1),
+            Project(42),
+            Release(2),
+            "a_package-1.2.3",
+            None,
+            1234,
+            "c0e0fa7373a12b45a91e4f4d4e2e186442fc6ee9b346caa2fdc1c09026a2144a",
+            &path,
+            &mut file
+        ).await.unwrap();
+
+        tokio::fs::remove_file(&path).await.unwrap();
+
+        let files = core.db.get_files(Release(2)).await.unwrap();
+        let copy = files.iter()
+            .find(|f| f.filename == "a_package-1.2.3")
+            .unwrap();
+        assert_eq!(copy.url, "https://example.com/a_package-1.2.3");
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn add_file_reuse_other_filename(pool: Pool) {
+        let core = make_core(pool, fake_now);
+
+        let path = std::env::temp_dir().join(
+            format!("gls-add-file-reuse-other-{}.txt", std::process::id())
+        );
+        tokio::fs::write(&path, "x".repeat(100)).await.unwrap();
+        let mut file = File::open(&path).await.unwrap();
+
+        // FakeUploader panics if we try to upload
+        core.add_file(
+            Owner(1),
+            Project(42),
+            Release(2),
+            "renamed.txt",
+            None,
+            1234,
+            "c0e0fa7373a12b45a91e4f4d4e2e186442fc6ee9b346caa2fdc1c09026a2144a",
+            &path,
+            &mut file
+        ).await.unwrap();
+
+        tokio::fs::remove_file(&path).await.unwrap();
+
+        let files = core.db.get_files(Release(2)).await.unwrap();
+        let copy = files.iter()
+            .find(|f| f.filename == "renamed.txt")
+            .unwrap();
+        assert_eq!(copy.url, "https://example.com/a_package-1.2.3");
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn add_file_stored_under_hash(pool: Pool) {
+        let (core, s3) = make_bucket_core(pool).await;
+
+        let path = std::env::temp_dir().join(
+            format!("gls-add-file-hash-{}.txt", std::process::id())
+        );
+        tokio::fs::write(&path, "hello".repeat(20)).await.unwrap();
+        let mut file = File::open(&path).await.unwrap();
+
+        let sha256 = "4e4ba7e5b2223cfbbd7cd1937cdde31663a1d9dd58c35a48c2658f884d562102";
+
+        core.add_file(
+            Owner(1),
+            Project(42),
+            Release(2),
+            "hello.txt",
+            None,
+            100,
+            sha256,
+            &path,
+            &mut file
+        ).await.unwrap();
+
+        tokio::fs::remove_file(&path).await.unwrap();
+
+        let key = format!("images/4/e/{sha256}");
+        assert!(s3.keys().contains(&key));
+        assert_eq!(
+            s3.content_disposition(&key).unwrap(),
+            "attachment; filename=\"hello.txt\"; filename*=UTF-8''hello.txt"
+        );
+
+        let files = core.db.get_files(Release(2)).await.unwrap();
+        let f = files.iter().find(|f| f.filename == "hello.txt").unwrap();
+        assert_eq!(f.url, format!("https://example.com/{key}"));
+    }
+
+    async fn make_bucket_core(
+        pool: Pool
+    ) -> (ProdCore<SqlxDatabaseClient<sqlx::sqlite::Sqlite>, BucketUploader>, FakeS3)
+    {
+        let (s3, endpoint) = FakeS3::start().await;
+
+        let t = *NOW_DT;
+        s3.insert("images/img.png", 5, t - chrono::TimeDelta::days(30));
+        s3.insert("images/orphan.png", 5, t - chrono::TimeDelta::days(30));
+        s3.insert("images/new.png", 5, t - chrono::TimeDelta::hours(1));
+        s3.insert("other/x.png", 5, t - chrono::TimeDelta::days(30));
+
+        let core = ProdCore {
+            db: SqlxDatabaseClient(pool),
+            uploader: FakeS3::uploader(&endpoint, "images"),
+            now: fake_now,
+            max_file_size: 256,
+            max_image_size: 256,
+            image_limits: ImageLimits {
+                max_width: 4096,
+                max_height: 4096
+            },
+            upload_dir: "uploads".into()
+        };
+
+        (core, s3)
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "images"))]
+    async fn collect_garbage_dry_run(pool: Pool) {
+        let (core, s3) = make_bucket_core(pool).await;
+
+        assert_eq!(
+            core.collect_garbage(true, 24 * 60 * 60).await.unwrap(),
+            OrphanedObjects {
+                dry_run: true,
+                objects: vec![
+                    OrphanedObject {
+                        url: "https://example.com/images/orphan.png".into(),
+                        size: 5,
+                        last_modified: "2023-10-13T15:50:06.419Z".into()
+                    }
+                ]
+            }
+        );
+
+        assert_eq!(
+            s3.keys(),
+            [
+                "images/img.png",
+                "images/new.png",
+                "images/orphan.png",
+                "other/x.png"
+            ]
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "images"))]
+    async fn collect_garbage_delete(pool: Pool) {
+        let (core, s3) = make_bucket_core(pool).await;
+
+        assert_eq!(
+            core.collect_garbage(false, 24 * 60 * 60).await.unwrap(),
+            OrphanedObjects {
+                dry_run: false,
+                objects: vec![
+                    OrphanedObject {
+                        url: "https://example.com/images/orphan.png".into(),
+                        size: 5,
+                        last_modified: "2023-10-13T15:50:06.419Z".into()
+                    }
+                ]
+            }
+        );
+
+        assert_eq!(
+            s3.keys(),
+            ["images/img.png", "images/new.png", "other/x.png"]
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "images"))]
+    async fn collect_garbage_no_grace(pool: Pool) {
+        let (core, s3) = make_bucket_core(pool).await;
+
+        assert_eq!(
+            core.collect_garbage(false, 0)
+                .await
+                .unwrap()
+                .objects
+                .into_iter()
+                .map(|o| o.url)
+                .collect::<Vec<_>>(),
+            [
+                "https://example.com/images/new.png",
+                "https://example.com/images/orphan.png"
+            ]
+        );
+
+        assert_eq!(s3.keys(), ["images/img.png", "other/x.png"]);
+    }
+
     #[sqlx::test(fixtures("users", "projects", "images"))]
     async fn get_image_ok(pool: Pool) {
         let core = make_core(pool, fake_now);
         assert_eq!(
-            core.get_image(Project(42), "img.png").await.unwrap(),
+            core.get_image(Project(42), "img.png", None).await.unwrap(),
             "https://example.com/images/img.png"
         );
     }
 
+    #[sqlx::test(fixtures("users", "projects", "images"))]
+    async fn get_image_size_rendition(pool: Pool) {
+        let core = make_core(pool, fake_now);
+
+        core.db.add_image_url(
+            Owner(1),
+            Project(42),
+            "img.png",
+            "https://example.com/images/img.png",
+            "image/png",
+            None,
+            &[(256, "https://example.com/images/img.png.256.webp".into())],
+            1703980420641538067
+        ).await.unwrap();
+
+        assert_eq!(
+            core.get_image(Project(42), "img.png", Some(200)).await.unwrap(),
+            "https://example.com/images/img.png.256.webp"
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "images"))]
+    async fn get_image_size_original(pool: Pool) {
+        let core = make_core(pool, fake_now);
+
+        core.db.add_image_url(
+            Owner(1),
+            Project(42),
+            "img.png",
+            "https://example.com/images/img.png",
+            "image/png",
+            None,
+            &[(256, "https://example.com/images/img.png.256.webp".into())],
+            1703980420641538067
+        ).await.unwrap();
+
+        assert_eq!(
+            core.get_image(Project(42), "img.png", Some(512)).await.unwrap(),
+            "https://example.com/images/img.png"
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "images"))]
+    async fn get_image_size_not_an_image(pool: Pool) {
+        let core = make_core(pool, fake_now);
+        assert_eq!(
+            core.get_image(Project(42), "bogus", Some(256)).await.unwrap_err(),
+            GetImageError::NotFound
+        );
+    }
+
     #[sqlx::test(fixtures("users", "projects", "images"))]
     async fn get_image_not_a_project(pool: Pool) {
         let core = make_core(pool, fake_now);
         assert_eq!(
-            core.get_image(Project(1), "img.png").await.unwrap_err(),
+            core.get_image(Project(1), "img.png", None).await.unwrap_err(),
             GetImageError::NotFound
         );
     }
@@ -2898,7 +4797,7 @@ mod test {
     async fn get_image_not_an_image(pool: Pool) {
         let core = make_core(pool, fake_now);
         assert_eq!(
-            core.get_image(Project(42), "bogus").await.unwrap_err(),
+            core.get_image(Project(42), "bogus", None).await.unwrap_err(),
             GetImageError::NotFound
         );
     }
@@ -2945,4 +4844,152 @@ mod test {
         assert!(!is_valid_project_name("x  x"));
         assert!(!is_valid_project_name("x💩x"));
     }
+
+    #[test]
+    fn test_player_side_count() {
+        let sides = |s: &[&str]| s.iter()
+            .map(|s| s.to_string())
+            .collect::<Vec<_>>();
+
+        assert_eq!(player_side_count(&sides(&[])), None);
+        assert_eq!(player_side_count(&sides(&["Red", "Blue"])), Some(2));
+        assert_eq!(
+            player_side_count(&sides(&["Red", "Blue", "Solitaire", "Referee"])),
+            Some(2)
+        );
+        assert_eq!(
+            player_side_count(&sides(&["Observer", " solitaire "])),
+            None
+        );
+    }
+
+    async fn add_module(
+        core: &ProdCore<SqlxDatabaseClient<sqlx::sqlite::Sqlite>, FakeUploader>,
+        rel: i64
+    )
+    {
+        let module = ModuleMetadata {
+            name: Some("Test Game".into()),
+            ..Default::default()
+        };
+
+        core.db.add_file_url(
+            Owner(1),
+            Project(42),
+            Release(rel),
+            &format!("test-{rel}.vmod"),
+            5,
+            "deadbeef",
+            "application/zip",
+            None,
+            Some(&module),
+            None,
+            None,
+            "https://example.com/test.vmod",
+            1702137389180282478
+        ).await.unwrap();
+    }
+
+    fn extension(version: Option<&str>) -> ModuleMetadata {
+        ModuleMetadata {
+            name: Some("Extra Counters".into()),
+            target_module: Some("Test Game".into()),
+            target_module_version: version.map(String::from),
+            ..Default::default()
+        }
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn resolve_module_target_newest(pool: Pool) {
+        let core = make_core(pool, fake_now);
+        add_module(&core, 1).await;
+        add_module(&core, 2).await;
+
+        assert_eq!(
+            core.resolve_module_target(Project(6), &extension(Some("1.2")))
+                .await
+                .unwrap(),
+            Some(ModuleTarget {
+                project: Project(42),
+                release: Some(Release(2))
+            })
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn resolve_module_target_no_version(pool: Pool) {
+        let core = make_core(pool, fake_now);
+        add_module(&core, 1).await;
+
+        assert_eq!(
+            core.resolve_module_target(Project(6), &extension(None))
+                .await
+                .unwrap(),
+            Some(ModuleTarget {
+                project: Project(42),
+                release: Some(Release(1))
+            })
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn resolve_module_target_too_old(pool: Pool) {
+        let core = make_core(pool, fake_now);
+        add_module(&core, 1).await;
+
+        assert_eq!(
+            core.resolve_module_target(Project(6), &extension(Some("1.2.4")))
+                .await
+                .unwrap(),
+            Some(ModuleTarget { project: Project(42), release: None })
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn resolve_module_target_bad_version(pool: Pool) {
+        let core = make_core(pool, fake_now);
+        add_module(&core, 1).await;
+
+        assert_eq!(
+            core.resolve_module_target(Project(6), &extension(Some("v2")))
+                .await
+                .unwrap(),
+            Some(ModuleTarget { project: Project(42), release: None })
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn resolve_module_target_not_found(pool: Pool) {
+        let core = make_core(pool, fake_now);
+
+        assert_eq!(
+            core.resolve_module_target(Project(6), &extension(Some("1.2")))
+                .await
+                .unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn minimum_module_version_short() {
+        assert_eq!(
+            minimum_module_version("2.1"),
+            Some("2.1.0".parse().unwrap())
+        );
+        assert_eq!(
+            minimum_module_version("2"),
+            Some("2.0.0".parse().unwrap())
+        );
+        assert_eq!(minimum_module_version("bogus"), None);
+    }
+
+    #[tokio::test]
+    async fn read_module_metadata_bad_buildfile() {
+        let path = Path::new("test/bad_buildfile.vmod");
+        let md = dump_moduledata(path).await.unwrap();
+        let module = read_module_metadata(&md, path, "vmod").await.unwrap();
+        assert_eq!(module.name.as_deref(), Some("Unnamed module"));
+        assert!(module.maps.is_empty());
+        assert!(module.sides.is_empty());
+    }
 }
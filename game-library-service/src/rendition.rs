@@ -0,0 +1,891 @@
+use image::{
+    DynamicImage, ImageDecoder, ImageError, ImageFormat, ImageReader, Limits,
+    codecs::{
+        jpeg::JpegEncoder,
+        png::PngEncoder,
+        webp::WebPEncoder
+    },
+    imageops::FilterType,
+    metadata::Orientation
+};
+use mime::Mime;
+use std::io::{self, Cursor};
+
+// the longest side, in pixels, of each rendition we generate
+pub const RENDITION_SIZES: [u32; 2] = [256, 1024];
+
+const JPEG_QUALITY: u8 = 90;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("{0}")]
+    Image(#[from] ImageError),
+    #[error("{0}")]
+    Io(#[from] io::Error),
+    #[error("Malformed image")]
+    Malformed,
+    #[error("Image metadata cannot be removed")]
+    Metadata,
+    #[error("Image dimensions {0}x{1} exceed limits")]
+    TooLarge(u32, u32)
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ImageLimits {
+    pub max_width: u32,
+    pub max_height: u32
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct Rendition {
+    pub size: u32,
+    pub data: Vec<u8>
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct ProcessedImage {
+    pub data: Vec<u8>,
+    pub width: u32,
+    pub height: u32,
+    pub renditions: Vec<Rendition>
+}
+
+pub fn image_format(content_type: &Mime) -> Option<ImageFormat> {
+    match content_type.essence_str() {
+        "image/avif" => Some(ImageFormat::Avif),
+        "image/gif" => Some(ImageFormat::Gif),
+        "image/jpeg" => Some(ImageFormat::Jpeg),
+        "image/png" => Some(ImageFormat::Png),
+        "image/webp" => Some(ImageFormat::WebP),
+        _ => None
+    }
+}
+
+pub fn rendition_filename(filename: &str, size: u32) -> String {
+    format!("{filename}.{size}.webp")
+}
+
+fn decode(
+    buf: &[u8],
+    format: ImageFormat,
+    limits: &ImageLimits
+) -> Result<(DynamicImage, Orientation), Error>
+{
+    // check the dimensions before decoding, to avoid decompression bombs
+    let (width, height) = ImageReader::with_format(Cursor::new(buf), format)
+        .into_dimensions()?;
+
+    if width > limits.max_width || height > limits.max_height {
+        return Err(Error::TooLarge(width, height));
+    }
+
+    let mut il = Limits::default();
+    il.max_image_width = Some(limits.max_width);
+    il.max_image_height = Some(limits.max_height);
+
+    let mut reader = ImageReader::with_format(Cursor::new(buf), format);
+    reader.limits(il);
+    let mut decoder = reader.into_decoder()?;
+
+    // the orientation is lost with the rest of the metadata, so apply it;
+    // unreadable metadata is no reason to reject the image
+    let orientation = decoder.orientation()
+        .unwrap_or(Orientation::NoTransforms);
+
+    let mut img = DynamicImage::from_decoder(decoder)?;
+    img.apply_orientation(orientation);
+    Ok((img, orientation))
+}
+
+// WebP is a RIFF container: a 12-byte header followed by chunks, each with
+// a FourCC, a little-endian length, and data padded to an even length
+fn strip_webp_metadata(buf: &[u8]) -> Option<Vec<u8>> {
+    if buf.get(0..4)? != b"RIFF" || buf.get(8..12)? != b"WEBP" {
+        return None;
+    }
+
+    let mut data = buf[..12].to_vec();
+    let mut pos = 12;
+
+    while pos < buf.len() {
+        let header = buf.get(pos..pos + 8)?;
+        let len = u32::from_le_bytes(header[4..8].try_into().ok()?) as usize;
+        let end = pos.checked_add(8)?.checked_add(len)?;
+        let chunk = buf.get(pos..end)?;
+
+        let fourcc = &header[..4];
+        if fourcc != b"EXIF" && fourcc != b"XMP " {
+            let start = data.len();
+            data.extend_from_slice(chunk);
+            if len % 2 == 1 {
+                data.push(0);
+            }
+
+            if fourcc == b"VP8X" {
+                // clear the EXIF and XMP flags
+                *data.get_mut(start + 8)? &= !0b0000_1100;
+            }
+        }
+
+        pos = end + len % 2;
+    }
+
+    let riff_len = u32::try_from(data.len() - 8).ok()?;
+    data[4..8].copy_from_slice(&riff_len.to_le_bytes());
+    Some(data)
+}
+
+// A GIF is a header, a screen descriptor, and an optional global color
+// table, followed by images and extensions, each of which ends in a run of
+// length-prefixed sub-blocks
+fn gif_sub_blocks_end(buf: &[u8], mut pos: usize) -> Option<usize> {
+    loop {
+        let len = *buf.get(pos)? as usize;
+        pos = pos.checked_add(1 + len)?;
+        if len == 0 {
+            return Some(pos);
+        }
+    }
+}
+
+fn gif_color_table_len(packed: u8) -> usize {
+    if packed & 0x80 == 0 {
+        0
+    }
+    else {
+        3 << ((packed & 0x07) + 1)
+    }
+}
+
+fn strip_gif_metadata(buf: &[u8]) -> Option<Vec<u8>> {
+    if buf.get(0..6)? != b"GIF87a" && buf.get(0..6)? != b"GIF89a" {
+        return None;
+    }
+
+    let mut pos = 13 + gif_color_table_len(*buf.get(10)?);
+    let mut data = buf.get(..pos)?.to_vec();
+
+    loop {
+        match *buf.get(pos)? {
+            // image descriptor
+            0x2C => {
+                let packed = *buf.get(pos + 9)?;
+                // skip the color table and the LZW minimum code size
+                let start = pos + 10 + gif_color_table_len(packed) + 1;
+                let end = gif_sub_blocks_end(buf, start)?;
+                data.extend_from_slice(buf.get(pos..end)?);
+                pos = end;
+            },
+            // extension
+            0x21 => {
+                let label = *buf.get(pos + 1)?;
+                let end = gif_sub_blocks_end(buf, pos + 2)?;
+
+                // keep graphic control, plain text, and the application
+                // extensions which loop animations; drop comments and other
+                // application extensions, such as XMP
+                let keep = match label {
+                    0xF9 | 0x01 => true,
+                    0xFF => matches!(
+                        buf.get(pos + 2..pos + 14)?,
+                        b"\x0bNETSCAPE2.0" | b"\x0bANIMEXTS1.0"
+                    ),
+                    _ => false
+                };
+
+                if keep {
+                    data.extend_from_slice(buf.get(pos..end)?);
+                }
+                pos = end;
+            },
+            // trailer; anything after it is dropped
+            0x3B => {
+                data.push(0x3B);
+                return Some(data);
+            },
+            _ => return None
+        }
+    }
+}
+
+// AVIF is an ISOBMFF container: a sequence of boxes, each with a
+// big-endian 32-bit size (1 for a 64-bit size after the type, 0 for the
+// rest of the data), a FourCC, and contents, which may be more boxes
+fn isobmff_boxes(buf: &[u8]) -> Option<Vec<(&[u8], &[u8])>> {
+    let mut boxes = vec![];
+    let mut pos = 0;
+
+    while pos < buf.len() {
+        let size = u32::from_be_bytes(buf.get(pos..pos + 4)?.try_into().ok()?);
+        let fourcc = buf.get(pos + 4..pos + 8)?;
+
+        let (start, end) = match size {
+            0 => (pos + 8, buf.len()),
+            1 => {
+                let size = u64::from_be_bytes(
+                    buf.get(pos + 8..pos + 16)?.try_into().ok()?
+                );
+                (pos + 16, pos.checked_add(usize::try_from(size).ok()?)?)
+            },
+            _ => (pos + 8, pos.checked_add(size as usize)?)
+        };
+
+        boxes.push((fourcc, buf.get(start..end)?));
+        pos = end;
+    }
+
+    Some(boxes)
+}
+
+fn be_u16(buf: &[u8], pos: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(buf.get(pos..pos + 2)?.try_into().ok()?))
+}
+
+fn be_u32(buf: &[u8], pos: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(buf.get(pos..pos + 4)?.try_into().ok()?))
+}
+
+#[derive(Debug, Default, Eq, PartialEq)]
+struct AvifInfo {
+    // the size of the primary item
+    primary: Option<(u32, u32)>,
+    // the sizes of all items, including thumbnails and grid tiles
+    sizes: Vec<(u32, u32)>,
+    // whether there are Exif or XMP items
+    metadata: bool
+}
+
+fn read_avif_info(buf: &[u8]) -> Option<AvifInfo> {
+    let (_, meta) = isobmff_boxes(buf)?
+        .into_iter()
+        .find(|(fourcc, _)| *fourcc == b"meta")?;
+
+    let mut info = AvifInfo::default();
+    let mut primary_id = None;
+    let mut properties = vec![];
+    let mut associations = vec![];
+
+    // meta is a full box; skip its version and flags
+    for (fourcc, b) in isobmff_boxes(meta.get(4..)?)? {
+        match fourcc {
+            b"pitm" => primary_id = Some(match b.first()? {
+                0 => be_u16(b, 4)? as u32,
+                _ => be_u32(b, 4)?
+            }),
+            b"iinf" => {
+                let start = if *b.first()? == 0 { 6 } else { 8 };
+                for (fourcc, infe) in isobmff_boxes(b.get(start..)?)? {
+                    if fourcc != b"infe" {
+                        continue;
+                    }
+
+                    // skip the item id and protection index
+                    let item_type = match infe.first()? {
+                        2 => infe.get(8..12)?,
+                        3 => infe.get(10..14)?,
+                        _ => return None
+                    };
+
+                    // XMP is stored as a MIME item
+                    if item_type == b"Exif" || item_type == b"mime" {
+                        info.metadata = true;
+                    }
+                }
+            },
+            b"iprp" => {
+                for (fourcc, b) in isobmff_boxes(b)? {
+                    match fourcc {
+                        b"ipco" => {
+                            for (fourcc, p) in isobmff_boxes(b)? {
+                                properties.push(
+                                    if fourcc == b"ispe" {
+                                        let size = (be_u32(p, 4)?, be_u32(p, 8)?);
+                                        info.sizes.push(size);
+                                        Some(size)
+                                    }
+                                    else {
+                                        None
+                                    }
+                                );
+                            }
+                        },
+                        b"ipma" => {
+                            let version = *b.first()?;
+                            let wide = *b.get(3)? & 1 == 1;
+                            let count = be_u32(b, 4)?;
+                            let mut pos = 8;
+
+                            for _ in 0..count {
+                                let id = if version == 0 {
+                                    pos += 2;
+                                    be_u16(b, pos - 2)? as u32
+                                }
+                                else {
+                                    pos += 4;
+                                    be_u32(b, pos - 4)?
+                                };
+
+                                let n = *b.get(pos)?;
+                                pos += 1;
+
+                                for _ in 0..n {
+                                    // 1-based property indices, after an
+                                    // "essential" bit
+                                    let index = if wide {
+                                        pos += 2;
+                                        be_u16(b, pos - 2)? & 0x7FFF
+                                    }
+                                    else {
+                                        pos += 1;
+                                        (*b.get(pos - 1)? & 0x7F) as u16
+                                    };
+                                    associations.push((id, index));
+                                }
+                            }
+                        },
+                        _ => {}
+                    }
+                }
+            },
+            _ => {}
+        }
+    }
+
+    let primary_id = primary_id?;
+    info.primary = associations.iter()
+        .filter(|(id, _)| *id == primary_id)
+        .find_map(|(_, index)|
+            *properties.get((*index as usize).checked_sub(1)?)?
+        );
+
+    Some(info)
+}
+
+// AVIFs we cannot decode, so check them and serve them as-is
+fn check_avif(
+    buf: &[u8],
+    limits: &ImageLimits
+) -> Result<(u32, u32), Error>
+{
+    let info = read_avif_info(buf).ok_or(Error::Malformed)?;
+
+    if let Some((width, height)) = info.sizes.iter()
+        .find(|(w, h)| *w > limits.max_width || *h > limits.max_height)
+    {
+        return Err(Error::TooLarge(*width, *height));
+    }
+
+    if info.metadata {
+        return Err(Error::Metadata);
+    }
+
+    info.primary.ok_or(Error::Malformed)
+}
+
+fn encode(
+    buf: &[u8],
+    img: &DynamicImage,
+    format: ImageFormat,
+    orientation: Orientation
+) -> Result<Vec<u8>, Error>
+{
+    // WebPs may be lossy, but we can encode only lossless ones, so keep the
+    // original image data unless it needs to be rotated
+    if format == ImageFormat::WebP && orientation == Orientation::NoTransforms
+        && let Some(data) = strip_webp_metadata(buf)
+    {
+        return Ok(data);
+    }
+
+    let mut data = vec![];
+
+    // the encoders write no metadata unless asked to
+    match format {
+        ImageFormat::Jpeg => img.write_with_encoder(
+            JpegEncoder::new_with_quality(&mut data, JPEG_QUALITY)
+        )?,
+        ImageFormat::Png => img.write_with_encoder(
+            PngEncoder::new(&mut data)
+        )?,
+        ImageFormat::WebP => img.to_rgba8().write_with_encoder(
+            WebPEncoder::new_lossless(&mut data)
+        )?,
+        // decoding would lose any animation, so strip the original
+        ImageFormat::Gif => data = strip_gif_metadata(buf)
+            .ok_or(Error::Malformed)?,
+        _ => return Err(Error::Malformed)
+    }
+
+    Ok(data)
+}
+
+fn make_renditions(
+    img: &DynamicImage,
+    sizes: &[u32],
+    original_len: usize
+) -> Result<Vec<Rendition>, Error>
+{
+    let longest = img.width().max(img.height());
+
+    let mut renditions = vec![];
+
+    // don't scale up images smaller than the rendition
+    for size in sizes.iter().filter(|size| **size < longest) {
+        let mut data = vec![];
+        let r = img.resize(*size, *size, FilterType::Lanczos3).to_rgba8();
+        r.write_with_encoder(WebPEncoder::new_lossless(&mut data))?;
+
+        // renditions are lossless, so can be larger than a lossy original;
+        // those are dropped, and the original is served in their place
+        if data.len() < original_len {
+            renditions.push(Rendition { size: *size, data });
+        }
+    }
+
+    Ok(renditions)
+}
+
+fn process_image_sync(
+    buf: &[u8],
+    format: ImageFormat,
+    limits: &ImageLimits,
+    sizes: &[u32]
+) -> Result<ProcessedImage, Error>
+{
+    if format == ImageFormat::Avif {
+        let (width, height) = check_avif(buf, limits)?;
+        return Ok(
+            ProcessedImage {
+                data: buf.to_vec(),
+                width,
+                height,
+                renditions: vec![]
+            }
+        );
+    }
+
+    let (img, orientation) = decode(buf, format, limits)?;
+    let data = encode(buf, &img, format, orientation)?;
+    let renditions = make_renditions(&img, sizes, data.len())?;
+
+    Ok(
+        ProcessedImage {
+            data,
+            width: img.width(),
+            height: img.height(),
+            renditions
+        }
+    )
+}
+
+pub async fn process_image(
+    buf: Vec<u8>,
+    format: ImageFormat,
+    limits: ImageLimits
+) -> Result<ProcessedImage, Error>
+{
+    match tokio::task::spawn_blocking(
+        move || process_image_sync(&buf, format, &limits, &RENDITION_SIZES)
+    ).await {
+        Ok(Ok(v)) => Ok(v),
+        Ok(Err(e)) => Err(e),
+        Err(e) => Err(Error::Io(io::Error::from(e)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use image::RgbImage;
+
+    const LIMITS: ImageLimits = ImageLimits {
+        max_width: 4096,
+        max_height: 4096
+    };
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let mut buf = vec![];
+        DynamicImage::ImageRgb8(RgbImage::new(width, height))
+            .write_to(&mut Cursor::new(&mut buf), ImageFormat::Png)
+            .unwrap();
+        buf
+    }
+
+    fn jpeg_with_exif(width: u32, height: u32) -> Vec<u8> {
+        let mut jpeg = vec![];
+        DynamicImage::ImageRgb8(RgbImage::new(width, height))
+            .write_to(&mut Cursor::new(&mut jpeg), ImageFormat::Jpeg)
+            .unwrap();
+
+        // insert an APP1 segment after the SOI marker
+        let payload = b"Exif\0\0GPS";
+        let len = (payload.len() + 2) as u16;
+        let mut buf = jpeg[..2].to_vec();
+        buf.extend_from_slice(&[0xFF, 0xE1]);
+        buf.extend_from_slice(&len.to_be_bytes());
+        buf.extend_from_slice(payload);
+        buf.extend_from_slice(&jpeg[2..]);
+        buf
+    }
+
+    fn dimensions(buf: &[u8], format: ImageFormat) -> (u32, u32) {
+        ImageReader::with_format(Cursor::new(buf), format)
+            .into_dimensions()
+            .unwrap()
+    }
+
+    fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+        haystack.windows(needle.len()).any(|w| w == needle)
+    }
+
+    #[test]
+    fn image_format_png() {
+        assert_eq!(
+            image_format(&mime::IMAGE_PNG),
+            Some(ImageFormat::Png)
+        );
+    }
+
+    #[test]
+    fn image_format_svg() {
+        assert_eq!(image_format(&mime::IMAGE_SVG), None);
+    }
+
+    #[test]
+    fn rendition_filename_ok() {
+        assert_eq!(rendition_filename("img.png", 256), "img.png.256.webp");
+    }
+
+    #[test]
+    fn process_image_landscape() {
+        let p = process_image_sync(
+            &png(2000, 1000),
+            ImageFormat::Png,
+            &LIMITS,
+            &RENDITION_SIZES
+        ).unwrap();
+        assert_eq!((p.width, p.height), (2000, 1000));
+        assert_eq!(dimensions(&p.data, ImageFormat::Png), (2000, 1000));
+        assert_eq!(p.renditions.len(), 2);
+        assert_eq!(p.renditions[0].size, 256);
+        assert_eq!(
+            dimensions(&p.renditions[0].data, ImageFormat::WebP),
+            (256, 128)
+        );
+        assert_eq!(p.renditions[1].size, 1024);
+        assert_eq!(
+            dimensions(&p.renditions[1].data, ImageFormat::WebP),
+            (1024, 512)
+        );
+    }
+
+    #[test]
+    fn process_image_portrait() {
+        let p = process_image_sync(
+            &png(300, 600),
+            ImageFormat::Png,
+            &LIMITS,
+            &RENDITION_SIZES
+        ).unwrap();
+        assert_eq!((p.width, p.height), (300, 600));
+        assert_eq!(p.renditions.len(), 1);
+        assert_eq!(p.renditions[0].size, 256);
+        assert_eq!(
+            dimensions(&p.renditions[0].data, ImageFormat::WebP),
+            (128, 256)
+        );
+    }
+
+    #[test]
+    fn process_image_small() {
+        let p = process_image_sync(
+            &png(100, 100),
+            ImageFormat::Png,
+            &LIMITS,
+            &RENDITION_SIZES
+        ).unwrap();
+        assert_eq!((p.width, p.height), (100, 100));
+        assert_eq!(p.renditions, []);
+    }
+
+    #[test]
+    fn process_image_rendition_not_smaller() {
+        // noise compresses well lossily, but not losslessly
+        let mut seed = 1_u32;
+        let img = RgbImage::from_fn(1200, 600, |_, _| {
+            seed = seed.wrapping_mul(1664525).wrapping_add(1013904223);
+            let [r, g, b, _] = seed.to_be_bytes();
+            image::Rgb([r, g, b])
+        });
+
+        let mut buf = vec![];
+        DynamicImage::ImageRgb8(img)
+            .write_with_encoder(JpegEncoder::new_with_quality(&mut buf, 10))
+            .unwrap();
+
+        let p = process_image_sync(
+            &buf,
+            ImageFormat::Jpeg,
+            &LIMITS,
+            &RENDITION_SIZES
+        ).unwrap();
+        assert_eq!((p.width, p.height), (1200, 600));
+        assert!(p.renditions.iter().all(|r| r.data.len() < p.data.len()));
+        assert!(p.renditions.len() < 2);
+    }
+
+    #[test]
+    fn process_image_strip_exif() {
+        let buf = jpeg_with_exif(64, 32);
+        assert!(contains(&buf, b"Exif"));
+
+        let p = process_image_sync(
+            &buf,
+            ImageFormat::Jpeg,
+            &LIMITS,
+            &RENDITION_SIZES
+        ).unwrap();
+        assert_eq!((p.width, p.height), (64, 32));
+        assert_eq!(dimensions(&p.data, ImageFormat::Jpeg), (64, 32));
+        assert!(!contains(&p.data, b"Exif"));
+    }
+
+    #[test]
+    fn process_image_apply_orientation() {
+        let mut jpeg = vec![];
+        DynamicImage::ImageRgb8(RgbImage::new(64, 32))
+            .write_to(&mut Cursor::new(&mut jpeg), ImageFormat::Jpeg)
+            .unwrap();
+
+        // a big-endian TIFF with one IFD entry: orientation 6, rotate 90°
+        let payload = [
+            b"Exif\0\0MM\0\x2a\0\0\0\x08".as_slice(),
+            &[0x00, 0x01],
+            &[0x01, 0x12, 0x00, 0x03, 0x00, 0x00, 0x00, 0x01],
+            &[0x00, 0x06, 0x00, 0x00],
+            &[0x00, 0x00, 0x00, 0x00]
+        ].concat();
+
+        let len = (payload.len() + 2) as u16;
+        let mut buf = jpeg[..2].to_vec();
+        buf.extend_from_slice(&[0xFF, 0xE1]);
+        buf.extend_from_slice(&len.to_be_bytes());
+        buf.extend_from_slice(&payload);
+        buf.extend_from_slice(&jpeg[2..]);
+
+        let p = process_image_sync(
+            &buf,
+            ImageFormat::Jpeg,
+            &LIMITS,
+            &RENDITION_SIZES
+        ).unwrap();
+        assert_eq!((p.width, p.height), (32, 64));
+        assert_eq!(dimensions(&p.data, ImageFormat::Jpeg), (32, 64));
+        assert!(!contains(&p.data, b"Exif"));
+    }
+
+    #[test]
+    fn process_image_webp_keep_data() {
+        let mut lossless = vec![];
+        DynamicImage::ImageRgb8(RgbImage::new(64, 32))
+            .to_rgba8()
+            .write_with_encoder(WebPEncoder::new_lossless(&mut lossless))
+            .unwrap();
+
+        // wrap the image in an extended WebP with an odd-length EXIF chunk
+        let chunk = |fourcc: &[u8], data: &[u8]| {
+            let mut c = fourcc.to_vec();
+            c.extend_from_slice(&(data.len() as u32).to_le_bytes());
+            c.extend_from_slice(data);
+            if data.len() % 2 == 1 {
+                c.push(0);
+            }
+            c
+        };
+
+        let vp8x = chunk(b"VP8X", &[0x08, 0, 0, 0, 63, 0, 0, 31, 0, 0]);
+        let body = [
+            b"WEBP".as_slice(),
+            &vp8x,
+            &lossless[12..],
+            &chunk(b"EXIF", b"MM\0\x2a\0\0\0\x08\0\0GPS")
+        ].concat();
+
+        let mut buf = b"RIFF".to_vec();
+        buf.extend_from_slice(&(body.len() as u32).to_le_bytes());
+        buf.extend_from_slice(&body);
+
+        let p = process_image_sync(
+            &buf,
+            ImageFormat::WebP,
+            &LIMITS,
+            &RENDITION_SIZES
+        ).unwrap();
+        assert_eq!((p.width, p.height), (64, 32));
+        assert_eq!(dimensions(&p.data, ImageFormat::WebP), (64, 32));
+        assert!(!contains(&p.data, b"EXIF"));
+        assert!(!contains(&p.data, b"GPS"));
+
+        // the image data is kept as-is
+        let riff_len = 4 + vp8x.len() + lossless.len() - 12;
+        let mut expected = b"RIFF".to_vec();
+        expected.extend_from_slice(&(riff_len as u32).to_le_bytes());
+        expected.extend_from_slice(b"WEBP");
+        expected.extend_from_slice(
+            &chunk(b"VP8X", &[0, 0, 0, 0, 63, 0, 0, 31, 0, 0])
+        );
+        expected.extend_from_slice(&lossless[12..]);
+        assert_eq!(p.data, expected);
+    }
+
+    fn gif_extension(label: u8, blocks: &[&[u8]]) -> Vec<u8> {
+        let mut ext = vec![0x21, label];
+        for b in blocks {
+            ext.push(b.len() as u8);
+            ext.extend_from_slice(b);
+        }
+        ext.push(0);
+        ext
+    }
+
+    #[test]
+    fn process_image_gif_strip_metadata() {
+        let gif = std::fs::read("test/a.gif").unwrap();
+        // a.gif has a header, a screen descriptor, a two-color global
+        // color table, then the image
+        let (head, image) = gif.split_at(19);
+
+        let netscape = gif_extension(0xFF, &[b"NETSCAPE2.0", &[1, 0, 0]]);
+        let buf = [
+            head,
+            &netscape,
+            &gif_extension(0xFE, &[b"GPS"]),
+            &gif_extension(0xFF, &[b"XMP DataXMP", b"<x:xmpmeta/>"]),
+            image,
+            b"trailing"
+        ].concat();
+
+        let p = process_image_sync(
+            &buf,
+            ImageFormat::Gif,
+            &LIMITS,
+            &RENDITION_SIZES
+        ).unwrap();
+        assert_eq!((p.width, p.height), (1, 1));
+        assert_eq!(p.data, [head, &netscape, image].concat());
+    }
+
+    #[test]
+    fn process_image_avif() {
+        let buf = std::fs::read("test/a.avif").unwrap();
+        let p = process_image_sync(
+            &buf,
+            ImageFormat::Avif,
+            &LIMITS,
+            &RENDITION_SIZES
+        ).unwrap();
+        assert_eq!((p.width, p.height), (1, 1));
+        assert_eq!(p.data, buf);
+        assert_eq!(p.renditions, []);
+    }
+
+    #[test]
+    fn process_image_avif_too_large() {
+        let mut buf = std::fs::read("test/a.avif").unwrap();
+        let ispe = buf.windows(4).position(|w| w == b"ispe").unwrap();
+        buf[ispe + 8..ispe + 12].copy_from_slice(&4097_u32.to_be_bytes());
+
+        assert!(
+            matches!(
+                process_image_sync(
+                    &buf,
+                    ImageFormat::Avif,
+                    &LIMITS,
+                    &RENDITION_SIZES
+                ).unwrap_err(),
+                Error::TooLarge(4097, 1)
+            )
+        );
+    }
+
+    #[test]
+    fn process_image_avif_exif() {
+        let mut buf = std::fs::read("test/a.avif").unwrap();
+        let av01 = buf.windows(4).position(|w| w == b"av01").unwrap();
+        buf[av01..av01 + 4].copy_from_slice(b"Exif");
+
+        assert!(
+            matches!(
+                process_image_sync(
+                    &buf,
+                    ImageFormat::Avif,
+                    &LIMITS,
+                    &RENDITION_SIZES
+                ).unwrap_err(),
+                Error::Metadata
+            )
+        );
+    }
+
+    #[test]
+    fn process_image_avif_malformed() {
+        let buf = std::fs::read("test/a.avif").unwrap();
+        assert!(
+            matches!(
+                process_image_sync(
+                    &buf[..100],
+                    ImageFormat::Avif,
+                    &LIMITS,
+                    &RENDITION_SIZES
+                ).unwrap_err(),
+                Error::Malformed
+            )
+        );
+    }
+
+    #[test]
+    fn process_image_too_wide() {
+        assert!(
+            matches!(
+                process_image_sync(
+                    &png(4097, 1),
+                    ImageFormat::Png,
+                    &LIMITS,
+                    &RENDITION_SIZES
+                ).unwrap_err(),
+                Error::TooLarge(4097, 1)
+            )
+        );
+    }
+
+    #[test]
+    fn process_image_too_tall() {
+        assert!(
+            matches!(
+                process_image_sync(
+                    &png(1, 4097),
+                    ImageFormat::Png,
+                    &LIMITS,
+                    &RENDITION_SIZES
+                ).unwrap_err(),
+                Error::TooLarge(1, 4097)
+            )
+        );
+    }
+
+    #[test]
+    fn process_image_bad_image() {
+        assert!(
+            matches!(
+                process_image_sync(
+                    b"bogus",
+                    ImageFormat::Png,
+                    &LIMITS,
+                    &RENDITION_SIZES
+                ).unwrap_err(),
+                Error::Image(_)
+            )
+        );
+    }
+}
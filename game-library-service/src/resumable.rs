@@ -0,0 +1,286 @@
+use async_tempfile::TempFile;
+use mime::Mime;
+use sha2::{Digest, Sha256};
+use std::{
+    collections::HashMap,
+    io,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant}
+};
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+
+use crate::model::{Owner, Project, Release};
+
+// how long an idle upload session is kept before being discarded
+pub const SESSION_EXPIRY: Duration = Duration::from_secs(24 * 60 * 60);
+
+// how often expired upload sessions are discarded
+pub const PRUNE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+// how many upload sessions a user may have open at once, as each holds
+// up to the maximum file size on disk until it expires
+pub const MAX_SESSIONS_PER_OWNER: usize = 4;
+
+#[derive(Debug, Eq, PartialEq, thiserror::Error)]
+#[error("Too many upload sessions")]
+pub struct TooManySessions;
+
+pub struct UploadSession {
+    pub owner: Owner,
+    pub proj: Project,
+    pub release: Release,
+    pub filename: String,
+    pub content_type: Option<Mime>,
+    pub length: Option<u64>,
+    pub offset: u64,
+    pub file: TempFile,
+    hasher: Sha256,
+    touched: Instant
+}
+
+impl UploadSession {
+    pub fn new(
+        owner: Owner,
+        proj: Project,
+        release: Release,
+        filename: &str,
+        content_type: Option<Mime>,
+        length: Option<u64>,
+        file: TempFile
+    ) -> Self
+    {
+        UploadSession {
+            owner,
+            proj,
+            release,
+            filename: filename.into(),
+            content_type,
+            length,
+            offset: 0,
+            file,
+            hasher: Sha256::new(),
+            touched: Instant::now()
+        }
+    }
+
+    pub fn belongs_to(
+        &self,
+        owner: Owner,
+        proj: Project,
+        release: Release,
+        filename: &str
+    ) -> bool
+    {
+        self.owner == owner &&
+        self.proj == proj &&
+        self.release == release &&
+        self.filename == filename
+    }
+
+    pub async fn append(
+        &mut self,
+        chunk: &mut TempFile
+    ) -> Result<(), io::Error>
+    {
+        chunk.rewind().await?;
+
+        let mut buf = vec![0; 65536];
+
+        loop {
+            match chunk.read(&mut buf).await? {
+                0 => break,
+                r => {
+                    self.file.write_all(&buf[..r]).await?;
+                    self.hasher.update(&buf[..r]);
+                    self.offset += r as u64;
+                }
+            }
+        }
+
+        self.file.flush().await?;
+        self.touched = Instant::now();
+
+        Ok(())
+    }
+
+    pub fn sha256(&self) -> String {
+        format!("{:x}", self.hasher.clone().finalize())
+    }
+
+    fn expired(&self, now: Instant) -> bool {
+        now.duration_since(self.touched) > SESSION_EXPIRY
+    }
+}
+
+pub type UploadSessionArc = Arc<tokio::sync::Mutex<UploadSession>>;
+
+#[derive(Default)]
+pub struct UploadSessions {
+    sessions: Mutex<HashMap<String, (Owner, UploadSessionArc)>>
+}
+
+impl UploadSessions {
+    fn prune(&self, now: Instant) {
+        // drop idle sessions; sessions in use are locked, so are kept
+        self.sessions.lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|_, (_, s)| s.try_lock().map_or(true, |s| !s.expired(now)));
+    }
+
+    // Sessions are kept only in memory, so are lost on restart. Expired
+    // ones are discarded here, so that their temporary files are removed
+    // even if no one opens another session.
+    pub async fn prune_periodically(self: Arc<Self>) {
+        let mut interval = tokio::time::interval(PRUNE_INTERVAL);
+        loop {
+            interval.tick().await;
+            self.prune(Instant::now());
+        }
+    }
+
+    pub fn insert(
+        &self,
+        session: UploadSession
+    ) -> Result<String, TooManySessions>
+    {
+        let id = format!("{:032x}", rand::random::<u128>());
+
+        self.prune(Instant::now());
+
+        let mut sessions = self.sessions.lock()
+            .unwrap_or_else(|e| e.into_inner());
+
+        let open = sessions.values()
+            .filter(|(owner, _)| *owner == session.owner)
+            .count();
+
+        if open >= MAX_SESSIONS_PER_OWNER {
+            return Err(TooManySessions);
+        }
+
+        sessions.insert(
+            id.clone(),
+            (session.owner, Arc::new(tokio::sync::Mutex::new(session)))
+        );
+
+        Ok(id)
+    }
+
+    pub fn get(&self, id: &str) -> Option<UploadSessionArc> {
+        self.sessions.lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(id)
+            .map(|(_, s)| s.clone())
+    }
+
+    pub fn remove(&self, id: &str) -> Option<UploadSessionArc> {
+        self.sessions.lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(id)
+            .map(|(_, s)| s)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    async fn temp_file_with(data: &[u8]) -> TempFile {
+        let mut file = TempFile::new().await.unwrap();
+        file.write_all(data).await.unwrap();
+        file.flush().await.unwrap();
+        file
+    }
+
+    async fn session() -> UploadSession {
+        UploadSession::new(
+            Owner(1),
+            Project(2),
+            Release(3),
+            "a_file",
+            None,
+            None,
+            TempFile::new().await.unwrap()
+        )
+    }
+
+    #[tokio::test]
+    async fn upload_session_append() {
+        let mut s = session().await;
+
+        s.append(&mut temp_file_with(b"abc").await).await.unwrap();
+        assert_eq!(s.offset, 3);
+        s.append(&mut temp_file_with(b"def").await).await.unwrap();
+        assert_eq!(s.offset, 6);
+
+        assert_eq!(
+            s.sha256(),
+            format!("{:x}", Sha256::digest(b"abcdef"))
+        );
+
+        let mut buf = vec![];
+        s.file.rewind().await.unwrap();
+        s.file.read_to_end(&mut buf).await.unwrap();
+        assert_eq!(buf, b"abcdef");
+    }
+
+    #[tokio::test]
+    async fn upload_session_belongs_to() {
+        let s = session().await;
+        assert!(s.belongs_to(Owner(1), Project(2), Release(3), "a_file"));
+        assert!(!s.belongs_to(Owner(4), Project(2), Release(3), "a_file"));
+        assert!(!s.belongs_to(Owner(1), Project(2), Release(4), "a_file"));
+        assert!(!s.belongs_to(Owner(1), Project(2), Release(3), "b_file"));
+    }
+
+    #[tokio::test]
+    async fn upload_sessions_insert_get_remove() {
+        let sessions = UploadSessions::default();
+        let id = sessions.insert(session().await).unwrap();
+        assert!(sessions.get(&id).is_some());
+        assert!(sessions.remove(&id).is_some());
+        assert!(sessions.get(&id).is_none());
+        assert!(sessions.remove(&id).is_none());
+    }
+
+    #[tokio::test]
+    async fn upload_sessions_prune() {
+        let sessions = UploadSessions::default();
+        let old_id = sessions.insert(session().await).unwrap();
+        let later = Instant::now() + SESSION_EXPIRY + Duration::from_secs(1);
+
+        // a session in use is not pruned
+        let old = sessions.get(&old_id).unwrap();
+        let guard = old.lock().await;
+        sessions.prune(later);
+        assert!(sessions.get(&old_id).is_some());
+        drop(guard);
+
+        sessions.prune(Instant::now());
+        assert!(sessions.get(&old_id).is_some());
+
+        sessions.prune(later);
+        assert!(sessions.get(&old_id).is_none());
+    }
+
+    #[tokio::test]
+    async fn upload_sessions_too_many() {
+        let sessions = UploadSessions::default();
+
+        let mut ids = vec![];
+        for _ in 0..MAX_SESSIONS_PER_OWNER {
+            ids.push(sessions.insert(session().await).unwrap());
+        }
+
+        assert_eq!(sessions.insert(session().await), Err(TooManySessions));
+
+        // other users are not limited by this one's sessions
+        let mut other = session().await;
+        other.owner = Owner(5);
+        assert!(sessions.insert(other).is_ok());
+
+        // closing a session makes room for another
+        sessions.remove(&ids[0]);
+        assert!(sessions.insert(session().await).is_ok());
+    }
+}
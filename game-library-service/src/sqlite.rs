@@ -1,6 +1,6 @@
 use glc::{
     discourse::UserUpdateParams,
-    model::{GalleryImage, Users},
+    model::{FacetCounts, GalleryImage, SuggestKind, UserMergeReport, Users},
     pagination::{Direction, Facet, SortBy}
 };
 use sqlx::{
@@ -10,6 +10,7 @@ use sqlx::{
 
 mod flag;
 mod images;
+mod objects;
 mod packages;
 mod players;
 mod project;
@@ -20,9 +21,10 @@ mod tags;
 mod users;
 
 use crate::{
-    db::{DatabaseClient, DatabaseError, FileRow, FlagRow, MidField, PackageRow, ProjectRow, ProjectSummaryRow, ReleaseRow},
-    input::{FlagPost, GalleryPatch, PackageDataPatch, PackageDataPost, ProjectDataPatch, ProjectDataPost},
+    db::{DatabaseClient, DatabaseError, FileModuleItemRow, FileRow, FlagRow, MidField, ModuleTarget, PackageRow, ProjectRow, ProjectSummaryRow, PublisherRow, ReleaseRow, SharedFileRow},
+    input::{FlagPost, GalleryPatch, PackageDataPatch, PackageDataPost, ProjectDataPatch, ProjectDataPost, ReleaseDataPatch, ReleaseDataPost},
     model::{Admin, Flag, Owner, Package, Project, Release, User},
+    module::ModuleMetadata,
     version::Version
 };
 
@@ -40,6 +42,30 @@ impl DatabaseClient for SqlxDatabaseClient<Sqlite> {
         project::get_project_id(&self.0, projname).await
     }
 
+    async fn get_deleted_project_id(
+        &self,
+        projname: &str
+    ) -> Result<Option<Project>, DatabaseError>
+    {
+        project::get_deleted_project_id(&self.0, projname).await
+    }
+
+    async fn get_project_alias_id(
+        &self,
+        projname: &str
+    ) -> Result<Option<Project>, DatabaseError>
+    {
+        project::get_project_alias_id(&self.0, projname).await
+    }
+
+    async fn get_search_facets(
+        &self,
+        facets: &[Facet]
+    ) -> Result<Vec<Facet>, DatabaseError>
+    {
+        projects::fts5_facets(&self.0, facets).await
+    }
+
     async fn get_projects_count(
         &self,
         facets: &[Facet]
@@ -48,6 +74,14 @@ impl DatabaseClient for SqlxDatabaseClient<Sqlite> {
         projects::get_projects_count(&self.0, facets).await
     }
 
+    async fn get_facet_counts(
+        &self,
+        facets: &[Facet]
+    ) -> Result<FacetCounts, DatabaseError>
+    {
+        projects::get_facet_counts(&self.0, facets).await
+    }
+
     async fn get_user_id(
         &self,
         username: &str
@@ -63,6 +97,15 @@ impl DatabaseClient for SqlxDatabaseClient<Sqlite> {
         users::update_user(&self.0, params).await
     }
 
+    async fn merge_users(
+        &self,
+        src: User,
+        dst: User
+    ) -> Result<UserMergeReport, DatabaseError>
+    {
+        users::merge_users(&self.0, src, dst).await
+    }
+
     async fn get_owners(
         &self,
         proj: Project
@@ -155,6 +198,15 @@ impl DatabaseClient for SqlxDatabaseClient<Sqlite> {
                 id,
                 limit
             ).await,
+            MidField::Count(f) => projects::get_projects_mid_window(
+                &self.0,
+                facets,
+                sort_by,
+                dir,
+                &f,
+                id,
+                limit
+            ).await,
             MidField::Weight(f) => projects::get_projects_mid_window(
                 &self.0,
                 facets,
@@ -198,6 +250,46 @@ impl DatabaseClient for SqlxDatabaseClient<Sqlite> {
         project::update_project(&self.0, owner, proj, proj_data, now).await
     }
 
+    async fn delete_project(
+        &self,
+        user: User,
+        proj: Project,
+        now: i64
+    ) -> Result<(), DatabaseError>
+    {
+        project::delete_project(&self.0, user, proj, now).await
+    }
+
+    async fn restore_project(
+        &self,
+        admin: Admin,
+        proj: Project,
+        now: i64
+    ) -> Result<(), DatabaseError>
+    {
+        project::restore_project(&self.0, admin, proj, now).await
+    }
+
+    async fn add_download(
+        &self,
+        proj: Project
+    ) -> Result<(), DatabaseError>
+    {
+        project::add_download(&self.0, proj).await
+    }
+
+    async fn rename_project(
+        &self,
+        admin: Admin,
+        proj: Project,
+        name: &str,
+        slug: &str,
+        now: i64
+    ) -> Result<(), DatabaseError>
+    {
+        project::rename_project(&self.0, admin, proj, name, slug, now).await
+    }
+
     async fn get_project_row(
         &self,
         proj: Project
@@ -279,10 +371,12 @@ impl DatabaseClient for SqlxDatabaseClient<Sqlite> {
         owner: Owner,
         proj: Project,
         pkg: Package,
+        cascade: bool,
         now: i64
     ) -> Result<(), DatabaseError>
     {
-        packages::delete_package(&self.0, owner, proj, pkg, now).await
+        packages::delete_package(&self.0, owner, proj, pkg, cascade, now)
+            .await
     }
 
     async fn get_releases(
@@ -293,6 +387,15 @@ impl DatabaseClient for SqlxDatabaseClient<Sqlite> {
         releases::get_releases(&self.0, pkg).await
     }
 
+    async fn get_vassal_compatible_releases(
+        &self,
+        proj: Project,
+        vassal: &Version
+    ) -> Result<Vec<i64>, DatabaseError>
+    {
+        releases::get_vassal_compatible_releases(&self.0, proj, vassal).await
+    }
+
     async fn get_releases_at(
         &self,
         pkg: Package,
@@ -332,10 +435,32 @@ impl DatabaseClient for SqlxDatabaseClient<Sqlite> {
         proj: Project,
         pkg: Package,
         version: &Version,
+        rel_data: &ReleaseDataPost,
+        now: i64
+    ) -> Result<(), DatabaseError>
+    {
+        releases::create_release(
+            &self.0,
+            owner,
+            proj,
+            pkg,
+            version,
+            rel_data,
+            now
+        ).await
+    }
+
+    async fn update_release(
+        &self,
+        owner: Owner,
+        proj: Project,
+        rel: Release,
+        rel_data: &ReleaseDataPatch,
         now: i64
     ) -> Result<(), DatabaseError>
     {
-        releases::create_release(&self.0, owner, proj, pkg, version, now).await
+        releases::update_release(&self.0, owner, proj, rel, rel_data, now)
+            .await
     }
 
     async fn delete_release(
@@ -343,10 +468,12 @@ impl DatabaseClient for SqlxDatabaseClient<Sqlite> {
         owner: Owner,
         proj: Project,
         rel: Release,
+        cascade: bool,
         now: i64
     ) -> Result<(), DatabaseError>
     {
-        releases::delete_release(&self.0, owner, proj, rel, now).await
+        releases::delete_release(&self.0, owner, proj, rel, cascade, now)
+            .await
     }
 
     async fn get_release_version(
@@ -382,7 +509,10 @@ impl DatabaseClient for SqlxDatabaseClient<Sqlite> {
         size: i64,
         sha256: &str,
         content_type: &str,
-        requires: Option<&str>,
+        requires: Option<&Version>,
+        module: Option<&ModuleMetadata>,
+        target: Option<&ModuleTarget>,
+        players: Option<u32>,
         url: &str,
         now: i64
     ) -> Result<(), DatabaseError>
@@ -397,11 +527,77 @@ impl DatabaseClient for SqlxDatabaseClient<Sqlite> {
             sha256,
             content_type,
             requires,
+            module,
+            target,
+            players,
             url,
             now
         ).await
     }
 
+    async fn delete_file(
+        &self,
+        owner: Owner,
+        proj: Project,
+        release: Release,
+        filename: &str,
+        now: i64
+    ) -> Result<(), DatabaseError>
+    {
+        releases::delete_file(&self.0, owner, proj, release, filename, now)
+            .await
+    }
+
+    async fn get_file_url_by_hash(
+        &self,
+        sha256: &str,
+        size: i64
+    ) -> Result<Option<String>, DatabaseError>
+    {
+        releases::get_file_url_by_hash(&self.0, sha256, size).await
+    }
+
+    async fn get_files_by_hash(
+        &self,
+        sha256: &str
+    ) -> Result<Vec<SharedFileRow>, DatabaseError>
+    {
+        releases::get_files_by_hash(&self.0, sha256).await
+    }
+
+    async fn get_referenced_urls(
+        &self
+    ) -> Result<Vec<String>, DatabaseError>
+    {
+        objects::get_referenced_urls(&self.0).await
+    }
+
+    async fn get_release_module_items(
+        &self,
+        rel: Release
+    ) -> Result<Vec<FileModuleItemRow>, DatabaseError>
+    {
+        releases::get_release_module_items(&self.0, rel).await
+    }
+
+    async fn get_module_project(
+        &self,
+        proj: Project,
+        module: &str
+    ) -> Result<Option<Project>, DatabaseError>
+    {
+        releases::get_module_project(&self.0, proj, module).await
+    }
+
+    async fn get_module_releases(
+        &self,
+        proj: Project,
+        module: &str
+    ) -> Result<Vec<(Release, Version)>, DatabaseError>
+    {
+        releases::get_module_releases(&self.0, proj, module).await
+    }
+
     async fn get_players(
         &self,
         proj: Project
@@ -437,6 +633,16 @@ impl DatabaseClient for SqlxDatabaseClient<Sqlite> {
         images::get_image_url(&self.0, proj, img_name).await
     }
 
+    async fn get_image_rendition_url(
+        &self,
+        proj: Project,
+        img_name: &str,
+        size: u32
+    ) -> Result<Option<String>, DatabaseError>
+    {
+        images::get_image_rendition_url(&self.0, proj, img_name, size).await
+    }
+
     async fn get_image_url_at(
         &self,
         proj: Project,
@@ -454,6 +660,8 @@ impl DatabaseClient for SqlxDatabaseClient<Sqlite> {
         img_name: &str,
         url: &str,
         content_type: &str,
+        dimensions: Option<(u32, u32)>,
+        renditions: &[(u32, String)],
         now: i64
     ) -> Result<(), DatabaseError>
     {
@@ -464,6 +672,8 @@ impl DatabaseClient for SqlxDatabaseClient<Sqlite> {
             img_name,
             url,
             content_type,
+            dimensions,
+            renditions,
             now
         ).await
     }
@@ -507,6 +717,8 @@ impl DatabaseClient for SqlxDatabaseClient<Sqlite> {
         img_name: &str,
         url: &str,
         content_type: &str,
+        dimensions: Option<(u32, u32)>,
+        renditions: &[(u32, String)],
         now: i64
     ) -> Result<(), DatabaseError>
     {
@@ -517,6 +729,8 @@ impl DatabaseClient for SqlxDatabaseClient<Sqlite> {
             img_name,
             url,
             content_type,
+            dimensions,
+            renditions,
             now
         ).await
     }
@@ -546,6 +760,31 @@ impl DatabaseClient for SqlxDatabaseClient<Sqlite> {
         publishers::get_publishers_active(&self.0).await
     }
 
+    async fn get_publishers_active_counts(
+        &self
+    ) -> Result<Vec<PublisherRow>, DatabaseError>
+    {
+        publishers::get_publishers_active_counts(&self.0).await
+    }
+
+    async fn rename_publisher(
+        &self,
+        publisher: &str,
+        name: &str
+    ) -> Result<(), DatabaseError>
+    {
+        publishers::rename_publisher(&self.0, publisher, name).await
+    }
+
+    async fn merge_publishers(
+        &self,
+        src: &str,
+        dst: &str
+    ) -> Result<(), DatabaseError>
+    {
+        publishers::merge_publishers(&self.0, src, dst).await
+    }
+
     async fn get_tags(
         &self
     ) -> Result<Vec<String>, DatabaseError>
@@ -560,6 +799,25 @@ impl DatabaseClient for SqlxDatabaseClient<Sqlite> {
         tags::get_tags_active(&self.0).await
     }
 
+    async fn get_suggestions(
+        &self,
+        kind: SuggestKind,
+        prefix: &str,
+        limit: u32
+    ) -> Result<Vec<String>, DatabaseError>
+    {
+        match kind {
+            SuggestKind::Title =>
+                projects::get_title_suggestions(&self.0, prefix, limit).await,
+            SuggestKind::Publisher =>
+                publishers::get_publisher_suggestions(&self.0, prefix, limit).await,
+            SuggestKind::Tag =>
+                tags::get_tag_suggestions(&self.0, prefix, limit).await,
+            SuggestKind::Owner =>
+                users::get_owner_suggestions(&self.0, prefix, limit).await
+        }
+    }
+
     async fn get_flag_id(
         &self,
         flag: i64
@@ -594,6 +852,17 @@ impl DatabaseClient for SqlxDatabaseClient<Sqlite> {
     }
 }
 
+pub fn like_prefix(s: &str) -> String {
+    // Matches strings starting with s in a LIKE expression with ESCAPE '\';
+    // wildcards in s must be escaped to be matched literally.
+    format!(
+        "{}%",
+        s.replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_")
+    )
+}
+
 pub fn require_one_modified(r: SqliteQueryResult) -> Result<(), DatabaseError> {
     if r.rows_affected() == 1 {
         Ok(())
@@ -0,0 +1,74 @@
+INSERT INTO players (user_id, project_id)
+VALUES
+  (1, 1),
+  (1, 2),
+  (2, 2),
+  (3, 3);
+
+UPDATE projects_history SET downloads = 5 WHERE project_id = 1;
+UPDATE projects_history SET downloads = 5 WHERE project_id = 3;
+UPDATE projects_history SET downloads = 9 WHERE project_id = 4;
+
+INSERT INTO packages_history (
+  package_id,
+  project_id,
+  created_at,
+  created_by,
+  deleted_at,
+  deleted_by
+)
+VALUES
+  (1, 1, 0, 1, NULL, NULL),
+  (2, 2, 0, 1, NULL, NULL),
+  (3, 4, 0, 1, NULL, NULL);
+
+INSERT INTO packages_revisions (
+  package_id,
+  name,
+  slug,
+  sort_key,
+  modified_at,
+  modified_by
+)
+VALUES
+  (1, "a", "a", 0, 0, 1),
+  (2, "b", "b", 0, 0, 1),
+  (3, "d", "d", 0, 0, 1);
+
+INSERT INTO packages (
+  package_id,
+  project_id,
+  name,
+  slug,
+  sort_key,
+  created_at,
+  created_by
+)
+VALUES
+  (1, 1, "a", "a", 0, 0, 1),
+  (2, 2, "b", "b", 0, 0, 1),
+  (3, 4, "d", "d", 0, 0, 1);
+
+INSERT INTO releases_history (
+  release_id,
+  package_id,
+  version,
+  version_major,
+  version_minor,
+  version_patch,
+  version_pre,
+  version_build,
+  published_at,
+  published_by,
+  deleted_at,
+  deleted_by
+)
+VALUES
+  (1, 1, "1.0.0", 1, 0, 0, "", "", 100, 1, NULL, NULL),
+  (2, 2, "1.0.0", 1, 0, 0, "", "", 300, 1, NULL, NULL),
+  (3, 3, "1.0.0", 1, 0, 0, "", "", 300, 1, NULL, NULL),
+  (4, 3, "1.0.1", 1, 0, 1, "", "", 500, 1, NULL, NULL);
+
+UPDATE releases
+SET state = "yanked", yank_reason = "broken"
+WHERE release_id = 4;
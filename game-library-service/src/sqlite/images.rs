@@ -41,6 +41,40 @@ LIMIT 1
     )
 }
 
+pub async fn get_image_rendition_url<'e, E>(
+    ex: E,
+    proj: Project,
+    img_name: &str,
+    size: u32
+) -> Result<Option<String>, DatabaseError>
+where
+    E: Executor<'e, Database = Sqlite>
+{
+    // find the smallest rendition at least as large as requested
+    Ok(
+        sqlx::query_scalar!(
+            "
+SELECT image_renditions.url
+FROM images
+JOIN image_renditions
+ON images.project_id = image_renditions.project_id
+    AND images.filename = image_renditions.filename
+    AND images.published_at = image_renditions.published_at
+WHERE images.project_id = ?
+    AND images.filename = ?
+    AND image_renditions.size >= ?
+ORDER BY image_renditions.size ASC
+LIMIT 1
+            ",
+            proj.0,
+            img_name,
+            size
+        )
+        .fetch_optional(ex)
+        .await?
+    )
+}
+
 pub async fn get_image_url_at<'e, E>(
     ex: E,
     proj: Project,
@@ -70,6 +104,7 @@ LIMIT 1
     )
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn create_image_revision_row<'e, E>(
     ex: E,
     owner: Owner,
@@ -77,11 +112,15 @@ async fn create_image_revision_row<'e, E>(
     img_name: &str,
     url: &str,
     content_type: &str,
+    dimensions: Option<(u32, u32)>,
     now: i64
 ) -> Result<(), DatabaseError>
 where
     E: Executor<'e, Database = Sqlite>
 {
+    let width = dimensions.map(|(w, _)| w);
+    let height = dimensions.map(|(_, h)| h);
+
     sqlx::query!(
         "
 INSERT INTO image_revisions (
@@ -90,16 +129,20 @@ INSERT INTO image_revisions (
     url,
     content_type,
     published_at,
-    published_by
+    published_by,
+    width,
+    height
 )
-VALUES (?, ?, ?, ?, ?, ?)
+VALUES (?, ?, ?, ?, ?, ?, ?, ?)
         ",
         proj.0,
         img_name,
         url,
         content_type,
         now,
-        owner.0
+        owner.0,
+        width,
+        height
     )
     .execute(ex)
     .await?;
@@ -107,6 +150,40 @@ VALUES (?, ?, ?, ?, ?, ?)
     Ok(())
 }
 
+async fn create_image_rendition_rows(
+    tx: &mut Transaction<'_, Sqlite>,
+    proj: Project,
+    img_name: &str,
+    renditions: &[(u32, String)],
+    now: i64
+) -> Result<(), DatabaseError>
+{
+    for (size, url) in renditions {
+        sqlx::query!(
+            "
+INSERT INTO image_renditions (
+    project_id,
+    filename,
+    published_at,
+    size,
+    url
+)
+VALUES (?, ?, ?, ?, ?)
+            ",
+            proj.0,
+            img_name,
+            now,
+            size,
+            url
+        )
+        .execute(&mut **tx)
+        .await?;
+    }
+
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
 pub async fn add_image_url<'a, A>(
     conn: A,
     owner: Owner,
@@ -114,6 +191,8 @@ pub async fn add_image_url<'a, A>(
     img_name: &str,
     url: &str,
     content_type: &str,
+    dimensions: Option<(u32, u32)>,
+    renditions: &[(u32, String)],
     now: i64
 ) -> Result<(), DatabaseError>
 where
@@ -129,6 +208,16 @@ where
         img_name,
         url,
         content_type,
+        dimensions,
+        now
+    ).await?;
+
+    // insert rows in image_renditions
+    create_image_rendition_rows(
+        &mut tx,
+        proj,
+        img_name,
+        renditions,
         now
     ).await?;
 
@@ -152,12 +241,17 @@ where
             GalleryImage,
             "
 SELECT
-    gallery_id AS id,
-    filename,
-    description
+    galleries.gallery_id AS id,
+    galleries.filename,
+    galleries.description,
+    images.width,
+    images.height
 FROM galleries
-WHERE project_id = ?
-ORDER BY sort_key
+JOIN images
+ON galleries.project_id = images.project_id
+    AND galleries.filename = images.filename
+WHERE galleries.project_id = ?
+ORDER BY galleries.sort_key
             ",
             proj.0
         )
@@ -179,15 +273,28 @@ where
             GalleryImage,
             "
 SELECT
-    gallery_id AS id,
-    filename,
-    description
+    galleries_history.gallery_id AS id,
+    galleries_history.filename,
+    galleries_history.description,
+    image_revisions.width,
+    image_revisions.height
 FROM galleries_history
-WHERE project_id = ?
-    AND published_at <= ?
-    AND (removed_at > ? OR removed_at IS NULL)
-ORDER BY sort_key
+LEFT JOIN image_revisions
+ON galleries_history.project_id = image_revisions.project_id
+    AND galleries_history.filename = image_revisions.filename
+    AND image_revisions.published_at = (
+        SELECT MAX(published_at)
+        FROM image_revisions
+        WHERE project_id = galleries_history.project_id
+            AND filename = galleries_history.filename
+            AND published_at <= ?
+    )
+WHERE galleries_history.project_id = ?
+    AND galleries_history.published_at <= ?
+    AND (galleries_history.removed_at > ? OR galleries_history.removed_at IS NULL)
+ORDER BY galleries_history.sort_key
             ",
+            date,
             proj.0,
             date,
             date
@@ -613,6 +720,7 @@ where
     Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
 pub async fn add_gallery_image<'a, A>(
     conn: A,
     owner: Owner,
@@ -620,6 +728,8 @@ pub async fn add_gallery_image<'a, A>(
     img_name: &str,
     url: &str,
     content_type: &str,
+    dimensions: Option<(u32, u32)>,
+    renditions: &[(u32, String)],
     now: i64
 ) -> Result<(), DatabaseError>
 where
@@ -635,6 +745,16 @@ where
         img_name,
         url,
         content_type,
+        dimensions,
+        now
+    ).await?;
+
+    // add rows to image_renditions
+    create_image_rendition_rows(
+        &mut tx,
+        proj,
+        img_name,
+        renditions,
         now
     ).await?;
 
@@ -742,6 +862,8 @@ mod test {
             "image.png",
             "https://example.com/image.png",
             "image/png",
+            None,
+            &[],
             1703980420641538067
         ).await.unwrap();
 
@@ -751,6 +873,141 @@ mod test {
         );
     }
 
+    #[sqlx::test(fixtures("users", "projects", "images"))]
+    async fn add_image_url_renditions(pool: Pool) {
+        add_image_url(
+            &pool,
+            Owner(1),
+            Project(42),
+            "image.png",
+            "https://example.com/image.png",
+            "image/png",
+            Some((2048, 1024)),
+            &[
+                (256, "https://example.com/image.png.256.webp".into()),
+                (1024, "https://example.com/image.png.1024.webp".into())
+            ],
+            1703980420641538067
+        ).await.unwrap();
+
+        assert_eq!(
+            get_image_rendition_url(&pool, Project(42), "image.png", 100)
+                .await
+                .unwrap(),
+            Some("https://example.com/image.png.256.webp".into())
+        );
+
+        assert_eq!(
+            get_image_rendition_url(&pool, Project(42), "image.png", 256)
+                .await
+                .unwrap(),
+            Some("https://example.com/image.png.256.webp".into())
+        );
+
+        assert_eq!(
+            get_image_rendition_url(&pool, Project(42), "image.png", 257)
+                .await
+                .unwrap(),
+            Some("https://example.com/image.png.1024.webp".into())
+        );
+
+        assert_eq!(
+            get_image_rendition_url(&pool, Project(42), "image.png", 2048)
+                .await
+                .unwrap(),
+            None
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "images"))]
+    async fn add_image_url_renditions_replaced(pool: Pool) {
+        add_image_url(
+            &pool,
+            Owner(1),
+            Project(42),
+            "image.png",
+            "https://example.com/image.png",
+            "image/png",
+            None,
+            &[(256, "https://example.com/image.png.256.webp".into())],
+            1703980420641538067
+        ).await.unwrap();
+
+        // a new upload without renditions replaces the old ones
+        add_image_url(
+            &pool,
+            Owner(1),
+            Project(42),
+            "image.png",
+            "https://example.com/image.png",
+            "image/png",
+            None,
+            &[],
+            1703980420641538068
+        ).await.unwrap();
+
+        assert_eq!(
+            get_image_rendition_url(&pool, Project(42), "image.png", 256)
+                .await
+                .unwrap(),
+            None
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "images"))]
+    async fn add_gallery_image_dimensions(pool: Pool) {
+        add_gallery_image(
+            &pool,
+            Owner(1),
+            Project(42),
+            "image.png",
+            "https://example.com/image.png",
+            "image/png",
+            Some((640, 480)),
+            &[],
+            1703980420641538067
+        ).await.unwrap();
+
+        let expected = [
+            GalleryImage {
+                id: 1,
+                filename: "image.png".into(),
+                description: "".into(),
+                width: Some(640),
+                height: Some(480)
+            }
+        ];
+
+        assert_eq!(get_gallery(&pool, Project(42)).await.unwrap(), expected);
+
+        assert_eq!(
+            get_gallery_at(&pool, Project(42), 1703980420641538067)
+                .await
+                .unwrap(),
+            expected
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "images"))]
+    async fn get_image_rendition_url_no_renditions(pool: Pool) {
+        assert_eq!(
+            get_image_rendition_url(&pool, Project(42), "img.png", 256)
+                .await
+                .unwrap(),
+            None
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "images"))]
+    async fn get_image_rendition_url_not_an_image(pool: Pool) {
+        assert_eq!(
+            get_image_rendition_url(&pool, Project(42), "bogus", 256)
+                .await
+                .unwrap(),
+            None
+        );
+    }
+
     #[sqlx::test(fixtures("users", "projects", "images"))]
     async fn add_image_url_not_a_user(pool: Pool) {
         // This should not happen; the Owner passed in should be good.
@@ -763,6 +1020,8 @@ mod test {
                     "image.png",
                     "https://example.com/image.png",
                     "image/png",
+                    None,
+                    &[],
                     0
                 ).await.unwrap_err(),
                 DatabaseError::SqlxError(_)
@@ -782,6 +1041,8 @@ mod test {
                     "image.png",
                     "https://example.com/image.png",
                     "image/png",
+                    None,
+                    &[],
                     0
                 ).await.unwrap_err(),
                 DatabaseError::SqlxError(_)
@@ -842,7 +1103,9 @@ mod test {
                 GalleryImage {
                     id: 1,
                     filename: "img.png".into(),
-                    description: "".into()
+                    description: "".into(),
+                    width: None,
+                    height: None
                 }
             ]
         );
@@ -928,7 +1191,9 @@ mod test {
                 GalleryImage {
                     id: 1,
                     filename: "img.png".into(),
-                    description: "".into()
+                    description: "".into(),
+                    width: None,
+                    height: None
                 }
             ]
         );
@@ -951,7 +1216,9 @@ mod test {
                 GalleryImage {
                     id: 5,
                     filename: "img.png".into(),
-                    description: "x".into()
+                    description: "x".into(),
+                    width: None,
+                    height: None
                 }
             ]
         );
@@ -1020,17 +1287,23 @@ mod test {
                 GalleryImage {
                     id: 2,
                     filename: "a.png".into(),
-                    description: "".into()
+                    description: "".into(),
+                    width: None,
+                    height: None
                 },
                 GalleryImage {
                     id: 3,
                     filename: "b.png".into(),
-                    description: "".into()
+                    description: "".into(),
+                    width: None,
+                    height: None
                 },
                 GalleryImage {
                     id: 4,
                     filename: "c.png".into(),
-                    description: "".into()
+                    description: "".into(),
+                    width: None,
+                    height: None
                 }
             ]
         );
@@ -1053,17 +1326,23 @@ mod test {
                 GalleryImage {
                     id: 2,
                     filename: "a.png".into(),
-                    description: "".into()
+                    description: "".into(),
+                    width: None,
+                    height: None
                 },
                 GalleryImage {
                     id: 4,
                     filename: "c.png".into(),
-                    description: "".into()
+                    description: "".into(),
+                    width: None,
+                    height: None
                 },
                 GalleryImage {
                     id: 5,
                     filename: "b.png".into(),
-                    description: "".into()
+                    description: "".into(),
+                    width: None,
+                    height: None
                 },
 
             ]
@@ -1078,17 +1357,23 @@ mod test {
                 GalleryImage {
                     id: 2,
                     filename: "a.png".into(),
-                    description: "".into()
+                    description: "".into(),
+                    width: None,
+                    height: None
                 },
                 GalleryImage {
                     id: 3,
                     filename: "b.png".into(),
-                    description: "".into()
+                    description: "".into(),
+                    width: None,
+                    height: None
                 },
                 GalleryImage {
                     id: 4,
                     filename: "c.png".into(),
-                    description: "".into()
+                    description: "".into(),
+                    width: None,
+                    height: None
                 }
             ]
         );
@@ -1111,17 +1396,23 @@ mod test {
                 GalleryImage {
                     id: 5,
                     filename: "b.png".into(),
-                    description: "".into()
+                    description: "".into(),
+                    width: None,
+                    height: None
                 },
                 GalleryImage {
                     id: 2,
                     filename: "a.png".into(),
-                    description: "".into()
+                    description: "".into(),
+                    width: None,
+                    height: None
                 },
                 GalleryImage {
                     id: 4,
                     filename: "c.png".into(),
-                    description: "".into()
+                    description: "".into(),
+                    width: None,
+                    height: None
                 }
             ]
         );
@@ -1135,17 +1426,23 @@ mod test {
                 GalleryImage {
                     id: 2,
                     filename: "a.png".into(),
-                    description: "".into()
+                    description: "".into(),
+                    width: None,
+                    height: None
                 },
                 GalleryImage {
                     id: 3,
                     filename: "b.png".into(),
-                    description: "".into()
+                    description: "".into(),
+                    width: None,
+                    height: None
                 },
                 GalleryImage {
                     id: 4,
                     filename: "c.png".into(),
-                    description: "".into()
+                    description: "".into(),
+                    width: None,
+                    height: None
                 }
             ]
         );
@@ -1168,17 +1465,23 @@ mod test {
                 GalleryImage {
                     id: 2,
                     filename: "a.png".into(),
-                    description: "".into()
+                    description: "".into(),
+                    width: None,
+                    height: None
                 },
                 GalleryImage {
                     id: 5,
                     filename: "c.png".into(),
-                    description: "".into()
+                    description: "".into(),
+                    width: None,
+                    height: None
                 },
                 GalleryImage {
                     id: 3,
                     filename: "b.png".into(),
-                    description: "".into()
+                    description: "".into(),
+                    width: None,
+                    height: None
                 }
             ]
         );
@@ -1228,7 +1531,9 @@ mod test {
                 GalleryImage {
                     id: 1,
                     filename: "img.png".into(),
-                    description: "".into()
+                    description: "".into(),
+                    width: None,
+                    height: None
                 }
             ]
         );
@@ -1251,7 +1556,9 @@ mod test {
                 GalleryImage {
                     id: 5,
                     filename: "img.png".into(),
-                    description: "".into()
+                    description: "".into(),
+                    width: None,
+                    height: None
                 }
             ]
         );
@@ -1302,17 +1609,23 @@ mod test {
                 GalleryImage {
                     id: 2,
                     filename: "a.png".into(),
-                    description: "".into()
+                    description: "".into(),
+                    width: None,
+                    height: None
                 },
                 GalleryImage {
                     id: 3,
                     filename: "b.png".into(),
-                    description: "".into()
+                    description: "".into(),
+                    width: None,
+                    height: None
                 },
                 GalleryImage {
                     id: 4,
                     filename: "c.png".into(),
-                    description: "".into()
+                    description: "".into(),
+                    width: None,
+                    height: None
                 }
             ]
         );
@@ -1336,17 +1649,23 @@ mod test {
                 GalleryImage {
                     id: 6,
                     filename: "c.png".into(),
-                    description: "".into()
+                    description: "".into(),
+                    width: None,
+                    height: None
                 },
                 GalleryImage {
                     id: 5,
                     filename: "b.png".into(),
-                    description: "".into()
+                    description: "".into(),
+                    width: None,
+                    height: None
                 },
                 GalleryImage {
                     id: 2,
                     filename: "a.png".into(),
-                    description: "".into()
+                    description: "".into(),
+                    width: None,
+                    height: None
                 }
             ]
         );
@@ -1360,17 +1679,23 @@ mod test {
                 GalleryImage {
                     id: 2,
                     filename: "a.png".into(),
-                    description: "".into()
+                    description: "".into(),
+                    width: None,
+                    height: None
                 },
                 GalleryImage {
                     id: 3,
                     filename: "b.png".into(),
-                    description: "".into()
+                    description: "".into(),
+                    width: None,
+                    height: None
                 },
                 GalleryImage {
                     id: 4,
                     filename: "c.png".into(),
-                    description: "".into()
+                    description: "".into(),
+                    width: None,
+                    height: None
                 }
             ]
         );
@@ -1395,17 +1720,23 @@ mod test {
                 GalleryImage {
                     id: 7,
                     filename: "c.png".into(),
-                    description: "x".into()
+                    description: "x".into(),
+                    width: None,
+                    height: None
                 },
                 GalleryImage {
                     id: 2,
                     filename: "a.png".into(),
-                    description: "".into()
+                    description: "".into(),
+                    width: None,
+                    height: None
                 },
                 GalleryImage {
                     id: 3,
                     filename: "b.png".into(),
-                    description: "".into()
+                    description: "".into(),
+                    width: None,
+                    height: None
                 }
             ]
         );
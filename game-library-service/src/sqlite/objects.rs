@@ -0,0 +1,82 @@
+use sqlx::{
+    Executor,
+    sqlite::Sqlite
+};
+
+use crate::db::DatabaseError;
+
+// History is kept so that past states of projects can be read, so every
+// object a file or image ever pointed to counts as referenced, including
+// those of deleted releases and packages and of replaced images. Only
+// objects no row ever referenced, such as abandoned uploads, are orphans.
+pub async fn get_referenced_urls<'e, E>(
+    ex: E
+) -> Result<Vec<String>, DatabaseError>
+where
+    E: Executor<'e, Database = Sqlite>
+{
+    Ok(
+        sqlx::query_scalar!(
+            r#"
+SELECT url AS "url!"
+FROM files_history
+UNION
+SELECT url
+FROM image_revisions
+UNION
+SELECT url
+FROM images
+UNION
+SELECT url
+FROM image_renditions
+ORDER BY 1
+            "#
+        )
+        .fetch_all(ex)
+        .await?
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    type Pool = sqlx::Pool<Sqlite>;
+
+    #[sqlx::test(fixtures("users", "projects", "packages", "images"))]
+    async fn get_referenced_urls_ok(pool: Pool) {
+        sqlx::query!(
+            "
+INSERT INTO image_renditions (
+    project_id,
+    filename,
+    published_at,
+    size,
+    url
+)
+VALUES (
+    42,
+    'img.png',
+    1694804206419538067,
+    256,
+    'https://example.com/images/img.png.256.webp'
+)
+            "
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        assert_eq!(
+            get_referenced_urls(&pool).await.unwrap(),
+            [
+                "",
+                "https://example.com/a_package-1.2.3",
+                "https://example.com/a_package-1.2.4",
+                "https://example.com/c_package-0.1.0",
+                "https://example.com/images/img.png",
+                "https://example.com/images/img.png.256.webp"
+            ]
+        );
+    }
+}
@@ -9,7 +9,8 @@ use crate::{
     model::{Owner, Package,  Project},
     sqlite::{
         require_one_modified,
-        project::update_project_non_project_data
+        project::update_project_non_project_data,
+        releases::{retire_package_file_history_rows, retire_package_release_history_rows}
     }
 };
 
@@ -121,9 +122,12 @@ where
 SELECT packages.project_id,
     packages.package_id
 FROM projects
+JOIN projects_history
+ON projects.project_id = projects_history.project_id
 JOIN packages
 ON projects.project_id = packages.project_id
 WHERE projects.slug = ?
+    AND projects_history.deleted_at IS NULL
     AND packages.slug = ?
             ",
             projslug,
@@ -461,6 +465,7 @@ pub async fn delete_package<'a, A>(
     owner: Owner,
     proj: Project,
     pkg: Package,
+    cascade: bool,
     now: i64
 ) -> Result<(), DatabaseError>
 where
@@ -470,6 +475,13 @@ where
 
     check_package_row_exists(&mut *tx, pkg).await?;
 
+    if cascade {
+        // delete the file rows, then the release rows
+        retire_package_file_history_rows(&mut *tx, owner, pkg, now).await?;
+        retire_package_release_history_rows(&mut *tx, owner, pkg, now)
+            .await?;
+    }
+
     delete_package_row(&mut *tx, pkg).await?;
     retire_package_history_row(&mut *tx, owner, pkg, now).await?;
     update_project_non_project_data(&mut tx, owner, proj, now).await?;
@@ -485,7 +497,13 @@ mod test {
 
     use std::slice;
 
-    use crate::sqlite::project::get_project_row;
+    use crate::{
+        model::Release,
+        sqlite::{
+            project::get_project_row,
+            releases::{get_files, get_files_at, get_releases, get_releases_at}
+        }
+    };
 
     type Pool = sqlx::Pool<Sqlite>;
 
@@ -810,6 +828,7 @@ mod test {
             Owner(1),
             Project(42),
             Package(2),
+            false,
             1702137389180282478
         ).await.unwrap();
 
@@ -859,6 +878,7 @@ mod test {
                 Owner(1),
                 Project(0),
                 Package(2),
+                false,
                 1702137389180282478
             ).await.unwrap_err(),
             DatabaseError::NotFound
@@ -873,6 +893,7 @@ mod test {
                 Owner(1),
                 Project(42),
                 Package(5),
+                false,
                 1702137389180282478
             ).await.unwrap_err(),
             DatabaseError::NotFound
@@ -888,10 +909,73 @@ mod test {
                     Owner(1),
                     Project(42),
                     Package(1),
+                    false,
                     1702137389180282478
                 ).await.unwrap_err(),
                 DatabaseError::SqlxError(_)
             )
         );
     }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn delete_package_cascade(pool: Pool) {
+        let proj = Project(42);
+        let pkg = Package(1);
+
+        let rels_before = get_releases(&pool, pkg).await.unwrap();
+        assert_eq!(rels_before.len(), 3);
+
+        delete_package(
+            &pool,
+            Owner(3),
+            proj,
+            pkg,
+            true,
+            1702223789180282478
+        ).await.unwrap();
+
+        assert!(
+            !get_packages(&pool, proj).await.unwrap()
+                .iter()
+                .any(|p| p.package_id == pkg.0)
+        );
+        assert_eq!(get_releases(&pool, pkg).await.unwrap(), []);
+        assert_eq!(get_files(&pool, Release(1)).await.unwrap(), []);
+
+        // the releases and files remain in the history
+        assert_eq!(
+            get_releases_at(&pool, pkg, 1702223789180282477).await.unwrap(),
+            rels_before
+        );
+        assert_eq!(
+            get_files_at(&pool, Release(1), 1702223789180282477)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+
+        // the deletions are attributed
+        assert_eq!(
+            sqlx::query_scalar!(
+                "
+SELECT COUNT(*)
+FROM releases_history
+WHERE package_id = ?
+    AND deleted_by = 3
+    AND deleted_at = 1702223789180282478
+                ",
+                pkg.0
+            )
+            .fetch_one(&pool)
+            .await
+            .unwrap(),
+            3
+        );
+
+        assert_eq!(
+            get_project_row(&pool, proj).await.unwrap().revision,
+            4
+        );
+    }
 }
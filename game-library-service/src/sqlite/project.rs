@@ -9,7 +9,7 @@ use unicode_properties::{GeneralCategoryGroup, UnicodeGeneralCategory};
 use crate::{
     db::{DatabaseError, ProjectRow, map_unique},
     input::{ProjectDataPatch, ProjectDataPost},
-    model::{Owner, Project, User},
+    model::{Admin, Owner, Project, User},
     sqlite::{
         require_one_modified,
         publishers::{Publisher, get_publisher_id, get_or_create_publisher, create_publisher},
@@ -28,9 +28,62 @@ where
     Ok(
         sqlx::query_scalar!(
             "
-SELECT project_id
+SELECT projects.project_id
 FROM projects
-WHERE slug = ?
+JOIN projects_history
+ON projects.project_id = projects_history.project_id
+WHERE projects.slug = ?
+    AND projects_history.deleted_at IS NULL
+            ",
+            projslug
+        )
+        .fetch_optional(ex)
+        .await?
+        .map(Project)
+    )
+}
+
+pub async fn get_deleted_project_id<'e, E>(
+    ex: E,
+    projslug: &str
+) -> Result<Option<Project>, DatabaseError>
+where
+    E: Executor<'e, Database = Sqlite>
+{
+    Ok(
+        sqlx::query_scalar!(
+            "
+SELECT projects.project_id
+FROM projects
+JOIN projects_history
+ON projects.project_id = projects_history.project_id
+WHERE projects.slug = ?
+    AND projects_history.deleted_at IS NOT NULL
+            ",
+            projslug
+        )
+        .fetch_optional(ex)
+        .await?
+        .map(Project)
+    )
+}
+
+pub async fn get_project_alias_id<'e, E>(
+    ex: E,
+    projslug: &str
+) -> Result<Option<Project>, DatabaseError>
+where
+    E: Executor<'e, Database = Sqlite>
+{
+    Ok(
+        sqlx::query_scalar!(
+            "
+SELECT projects_aliases.project_id
+FROM projects_aliases
+JOIN projects_history
+ON projects_aliases.project_id = projects_history.project_id
+WHERE projects_aliases.slug = ?
+    AND projects_history.deleted_at IS NULL
             ",
             projslug
         )
@@ -432,14 +485,26 @@ where
     A: Acquire<'a, Database = Sqlite>
 {
     let mut tx = conn.begin().await?;
+    update_project_in_trans(&mut tx, owner, proj, pd, now).await?;
+    tx.commit().await?;
+    Ok(())
+}
 
+pub async fn update_project_in_trans(
+    tx: &mut Transaction<'_, Sqlite>,
+    owner: Owner,
+    proj: Project,
+    pd: &ProjectDataPatch,
+    now: i64
+) -> Result<(), DatabaseError>
+{
     // get project
-    let row = get_project_row(&mut *tx, proj).await?;
+    let row = get_project_row(&mut **tx, proj).await?;
     let revision = row.revision + 1;
 
     // get publisher id if publisher changed
     let publisher = match &pd.game.publisher {
-        Some(pname) => get_or_create_publisher(&mut tx, pname).await?,
+        Some(pname) => get_or_create_publisher(tx, pname).await?,
         None => Publisher(row.game_publisher_id)
     };
 
@@ -470,12 +535,12 @@ where
         image: pd.image.as_ref().unwrap_or(&row.image).as_deref()
     };
 
-    let project_data_id = create_project_data_row(&mut *tx, &dr).await?;
+    let project_data_id = create_project_data_row(&mut **tx, &dr).await?;
 
     // update tags if required
     if pd.tags_add.is_some() || pd.tags_remove.is_some() {
         update_project_tags_in_trans(
-            &mut tx,
+            tx,
             owner,
             proj,
             pd.tags_add.as_ref().map_or(&[], |v| v),
@@ -493,11 +558,11 @@ where
     };
 
     // create project revision
-    create_project_revision_row(&mut *tx, &rr).await?;
+    create_project_revision_row(&mut **tx, &rr).await?;
 
     // update project
     update_project_row(
-        &mut *tx,
+        &mut **tx,
         owner,
         proj,
         revision,
@@ -506,8 +571,6 @@ where
         now
     ).await?;
 
-    tx.commit().await?;
-
     Ok(())
 }
 
@@ -667,6 +730,287 @@ pub async fn update_project_non_project_data(
     Ok(())
 }
 
+async fn retire_project_history_row<'e, E>(
+    ex: E,
+    user: User,
+    proj: Project,
+    now: i64
+) -> Result<(), DatabaseError>
+where
+    E: Executor<'e, Database = Sqlite>
+{
+    sqlx::query!(
+        "
+UPDATE projects_history
+SET
+    deleted_by = ?,
+    deleted_at = ?
+WHERE project_id = ?
+    AND deleted_at IS NULL
+        ",
+        user.0,
+        now,
+        proj.0
+    )
+    .execute(ex)
+    .await
+    .map_err(DatabaseError::from)
+    .and_then(require_one_modified)
+}
+
+pub async fn delete_project<'a, A>(
+    conn: A,
+    user: User,
+    proj: Project,
+    now: i64
+) -> Result<(), DatabaseError>
+where
+    A: Acquire<'a, Database = Sqlite>
+{
+    let mut tx = conn.begin().await?;
+
+    // record who deleted the project as a new revision; this must happen
+    // before the project is retired, as retiring removes it from the FTS
+    // index and updates to retired projects are not indexed
+    update_project_non_project_data(&mut tx, Owner(user.0), proj, now).await?;
+
+    // the projects row is retained so that the project can be restored
+    retire_project_history_row(&mut *tx, user, proj, now).await?;
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+async fn unretire_project_history_row<'e, E>(
+    ex: E,
+    proj: Project
+) -> Result<(), DatabaseError>
+where
+    E: Executor<'e, Database = Sqlite>
+{
+    sqlx::query!(
+        "
+UPDATE projects_history
+SET
+    deleted_by = NULL,
+    deleted_at = NULL
+WHERE project_id = ?
+    AND deleted_at IS NOT NULL
+        ",
+        proj.0
+    )
+    .execute(ex)
+    .await
+    .map_err(DatabaseError::from)
+    .and_then(require_one_modified)
+}
+
+pub async fn restore_project<'a, A>(
+    conn: A,
+    admin: Admin,
+    proj: Project,
+    now: i64
+) -> Result<(), DatabaseError>
+where
+    A: Acquire<'a, Database = Sqlite>
+{
+    let mut tx = conn.begin().await?;
+
+    unretire_project_history_row(&mut *tx, proj).await?;
+
+    // record who restored the project as a new revision
+    update_project_non_project_data(&mut tx, Owner(admin.0), proj, now)
+        .await?;
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+pub async fn add_download<'e, E>(
+    ex: E,
+    proj: Project
+) -> Result<(), DatabaseError>
+where
+    E: Executor<'e, Database = Sqlite>
+{
+    sqlx::query!(
+        "
+UPDATE projects_history
+SET downloads = downloads + 1
+WHERE project_id = ?
+        ",
+        proj.0
+    )
+    .execute(ex)
+    .await
+    .map_err(DatabaseError::from)
+    .and_then(require_one_modified)
+}
+
+async fn rename_project_row<'e, E>(
+    ex: E,
+    admin: Admin,
+    proj: Project,
+    revision: i64,
+    name: &str,
+    slug: &str,
+    now: i64
+) -> Result<(), DatabaseError>
+where
+    E: Executor<'e, Database = Sqlite>
+{
+    let proj_norm = normalize_project_name(name);
+
+    sqlx::query!(
+        "
+UPDATE projects
+SET
+    name = ?,
+    normalized_name = ?,
+    slug = ?,
+    revision = ?,
+    modified_at = ?,
+    modified_by = ?
+WHERE project_id = ?
+        ",
+        name,
+        proj_norm,
+        slug,
+        revision,
+        now,
+        admin.0,
+        proj.0
+    )
+    .execute(ex)
+    .await
+    .map_err(map_unique)
+    .and_then(require_one_modified)
+}
+
+async fn create_project_alias_row<'e, E>(
+    ex: E,
+    proj: Project,
+    slug: &str,
+    now: i64
+) -> Result<(), DatabaseError>
+where
+    E: Executor<'e, Database = Sqlite>
+{
+    sqlx::query!(
+        "
+INSERT INTO projects_aliases (
+    slug,
+    project_id,
+    created_at
+)
+VALUES (?, ?, ?)
+ON CONFLICT(slug) DO UPDATE
+SET
+    project_id = excluded.project_id,
+    created_at = excluded.created_at
+        ",
+        slug,
+        proj.0,
+        now
+    )
+    .execute(ex)
+    .await?;
+
+    Ok(())
+}
+
+async fn delete_project_alias_row<'e, E>(
+    ex: E,
+    slug: &str
+) -> Result<(), DatabaseError>
+where
+    E: Executor<'e, Database = Sqlite>
+{
+    sqlx::query!(
+        "
+DELETE FROM projects_aliases
+WHERE slug = ?
+        ",
+        slug
+    )
+    .execute(ex)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn rename_project<'a, A>(
+    conn: A,
+    admin: Admin,
+    proj: Project,
+    name: &str,
+    slug: &str,
+    now: i64
+) -> Result<(), DatabaseError>
+where
+    A: Acquire<'a, Database = Sqlite>
+{
+    let mut tx = conn.begin().await?;
+
+    // get project
+    let row = get_project_row(&mut *tx, proj).await?;
+    let revision = row.revision + 1;
+
+    // create project data
+    let dr = ProjectDataRow {
+        project_id: proj.0,
+        name,
+        slug,
+        description: &row.description,
+        game_title: &row.game_title,
+        game_title_sort: &row.game_title_sort,
+        game_publisher_id: row.game_publisher_id,
+        game_year: &row.game_year,
+        game_players_min: row.game_players_min.map(|p| p as u32),
+        game_players_max: row.game_players_max.map(|p| p as u32),
+        game_length_min: row.game_length_min.map(|l| l as u32),
+        game_length_max: row.game_length_max.map(|l| l as u32),
+        readme: &row.readme,
+        image: row.image.as_deref()
+    };
+
+    let project_data_id = create_project_data_row(&mut *tx, &dr).await?;
+
+    let rr = ProjectRevisionRow {
+        project_id: proj.0,
+        modified_at: now,
+        modified_by: admin.0,
+        revision,
+        project_data_id
+    };
+
+    // create project revision
+    create_project_revision_row(&mut *tx, &rr).await?;
+
+    // update project
+    rename_project_row(
+        &mut *tx,
+        admin,
+        proj,
+        revision,
+        name,
+        slug,
+        now
+    ).await?;
+
+    // keep the old slug as an alias, unless it is unchanged
+    if row.slug != slug {
+        delete_project_alias_row(&mut *tx, slug).await?;
+        create_project_alias_row(&mut *tx, proj, &row.slug, now).await?;
+    }
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -1106,4 +1450,268 @@ mod test {
             DatabaseError::NotFound
         );
     }
+
+    #[sqlx::test(fixtures("users", "projects"))]
+    async fn get_deleted_project_id_not_deleted(pool: Pool) {
+        assert_eq!(
+            get_deleted_project_id(&pool, "test_game").await.unwrap(),
+            None
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects"))]
+    async fn delete_project_ok(pool: Pool) {
+        let proj = Project(42);
+        let orig_row = get_project_row(&pool, proj).await.unwrap();
+
+        delete_project(&pool, User(1), proj, 1702569006419538068)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            get_project_id(&pool, "test_game").await.unwrap(),
+            None
+        );
+
+        assert_eq!(
+            get_deleted_project_id(&pool, "test_game").await.unwrap(),
+            Some(proj)
+        );
+
+        let new_row = get_project_row(&pool, proj).await.unwrap();
+        assert_eq!(new_row.revision, orig_row.revision + 1);
+    }
+
+    #[sqlx::test(fixtures("users", "projects"))]
+    async fn delete_project_already_deleted(pool: Pool) {
+        let proj = Project(42);
+
+        delete_project(&pool, User(1), proj, 1702569006419538068)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            delete_project(&pool, User(1), proj, 1702569006419538069)
+                .await
+                .unwrap_err(),
+            DatabaseError::NotFound
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects"))]
+    async fn delete_project_not_a_project(pool: Pool) {
+        // This should not happen; the Project passed in should be good.
+        assert_eq!(
+            delete_project(&pool, User(1), Project(0), 1702569006419538068)
+                .await
+                .unwrap_err(),
+            DatabaseError::NotFound
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects"))]
+    async fn add_download_ok(pool: Pool) {
+        add_download(&pool, Project(42)).await.unwrap();
+        add_download(&pool, Project(42)).await.unwrap();
+
+        assert_eq!(
+            sqlx::query_scalar!(
+                "
+SELECT downloads
+FROM projects_history
+WHERE project_id = 42
+                "
+            )
+            .fetch_one(&pool)
+            .await
+            .unwrap(),
+            2
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects"))]
+    async fn add_download_not_a_project(pool: Pool) {
+        assert_eq!(
+            add_download(&pool, Project(0)).await.unwrap_err(),
+            DatabaseError::NotFound
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects"))]
+    async fn restore_project_ok(pool: Pool) {
+        let proj = Project(42);
+
+        delete_project(&pool, User(1), proj, 1702569006419538068)
+            .await
+            .unwrap();
+
+        restore_project(&pool, Admin(2), proj, 1702569006419538069)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            get_project_id(&pool, "test_game").await.unwrap(),
+            Some(proj)
+        );
+
+        assert_eq!(
+            get_deleted_project_id(&pool, "test_game").await.unwrap(),
+            None
+        );
+
+        let row = get_project_row(&pool, proj).await.unwrap();
+        assert_eq!(row.modified_by, 2);
+    }
+
+    #[sqlx::test(fixtures("users", "projects"))]
+    async fn restore_project_not_deleted(pool: Pool) {
+        assert_eq!(
+            restore_project(&pool, Admin(2), Project(42), 1702569006419538068)
+                .await
+                .unwrap_err(),
+            DatabaseError::NotFound
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects"))]
+    async fn get_project_alias_id_none(pool: Pool) {
+        assert_eq!(
+            get_project_alias_id(&pool, "test_game").await.unwrap(),
+            None
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects"))]
+    async fn rename_project_ok(pool: Pool) {
+        let proj = Project(42);
+        let orig_row = get_project_row(&pool, proj).await.unwrap();
+
+        rename_project(
+            &pool,
+            Admin(2),
+            proj,
+            "New Name",
+            "New-Name",
+            1702569006419538068
+        ).await.unwrap();
+
+        assert_eq!(
+            get_project_id(&pool, "New-Name").await.unwrap(),
+            Some(proj)
+        );
+
+        assert_eq!(
+            get_project_id(&pool, "test_game").await.unwrap(),
+            None
+        );
+
+        assert_eq!(
+            get_project_alias_id(&pool, "test_game").await.unwrap(),
+            Some(proj)
+        );
+
+        let new_row = get_project_row(&pool, proj).await.unwrap();
+        assert_eq!(new_row.name, "New Name");
+        assert_eq!(new_row.slug, "New-Name");
+        assert_eq!(new_row.revision, orig_row.revision + 1);
+        assert_eq!(new_row.modified_by, 2);
+
+        // the old name is kept in the previous revision
+        let old_row = get_project_row_revision(&pool, proj, orig_row.revision)
+            .await
+            .unwrap();
+        assert_eq!(old_row.name, "test_game");
+    }
+
+    #[sqlx::test(fixtures("users", "projects"))]
+    async fn rename_project_and_back(pool: Pool) {
+        let proj = Project(42);
+
+        rename_project(
+            &pool,
+            Admin(2),
+            proj,
+            "New Name",
+            "New-Name",
+            1702569006419538068
+        ).await.unwrap();
+
+        rename_project(
+            &pool,
+            Admin(2),
+            proj,
+            "test_game",
+            "test_game",
+            1702569006419538069
+        ).await.unwrap();
+
+        assert_eq!(
+            get_project_id(&pool, "test_game").await.unwrap(),
+            Some(proj)
+        );
+
+        assert_eq!(
+            get_project_alias_id(&pool, "test_game").await.unwrap(),
+            None
+        );
+
+        assert_eq!(
+            get_project_alias_id(&pool, "New-Name").await.unwrap(),
+            Some(proj)
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects"))]
+    async fn rename_project_already_exists(pool: Pool) {
+        assert_eq!(
+            rename_project(
+                &pool,
+                Admin(2),
+                Project(42),
+                "a_game",
+                "a_game",
+                1702569006419538068
+            ).await.unwrap_err(),
+            DatabaseError::AlreadyExists
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects"))]
+    async fn rename_project_not_a_project(pool: Pool) {
+        // This should not happen; the Project passed in should be good.
+        assert_eq!(
+            rename_project(
+                &pool,
+                Admin(2),
+                Project(0),
+                "New Name",
+                "New-Name",
+                1702569006419538068
+            ).await.unwrap_err(),
+            DatabaseError::NotFound
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects"))]
+    async fn get_project_alias_id_deleted(pool: Pool) {
+        let proj = Project(42);
+
+        rename_project(
+            &pool,
+            Admin(2),
+            proj,
+            "New Name",
+            "New-Name",
+            1702569006419538068
+        ).await.unwrap();
+
+        delete_project(&pool, User(1), proj, 1702569006419538069)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            get_project_alias_id(&pool, "test_game").await.unwrap(),
+            None
+        );
+    }
 }
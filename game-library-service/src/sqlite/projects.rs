@@ -1,12 +1,23 @@
 use const_format::formatcp;
-use glc::pagination::{Direction, Facet, SortBy};
+use glc::{
+    model::{FacetCounts, LengthBucket},
+    pagination::{Direction, Facet, SortBy}
+};
 use sqlx::{
-    Encode, Executor, QueryBuilder, Type,
+    Encode, Executor, QueryBuilder, Row, Type,
     query_builder::Separated,
     sqlite::Sqlite
 };
-
-use crate::db::{DatabaseError, ProjectSummaryRow};
+use std::collections::BTreeMap;
+
+use crate::{
+    db::{DatabaseError, ProjectSummaryRow},
+    sqlite::{
+        like_prefix,
+        releases::{VASSAL_COMPATIBLE_RELEASE, vassal_requires_later}
+    },
+    version::Version
+};
 
 // TODO: put a QueryBuilder into the db object for reuse?
 
@@ -17,6 +28,185 @@ fn fts5_quote(s: &str) -> String {
     format!("\"{}\"", s.replace("\"", "\"\""))
 }
 
+fn fts5_prefix(s: &str) -> String {
+    // A star following a quoted string makes the final token in it a
+    // prefix, so that partially-typed words match.
+    format!("{}*", fts5_quote(s))
+}
+
+fn fts5_tokens(s: &str) -> Vec<String> {
+    // approximates the unicode61 tokenizer used by projects_fts
+    s.split(|c: char| !c.is_alphanumeric())
+        .filter(|t| !t.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+fn max_typos(len: usize) -> usize {
+    // short words have too many near neighbors to correct usefully
+    match len {
+        0..4 => 0,
+        4..7 => 1,
+        _ => 2
+    }
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut prev = (0..=b.len()).collect::<Vec<_>>();
+    let mut cur = vec![0; b.len() + 1];
+
+    for (i, ac) in a.chars().enumerate() {
+        cur[0] = i + 1;
+        for (j, bc) in b.iter().enumerate() {
+            cur[j + 1] = (prev[j] + usize::from(ac != *bc))
+                .min(prev[j + 1] + 1)
+                .min(cur[j] + 1);
+        }
+        std::mem::swap(&mut prev, &mut cur);
+    }
+
+    prev[b.len()]
+}
+
+async fn fts5_has_match<'e, E>(
+    ex: E,
+    expr: &str
+) -> Result<bool, DatabaseError>
+where
+    E: Executor<'e, Database = Sqlite>
+{
+    Ok(
+        sqlx::query_scalar!(
+            r#"
+SELECT EXISTS(
+    SELECT 1
+    FROM projects_fts
+    WHERE projects_fts MATCH ?
+) AS "e!: bool"
+            "#,
+            expr
+        )
+        .fetch_one(ex)
+        .await?
+    )
+}
+
+async fn fts5_similar_terms<'e, E>(
+    ex: E,
+    token: &str
+) -> Result<Vec<String>, DatabaseError>
+where
+    E: Executor<'e, Database = Sqlite>
+{
+    let len = token.chars().count();
+    let typos = max_typos(len);
+
+    let Some(first) = token.chars().next() else {
+        return Ok(vec![]);
+    };
+
+    if typos == 0 {
+        return Ok(vec![]);
+    }
+
+    let min_len = (len - typos) as i64;
+    let max_len = (len + typos) as i64;
+
+    // Only terms with the same first letter are candidates. Typos in the
+    // first letter are rare, and the vocabulary is sorted by term, so the
+    // range constraint saves scanning all of it.
+    let lo = first.to_string();
+    let hi = format!("{first}{}", char::MAX);
+
+    let terms = sqlx::query_scalar!(
+        r#"
+SELECT term AS "term!: String"
+FROM projects_fts_vocab
+WHERE term BETWEEN ? AND ?
+    AND length(term) BETWEEN ? AND ?
+        "#,
+        lo,
+        hi,
+        min_len,
+        max_len
+    )
+    .fetch_all(ex)
+    .await?;
+
+    // keep only the closest terms
+    let dists = terms.iter()
+        .map(|t| edit_distance(token, t))
+        .collect::<Vec<_>>();
+
+    Ok(
+        match dists.iter().min() {
+            Some(&best) if best <= typos => terms.into_iter()
+                .zip(dists)
+                .filter(|(_, d)| *d == best)
+                .map(|(t, _)| t)
+                .collect(),
+            _ => vec![]
+        }
+    )
+}
+
+async fn fts5_match<'e, E>(
+    ex: E,
+    q: &str
+) -> Result<String, DatabaseError>
+where
+    E: Executor<'e, Database = Sqlite> + Copy
+{
+    let expr = fts5_prefix(q);
+    if fts5_has_match(ex, &expr).await? {
+        return Ok(expr);
+    }
+
+    // Nothing matched, so fall back to matching the words of the query
+    // individually, substituting the closest terms from the index for
+    // any which are not in it.
+    let tokens = fts5_tokens(q);
+    if tokens.is_empty() {
+        return Ok(expr);
+    }
+
+    let mut parts = Vec::with_capacity(tokens.len());
+    for t in &tokens {
+        let mut alts = vec![ fts5_quote(t) ];
+        alts.extend(
+            fts5_similar_terms(ex, t).await?
+                .iter()
+                .filter(|a| *a != t)
+                .map(|a| fts5_quote(a))
+        );
+        parts.push(format!("({})", alts.join(" OR ")));
+    }
+
+    Ok(parts.join(" AND "))
+}
+
+pub async fn fts5_facets<'e, E>(
+    ex: E,
+    facets: &[Facet]
+) -> Result<Vec<Facet>, DatabaseError>
+where
+    E: Executor<'e, Database = Sqlite> + Copy
+{
+    // Query facets are replaced by the FTS5 expressions to match; the
+    // other functions here expect facets which have been through this.
+    let mut out = Vec::with_capacity(facets.len());
+    for f in facets {
+        out.push(
+            match f {
+                Facet::Query(q) => Facet::Query(fts5_match(ex, q).await?),
+                f => f.clone()
+            }
+        );
+    }
+    Ok(out)
+}
+
 fn join_proj(table: &str, n: usize) -> String {
     format!(" JOIN {table} AS {table}_{n} ON projects.project_id = {table}_{n}.project_id ")
 }
@@ -31,6 +221,22 @@ fn join_tags(table: &str, n: usize) -> String {
 
 const JOIN_FTS: &str = " JOIN projects_fts ON projects.project_id = projects_fts.rowid ";
 
+// deleted projects retain their projects rows, so must be filtered out
+const JOIN_HISTORY: &str = " JOIN projects_history ON projects.project_id = projects_history.project_id ";
+
+const NOT_DELETED: &str = " projects_history.deleted_at IS NULL ";
+
+// a project is compatible with a Vassal version if it has a release
+// which is compatible with it
+const VASSAL_COMPATIBLE: &str = formatcp!("
+EXISTS (
+    SELECT 1
+    FROM packages
+    JOIN releases
+    ON packages.package_id = releases.package_id
+    WHERE packages.project_id = projects.project_id
+        AND {VASSAL_COMPATIBLE_RELEASE}");
+
 trait JoinsExt {
     fn joins(&self) -> impl Iterator<Item = String>;
 }
@@ -70,7 +276,7 @@ where
         match f {
             Facet::Query(q) =>
                 self.push(" projects_fts MATCH ")
-                    .push_bind_unseparated(fts5_quote(q)),
+                    .push_bind_unseparated(q),
             Facet::Publisher(p) =>
                 self.push(" projects.game_publisher = ")
                     .push_bind_unseparated(p),
@@ -98,7 +304,19 @@ where
             Facet::Owner(u) |
             Facet::Player(u) =>
                 self.push(format!(" users_{i}.username = "))
-                    .push_bind_unseparated(u)
+                    .push_bind_unseparated(u),
+            Facet::Vassal(v) => match v.parse::<Version>() {
+                Ok(v) => self.push(VASSAL_COMPATIBLE)
+                    .push_unseparated(vassal_requires_later(&v))
+                    .push_bind_unseparated(v.major)
+                    .push_unseparated(", ")
+                    .push_bind_unseparated(v.minor)
+                    .push_unseparated(", ")
+                    .push_bind_unseparated(v.patch)
+                    .push_unseparated(")))"),
+                // unreachable, as the version is checked when parsed
+                Err(_) => self.push(" 0 ")
+            }
         }
     }
 }
@@ -117,35 +335,38 @@ where
                     "
 SELECT COUNT(1)
 FROM projects
+JOIN projects_history
+ON projects.project_id = projects_history.project_id
+WHERE projects_history.deleted_at IS NULL
                     "
                 )
                 .fetch_one(ex)
                 .await?
             },
             1 if matches!(facets[0], Facet::Query(_)) => {
-                // pure queries avoid joining on the projects table
+                // pure queries avoid joining on the projects table;
+                // deleted projects are not in the FTS index
                 let Facet::Query(ref q) = facets[0] else { unreachable!() };
 
-                let query = fts5_quote(q);
-
                 sqlx::query_scalar!(
                     "
 SELECT COUNT(1)
 FROM projects_fts
 WHERE projects_fts MATCH ?
                     ",
-                    query
+                    q
                 )
                 .fetch_one(ex)
                 .await?
             },
             _ => {
-                let mut qb = QueryBuilder::new(
+                let mut qb = QueryBuilder::new(formatcp!(
                     "
 SELECT COUNT(1)
 FROM projects
+{JOIN_HISTORY}
                     "
-                );
+                ));
 
                 for j in facets.joins() {
                     qb.push(j);
@@ -154,6 +375,7 @@ FROM projects
                 qb.push(" WHERE ");
 
                 let mut qbs = qb.separated(" AND ");
+                qbs.push(NOT_DELETED);
                 for (i, f) in facets.iter().enumerate() {
                     qbs.push_where(i, f);
                 }
@@ -167,6 +389,204 @@ FROM projects
     )
 }
 
+// player counts are reported for games playable by 1 to this many players
+const PLAYERS_COUNT_MAX: u32 = 10;
+
+const LENGTH_BUCKETS: [(Option<u32>, Option<u32>); 6] = [
+    (None, Some(30)),
+    (Some(30), Some(60)),
+    (Some(60), Some(120)),
+    (Some(120), Some(240)),
+    (Some(240), Some(480)),
+    (Some(480), None)
+];
+
+fn faceted_query<'args>(
+    select: &str,
+    join: &str,
+    facets: &'args [Facet]
+) -> QueryBuilder<'args, Sqlite>
+{
+    let mut qb = QueryBuilder::new(select);
+    qb.push(" FROM projects ").push(JOIN_HISTORY);
+
+    for j in facets.joins() {
+        qb.push(j);
+    }
+
+    qb.push(join).push(" WHERE ");
+
+    let mut qbs = qb.separated(" AND ");
+    qbs.push(NOT_DELETED);
+    for (i, f) in facets.iter().enumerate() {
+        qbs.push_where(i, f);
+    }
+
+    qb
+}
+
+async fn get_value_counts<'e, E>(
+    ex: E,
+    field: &str,
+    join: &str,
+    facets: &[Facet]
+) -> Result<BTreeMap<String, i64>, DatabaseError>
+where
+    E: Executor<'e, Database = Sqlite>
+{
+    let mut qb = faceted_query(
+        &format!("SELECT {field}, COUNT(DISTINCT projects.project_id)"),
+        join,
+        facets
+    );
+
+    Ok(
+        qb
+            .push(format!(" AND {field} != '' GROUP BY {field}"))
+            .build_query_as::<(String, i64)>()
+            .fetch_all(ex)
+            .await?
+            .into_iter()
+            .collect()
+    )
+}
+
+async fn get_players_counts<'e, E>(
+    ex: E,
+    facets: &[Facet]
+) -> Result<BTreeMap<u32, i64>, DatabaseError>
+where
+    E: Executor<'e, Database = Sqlite>
+{
+    let mut qb = faceted_query(
+        &format!("
+WITH RECURSIVE players_counts(n) AS (
+    SELECT 1
+    UNION ALL
+    SELECT n + 1 FROM players_counts WHERE n < {PLAYERS_COUNT_MAX}
+)
+SELECT players_counts.n, COUNT(DISTINCT projects.project_id)
+        "),
+        " JOIN players_counts ON players_counts.n BETWEEN projects.game_players_min AND projects.game_players_max ",
+        facets
+    );
+
+    Ok(
+        qb
+            .push(" GROUP BY players_counts.n")
+            .build_query_as::<(u32, i64)>()
+            .fetch_all(ex)
+            .await?
+            .into_iter()
+            .collect()
+    )
+}
+
+async fn get_length_counts<'e, E>(
+    ex: E,
+    facets: &[Facet]
+) -> Result<Vec<LengthBucket>, DatabaseError>
+where
+    E: Executor<'e, Database = Sqlite>
+{
+    // buckets match the length_min and length_max facets, so a project
+    // may fall into none or several of them
+    let select = LENGTH_BUCKETS.iter()
+        .map(|b| match b {
+            (None, Some(max)) =>
+                format!("projects.game_length_max <= {max}"),
+            (Some(min), None) =>
+                format!("projects.game_length_min >= {min}"),
+            (Some(min), Some(max)) =>
+                format!("projects.game_length_min >= {min} AND projects.game_length_max <= {max}"),
+            (None, None) => "1".into()
+        })
+        .map(|cond| format!("COUNT(CASE WHEN {cond} THEN 1 END)"))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let row = faceted_query(&format!("SELECT {select}"), "", facets)
+        .build()
+        .fetch_one(ex)
+        .await?;
+
+    LENGTH_BUCKETS.iter()
+        .enumerate()
+        .map(|(i, (min, max))| Ok(
+            LengthBucket {
+                min: *min,
+                max: *max,
+                count: row.try_get(i)?
+            }
+        ))
+        .collect()
+}
+
+pub async fn get_facet_counts<'e, E>(
+    ex: E,
+    facets: &[Facet]
+) -> Result<FacetCounts, DatabaseError>
+where
+    E: Executor<'e, Database = Sqlite> + Copy
+{
+    Ok(
+        FacetCounts {
+            tags: get_value_counts(
+                ex,
+                "count_tags.tag",
+                " JOIN projects_tags AS count_projects_tags ON projects.project_id = count_projects_tags.project_id JOIN tags AS count_tags ON count_tags.tag_id = count_projects_tags.tag_id ",
+                facets
+            ).await?,
+            publishers: get_value_counts(
+                ex,
+                "projects.game_publisher",
+                "",
+                facets
+            ).await?,
+            years: get_value_counts(
+                ex,
+                "projects.game_year",
+                "",
+                facets
+            ).await?,
+            players: get_players_counts(ex, facets).await?,
+            lengths: get_length_counts(ex, facets).await?
+        }
+    )
+}
+
+pub async fn get_title_suggestions<'e, E>(
+    ex: E,
+    prefix: &str,
+    limit: u32
+) -> Result<Vec<String>, DatabaseError>
+where
+    E: Executor<'e, Database = Sqlite>
+{
+    // titles shared by more projects rank first
+    let prefix = like_prefix(prefix);
+
+    Ok(
+        sqlx::query_scalar!(
+            "
+SELECT projects.game_title
+FROM projects
+JOIN projects_history
+ON projects.project_id = projects_history.project_id
+WHERE projects.game_title LIKE ? ESCAPE '\\'
+    AND projects_history.deleted_at IS NULL
+GROUP BY projects.game_title
+ORDER BY COUNT(1) DESC, projects.game_title COLLATE NOCASE
+LIMIT ?
+            ",
+            prefix,
+            limit
+        )
+        .fetch_all(ex)
+        .await?
+    )
+}
+
 trait SortByField {
     fn field(&self) -> &'static str;
 }
@@ -178,7 +598,10 @@ impl SortByField for SortBy {
             SortBy::GameTitle => "projects.game_title_sort COLLATE NOCASE",
             SortBy::ModificationTime => "projects.modified_at",
             SortBy::CreationTime => "projects.created_at",
-            SortBy::Relevance => "projects_fts.rank"
+            SortBy::Relevance => "projects_fts.rank",
+            SortBy::Players => "projects_history.players",
+            SortBy::LatestDownloads => "projects_history.downloads",
+            SortBy::ReleaseTime => "projects_history.released_at"
         }
     }
 }
@@ -221,7 +644,10 @@ const SUMMARY_FIELDS: &str = "
     projects.game_players_max,
     projects.game_length_min,
     projects.game_length_max,
-    projects.image
+    projects.image,
+    projects_history.players,
+    projects_history.downloads,
+    projects_history.released_at
 ";
 
 const WINDOW_SELECT: &str = formatcp!("
@@ -229,6 +655,7 @@ SELECT
     0.0 AS rank,
     {SUMMARY_FIELDS}
 FROM projects
+{JOIN_HISTORY}
 ");
 
 const WINDOW_SELECT_FTS: &str = formatcp!("
@@ -236,6 +663,7 @@ SELECT
     projects_fts.rank,
     {SUMMARY_FIELDS}
 FROM projects
+{JOIN_HISTORY}
 ");
 
 pub async fn get_projects_end_window<'e, E>(
@@ -250,11 +678,11 @@ where
 {
     Ok(
         match facets.len() {
-            0 => QueryBuilder::new(formatcp!("{WINDOW_SELECT} ORDER BY "))
+            0 => QueryBuilder::new(formatcp!("{WINDOW_SELECT} WHERE {NOT_DELETED} ORDER BY "))
                 .push(sort_by.field())
                 .push(" ")
                 .push(dir.dir())
-                .push(", project_id ")
+                .push(", projects.project_id ")
                 .push(dir.dir())
                 .push(" LIMIT ")
                 .push_bind(limit)
@@ -278,6 +706,7 @@ where
                 qb.push(" WHERE ");
 
                 let mut qbs = qb.separated(" AND ");
+                qbs.push(NOT_DELETED);
                 for (i, f) in facets.iter().enumerate() {
                     qbs.push_where(i, f);
                 }
@@ -314,7 +743,7 @@ where
 {
     Ok(
         match facets.len() {
-            0 => QueryBuilder::new(formatcp!("{WINDOW_SELECT} WHERE "))
+            0 => QueryBuilder::new(formatcp!("{WINDOW_SELECT} WHERE {NOT_DELETED} AND ("))
                 .push(sort_by.field())
                 .push(" ")
                 .push(dir.op())
@@ -324,15 +753,15 @@ where
                 .push(sort_by.field())
                 .push(" = ")
                 .push_bind(field)
-                .push(" AND project_id ")
+                .push(" AND projects.project_id ")
                 .push(dir.op())
                 .push(" ")
                 .push_bind(id)
-                .push(") ORDER BY ")
+                .push(")) ORDER BY ")
                 .push(sort_by.field())
                 .push(" ")
                 .push(dir.dir())
-                .push(", project_id ")
+                .push(", projects.project_id ")
                 .push(dir.dir())
                 .push(" LIMIT ")
                 .push_bind(limit)
@@ -356,6 +785,7 @@ where
                 qb.push(" WHERE ");
 
                 let mut qbs = qb.separated(" AND ");
+                qbs.push(NOT_DELETED);
                 for (i, f) in facets.iter().enumerate() {
                     qbs.push_where(i, f);
                 }
@@ -395,6 +825,20 @@ where
 mod test {
     use super::*;
 
+    use glc::model::ReleaseState;
+
+    use crate::{
+        input::{PackageDataPatch, ReleaseDataPatch},
+        model::{Admin, Owner, Package, Project, Release, User},
+        sqlite::{
+            packages::{delete_package, update_package},
+            players::{add_player, remove_player},
+            project::{delete_project, restore_project},
+            releases::update_release,
+            tags::update_project_tags
+        }
+    };
+
     type Pool = sqlx::Pool<Sqlite>;
 
     #[test]
@@ -412,11 +856,290 @@ mod test {
         assert_eq!("\"abc\"\" def\"", fts5_quote("abc\" def"));
     }
 
+    #[test]
+    fn fts5_prefix_abc_def() {
+        assert_eq!("\"abc def\"*", fts5_prefix("abc def"));
+    }
+
+    #[test]
+    fn fts5_tokens_punct() {
+        assert_eq!(fts5_tokens("Brian's  GAME-of"), ["brian", "s", "game", "of"]);
+    }
+
+    #[test]
+    fn fts5_tokens_none() {
+        assert!(fts5_tokens(" !? ").is_empty());
+    }
+
+    #[test]
+    fn edit_distance_same() {
+        assert_eq!(edit_distance("struggle", "struggle"), 0);
+    }
+
+    #[test]
+    fn edit_distance_deletion() {
+        assert_eq!(edit_distance("strugle", "struggle"), 1);
+    }
+
+    #[test]
+    fn edit_distance_substitution() {
+        assert_eq!(edit_distance("rone", "rome"), 1);
+    }
+
+    #[test]
+    fn edit_distance_transposition() {
+        assert_eq!(edit_distance("rmoe", "rome"), 2);
+    }
+
+    #[test]
+    fn edit_distance_empty() {
+        assert_eq!(edit_distance("", "rome"), 4);
+    }
+
+    #[sqlx::test(fixtures("users", "projects"))]
+    async fn get_title_suggestions_ok(pool: Pool) {
+        assert_eq!(
+            get_title_suggestions(&pool, "a game", 10).await.unwrap(),
+            ["A Game of Tests".to_string()]
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects"))]
+    async fn get_title_suggestions_not_prefix(pool: Pool) {
+        assert_eq!(
+            get_title_suggestions(&pool, "game", 10).await.unwrap(),
+            [] as [String; 0]
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects"))]
+    async fn get_title_suggestions_deleted(pool: Pool) {
+        delete_project(&pool, User(1), Project(6), 1702569006419538068)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            get_title_suggestions(&pool, "some", 10).await.unwrap(),
+            [] as [String; 0]
+        );
+    }
+
+    async fn query_count(pool: &Pool, q: &str) -> i64 {
+        let facets = fts5_facets(pool, &[Facet::Query(q.into())])
+            .await
+            .unwrap();
+        get_projects_count(pool, &facets).await.unwrap()
+    }
+
+    #[sqlx::test(fixtures("users", "projects"))]
+    async fn fts5_match_exact(pool: Pool) {
+        assert_eq!(
+            fts5_match(&pool, "Another").await.unwrap(),
+            "\"Another\"*"
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects"))]
+    async fn fts5_match_typo(pool: Pool) {
+        assert_eq!(
+            fts5_match(&pool, "Anothr game").await.unwrap(),
+            "(\"anothr\" OR \"another\") AND (\"game\")"
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects"))]
+    async fn fts5_match_no_tokens(pool: Pool) {
+        assert_eq!(
+            fts5_match(&pool, "!!!").await.unwrap(),
+            "\"!!!\"*"
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects"))]
+    async fn get_projects_query_count_prefix(pool: Pool) {
+        assert_eq!(query_count(&pool, "Anoth").await, 1);
+    }
+
+    #[sqlx::test(fixtures("users", "projects"))]
+    async fn get_projects_query_count_phrase_prefix(pool: Pool) {
+        assert_eq!(query_count(&pool, "game of te").await, 1);
+    }
+
+    #[sqlx::test(fixtures("users", "projects"))]
+    async fn get_projects_query_count_typo(pool: Pool) {
+        assert_eq!(query_count(&pool, "Trademrked").await, 1);
+    }
+
+    #[sqlx::test(fixtures("users", "projects"))]
+    async fn get_projects_query_count_typo_words(pool: Pool) {
+        assert_eq!(query_count(&pool, "Anothr game").await, 1);
+    }
+
+    #[sqlx::test(fixtures("users", "projects"))]
+    async fn get_projects_query_count_typo_short(pool: Pool) {
+        assert_eq!(query_count(&pool, "gme").await, 0);
+    }
+
+    #[sqlx::test(fixtures("users", "projects"))]
+    async fn get_projects_query_count_typo_too_many(pool: Pool) {
+        assert_eq!(query_count(&pool, "Trdmrked").await, 0);
+    }
+
+    #[sqlx::test(fixtures("users", "projects"))]
+    async fn get_projects_query_count_typo_first_letter(pool: Pool) {
+        assert_eq!(query_count(&pool, "Bnother").await, 0);
+    }
+
+    #[sqlx::test(fixtures("users", "projects"))]
+    async fn get_projects_query_count_typo_deleted(pool: Pool) {
+        delete_project(&pool, User(1), Project(6), 1702569006419538068)
+            .await
+            .unwrap();
+        assert_eq!(query_count(&pool, "Anothr").await, 0);
+    }
+
+    #[sqlx::test(fixtures("users", "projects"))]
+    async fn get_projects_query_typo_relevance_window(pool: Pool) {
+        let facets = fts5_facets(
+            &pool,
+            &[Facet::Query("Trademrked".into())]
+        ).await.unwrap();
+
+        assert_projects_window(
+            get_projects_end_window(
+                &pool,
+                &facets,
+                SortBy::Relevance,
+                Direction::Ascending,
+                3
+            ).await,
+            &["test_game"]
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects"))]
+    async fn get_projects_query_count_name(pool: Pool) {
+        assert_eq!(query_count(&pool, "test_game").await, 1);
+    }
+
+    async fn add_napoleonic_tag(pool: &Pool) {
+        sqlx::query("INSERT INTO tags (tag_id, tag) VALUES (4, 'Napoleonic')")
+            .execute(pool)
+            .await
+            .unwrap();
+
+        update_project_tags(
+            pool,
+            Owner(1),
+            Project(42),
+            &["Napoleonic"],
+            &[],
+            1762897247000000001
+        ).await.unwrap();
+    }
+
+    #[sqlx::test(fixtures("users", "projects"))]
+    async fn get_projects_query_count_tag(pool: Pool) {
+        assert_eq!(query_count(&pool, "Napoleonic").await, 0);
+        add_napoleonic_tag(&pool).await;
+        assert_eq!(query_count(&pool, "Napoleonic").await, 1);
+    }
+
+    #[sqlx::test(fixtures("users", "projects"))]
+    async fn get_projects_query_count_tag_removed(pool: Pool) {
+        add_napoleonic_tag(&pool).await;
+
+        update_project_tags(
+            &pool,
+            Owner(1),
+            Project(42),
+            &[],
+            &["Napoleonic"],
+            1762897247000000002
+        ).await.unwrap();
+
+        assert_eq!(query_count(&pool, "Napoleonic").await, 0);
+    }
+
+    #[sqlx::test(fixtures("users", "projects"))]
+    async fn get_projects_query_count_tag_renamed(pool: Pool) {
+        add_napoleonic_tag(&pool).await;
+
+        sqlx::query("UPDATE tags SET tag = 'Revolutionary' WHERE tag_id = 4")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        assert_eq!(query_count(&pool, "Napoleonic").await, 0);
+        assert_eq!(query_count(&pool, "Revolutionary").await, 1);
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn get_projects_query_count_package(pool: Pool) {
+        assert_eq!(query_count(&pool, "b_package").await, 1);
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn get_projects_query_count_package_deleted(pool: Pool) {
+        // deletion must follow the creation of the package's releases
+        // and files
+        delete_package(
+            &pool,
+            Owner(1),
+            Project(42),
+            Package(2),
+            true,
+            1767312000000000000
+        ).await.unwrap();
+
+        assert_eq!(query_count(&pool, "b_package").await, 0);
+        assert_eq!(query_count(&pool, "c_package").await, 1);
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn get_projects_query_count_package_renamed(pool: Pool) {
+        update_package(
+            &pool,
+            Owner(1),
+            Project(42),
+            Package(2),
+            &PackageDataPatch {
+                name: Some("Scenario Pack".into()),
+                ..Default::default()
+            },
+            1767312000000000000
+        ).await.unwrap();
+
+        assert_eq!(query_count(&pool, "b_package").await, 0);
+        assert_eq!(query_count(&pool, "Scenario Pack").await, 1);
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn get_projects_query_count_package_project_deleted(pool: Pool) {
+        delete_project(&pool, User(1), Project(42), 1767312000000000000)
+            .await
+            .unwrap();
+        assert_eq!(query_count(&pool, "b_package").await, 0);
+
+        restore_project(&pool, Admin(1), Project(42), 1767312000000000001)
+            .await
+            .unwrap();
+        assert_eq!(query_count(&pool, "b_package").await, 1);
+    }
+
     #[sqlx::test(fixtures("users", "projects"))]
     async fn get_projects_count_ok(pool: Pool) {
         assert_eq!(get_projects_count(&pool, &[]).await.unwrap(), 2);
     }
 
+    #[sqlx::test(fixtures("users", "projects"))]
+    async fn get_projects_count_deleted(pool: Pool) {
+        delete_project(&pool, User(1), Project(6), 1702569006419538068)
+            .await
+            .unwrap();
+        assert_eq!(get_projects_count(&pool, &[]).await.unwrap(), 1);
+    }
+
     #[sqlx::test(fixtures("users", "projects"))]
     async fn get_projects_query_count_one(pool: Pool) {
         let facets = [
@@ -433,6 +1156,42 @@ mod test {
         assert_eq!(get_projects_count(&pool, &facets).await.unwrap(), 0);
     }
 
+    #[sqlx::test(fixtures("users", "projects"))]
+    async fn get_projects_query_count_deleted(pool: Pool) {
+        delete_project(&pool, User(1), Project(6), 1702569006419538068)
+            .await
+            .unwrap();
+        let facets = [
+            Facet::Query("Another".into())
+        ];
+        assert_eq!(get_projects_count(&pool, &facets).await.unwrap(), 0);
+    }
+
+    #[sqlx::test(fixtures("users", "projects"))]
+    async fn get_projects_query_count_restored(pool: Pool) {
+        delete_project(&pool, User(1), Project(6), 1702569006419538068)
+            .await
+            .unwrap();
+        restore_project(&pool, Admin(1), Project(6), 1702569006419538069)
+            .await
+            .unwrap();
+        let facets = [
+            Facet::Query("Another".into())
+        ];
+        assert_eq!(get_projects_count(&pool, &facets).await.unwrap(), 1);
+    }
+
+    #[sqlx::test(fixtures("users", "projects"))]
+    async fn get_projects_facet_count_deleted(pool: Pool) {
+        delete_project(&pool, User(1), Project(6), 1702569006419538068)
+            .await
+            .unwrap();
+        let facets = [
+            Facet::Publisher("XYZ".into())
+        ];
+        assert_eq!(get_projects_count(&pool, &facets).await.unwrap(), 0);
+    }
+
     #[sqlx::test(fixtures("users", "projects"))]
     async fn get_projects_facet_count_one(pool: Pool) {
         let facets = [
@@ -554,6 +1313,168 @@ mod test {
         assert_eq!(get_projects_count(&pool, &facets).await.unwrap(), 1);
     }
 
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn get_projects_facet_count_vassal(pool: Pool) {
+        let facets = [
+            Facet::Vassal("3.7.12".into())
+        ];
+        assert_eq!(get_projects_count(&pool, &facets).await.unwrap(), 1);
+    }
+
+    async fn yank_unrequiring_release(pool: &Pool) {
+        // c_package-0.1.0 has no requirement, so is always compatible
+        let rd = ReleaseDataPatch {
+            state: Some(ReleaseState::Yanked),
+            yank_reason: Some("broken".into()),
+            ..Default::default()
+        };
+
+        update_release(
+            pool,
+            Owner(1),
+            Project(42),
+            Release(3),
+            &rd,
+            1702655789180282478
+        ).await.unwrap();
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn get_projects_facet_count_vassal_minimum(pool: Pool) {
+        yank_unrequiring_release(&pool).await;
+        let facets = [
+            Facet::Vassal("3.2.17".into())
+        ];
+        assert_eq!(get_projects_count(&pool, &facets).await.unwrap(), 1);
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn get_projects_facet_count_vassal_too_old(pool: Pool) {
+        yank_unrequiring_release(&pool).await;
+        let facets = [
+            Facet::Vassal("3.2.16".into())
+        ];
+        assert_eq!(get_projects_count(&pool, &facets).await.unwrap(), 0);
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn get_projects_facet_count_vassal_pre_release(pool: Pool) {
+        yank_unrequiring_release(&pool).await;
+        let facets = [
+            Facet::Vassal("3.2.17-beta1".into())
+        ];
+        assert_eq!(get_projects_count(&pool, &facets).await.unwrap(), 0);
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn get_projects_facet_end_window_vassal(pool: Pool) {
+        yank_unrequiring_release(&pool).await;
+        assert_projects_window(
+            get_projects_end_window(
+                &pool,
+                &[ Facet::Vassal("3.7.12".into()) ],
+                SortBy::ProjectName,
+                Direction::Ascending,
+                5
+            ).await,
+            &["test_game"]
+        );
+    }
+
+    async fn set_players_and_length(pool: &Pool) {
+        sqlx::query(
+            "
+UPDATE projects
+SET
+    game_players_min = 2,
+    game_players_max = 4,
+    game_length_min = 60,
+    game_length_max = 90
+WHERE project_id = 6
+            "
+        )
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    fn length_buckets(counts: [i64; 6]) -> Vec<LengthBucket> {
+        LENGTH_BUCKETS.iter()
+            .zip(counts)
+            .map(|((min, max), count)| LengthBucket {
+                min: *min,
+                max: *max,
+                count
+            })
+            .collect()
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "tags"))]
+    async fn get_facet_counts_ok(pool: Pool) {
+        set_players_and_length(&pool).await;
+
+        assert_eq!(
+            get_facet_counts(&pool, &[]).await.unwrap(),
+            FacetCounts {
+                tags: [("a".into(), 2), ("b".into(), 1)].into(),
+                publishers: [
+                    ("Test Game Company".into(), 1),
+                    ("XYZ".into(), 1)
+                ].into(),
+                years: [("1979".into(), 1), ("1993".into(), 1)].into(),
+                players: [(2, 1), (3, 1), (4, 1)].into(),
+                lengths: length_buckets([0, 0, 1, 0, 0, 0])
+            }
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "tags"))]
+    async fn get_facet_counts_tag(pool: Pool) {
+        set_players_and_length(&pool).await;
+
+        assert_eq!(
+            get_facet_counts(&pool, &[ Facet::Tag("b".into()) ]).await.unwrap(),
+            FacetCounts {
+                tags: [("a".into(), 1), ("b".into(), 1)].into(),
+                publishers: [("XYZ".into(), 1)].into(),
+                years: [("1993".into(), 1)].into(),
+                players: [(2, 1), (3, 1), (4, 1)].into(),
+                lengths: length_buckets([0, 0, 1, 0, 0, 0])
+            }
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "tags"))]
+    async fn get_facet_counts_query(pool: Pool) {
+        set_players_and_length(&pool).await;
+
+        assert_eq!(
+            get_facet_counts(&pool, &[ Facet::Query("Trademarked".into()) ])
+                .await
+                .unwrap(),
+            FacetCounts {
+                tags: [("a".into(), 1)].into(),
+                publishers: [("Test Game Company".into(), 1)].into(),
+                years: [("1979".into(), 1)].into(),
+                players: BTreeMap::new(),
+                lengths: length_buckets([0; 6])
+            }
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects"))]
+    async fn get_facet_counts_deleted(pool: Pool) {
+        delete_project(&pool, User(1), Project(6), 1702569006419538068)
+            .await
+            .unwrap();
+
+        let counts = get_facet_counts(&pool, &[]).await.unwrap();
+        assert_eq!(
+            counts.publishers,
+            [("Test Game Company".into(), 1)].into()
+        );
+    }
+
     #[sqlx::test(fixtures("users", "projects", "tags", "two_owners", "players"))]
     async fn get_projects_facet_count_many(pool: Pool) {
         let facets = [
@@ -1374,4 +2295,203 @@ mod test {
             &["a"]
         );
     }
+
+    #[sqlx::test(fixtures("users", "proj_window", "proj_sort_window"))]
+    async fn get_projects_players_end_window_desc(pool: Pool) {
+        assert_projects_window(
+            get_projects_end_window(
+                &pool,
+                &[],
+                SortBy::Players,
+                Direction::Descending,
+                5
+            ).await,
+            &["b", "c", "a", "d"]
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "proj_window", "proj_sort_window"))]
+    async fn get_projects_players_end_window_asc(pool: Pool) {
+        assert_projects_window(
+            get_projects_end_window(
+                &pool,
+                &[],
+                SortBy::Players,
+                Direction::Ascending,
+                5
+            ).await,
+            &["d", "a", "c", "b"]
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "proj_window", "proj_sort_window"))]
+    async fn get_projects_players_mid_window_desc(pool: Pool) {
+        // ties on the count are broken by project id
+        assert_projects_window(
+            get_projects_mid_window(
+                &pool,
+                &[],
+                SortBy::Players,
+                Direction::Descending,
+                &1_i64,
+                3,
+                5
+            ).await,
+            &["a", "d"]
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "proj_window", "proj_sort_window"))]
+    async fn get_projects_players_mid_window_asc(pool: Pool) {
+        assert_projects_window(
+            get_projects_mid_window(
+                &pool,
+                &[],
+                SortBy::Players,
+                Direction::Ascending,
+                &1_i64,
+                1,
+                5
+            ).await,
+            &["c", "b"]
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "proj_window", "proj_sort_window"))]
+    async fn get_projects_downloads_end_window_desc(pool: Pool) {
+        assert_projects_window(
+            get_projects_end_window(
+                &pool,
+                &[],
+                SortBy::LatestDownloads,
+                Direction::Descending,
+                5
+            ).await,
+            &["d", "c", "a", "b"]
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "proj_window", "proj_sort_window"))]
+    async fn get_projects_downloads_mid_window_desc(pool: Pool) {
+        assert_projects_window(
+            get_projects_mid_window(
+                &pool,
+                &[],
+                SortBy::LatestDownloads,
+                Direction::Descending,
+                &5_i64,
+                3,
+                5
+            ).await,
+            &["a", "b"]
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "proj_window", "proj_sort_window"))]
+    async fn get_projects_release_time_end_window_desc(pool: Pool) {
+        // yanked releases are ignored; projects without releases are last
+        assert_projects_window(
+            get_projects_end_window(
+                &pool,
+                &[],
+                SortBy::ReleaseTime,
+                Direction::Descending,
+                5
+            ).await,
+            &["d", "b", "a", "c"]
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "proj_window", "proj_sort_window"))]
+    async fn get_projects_release_time_mid_window_desc(pool: Pool) {
+        assert_projects_window(
+            get_projects_mid_window(
+                &pool,
+                &[],
+                SortBy::ReleaseTime,
+                Direction::Descending,
+                &300_i64,
+                4,
+                5
+            ).await,
+            &["b", "a", "c"]
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "proj_window", "proj_sort_window"))]
+    async fn get_projects_release_time_facet_end_window_desc(pool: Pool) {
+        assert_projects_window(
+            get_projects_end_window(
+                &pool,
+                &[Facet::Player("bob".into())],
+                SortBy::ReleaseTime,
+                Direction::Descending,
+                5
+            ).await,
+            &["b", "a"]
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "proj_window", "proj_sort_window"))]
+    async fn get_projects_sort_fields(pool: Pool) {
+        let rows = get_projects_end_window(
+            &pool,
+            &[],
+            SortBy::ProjectName,
+            Direction::Ascending,
+            5
+        ).await.unwrap();
+
+        assert_eq!(
+            rows.iter()
+                .map(|r| (r.players, r.downloads, r.released_at))
+                .collect::<Vec<_>>(),
+            [(1, 5, 100), (2, 0, 300), (1, 5, 0), (0, 9, 300)]
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "proj_window", "proj_sort_window"))]
+    async fn get_projects_sort_fields_players_changed(pool: Pool) {
+        add_player(&pool, User(2), Project(4)).await.unwrap();
+        remove_player(&pool, User(1), Project(2)).await.unwrap();
+
+        let rows = get_projects_end_window(
+            &pool,
+            &[],
+            SortBy::ProjectName,
+            Direction::Ascending,
+            5
+        ).await.unwrap();
+
+        assert_eq!(
+            rows.iter().map(|r| r.players).collect::<Vec<_>>(),
+            [1, 1, 1, 1]
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "proj_window", "proj_sort_window"))]
+    async fn get_projects_sort_fields_release_changed(pool: Pool) {
+        // unyanking the newer release of d makes it the latest
+        let rd = ReleaseDataPatch {
+            state: Some(ReleaseState::Stable),
+            ..Default::default()
+        };
+
+        update_release(&pool, Owner(1), Project(4), Release(4), &rd, 600)
+            .await
+            .unwrap();
+
+        let rows = get_projects_end_window(
+            &pool,
+            &[],
+            SortBy::ProjectName,
+            Direction::Ascending,
+            5
+        ).await.unwrap();
+
+        assert_eq!(
+            rows.iter().map(|r| r.released_at).collect::<Vec<_>>(),
+            [100, 300, 0, 500]
+        );
+    }
 }
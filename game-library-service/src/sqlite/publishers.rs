@@ -1,10 +1,11 @@
 use sqlx::{
-    Executor, Transaction,
+    Acquire, Executor, Transaction,
     sqlite::Sqlite
 };
 
 use crate::{
-    db::{DatabaseError, map_unique}
+    db::{DatabaseError, PublisherRow, map_unique},
+    sqlite::like_prefix
 };
 
 pub async fn get_publishers<'e, E>(
@@ -41,6 +42,9 @@ SELECT DISTINCT publishers.name
 FROM publishers
 JOIN projects
 ON publishers.publisher_id = projects.game_publisher_id
+JOIN projects_history
+ON projects.project_id = projects_history.project_id
+WHERE projects_history.deleted_at IS NULL
 ORDER BY publishers.name COLLATE NOCASE
             "
         )
@@ -49,6 +53,69 @@ ORDER BY publishers.name COLLATE NOCASE
     )
 }
 
+pub async fn get_publishers_active_counts<'e, E>(
+    ex: E
+) -> Result<Vec<PublisherRow>, DatabaseError>
+where
+    E: Executor<'e, Database = Sqlite>
+{
+    // return publishers in use now, with their number of projects
+    Ok(
+        sqlx::query_as!(
+            PublisherRow,
+            "
+SELECT
+    publishers.name,
+    COUNT(projects.project_id) AS projects
+FROM publishers
+JOIN projects
+ON publishers.publisher_id = projects.game_publisher_id
+JOIN projects_history
+ON projects.project_id = projects_history.project_id
+WHERE projects_history.deleted_at IS NULL
+GROUP BY publishers.publisher_id
+ORDER BY publishers.name COLLATE NOCASE
+            "
+        )
+        .fetch_all(ex)
+        .await?
+    )
+}
+
+pub async fn get_publisher_suggestions<'e, E>(
+    ex: E,
+    prefix: &str,
+    limit: u32
+) -> Result<Vec<String>, DatabaseError>
+where
+    E: Executor<'e, Database = Sqlite>
+{
+    // publishers in use now, those with more projects first
+    let prefix = like_prefix(prefix);
+
+    Ok(
+        sqlx::query_scalar!(
+            "
+SELECT publishers.name
+FROM publishers
+JOIN projects
+ON publishers.publisher_id = projects.game_publisher_id
+JOIN projects_history
+ON projects.project_id = projects_history.project_id
+WHERE publishers.name LIKE ? ESCAPE '\\'
+    AND projects_history.deleted_at IS NULL
+GROUP BY publishers.publisher_id
+ORDER BY COUNT(1) DESC, publishers.name COLLATE NOCASE
+LIMIT ?
+            ",
+            prefix,
+            limit
+        )
+        .fetch_all(ex)
+        .await?
+    )
+}
+
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub struct Publisher(pub i64);
 
@@ -107,10 +174,187 @@ pub async fn get_or_create_publisher(
     }
 }
 
+async fn rename_publisher_row<'e, E>(
+    ex: E,
+    publisher: Publisher,
+    name: &str
+) -> Result<(), DatabaseError>
+where
+    E: Executor<'e, Database = Sqlite>
+{
+    sqlx::query!(
+        "
+UPDATE publishers
+SET name = ?
+WHERE publisher_id = ?
+        ",
+        name,
+        publisher.0
+    )
+    .execute(ex)
+    .await
+    .map_err(map_unique)?;
+
+    Ok(())
+}
+
+async fn update_projects_publisher_name<'e, E>(
+    ex: E,
+    publisher: Publisher,
+    name: &str
+) -> Result<(), DatabaseError>
+where
+    E: Executor<'e, Database = Sqlite>
+{
+    sqlx::query!(
+        "
+UPDATE projects
+SET game_publisher = ?
+WHERE game_publisher_id = ?
+        ",
+        name,
+        publisher.0
+    )
+    .execute(ex)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn rename_publisher<'a, A>(
+    conn: A,
+    publisher: &str,
+    name: &str
+) -> Result<(), DatabaseError>
+where
+    A: Acquire<'a, Database = Sqlite>
+{
+    let mut tx = conn.begin().await?;
+
+    let publisher = get_publisher_id(&mut *tx, publisher).await?
+        .ok_or(DatabaseError::NotFound)?;
+
+    // projects refer to publishers by name as well as by id, so the
+    // foreign key checks must wait until both have been updated
+    sqlx::query!("PRAGMA defer_foreign_keys = ON")
+        .execute(&mut *tx)
+        .await?;
+
+    rename_publisher_row(&mut *tx, publisher, name).await?;
+    update_projects_publisher_name(&mut *tx, publisher, name).await?;
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+async fn merge_projects_data_publisher<'e, E>(
+    ex: E,
+    src: Publisher,
+    dst: Publisher
+) -> Result<(), DatabaseError>
+where
+    E: Executor<'e, Database = Sqlite>
+{
+    sqlx::query!(
+        "
+UPDATE projects_data
+SET game_publisher_id = ?
+WHERE game_publisher_id = ?
+        ",
+        dst.0,
+        src.0
+    )
+    .execute(ex)
+    .await?;
+
+    Ok(())
+}
+
+async fn merge_projects_publisher<'e, E>(
+    ex: E,
+    src: Publisher,
+    dst: Publisher
+) -> Result<(), DatabaseError>
+where
+    E: Executor<'e, Database = Sqlite>
+{
+    sqlx::query!(
+        "
+UPDATE projects
+SET
+    game_publisher_id = publishers.publisher_id,
+    game_publisher = publishers.name
+FROM publishers
+WHERE projects.game_publisher_id = ?
+    AND publishers.publisher_id = ?
+        ",
+        src.0,
+        dst.0
+    )
+    .execute(ex)
+    .await?;
+
+    Ok(())
+}
+
+async fn delete_publisher_row<'e, E>(
+    ex: E,
+    publisher: Publisher
+) -> Result<(), DatabaseError>
+where
+    E: Executor<'e, Database = Sqlite>
+{
+    sqlx::query!(
+        "
+DELETE FROM publishers
+WHERE publisher_id = ?
+        ",
+        publisher.0
+    )
+    .execute(ex)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn merge_publishers<'a, A>(
+    conn: A,
+    src: &str,
+    dst: &str
+) -> Result<(), DatabaseError>
+where
+    A: Acquire<'a, Database = Sqlite>
+{
+    let mut tx = conn.begin().await?;
+
+    let src = get_publisher_id(&mut *tx, src).await?
+        .ok_or(DatabaseError::NotFound)?;
+
+    let dst = get_publisher_id(&mut *tx, dst).await?
+        .ok_or(DatabaseError::NotFound)?;
+
+    // move all project revisions and projects to the destination
+    merge_projects_data_publisher(&mut *tx, src, dst).await?;
+    merge_projects_publisher(&mut *tx, src, dst).await?;
+
+    // the source publisher is now unused
+    delete_publisher_row(&mut *tx, src).await?;
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
 
+    use crate::{
+        model::{Project, User},
+        sqlite::project::delete_project
+    };
+
     type Pool = sqlx::Pool<Sqlite>;
 
     #[sqlx::test(fixtures("users", "projects"))]
@@ -145,6 +389,175 @@ mod test {
         );
     }
 
+    #[sqlx::test(fixtures("users", "projects"))]
+    async fn get_publishers_active_deleted(pool: Pool) {
+        delete_project(&pool, User(1), Project(6), 1702569006419538068)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            get_publishers_active(&pool).await.unwrap(),
+            ["Test Game Company".to_string()]
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects"))]
+    async fn get_publisher_suggestions_ok(pool: Pool) {
+        assert_eq!(
+            get_publisher_suggestions(&pool, "test", 10).await.unwrap(),
+            ["Test Game Company".to_string()]
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects"))]
+    async fn get_publisher_suggestions_unused(pool: Pool) {
+        assert_eq!(
+            get_publisher_suggestions(&pool, "ABC", 10).await.unwrap(),
+            [] as [String; 0]
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects"))]
+    async fn get_publisher_suggestions_wildcard(pool: Pool) {
+        assert_eq!(
+            get_publisher_suggestions(&pool, "X_Z", 10).await.unwrap(),
+            [] as [String; 0]
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects"))]
+    async fn get_publisher_suggestions_deleted(pool: Pool) {
+        delete_project(&pool, User(1), Project(6), 1702569006419538068)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            get_publisher_suggestions(&pool, "X", 10).await.unwrap(),
+            [] as [String; 0]
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects"))]
+    async fn get_publishers_active_counts_ok(pool: Pool) {
+        assert_eq!(
+            get_publishers_active_counts(&pool).await.unwrap(),
+            [
+                PublisherRow {
+                    name: "Test Game Company".into(),
+                    projects: 1
+                },
+                PublisherRow {
+                    name: "XYZ".into(),
+                    projects: 1
+                }
+            ]
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects"))]
+    async fn get_publishers_active_counts_deleted(pool: Pool) {
+        delete_project(&pool, User(1), Project(6), 1702569006419538068)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            get_publishers_active_counts(&pool).await.unwrap(),
+            [
+                PublisherRow {
+                    name: "Test Game Company".into(),
+                    projects: 1
+                }
+            ]
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects"))]
+    async fn rename_publisher_ok(pool: Pool) {
+        rename_publisher(&pool, "XYZ", "XYZ Games").await.unwrap();
+
+        assert_eq!(
+            get_publishers(&pool).await.unwrap(),
+            ["ABC".to_string(), "Test Game Company".into(), "XYZ Games".into()]
+        );
+
+        assert_eq!(
+            get_publisher_id(&pool, "XYZ Games").await.unwrap(),
+            Some(Publisher(2))
+        );
+
+        assert_eq!(
+            sqlx::query_scalar!(
+                "SELECT game_publisher FROM projects WHERE project_id = 6"
+            )
+            .fetch_one(&pool)
+            .await
+            .unwrap(),
+            "XYZ Games"
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects"))]
+    async fn rename_publisher_already_exists(pool: Pool) {
+        assert_eq!(
+            rename_publisher(&pool, "XYZ", "ABC").await.unwrap_err(),
+            DatabaseError::AlreadyExists
+        );
+
+        assert_eq!(
+            get_publishers(&pool).await.unwrap(),
+            ["ABC".to_string(), "Test Game Company".into(), "XYZ".into()]
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects"))]
+    async fn rename_publisher_not_found(pool: Pool) {
+        assert_eq!(
+            rename_publisher(&pool, "bogus", "ABC").await.unwrap_err(),
+            DatabaseError::NotFound
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects"))]
+    async fn merge_publishers_ok(pool: Pool) {
+        merge_publishers(&pool, "XYZ", "ABC").await.unwrap();
+
+        assert_eq!(
+            get_publishers(&pool).await.unwrap(),
+            ["ABC".to_string(), "Test Game Company".into()]
+        );
+
+        assert_eq!(
+            get_publishers_active(&pool).await.unwrap(),
+            ["ABC".to_string(), "Test Game Company".into()]
+        );
+
+        assert_eq!(
+            sqlx::query_scalar!(
+                "SELECT game_publisher FROM projects WHERE project_id = 6"
+            )
+            .fetch_one(&pool)
+            .await
+            .unwrap(),
+            "ABC"
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects"))]
+    async fn merge_publishers_src_not_found(pool: Pool) {
+        assert_eq!(
+            merge_publishers(&pool, "bogus", "ABC").await.unwrap_err(),
+            DatabaseError::NotFound
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects"))]
+    async fn merge_publishers_dst_not_found(pool: Pool) {
+        assert_eq!(
+            merge_publishers(&pool, "XYZ", "bogus").await.unwrap_err(),
+            DatabaseError::NotFound
+        );
+    }
+
     #[sqlx::test]
     async fn create_publisher_ok(pool: Pool) {
         assert_eq!(
@@ -1,15 +1,17 @@
 use sqlx::{
-    Acquire, Executor,
+    Acquire, Executor, QueryBuilder, Transaction,
     sqlite::Sqlite
 };
 use std::cmp::Ordering;
 
 use crate::{
-    db::{DatabaseError, FileRow, ReleaseRow, map_unique},
+    db::{DatabaseError, FileModuleItemRow, FileRow, ModuleTarget, ReleaseRow, SharedFileRow, map_unique},
     model::{Owner, Package, Project, Release},
+    input::{GameDataPatch, ProjectDataPatch, RangePatch, ReleaseDataPatch, ReleaseDataPost},
+    module::ModuleMetadata,
     sqlite::{
         require_one_modified,
-        project::update_project_non_project_data
+        project::{get_project_row, update_project_in_trans, update_project_non_project_data}
     },
     version::Version
 };
@@ -64,7 +66,7 @@ where
 {
     let mut releases = sqlx::query_as!(
         ReleaseRow,
-        "
+        r#"
 SELECT
     releases.release_id,
     releases.version,
@@ -74,7 +76,11 @@ SELECT
     releases.version_pre,
     releases.version_build,
     releases.published_at,
-    users.username AS published_by
+    users.username AS published_by,
+    releases.changelog,
+    releases.released_at AS "released_at!",
+    releases.state,
+    releases.yank_reason
 FROM releases
 JOIN users
 ON releases.published_by = users.user_id
@@ -85,7 +91,7 @@ ORDER BY
     releases.version_patch DESC,
     releases.version_pre ASC,
     releases.version_build ASC
-        ",
+        "#,
         pkg.0
     )
     .fetch_all(ex)
@@ -105,7 +111,7 @@ where
 {
     let mut releases = sqlx::query_as!(
         ReleaseRow,
-        "
+        r#"
 SELECT
     releases_history.release_id,
     releases_history.version,
@@ -115,25 +121,38 @@ SELECT
     releases_history.version_pre,
     releases_history.version_build,
     releases_history.published_at,
-    users.username AS published_by
+    users.username AS published_by,
+    releases_revisions.changelog,
+    releases_revisions.released_at AS "released_at!",
+    releases_revisions.state,
+    releases_revisions.yank_reason
 FROM releases_history
 JOIN users
 ON releases_history.published_by = users.user_id
+JOIN releases_revisions
+ON releases_history.release_id = releases_revisions.release_id
 WHERE releases_history.package_id = ?
     AND releases_history.published_at <= ?
     AND (
         ? < releases_history.deleted_at OR
         releases_history.deleted_at IS NULL
     )
+    AND releases_revisions.modified_at = (
+        SELECT MAX(r.modified_at)
+        FROM releases_revisions AS r
+        WHERE r.release_id = releases_history.release_id
+            AND r.modified_at <= ?
+    )
 ORDER BY
     releases_history.version_major DESC,
     releases_history.version_minor DESC,
     releases_history.version_patch DESC,
     releases_history.version_pre ASC,
     releases_history.version_build ASC
-        ",
+        "#,
         pkg.0,
         date,
+        date,
         date
     )
     .fetch_all(ex)
@@ -185,11 +204,14 @@ SELECT packages.project_id,
     packages.package_id,
     releases.release_id
 FROM projects
+JOIN projects_history
+ON projects.project_id = projects_history.project_id
 JOIN packages
 ON projects.project_id = packages.project_id
 JOIN releases
 ON packages.package_id = releases.package_id
 WHERE projects.slug = ?
+    AND projects_history.deleted_at IS NULL
     AND packages.slug = ?
     AND releases.version = ?
             ",
@@ -259,6 +281,7 @@ pub async fn create_release<'a, A>(
     proj: Project,
     pkg: Package,
     version: &Version,
+    rel_data: &ReleaseDataPost,
     now: i64
 ) -> Result<(), DatabaseError>
 where
@@ -275,6 +298,19 @@ where
         now
     ).await?;
 
+    // the first revision is created with the release, so any changelog
+    // or release date given replaces it
+    if rel_data.changelog.is_some() || rel_data.released_at.is_some() {
+        let rd = ReleaseDataPatch {
+            changelog: rel_data.changelog.clone().map(Some),
+            released_at: rel_data.released_at,
+            ..Default::default()
+        };
+
+        update_release_row(&mut *tx, rel, &rd).await?;
+        update_release_revision_row(&mut *tx, owner, rel, now).await?;
+    }
+
     // update project to reflect the change
     update_project_non_project_data(&mut tx, owner, proj, now).await?;
 
@@ -321,7 +357,7 @@ where
     Ok(
         sqlx::query_as!(
             FileRow,
-            "
+            r#"
 SELECT
     files.file_id AS id,
     files.url,
@@ -330,14 +366,32 @@ SELECT
     files.sha256,
     files.requires,
     files.published_at,
-    users.username AS published_by
+    users.username AS published_by,
+    files_modules.name AS module_name,
+    files_modules.target_module,
+    files_modules.target_module_version,
+    target_projects.slug AS "target_project?",
+    target_releases.version AS "target_release?"
 FROM files
 JOIN users
 ON files.published_by = users.user_id
+LEFT JOIN files_modules
+ON files.file_id = files_modules.file_id
+LEFT JOIN projects AS target_projects
+ON files_modules.target_project_id = target_projects.project_id
+    AND NOT EXISTS (
+        SELECT 1
+        FROM projects_history
+        WHERE projects_history.project_id = target_projects.project_id
+            AND projects_history.deleted_at IS NOT NULL
+    )
+LEFT JOIN releases AS target_releases
+ON files_modules.target_release_id = target_releases.release_id
+    AND target_releases.state != 'yanked'
 WHERE files.release_id = ?
 ORDER BY
     files.filename ASC
-            ",
+            "#,
             release.0
         )
         .fetch_all(ex)
@@ -356,25 +410,48 @@ where
     Ok(
         sqlx::query_as!(
             FileRow,
-            "
+            r#"
 SELECT
-    files.file_id AS id,
-    files.url,
-    files.filename,
-    files.size,
-    files.sha256,
-    files.requires,
-    files.published_at,
-    users.username AS published_by
-FROM files
+    files_history.file_id AS id,
+    files_history.url,
+    files_history.filename,
+    files_history.size,
+    files_history.sha256,
+    files_history.requires,
+    files_history.published_at,
+    users.username AS published_by,
+    files_modules.name AS module_name,
+    files_modules.target_module,
+    files_modules.target_module_version,
+    target_projects.slug AS "target_project?",
+    target_releases.version AS "target_release?"
+FROM files_history
 JOIN users
-ON files.published_by = users.user_id
-WHERE files.release_id = ?
-    AND files.published_at <= ?
+ON files_history.published_by = users.user_id
+LEFT JOIN files_modules
+ON files_history.file_id = files_modules.file_id
+LEFT JOIN projects AS target_projects
+ON files_modules.target_project_id = target_projects.project_id
+    AND NOT EXISTS (
+        SELECT 1
+        FROM projects_history
+        WHERE projects_history.project_id = target_projects.project_id
+            AND projects_history.deleted_at IS NOT NULL
+    )
+LEFT JOIN releases AS target_releases
+ON files_modules.target_release_id = target_releases.release_id
+    AND target_releases.state != 'yanked'
+WHERE files_history.release_id = ?
+    AND files_history.published_at <= ?
+    AND (
+        ? < files_history.deleted_at OR
+        files_history.deleted_at IS NULL
+    )
 ORDER BY
-    files.filename ASC
-            ",
+    files_history.filename ASC
+            "#,
             release.0,
+            date,
             date
         )
         .fetch_all(ex)
@@ -391,16 +468,23 @@ async fn create_file_row<'e, E>(
     size: i64,
     sha256: &str,
     content_type: &str,
-    requires: Option<&str>,
+    requires: Option<&Version>,
     url: &str,
     now: i64
-) -> Result<(), DatabaseError>
+) -> Result<i64, DatabaseError>
 where
     E: Executor<'e, Database = Sqlite>
 {
-    sqlx::query!(
+    // the requirement is kept as text for display and as its parts
+    // for comparison
+    let requires_text = requires.map(|r| format!(">= {r}"));
+    let requires_major = requires.map(|r| r.major);
+    let requires_minor = requires.map(|r| r.minor);
+    let requires_patch = requires.map(|r| r.patch);
+
+    sqlx::query_scalar!(
         "
-INSERT INTO files (
+INSERT INTO files_history (
     release_id,
     url,
     filename,
@@ -408,10 +492,14 @@ INSERT INTO files (
     sha256,
     content_type,
     requires,
+    requires_major,
+    requires_minor,
+    requires_patch,
     published_at,
     published_by
 )
-VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+RETURNING file_id
         ",
         release.0,
         url,
@@ -419,17 +507,380 @@ VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
         size,
         sha256,
         content_type,
-        requires,
+        requires_text,
+        requires_major,
+        requires_minor,
+        requires_patch,
         now,
         owner.0
     )
-    .execute(ex)
+    .fetch_one(ex)
     .await
-    .map_err(map_unique)?;
+    .map_err(map_unique)
+}
+
+async fn create_file_module_row<'e, E>(
+    ex: E,
+    file_id: i64,
+    module: &ModuleMetadata,
+    target: Option<&ModuleTarget>
+) -> Result<(), DatabaseError>
+where
+    E: Executor<'e, Database = Sqlite>
+{
+    let target_project_id = target.map(|t| t.project.0);
+    let target_release_id = target.and_then(|t| t.release).map(|r| r.0);
+
+    sqlx::query!(
+        "
+INSERT INTO files_modules (
+    file_id,
+    name,
+    description,
+    date_saved,
+    target_module,
+    target_module_version,
+    target_project_id,
+    target_release_id
+)
+VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+        ",
+        file_id,
+        module.name,
+        module.description,
+        module.date_saved,
+        module.target_module,
+        module.target_module_version,
+        target_project_id,
+        target_release_id
+    )
+    .execute(ex)
+    .await?;
+
+    Ok(())
+}
+
+async fn create_file_module_item_rows(
+    tx: &mut Transaction<'_, Sqlite>,
+    file_id: i64,
+    kind: &str,
+    values: &[String]
+) -> Result<(), DatabaseError>
+{
+    for (position, value) in values.iter().enumerate() {
+        let position = position as i64;
+        sqlx::query!(
+            "
+INSERT INTO files_modules_items (
+    file_id,
+    kind,
+    position,
+    value
+)
+VALUES (?, ?, ?, ?)
+            ",
+            file_id,
+            kind,
+            position,
+            value
+        )
+        .execute(&mut **tx)
+        .await?;
+    }
 
     Ok(())
 }
 
+pub async fn get_file_module_items<'e, E>(
+    ex: E,
+    file_id: i64,
+    kind: &str
+) -> Result<Vec<String>, DatabaseError>
+where
+    E: Executor<'e, Database = Sqlite>
+{
+    Ok(
+        sqlx::query_scalar!(
+            "
+SELECT value
+FROM files_modules_items
+WHERE file_id = ?
+    AND kind = ?
+ORDER BY position
+            ",
+            file_id,
+            kind
+        )
+        .fetch_all(ex)
+        .await?
+    )
+}
+
+// a release is usable with a Vassal version if it is unyanked and has
+// files, none of which require a later Vassal version; the comparison and
+// the version are pushed after this
+pub const VASSAL_COMPATIBLE_RELEASE: &str = "
+releases.state != 'yanked'
+    AND EXISTS (
+        SELECT 1
+        FROM files
+        WHERE files.release_id = releases.release_id
+    )
+    AND NOT EXISTS (
+        SELECT 1
+        FROM files
+        WHERE files.release_id = releases.release_id
+            AND (
+                files.requires_major,
+                files.requires_minor,
+                files.requires_patch
+            ) ";
+
+pub fn vassal_requires_later(vassal: &Version) -> &'static str {
+    // a pre-release precedes its release version
+    match vassal.pre {
+        Some(_) => ">= (",
+        None => "> ("
+    }
+}
+
+pub async fn get_vassal_compatible_releases<'e, E>(
+    ex: E,
+    proj: Project,
+    vassal: &Version
+) -> Result<Vec<i64>, DatabaseError>
+where
+    E: Executor<'e, Database = Sqlite>
+{
+    let mut qb = QueryBuilder::new(
+        "
+SELECT releases.release_id
+FROM packages
+JOIN releases
+ON packages.package_id = releases.package_id
+WHERE packages.project_id = "
+    );
+
+    qb.push_bind(proj.0)
+        .push(" AND ")
+        .push(VASSAL_COMPATIBLE_RELEASE)
+        .push(vassal_requires_later(vassal))
+        .push_bind(vassal.major)
+        .push(", ")
+        .push_bind(vassal.minor)
+        .push(", ")
+        .push_bind(vassal.patch)
+        .push("))
+ORDER BY releases.release_id");
+
+    Ok(
+        qb.build_query_scalar()
+            .fetch_all(ex)
+            .await?
+    )
+}
+
+pub async fn get_release_module_items<'e, E>(
+    ex: E,
+    rel: Release
+) -> Result<Vec<FileModuleItemRow>, DatabaseError>
+where
+    E: Executor<'e, Database = Sqlite>
+{
+    Ok(
+        sqlx::query_as!(
+            FileModuleItemRow,
+            "
+SELECT
+    files_modules_items.file_id,
+    files_modules_items.kind,
+    files_modules_items.value
+FROM files_modules_items
+JOIN files_history
+ON files_modules_items.file_id = files_history.file_id
+WHERE files_history.release_id = ?
+ORDER BY
+    files_modules_items.file_id,
+    files_modules_items.kind,
+    files_modules_items.position
+            ",
+            rel.0
+        )
+        .fetch_all(ex)
+        .await?
+    )
+}
+
+pub async fn get_file_url_by_hash<'e, E>(
+    ex: E,
+    sha256: &str,
+    size: i64
+) -> Result<Option<String>, DatabaseError>
+where
+    E: Executor<'e, Database = Sqlite>
+{
+    Ok(
+        sqlx::query_scalar!(
+            "
+SELECT url
+FROM files
+WHERE sha256 = ?
+    AND size = ?
+ORDER BY file_id ASC
+LIMIT 1
+            ",
+            sha256,
+            size
+        )
+        .fetch_optional(ex)
+        .await?
+    )
+}
+
+pub async fn get_files_by_hash<'e, E>(
+    ex: E,
+    sha256: &str
+) -> Result<Vec<SharedFileRow>, DatabaseError>
+where
+    E: Executor<'e, Database = Sqlite>
+{
+    Ok(
+        sqlx::query_as!(
+            SharedFileRow,
+            "
+SELECT
+    projects.name AS project,
+    projects.slug,
+    packages.name AS package,
+    releases.version,
+    files.filename,
+    files.url,
+    files.size,
+    files.published_at,
+    users.username AS published_by
+FROM files
+JOIN releases
+ON files.release_id = releases.release_id
+JOIN packages
+ON releases.package_id = packages.package_id
+JOIN projects
+ON packages.project_id = projects.project_id
+JOIN users
+ON files.published_by = users.user_id
+WHERE files.sha256 = ?
+ORDER BY files.published_at, files.file_id
+            ",
+            sha256
+        )
+        .fetch_all(ex)
+        .await?
+    )
+}
+
+pub async fn get_module_project<'e, E>(
+    ex: E,
+    proj: Project,
+    module: &str
+) -> Result<Option<Project>, DatabaseError>
+where
+    E: Executor<'e, Database = Sqlite>
+{
+    // prefer a module in the same project, then the oldest other one
+    Ok(
+        sqlx::query_scalar!(
+            "
+SELECT projects.project_id
+FROM files_modules
+JOIN files
+ON files_modules.file_id = files.file_id
+JOIN releases
+ON files.release_id = releases.release_id
+JOIN packages
+ON releases.package_id = packages.package_id
+JOIN projects
+ON packages.project_id = projects.project_id
+JOIN projects_history
+ON projects.project_id = projects_history.project_id
+WHERE files_modules.name = ?
+    AND files_modules.target_module IS NULL
+    AND projects_history.deleted_at IS NULL
+    AND files.filename LIKE '%.vmod'
+ORDER BY
+    projects.project_id = ? DESC,
+    projects.created_at ASC,
+    projects.project_id ASC
+LIMIT 1
+            ",
+            module,
+            proj.0
+        )
+        .fetch_optional(ex)
+        .await?
+        .map(Project)
+    )
+}
+
+struct ModuleReleaseRow {
+    release_id: i64,
+    version_major: i64,
+    version_minor: i64,
+    version_patch: i64,
+    version_pre: String,
+    version_build: String
+}
+
+pub async fn get_module_releases<'e, E>(
+    ex: E,
+    proj: Project,
+    module: &str
+) -> Result<Vec<(Release, Version)>, DatabaseError>
+where
+    E: Executor<'e, Database = Sqlite>
+{
+    Ok(
+        sqlx::query_as!(
+            ModuleReleaseRow,
+            "
+SELECT DISTINCT
+    releases.release_id,
+    releases.version_major,
+    releases.version_minor,
+    releases.version_patch,
+    releases.version_pre,
+    releases.version_build
+FROM files_modules
+JOIN files
+ON files_modules.file_id = files.file_id
+JOIN releases
+ON files.release_id = releases.release_id
+JOIN packages
+ON releases.package_id = packages.package_id
+WHERE packages.project_id = ?
+    AND files_modules.name = ?
+    AND files_modules.target_module IS NULL
+    AND files.filename LIKE '%.vmod'
+    AND releases.state != 'yanked'
+            ",
+            proj.0,
+            module
+        )
+        .fetch_all(ex)
+        .await?
+        .into_iter()
+        .map(|r| (
+            Release(r.release_id),
+            Version {
+                major: r.version_major,
+                minor: r.version_minor,
+                patch: r.version_patch,
+                pre: Some(r.version_pre).filter(|v| !v.is_empty()),
+                build: Some(r.version_build).filter(|v| !v.is_empty())
+            }
+        ))
+        .collect()
+    )
+}
+
 async fn retire_release_history_row<'e, E>(
     ex: E,
     owner: Owner,
@@ -457,11 +908,98 @@ WHERE release_id = ?
     .and_then(require_one_modified)
 }
 
-pub async fn delete_release<'a, A>(
+async fn update_release_row<'e, E>(
+    ex: E,
+    rel: Release,
+    rd: &ReleaseDataPatch
+) -> Result<(), DatabaseError>
+where
+    E: Executor<'e, Database = Sqlite>
+{
+    let mut qb: QueryBuilder<E::Database> = QueryBuilder::new(
+        "UPDATE releases SET "
+    );
+
+    let mut qbs = qb.separated(", ");
+
+    if let Some(changelog) = &rd.changelog {
+        qbs.push("changelog = ").push_bind_unseparated(changelog);
+    }
+
+    if let Some(released_at) = &rd.released_at {
+        qbs.push("released_at = ").push_bind_unseparated(released_at);
+    }
+
+    if let Some(state) = &rd.state {
+        // setting the state always replaces the yank reason
+        qbs.push("state = ").push_bind_unseparated(state.as_str());
+        qbs.push("yank_reason = ").push_bind_unseparated(&rd.yank_reason);
+    }
+
+    qb
+        .push(" WHERE release_id = ")
+        .push_bind(rel.0)
+        .build()
+        .execute(ex)
+        .await
+        .map_err(DatabaseError::from)
+        .and_then(require_one_modified)
+}
+
+async fn update_release_revision_row<'e, E>(
+    ex: E,
+    owner: Owner,
+    rel: Release,
+    now: i64
+) -> Result<(), DatabaseError>
+where
+    E: Executor<'e, Database = Sqlite>
+{
+    sqlx::query!(
+        "
+INSERT INTO releases_revisions (
+    release_id,
+    changelog,
+    released_at,
+    state,
+    yank_reason,
+    modified_at,
+    modified_by
+)
+SELECT
+    release_id,
+    changelog,
+    released_at,
+    state,
+    yank_reason,
+    ? AS modified_at,
+    ? AS modified_by
+FROM releases
+WHERE release_id = ?
+ON CONFLICT(release_id, modified_at) DO UPDATE
+SET
+    changelog = excluded.changelog,
+    released_at = excluded.released_at,
+    state = excluded.state,
+    yank_reason = excluded.yank_reason,
+    modified_by = excluded.modified_by
+        ",
+        now,
+        owner.0,
+        rel.0
+    )
+    .execute(ex)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn update_release<'a, A>(
     conn: A,
     owner: Owner,
     proj: Project,
     rel: Release,
+    rel_data: &ReleaseDataPatch,
     now: i64
 ) -> Result<(), DatabaseError>
 where
@@ -469,10 +1007,8 @@ where
 {
     let mut tx = conn.begin().await?;
 
-    // delete release row
-    retire_release_history_row(&mut *tx, owner, rel, now).await?;
-
-    // update project to reflect the change
+    update_release_row(&mut *tx, rel, rel_data).await?;
+    update_release_revision_row(&mut *tx, owner, rel, now).await?;
     update_project_non_project_data(&mut tx, owner, proj, now).await?;
 
     tx.commit().await?;
@@ -480,7 +1016,179 @@ where
     Ok(())
 }
 
-#[allow(clippy::too_many_arguments)]
+pub async fn retire_release_file_history_rows<'e, E>(
+    ex: E,
+    owner: Owner,
+    rel: Release,
+    now: i64
+) -> Result<(), DatabaseError>
+where
+    E: Executor<'e, Database = Sqlite>
+{
+    sqlx::query!(
+        "
+UPDATE files_history
+SET
+    deleted_by = ?,
+    deleted_at = ?
+WHERE release_id = ?
+    AND deleted_at IS NULL
+        ",
+        owner.0,
+        now,
+        rel.0
+    )
+    .execute(ex)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn retire_package_release_history_rows<'e, E>(
+    ex: E,
+    owner: Owner,
+    pkg: Package,
+    now: i64
+) -> Result<(), DatabaseError>
+where
+    E: Executor<'e, Database = Sqlite>
+{
+    sqlx::query!(
+        "
+UPDATE releases_history
+SET
+    deleted_by = ?,
+    deleted_at = ?
+WHERE package_id = ?
+    AND deleted_at IS NULL
+        ",
+        owner.0,
+        now,
+        pkg.0
+    )
+    .execute(ex)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn retire_package_file_history_rows<'e, E>(
+    ex: E,
+    owner: Owner,
+    pkg: Package,
+    now: i64
+) -> Result<(), DatabaseError>
+where
+    E: Executor<'e, Database = Sqlite>
+{
+    sqlx::query!(
+        "
+UPDATE files_history
+SET
+    deleted_by = ?,
+    deleted_at = ?
+WHERE release_id IN (
+    SELECT release_id
+    FROM releases
+    WHERE package_id = ?
+)
+    AND deleted_at IS NULL
+        ",
+        owner.0,
+        now,
+        pkg.0
+    )
+    .execute(ex)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn delete_release<'a, A>(
+    conn: A,
+    owner: Owner,
+    proj: Project,
+    rel: Release,
+    cascade: bool,
+    now: i64
+) -> Result<(), DatabaseError>
+where
+    A: Acquire<'a, Database = Sqlite>
+{
+    let mut tx = conn.begin().await?;
+
+    if cascade {
+        // delete file rows
+        retire_release_file_history_rows(&mut *tx, owner, rel, now).await?;
+    }
+
+    // delete release row
+    retire_release_history_row(&mut *tx, owner, rel, now).await?;
+
+    // update project to reflect the change
+    update_project_non_project_data(&mut tx, owner, proj, now).await?;
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+async fn retire_file_history_row<'e, E>(
+    ex: E,
+    owner: Owner,
+    release: Release,
+    filename: &str,
+    now: i64
+) -> Result<(), DatabaseError>
+where
+    E: Executor<'e, Database = Sqlite>
+{
+    sqlx::query!(
+        "
+UPDATE files_history
+SET
+    deleted_by = ?,
+    deleted_at = ?
+WHERE release_id = ?
+    AND filename = ?
+    AND deleted_at IS NULL
+        ",
+        owner.0,
+        now,
+        release.0,
+        filename
+    )
+    .execute(ex)
+    .await
+    .map_err(DatabaseError::from)
+    .and_then(require_one_modified)
+}
+
+pub async fn delete_file<'a, A>(
+    conn: A,
+    owner: Owner,
+    proj: Project,
+    release: Release,
+    filename: &str,
+    now: i64
+) -> Result<(), DatabaseError>
+where
+    A: Acquire<'a, Database = Sqlite>
+{
+    let mut tx = conn.begin().await?;
+
+    // delete file row
+    retire_file_history_row(&mut *tx, owner, release, filename, now).await?;
+
+    // update project to reflect the change
+    update_project_non_project_data(&mut tx, owner, proj, now).await?;
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
 pub async fn add_file_url<'a, A>(
     conn: A,
     owner: Owner,
@@ -490,7 +1198,10 @@ pub async fn add_file_url<'a, A>(
     size: i64,
     sha256: &str,
     content_type: &str,
-    requires: Option<&str>,
+    requires: Option<&Version>,
+    module: Option<&ModuleMetadata>,
+    target: Option<&ModuleTarget>,
+    players: Option<u32>,
     url: &str,
     now: i64
 ) -> Result<(), DatabaseError>
@@ -500,7 +1211,7 @@ where
     let mut tx = conn.begin().await?;
 
     // insert file row
-    create_file_row(
+    let file_id = create_file_row(
         &mut *tx,
         owner,
         release,
@@ -513,8 +1224,44 @@ where
         now
     ).await?;
 
+    // insert module metadata rows
+    if let Some(module) = module {
+        create_file_module_row(&mut *tx, file_id, module, target).await?;
+
+        for (kind, values) in [
+            ("extension", &module.extensions),
+            ("palette", &module.palettes),
+            ("map", &module.maps),
+            ("side", &module.sides)
+        ] {
+            create_file_module_item_rows(&mut tx, file_id, kind, values)
+                .await?;
+        }
+    }
+
     // update project to reflect the change
-    update_project_non_project_data(&mut tx, owner, proj, now).await?;
+    let row = get_project_row(&mut *tx, proj).await?;
+    match players {
+        // fill in the number of players if the project has none
+        Some(players) if
+            row.game_players_min.is_none() &&
+            row.game_players_max.is_none() =>
+        {
+            let pd = ProjectDataPatch {
+                game: GameDataPatch {
+                    players: RangePatch {
+                        min: Some(Some(players)),
+                        max: Some(Some(players))
+                    },
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+
+            update_project_in_trans(&mut tx, owner, proj, &pd, now).await?;
+        },
+        _ => update_project_non_project_data(&mut tx, owner, proj, now).await?
+    }
 
     tx.commit().await?;
 
@@ -525,9 +1272,13 @@ where
 mod test {
     use super::*;
 
+    use glc::model::ReleaseState;
     use std::{slice, sync::LazyLock};
 
-    use crate::sqlite::project::get_project_row;
+    use crate::{
+        model::User,
+        sqlite::project::{delete_project, update_project}
+    };
 
     type Pool = sqlx::Pool<Sqlite>;
 
@@ -541,7 +1292,11 @@ mod test {
             version_pre: "".into(),
             version_build: "".into(),
             published_at: 1702137389180282477,
-            published_by: "bob".into()
+            published_by: "bob".into(),
+            changelog: None,
+            released_at: 1702137389180282477,
+            state: "stable".into(),
+            yank_reason: None
         }
     );
 
@@ -568,7 +1323,11 @@ mod test {
             version_pre: "".into(),
             version_build: "".into(),
             published_at: 1702223789180282477,
-            published_by: "alice".into()
+            published_by: "alice".into(),
+            changelog: None,
+            released_at: 1702223789180282477,
+            state: "stable".into(),
+            yank_reason: None
         }
     );
 
@@ -582,7 +1341,11 @@ mod test {
             version_pre: "".into(),
             version_build: "".into(),
             published_at: 1702223789180282477,
-            published_by: "alice".into()
+            published_by: "alice".into(),
+            changelog: None,
+            released_at: 1702223789180282477,
+            state: "stable".into(),
+            yank_reason: None
         }
     );
 
@@ -703,6 +1466,7 @@ mod test {
             proj,
             pkg,
             &ver,
+            &ReleaseDataPost::default(),
             1699804206419538067
         ).await.unwrap();
 
@@ -715,7 +1479,11 @@ mod test {
             version_pre: "".into(),
             version_build: "".into(),
             published_at: 1699804206419538067,
-            published_by: "bob".into()
+            published_by: "bob".into(),
+            changelog: None,
+            released_at: 1699804206419538067,
+            state: "stable".into(),
+            yank_reason: None
         };
 
         assert_eq!(
@@ -752,6 +1520,7 @@ mod test {
                     Project(0),
                     Package(6),
                     &ver,
+                    &ReleaseDataPost::default(),
                     1699804206419538067
                 ).await.unwrap_err(),
                 DatabaseError::SqlxError(_)
@@ -777,6 +1546,7 @@ mod test {
                     Project(42),
                     Package(6),
                     &ver,
+                    &ReleaseDataPost::default(),
                     1699804206419538067
                 ).await.unwrap_err(),
                 DatabaseError::SqlxError(_)
@@ -801,6 +1571,7 @@ mod test {
                 Project(42),
                 Package(1),
                 &ver,
+                &ReleaseDataPost::default(),
                 1699804206419538067
             ).await.unwrap_err(),
             DatabaseError::AlreadyExists
@@ -822,7 +1593,11 @@ mod test {
                 version_pre: "".into(),
                 version_build: "".into(),
                 published_at: 1702223789180282477,
-                published_by: "alice".into()
+                published_by: "alice".into(),
+                changelog: None,
+                released_at: 1702223789180282477,
+                state: "stable".into(),
+                yank_reason: None
             },
             ReleaseRow {
                 release_id: 2,
@@ -833,7 +1608,11 @@ mod test {
                 version_pre: "".into(),
                 version_build: "".into(),
                 published_at: 1702223789180282477,
-                published_by: "alice".into()
+                published_by: "alice".into(),
+                changelog: None,
+                released_at: 1702223789180282477,
+                state: "stable".into(),
+                yank_reason: None
             },
             ReleaseRow {
                 release_id: 1,
@@ -844,7 +1623,11 @@ mod test {
                 version_pre: "".into(),
                 version_build: "".into(),
                 published_at: 1702137389180282477,
-                published_by: "bob".into()
+                published_by: "bob".into(),
+                changelog: None,
+                released_at: 1702137389180282477,
+                state: "stable".into(),
+                yank_reason: None
             }
         ];
 
@@ -868,6 +1651,7 @@ mod test {
             Owner(1),
             Project(42),
             Release(4),
+            false,
             1702223789180282478
         ).await.unwrap();
 
@@ -881,7 +1665,11 @@ mod test {
                 version_pre: "".into(),
                 version_build: "".into(),
                 published_at: 1702223789180282477,
-                published_by: "alice".into()
+                published_by: "alice".into(),
+                changelog: None,
+                released_at: 1702223789180282477,
+                state: "stable".into(),
+                yank_reason: None
             },
             ReleaseRow {
                 release_id: 1,
@@ -892,7 +1680,11 @@ mod test {
                 version_pre: "".into(),
                 version_build: "".into(),
                 published_at: 1702137389180282477,
-                published_by: "bob".into()
+                published_by: "bob".into(),
+                changelog: None,
+                released_at: 1702137389180282477,
+                state: "stable".into(),
+                yank_reason: None
             }
         ];
 
@@ -926,6 +1718,7 @@ mod test {
                     Owner(1),
                     Project(0),
                     Release(2),
+                    false,
                     1702137389180282478
                 ).await.unwrap_err(),
                 DatabaseError::SqlxError(_)
@@ -941,6 +1734,7 @@ mod test {
                 Owner(1),
                 Project(42),
                 Release(5),
+                false,
                 1702137389180282478
             ).await.unwrap_err(),
             DatabaseError::NotFound
@@ -956,6 +1750,7 @@ mod test {
                     Owner(1),
                     Project(42),
                     Release(1),
+                    false,
                     1702137389180282478
                 ).await.unwrap_err(),
                 DatabaseError::SqlxError(_)
@@ -963,5 +1758,1113 @@ mod test {
         );
     }
 
-// TODO: add_file_url tests
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn update_release_ok(pool: Pool) {
+        let before = get_releases(&pool, Package(1)).await.unwrap();
+        let row = get_project_row(&pool, Project(42)).await.unwrap();
+
+        let rd = ReleaseDataPatch {
+            changelog: Some(Some("* Fixed the map".into())),
+            released_at: Some(1702000000000000000),
+            ..Default::default()
+        };
+
+        update_release(
+            &pool,
+            Owner(1),
+            Project(42),
+            Release(1),
+            &rd,
+            1702223789180282478
+        ).await.unwrap();
+
+        let after = get_releases(&pool, Package(1)).await.unwrap();
+        let rel = after.iter().find(|r| r.release_id == 1).unwrap();
+        assert_eq!(rel.changelog, Some("* Fixed the map".into()));
+        assert_eq!(rel.released_at, 1702000000000000000);
+
+        // the old values are kept as a revision
+        assert_eq!(
+            get_releases_at(&pool, Package(1), 1702223789180282477)
+                .await
+                .unwrap(),
+            before
+        );
+        assert_eq!(
+            get_releases_at(&pool, Package(1), 1702223789180282478)
+                .await
+                .unwrap(),
+            after
+        );
+
+        let new_row = get_project_row(&pool, Project(42)).await.unwrap();
+        assert_eq!(new_row.revision, row.revision + 1);
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn update_release_clear_changelog(pool: Pool) {
+        for changelog in [Some("* Fixed the map".into()), None] {
+            let rd = ReleaseDataPatch {
+                changelog: Some(changelog.clone()),
+                released_at: None,
+                ..Default::default()
+            };
+
+            update_release(
+                &pool,
+                Owner(1),
+                Project(42),
+                Release(1),
+                &rd,
+                1702223789180282478
+            ).await.unwrap();
+
+            let rels = get_releases(&pool, Package(1)).await.unwrap();
+            let rel = rels.iter().find(|r| r.release_id == 1).unwrap();
+            assert_eq!(rel.changelog, changelog);
+            assert_eq!(rel.released_at, 1702137389180282477);
+        }
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn update_release_same_time(pool: Pool) {
+        let now = 1702223789180282478;
+
+        for changelog in ["* Fixed the map", "* Fixed the map again"] {
+            let rd = ReleaseDataPatch {
+                changelog: Some(Some(changelog.into())),
+                ..Default::default()
+            };
+
+            update_release(
+                &pool,
+                Owner(1),
+                Project(42),
+                Release(1),
+                &rd,
+                now
+            ).await.unwrap();
+        }
+
+        // the later edit replaces the revision made at the same time
+        let rels = get_releases_at(&pool, Package(1), now).await.unwrap();
+        let rel = rels.iter()
+            .filter(|r| r.release_id == 1)
+            .collect::<Vec<_>>();
+        assert_eq!(rel.len(), 1);
+        assert_eq!(rel[0].changelog.as_deref(), Some("* Fixed the map again"));
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn update_release_yank_unyank(pool: Pool) {
+        let rd = ReleaseDataPatch {
+            state: Some(ReleaseState::Yanked),
+            yank_reason: Some("corrupts saves".into()),
+            ..Default::default()
+        };
+
+        update_release(
+            &pool,
+            Owner(1),
+            Project(42),
+            Release(1),
+            &rd,
+            1702223789180282478
+        ).await.unwrap();
+
+        let rels = get_releases(&pool, Package(1)).await.unwrap();
+        let rel = rels.iter().find(|r| r.release_id == 1).unwrap();
+        assert_eq!(rel.state, "yanked");
+        assert_eq!(rel.yank_reason, Some("corrupts saves".into()));
+
+        // restoring the release clears the reason
+        let rd = ReleaseDataPatch {
+            state: Some(ReleaseState::Stable),
+            ..Default::default()
+        };
+
+        update_release(
+            &pool,
+            Owner(1),
+            Project(42),
+            Release(1),
+            &rd,
+            1702223789180282479
+        ).await.unwrap();
+
+        let rels = get_releases(&pool, Package(1)).await.unwrap();
+        let rel = rels.iter().find(|r| r.release_id == 1).unwrap();
+        assert_eq!(rel.state, "stable");
+        assert_eq!(rel.yank_reason, None);
+
+        // the yanked state is kept as a revision
+        let rels = get_releases_at(&pool, Package(1), 1702223789180282478)
+            .await
+            .unwrap();
+        let rel = rels.iter().find(|r| r.release_id == 1).unwrap();
+        assert_eq!(rel.state, "yanked");
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn create_release_pre_release(pool: Pool) {
+        create_release(
+            &pool,
+            Owner(1),
+            Project(42),
+            Package(1),
+            &"1.3.0-beta.1".parse::<Version>().unwrap(),
+            &ReleaseDataPost::default(),
+            1702223789180282478
+        ).await.unwrap();
+
+        let rels = get_releases(&pool, Package(1)).await.unwrap();
+        let rel = rels.iter().find(|r| r.version == "1.3.0-beta.1").unwrap();
+        assert_eq!(rel.state, "pre-release");
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn create_release_with_data(pool: Pool) {
+        let now = 1702223789180282478;
+
+        create_release(
+            &pool,
+            Owner(1),
+            Project(42),
+            Package(1),
+            &"1.3.0".parse::<Version>().unwrap(),
+            &ReleaseDataPost {
+                changelog: Some("* Fixed the map".into()),
+                released_at: Some(1702137389180282477)
+            },
+            now
+        ).await.unwrap();
+
+        let rels = get_releases(&pool, Package(1)).await.unwrap();
+        let rel = rels.iter().find(|r| r.version == "1.3.0").unwrap();
+        assert_eq!(rel.changelog.as_deref(), Some("* Fixed the map"));
+        assert_eq!(rel.released_at, 1702137389180282477);
+
+        // the release has one revision, with the data it was created with
+        let rels = get_releases_at(&pool, Package(1), now).await.unwrap();
+        let rel = rels.iter()
+            .filter(|r| r.version == "1.3.0")
+            .collect::<Vec<_>>();
+        assert_eq!(rel.len(), 1);
+        assert_eq!(rel[0].changelog.as_deref(), Some("* Fixed the map"));
+        assert_eq!(rel[0].released_at, 1702137389180282477);
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn update_release_not_release(pool: Pool) {
+        let rd = ReleaseDataPatch {
+            changelog: Some(Some("* Fixed the map".into())),
+            released_at: None,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            update_release(
+                &pool,
+                Owner(1),
+                Project(42),
+                Release(5),
+                &rd,
+                1702223789180282478
+            ).await.unwrap_err(),
+            DatabaseError::NotFound
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn delete_release_cascade(pool: Pool) {
+        let files_before = get_files(&pool, Release(1)).await.unwrap();
+        assert_eq!(files_before.len(), 1);
+
+        delete_release(
+            &pool,
+            Owner(1),
+            Project(42),
+            Release(1),
+            true,
+            1702137389180282478
+        ).await.unwrap();
+
+        assert!(
+            !get_releases(&pool, Package(1)).await.unwrap()
+                .iter()
+                .any(|r| r.release_id == 1)
+        );
+        assert_eq!(get_files(&pool, Release(1)).await.unwrap(), []);
+
+        // the files remain in the history
+        assert_eq!(
+            get_files_at(&pool, Release(1), 1702137389180282477)
+                .await
+                .unwrap(),
+            files_before
+        );
+        assert_eq!(
+            get_files_at(&pool, Release(1), 1702137389180282478)
+                .await
+                .unwrap(),
+            []
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn delete_release_cascade_empty(pool: Pool) {
+        delete_release(
+            &pool,
+            Owner(1),
+            Project(42),
+            Release(4),
+            true,
+            1702223789180282478
+        ).await.unwrap();
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn delete_release_cascade_not_release(pool: Pool) {
+        assert_eq!(
+            delete_release(
+                &pool,
+                Owner(1),
+                Project(42),
+                Release(5),
+                true,
+                1702137389180282478
+            ).await.unwrap_err(),
+            DatabaseError::NotFound
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn add_file_url_ok(pool: Pool) {
+        add_file_url(
+            &pool,
+            Owner(1),
+            Project(42),
+            Release(1),
+            "new.zip",
+            5,
+            "deadbeef",
+            "application/zip",
+            None,
+            None,
+            None,
+            None,
+            "https://example.com/new.zip",
+            1702137389180282478
+        ).await.unwrap();
+
+        let files = get_files(&pool, Release(1)).await.unwrap();
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[1].filename, "new.zip");
+        assert_eq!(files[1].module_name, None);
+        assert_eq!(
+            get_file_module_items(&pool, files[1].id, "side").await.unwrap(),
+            [] as [String; 0]
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn add_file_url_requires(pool: Pool) {
+        let requires = "3.7.12".parse::<Version>().unwrap();
+
+        add_file_url(
+            &pool,
+            Owner(1),
+            Project(42),
+            Release(1),
+            "new.vmod",
+            5,
+            "deadbeef",
+            "application/zip",
+            Some(&requires),
+            None,
+            None,
+            None,
+            "https://example.com/new.vmod",
+            1702137389180282478
+        ).await.unwrap();
+
+        let files = get_files(&pool, Release(1)).await.unwrap();
+        assert_eq!(files[1].requires.as_deref(), Some(">= 3.7.12"));
+
+        let parsed = sqlx::query!(
+            "
+SELECT
+    requires_major,
+    requires_minor,
+    requires_patch
+FROM files
+WHERE file_id = ?
+            ",
+            files[1].id
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+        assert_eq!(
+            (parsed.requires_major, parsed.requires_minor, parsed.requires_patch),
+            (Some(3), Some(7), Some(12))
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn add_file_url_module(pool: Pool) {
+        let module = ModuleMetadata {
+            name: Some("Test Game".into()),
+            description: None,
+            date_saved: Some(1690000000000000000),
+            extensions: vec![],
+            palettes: vec!["Counters".into()],
+            maps: vec!["Main Map".into(), "Hand".into()],
+            sides: vec!["Red".into(), "Blue".into()],
+            target_module: None,
+            target_module_version: None
+        };
+
+        add_file_url(
+            &pool,
+            Owner(1),
+            Project(42),
+            Release(1),
+            "new.vmod",
+            5,
+            "deadbeef",
+            "application/zip",
+            None,
+            Some(&module),
+            None,
+            None,
+            "https://example.com/new.vmod",
+            1702137389180282478
+        ).await.unwrap();
+
+        let files = get_files(&pool, Release(1)).await.unwrap();
+        assert_eq!(files[1].filename, "new.vmod");
+        assert_eq!(files[1].module_name.as_deref(), Some("Test Game"));
+        assert_eq!(
+            get_file_module_items(&pool, files[1].id, "map").await.unwrap(),
+            ["Main Map".to_string(), "Hand".into()]
+        );
+        assert_eq!(
+            get_file_module_items(&pool, files[1].id, "side").await.unwrap(),
+            ["Red".to_string(), "Blue".into()]
+        );
+        assert_eq!(
+            get_file_module_items(&pool, files[1].id, "palette").await.unwrap(),
+            ["Counters".to_string()]
+        );
+
+        let item = |kind: &str, value: &str| FileModuleItemRow {
+            file_id: files[1].id,
+            kind: kind.into(),
+            value: value.into()
+        };
+
+        assert_eq!(
+            get_release_module_items(&pool, Release(1)).await.unwrap(),
+            [
+                item("map", "Main Map"),
+                item("map", "Hand"),
+                item("palette", "Counters"),
+                item("side", "Red"),
+                item("side", "Blue")
+            ]
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn add_file_url_extension(pool: Pool) {
+        let module = ModuleMetadata {
+            name: Some("Extra Counters".into()),
+            target_module: Some("Test Game".into()),
+            target_module_version: Some("2.1".into()),
+            ..Default::default()
+        };
+
+        add_file_url(
+            &pool,
+            Owner(1),
+            Project(42),
+            Release(1),
+            "new.vmdx",
+            5,
+            "deadbeef",
+            "application/zip",
+            None,
+            Some(&module),
+            None,
+            None,
+            "https://example.com/new.vmdx",
+            1702137389180282478
+        ).await.unwrap();
+
+        let files = get_files(&pool, Release(1)).await.unwrap();
+        assert_eq!(files[1].filename, "new.vmdx");
+        assert_eq!(files[1].target_module.as_deref(), Some("Test Game"));
+        assert_eq!(files[1].target_module_version.as_deref(), Some("2.1"));
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn get_file_url_by_hash_ok(pool: Pool) {
+        assert_eq!(
+            get_file_url_by_hash(
+                &pool,
+                "c0e0fa7373a12b45a91e4f4d4e2e186442fc6ee9b346caa2fdc1c09026a2144a",
+                1234
+            ).await.unwrap(),
+            Some("https://example.com/a_package-1.2.3".into())
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn get_file_url_by_hash_wrong_size(pool: Pool) {
+        assert_eq!(
+            get_file_url_by_hash(
+                &pool,
+                "c0e0fa7373a12b45a91e4f4d4e2e186442fc6ee9b346caa2fdc1c09026a2144a",
+                1235
+            ).await.unwrap(),
+            None
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn get_file_url_by_hash_oldest(pool: Pool) {
+        add_file_url(
+            &pool,
+            Owner(1),
+            Project(42),
+            Release(2),
+            "copy.zip",
+            1234,
+            "c0e0fa7373a12b45a91e4f4d4e2e186442fc6ee9b346caa2fdc1c09026a2144a",
+            "application/zip",
+            None,
+            None,
+            None,
+            None,
+            "https://example.com/copy.zip",
+            1702137389180282478
+        ).await.unwrap();
+
+        assert_eq!(
+            get_file_url_by_hash(
+                &pool,
+                "c0e0fa7373a12b45a91e4f4d4e2e186442fc6ee9b346caa2fdc1c09026a2144a",
+                1234
+            ).await.unwrap(),
+            Some("https://example.com/a_package-1.2.3".into())
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn get_files_by_hash_ok(pool: Pool) {
+        add_file_url(
+            &pool,
+            Owner(2),
+            Project(42),
+            Release(2),
+            "copy.zip",
+            1234,
+            "c0e0fa7373a12b45a91e4f4d4e2e186442fc6ee9b346caa2fdc1c09026a2144a",
+            "application/zip",
+            None,
+            None,
+            None,
+            None,
+            "https://example.com/a_package-1.2.3",
+            1702137389180282478
+        ).await.unwrap();
+
+        assert_eq!(
+            get_files_by_hash(
+                &pool,
+                "c0e0fa7373a12b45a91e4f4d4e2e186442fc6ee9b346caa2fdc1c09026a2144a"
+            ).await.unwrap(),
+            [
+                SharedFileRow {
+                    project: "test_game".into(),
+                    slug: "test_game".into(),
+                    package: "a_package".into(),
+                    version: "1.2.3".into(),
+                    filename: "a_package-1.2.3".into(),
+                    url: "https://example.com/a_package-1.2.3".into(),
+                    size: 1234,
+                    published_at: 1702137389180282477,
+                    published_by: "bob".into()
+                },
+                SharedFileRow {
+                    project: "test_game".into(),
+                    slug: "test_game".into(),
+                    package: "a_package".into(),
+                    version: "1.2.4".into(),
+                    filename: "copy.zip".into(),
+                    url: "https://example.com/a_package-1.2.3".into(),
+                    size: 1234,
+                    published_at: 1702137389180282478,
+                    published_by: "alice".into()
+                }
+            ]
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn get_files_by_hash_none(pool: Pool) {
+        assert_eq!(
+            get_files_by_hash(&pool, "deadbeef").await.unwrap(),
+            []
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn get_module_project_ok(pool: Pool) {
+        let module = ModuleMetadata {
+            name: Some("Test Game".into()),
+            ..Default::default()
+        };
+
+        add_file_url(
+            &pool,
+            Owner(1),
+            Project(42),
+            Release(1),
+            "new.vmod",
+            5,
+            "deadbeef",
+            "application/zip",
+            None,
+            Some(&module),
+            None,
+            None,
+            "https://example.com/new.vmod",
+            1702137389180282478
+        ).await.unwrap();
+
+        assert_eq!(
+            get_module_project(&pool, Project(6), "Test Game").await.unwrap(),
+            Some(Project(42))
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn get_module_project_not_a_module(pool: Pool) {
+        let module = ModuleMetadata {
+            name: Some("Test Game".into()),
+            target_module: Some("Other Game".into()),
+            ..Default::default()
+        };
+
+        add_file_url(
+            &pool,
+            Owner(1),
+            Project(42),
+            Release(1),
+            "new.vmdx",
+            5,
+            "deadbeef",
+            "application/zip",
+            None,
+            Some(&module),
+            None,
+            None,
+            "https://example.com/new.vmdx",
+            1702137389180282478
+        ).await.unwrap();
+
+        assert_eq!(
+            get_module_project(&pool, Project(42), "Test Game").await.unwrap(),
+            None
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn get_module_project_deleted(pool: Pool) {
+        let module = ModuleMetadata {
+            name: Some("Test Game".into()),
+            ..Default::default()
+        };
+
+        add_file_url(
+            &pool,
+            Owner(1),
+            Project(42),
+            Release(1),
+            "new.vmod",
+            5,
+            "deadbeef",
+            "application/zip",
+            None,
+            Some(&module),
+            None,
+            None,
+            "https://example.com/new.vmod",
+            1702137389180282478
+        ).await.unwrap();
+
+        delete_project(&pool, User(1), Project(42), 1702137389180282479)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            get_module_project(&pool, Project(6), "Test Game").await.unwrap(),
+            None
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn get_module_releases_ok(pool: Pool) {
+        let module = ModuleMetadata {
+            name: Some("Test Game".into()),
+            ..Default::default()
+        };
+
+        for (rel, filename) in [(1, "a.vmod"), (2, "b.vmod")] {
+            add_file_url(
+                &pool,
+                Owner(1),
+                Project(42),
+                Release(rel),
+                filename,
+                5,
+                "deadbeef",
+                "application/zip",
+                None,
+                Some(&module),
+                None,
+                None,
+                "https://example.com/new.vmod",
+                1702137389180282478
+            ).await.unwrap();
+        }
+
+        let mut releases = get_module_releases(&pool, Project(42), "Test Game")
+            .await
+            .unwrap();
+        releases.sort_by_key(|(r, _)| r.0);
+
+        assert_eq!(
+            releases,
+            [
+                (Release(1), "1.2.3".parse::<Version>().unwrap()),
+                (Release(2), "1.2.4".parse::<Version>().unwrap())
+            ]
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn get_module_releases_none(pool: Pool) {
+        assert_eq!(
+            get_module_releases(&pool, Project(42), "Test Game")
+                .await
+                .unwrap(),
+            []
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn get_files_extension_target(pool: Pool) {
+        let module = ModuleMetadata {
+            name: Some("Extra Counters".into()),
+            target_module: Some("Test Game".into()),
+            target_module_version: Some("1.2".into()),
+            ..Default::default()
+        };
+
+        let target = ModuleTarget {
+            project: Project(42),
+            release: Some(Release(1))
+        };
+
+        add_file_url(
+            &pool,
+            Owner(1),
+            Project(42),
+            Release(2),
+            "new.vmdx",
+            5,
+            "deadbeef",
+            "application/zip",
+            None,
+            Some(&module),
+            Some(&target),
+            None,
+            "https://example.com/new.vmdx",
+            1702137389180282478
+        ).await.unwrap();
+
+        let files = get_files(&pool, Release(2)).await.unwrap();
+        let ext = files.iter().find(|f| f.filename == "new.vmdx").unwrap();
+        assert_eq!(ext.target_project.as_deref(), Some("test_game"));
+        assert_eq!(ext.target_release.as_deref(), Some("1.2.3"));
+
+        // the target release no longer counts once yanked
+        let rd = ReleaseDataPatch {
+            state: Some(ReleaseState::Yanked),
+            yank_reason: Some("broken".into()),
+            ..Default::default()
+        };
+
+        update_release(
+            &pool,
+            Owner(1),
+            Project(42),
+            Release(1),
+            &rd,
+            1702137389180282479
+        ).await.unwrap();
+
+        let files = get_files(&pool, Release(2)).await.unwrap();
+        let ext = files.iter().find(|f| f.filename == "new.vmdx").unwrap();
+        assert_eq!(ext.target_project.as_deref(), Some("test_game"));
+        assert_eq!(ext.target_release, None);
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn get_module_project_none(pool: Pool) {
+        assert_eq!(
+            get_module_project(&pool, Project(42), "Test Game").await.unwrap(),
+            None
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn add_file_url_already_exists(pool: Pool) {
+        assert_eq!(
+            add_file_url(
+                &pool,
+                Owner(1),
+                Project(42),
+                Release(1),
+                "a_package-1.2.3",
+                5,
+                "deadbeef",
+                "application/zip",
+                None,
+                Some(&ModuleMetadata::default()),
+                None,
+                None,
+                "https://example.com/a_package-1.2.3",
+                1702137389180282478
+            ).await.unwrap_err(),
+            DatabaseError::AlreadyExists
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn add_file_url_players_unset(pool: Pool) {
+        // clear the number of players
+        let pd = ProjectDataPatch {
+            game: GameDataPatch {
+                players: RangePatch {
+                    min: Some(None),
+                    max: Some(None)
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        update_project(&pool, Owner(1), Project(42), &pd, 1702137389180282478)
+            .await
+            .unwrap();
+
+        let row = get_project_row(&pool, Project(42)).await.unwrap();
+        assert_eq!(row.game_players_min, None);
+        assert_eq!(row.game_players_max, None);
+
+        add_file_url(
+            &pool,
+            Owner(1),
+            Project(42),
+            Release(1),
+            "new.vmod",
+            5,
+            "deadbeef",
+            "application/zip",
+            None,
+            None,
+            None,
+            Some(2),
+            "https://example.com/new.vmod",
+            1702137389180282479
+        ).await.unwrap();
+
+        let new_row = get_project_row(&pool, Project(42)).await.unwrap();
+        assert_eq!(new_row.game_players_min, Some(2));
+        assert_eq!(new_row.game_players_max, Some(2));
+        assert_eq!(new_row.revision, row.revision + 1);
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn add_file_url_players_set(pool: Pool) {
+        let row = get_project_row(&pool, Project(42)).await.unwrap();
+        assert_eq!(row.game_players_min, None);
+        assert_eq!(row.game_players_max, Some(3));
+
+        add_file_url(
+            &pool,
+            Owner(1),
+            Project(42),
+            Release(1),
+            "new.vmod",
+            5,
+            "deadbeef",
+            "application/zip",
+            None,
+            None,
+            None,
+            Some(2),
+            "https://example.com/new.vmod",
+            1702137389180282478
+        ).await.unwrap();
+
+        let new_row = get_project_row(&pool, Project(42)).await.unwrap();
+        assert_eq!(new_row.game_players_min, None);
+        assert_eq!(new_row.game_players_max, Some(3));
+        assert_eq!(new_row.revision, row.revision + 1);
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn delete_file_ok(pool: Pool) {
+        let row = get_project_row(&pool, Project(42)).await.unwrap();
+
+        let files_before = get_files(&pool, Release(1)).await.unwrap();
+        assert_eq!(files_before.len(), 1);
+        assert_eq!(files_before[0].filename, "a_package-1.2.3");
+
+        delete_file(
+            &pool,
+            Owner(1),
+            Project(42),
+            Release(1),
+            "a_package-1.2.3",
+            1702137389180282478
+        ).await.unwrap();
+
+        assert_eq!(get_files(&pool, Release(1)).await.unwrap(), []);
+
+        // the file is still there before the deletion
+        assert_eq!(
+            get_files_at(&pool, Release(1), 1702137389180282477)
+                .await
+                .unwrap(),
+            files_before
+        );
+
+        assert_eq!(
+            get_files_at(&pool, Release(1), 1702137389180282478)
+                .await
+                .unwrap(),
+            []
+        );
+
+        let new_row = get_project_row(&pool, Project(42)).await.unwrap();
+        assert_eq!(new_row.revision, row.revision + 1);
+        assert_eq!(new_row.modified_at, 1702137389180282478);
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn delete_file_not_file(pool: Pool) {
+        assert_eq!(
+            delete_file(
+                &pool,
+                Owner(1),
+                Project(42),
+                Release(1),
+                "bogus",
+                1702137389180282478
+            ).await.unwrap_err(),
+            DatabaseError::NotFound
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn delete_file_twice(pool: Pool) {
+        delete_file(
+            &pool,
+            Owner(1),
+            Project(42),
+            Release(1),
+            "a_package-1.2.3",
+            1702137389180282478
+        ).await.unwrap();
+
+        assert_eq!(
+            delete_file(
+                &pool,
+                Owner(1),
+                Project(42),
+                Release(1),
+                "a_package-1.2.3",
+                1702137389180282479
+            ).await.unwrap_err(),
+            DatabaseError::NotFound
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn delete_file_replace(pool: Pool) {
+        delete_file(
+            &pool,
+            Owner(1),
+            Project(42),
+            Release(1),
+            "a_package-1.2.3",
+            1702137389180282478
+        ).await.unwrap();
+
+        add_file_url(
+            &pool,
+            Owner(1),
+            Project(42),
+            Release(1),
+            "a_package-1.2.3",
+            5,
+            "deadbeef",
+            "application/zip",
+            None,
+            None,
+            None,
+            None,
+            "https://example.com/new/a_package-1.2.3",
+            1702137389180282479
+        ).await.unwrap();
+
+        let files = get_files(&pool, Release(1)).await.unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].url, "https://example.com/new/a_package-1.2.3");
+
+        // each revision sees the file current at that time
+        assert_eq!(
+            get_files_at(&pool, Release(1), 1702137389180282477)
+                .await
+                .unwrap()[0]
+                .url,
+            "https://example.com/a_package-1.2.3"
+        );
+
+        assert_eq!(
+            get_files_at(&pool, Release(1), 1702137389180282479)
+                .await
+                .unwrap(),
+            files
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn delete_file_module(pool: Pool) {
+        let module = ModuleMetadata {
+            name: Some("Test Game".into()),
+            sides: vec!["Red".into(), "Blue".into()],
+            ..Default::default()
+        };
+
+        add_file_url(
+            &pool,
+            Owner(1),
+            Project(42),
+            Release(1),
+            "new.vmod",
+            5,
+            "deadbeef",
+            "application/zip",
+            None,
+            Some(&module),
+            None,
+            None,
+            "https://example.com/new.vmod",
+            1702137389180282478
+        ).await.unwrap();
+
+        delete_file(
+            &pool,
+            Owner(1),
+            Project(42),
+            Release(1),
+            "new.vmod",
+            1702137389180282479
+        ).await.unwrap();
+
+        // module metadata is kept for old revisions
+        let files = get_files_at(&pool, Release(1), 1702137389180282478)
+            .await
+            .unwrap();
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[1].filename, "new.vmod");
+        assert_eq!(files[1].module_name, Some("Test Game".into()));
+        assert_eq!(
+            get_file_module_items(&pool, files[1].id, "side").await.unwrap(),
+            ["Red", "Blue"]
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn delete_file_then_release(pool: Pool) {
+        delete_file(
+            &pool,
+            Owner(1),
+            Project(42),
+            Release(1),
+            "a_package-1.2.3",
+            1702137389180282478
+        ).await.unwrap();
+
+        delete_release(
+            &pool,
+            Owner(1),
+            Project(42),
+            Release(1),
+            false,
+            1702137389180282479
+        ).await.unwrap();
+    }
+
+    async fn assert_vassal_compatible(pool: &Pool, vassal: &str, exp: &[i64]) {
+        assert_eq!(
+            get_vassal_compatible_releases(
+                pool,
+                Project(42),
+                &vassal.parse::<Version>().unwrap()
+            ).await.unwrap(),
+            exp
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn get_vassal_compatible_releases_ok(pool: Pool) {
+        // 1.2.5 has no files, so is never compatible
+        assert_vassal_compatible(&pool, "3.1.0", &[3]).await;
+        assert_vassal_compatible(&pool, "3.6.0", &[1, 3]).await;
+        assert_vassal_compatible(&pool, "3.7.12", &[1, 2, 3]).await;
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn get_vassal_compatible_releases_pre_release(pool: Pool) {
+        assert_vassal_compatible(&pool, "3.7.12-beta1", &[1, 3]).await;
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "packages"))]
+    async fn get_vassal_compatible_releases_yanked(pool: Pool) {
+        let rd = ReleaseDataPatch {
+            state: Some(ReleaseState::Yanked),
+            yank_reason: Some("broken".into()),
+            ..Default::default()
+        };
+
+        update_release(
+            &pool,
+            Owner(1),
+            Project(42),
+            Release(1),
+            &rd,
+            1702655789180282478
+        ).await.unwrap();
+
+        assert_vassal_compatible(&pool, "3.6.0", &[3]).await;
+    }
 }
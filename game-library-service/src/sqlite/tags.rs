@@ -7,6 +7,7 @@ use crate::{
     db::{DatabaseError, map_unique},
     model::{Owner, Project},
     sqlite::{
+        like_prefix,
         require_one_modified,
         project::update_project_non_project_data
     }
@@ -100,6 +101,40 @@ ORDER BY tag COLLATE NOCASE
     )
 }
 
+pub async fn get_tag_suggestions<'e, E>(
+    ex: E,
+    prefix: &str,
+    limit: u32
+) -> Result<Vec<String>, DatabaseError>
+where
+    E: Executor<'e, Database = Sqlite>
+{
+    // tags in use now, those on more projects first
+    let prefix = like_prefix(prefix);
+
+    Ok(
+        sqlx::query_scalar!(
+            "
+SELECT tags.tag
+FROM tags
+JOIN projects_tags
+ON tags.tag_id = projects_tags.tag_id
+JOIN projects_history
+ON projects_tags.project_id = projects_history.project_id
+WHERE tags.tag LIKE ? ESCAPE '\\'
+    AND projects_history.deleted_at IS NULL
+GROUP BY tags.tag_id
+ORDER BY COUNT(1) DESC, tags.tag COLLATE NOCASE
+LIMIT ?
+            ",
+            prefix,
+            limit
+        )
+        .fetch_all(ex)
+        .await?
+    )
+}
+
 pub async fn get_project_tags<'e, E>(
     ex: E,
     proj: Project
@@ -353,6 +388,58 @@ mod test {
         );
     }
 
+    #[sqlx::test(fixtures("users", "projects", "tags"))]
+    async fn get_tag_suggestions_ok(pool: Pool) {
+        assert_eq!(
+            get_tag_suggestions(&pool, "A", 10).await.unwrap(),
+            ["a".to_string()]
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "tags"))]
+    async fn get_tag_suggestions_ranked(pool: Pool) {
+        sqlx::query(
+            "
+INSERT INTO tags (tag_id, tag)
+VALUES (4, 'ab'), (5, 'aa');
+
+INSERT INTO projects_tags_history (project_id, tag_id, added_at, added_by)
+VALUES
+  (6, 4, 1762897247000000000, 1),
+  (6, 5, 1762897247000000000, 1);
+            "
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        assert_eq!(
+            get_tag_suggestions(&pool, "a", 10).await.unwrap(),
+            ["a".to_string(), "aa".into(), "ab".into()]
+        );
+
+        assert_eq!(
+            get_tag_suggestions(&pool, "a", 2).await.unwrap(),
+            ["a".to_string(), "aa".into()]
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "tags"))]
+    async fn get_tag_suggestions_unused(pool: Pool) {
+        assert_eq!(
+            get_tag_suggestions(&pool, "c", 10).await.unwrap(),
+            [] as [String; 0]
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "tags"))]
+    async fn get_tag_suggestions_wildcard(pool: Pool) {
+        assert_eq!(
+            get_tag_suggestions(&pool, "%", 10).await.unwrap(),
+            [] as [String; 0]
+        );
+    }
+
     #[sqlx::test(fixtures("users", "projects", "tags"))]
     async fn get_tags_ok(pool: Pool) {
         assert_eq!(
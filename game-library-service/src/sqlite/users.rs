@@ -1,6 +1,6 @@
 use glc::{
     discourse::UserUpdateParams,
-    model::Users
+    model::{UserMergeReport, Users}
 };
 use sqlx::{
     Acquire, Executor,
@@ -9,7 +9,8 @@ use sqlx::{
 
 use crate::{
     db::DatabaseError,
-    model::{Project, User}
+    model::{Project, User},
+    sqlite::like_prefix
 };
 
 pub async fn get_user_id<'e, E>(
@@ -63,6 +64,40 @@ ORDER BY users.username
     )
 }
 
+pub async fn get_owner_suggestions<'e, E>(
+    ex: E,
+    prefix: &str,
+    limit: u32
+) -> Result<Vec<String>, DatabaseError>
+where
+    E: Executor<'e, Database = Sqlite>
+{
+    // owners of projects now, those with more projects first
+    let prefix = like_prefix(prefix);
+
+    Ok(
+        sqlx::query_scalar!(
+            "
+SELECT users.username
+FROM users
+JOIN owners
+ON users.user_id = owners.user_id
+JOIN projects_history
+ON owners.project_id = projects_history.project_id
+WHERE users.username LIKE ? ESCAPE '\\'
+    AND projects_history.deleted_at IS NULL
+GROUP BY users.user_id
+ORDER BY COUNT(1) DESC, users.username COLLATE NOCASE
+LIMIT ?
+            ",
+            prefix,
+            limit
+        )
+        .fetch_all(ex)
+        .await?
+    )
+}
+
 pub async fn user_is_owner<'e, E>(
     ex: E,
     user: User,
@@ -237,6 +272,103 @@ VALUES (?, ?)
     Ok(())
 }
 
+// references to users which are unique per user, so may collide
+const USER_UNIQUE_COLUMNS: &[(&str, &str)] = &[
+    ("owners", "user_id"),
+    ("players", "user_id")
+];
+
+// all other references to users; every foreign key to users must be
+// listed here or above, or merging will fail on the deleted source user
+const USER_COLUMNS: &[(&str, &str)] = &[
+    ("packages", "created_by"),
+    ("packages_history", "created_by"),
+    ("packages_history", "deleted_by"),
+    ("packages_revisions", "modified_by"),
+    ("releases", "published_by"),
+    ("releases_history", "published_by"),
+    ("releases_history", "deleted_by"),
+    ("releases_revisions", "modified_by"),
+    ("files_history", "published_by"),
+    ("files_history", "deleted_by"),
+    ("files", "published_by"),
+    ("images", "published_by"),
+    ("image_revisions", "published_by"),
+    ("galleries", "published_by"),
+    ("galleries_history", "published_by"),
+    ("galleries_history", "removed_by"),
+    ("projects", "modified_by"),
+    ("projects_history", "deleted_by"),
+    ("projects_revisions", "modified_by"),
+    ("projects_tags_history", "added_by"),
+    ("projects_tags_history", "removed_by"),
+    ("flags", "user_id"),
+    ("flags", "closed_by")
+];
+
+pub async fn merge_users<'a, A>(
+    conn: A,
+    src: User,
+    dst: User
+) -> Result<UserMergeReport, DatabaseError>
+where
+    A: Acquire<'a, Database = Sqlite>
+{
+    let mut tx = conn.begin().await?;
+
+    let mut report = UserMergeReport::default();
+
+    for (table, column) in USER_UNIQUE_COLUMNS {
+        let r = sqlx::query(&format!(
+            "UPDATE OR IGNORE {table} SET {column} = ? WHERE {column} = ?"
+        ))
+        .bind(dst.0)
+        .bind(src.0)
+        .execute(&mut *tx)
+        .await?;
+        report.rewritten.insert(format!("{table}.{column}"), r.rows_affected());
+
+        // rows left behind duplicate rows already held by the destination
+        let r = sqlx::query(&format!(
+            "DELETE FROM {table} WHERE {column} = ?"
+        ))
+        .bind(src.0)
+        .execute(&mut *tx)
+        .await?;
+        report.dropped.insert(format!("{table}.{column}"), r.rows_affected());
+    }
+
+    for (table, column) in USER_COLUMNS {
+        let r = sqlx::query(&format!(
+            "UPDATE {table} SET {column} = ? WHERE {column} = ?"
+        ))
+        .bind(dst.0)
+        .bind(src.0)
+        .execute(&mut *tx)
+        .await?;
+        report.rewritten.insert(format!("{table}.{column}"), r.rows_affected());
+    }
+
+    // the source user is now unreferenced
+    let r = sqlx::query!(
+        "
+DELETE FROM users
+WHERE user_id = ?
+        ",
+        src.0
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    if r.rows_affected() == 0 {
+        return Err(DatabaseError::NotFound);
+    }
+
+    tx.commit().await?;
+
+    Ok(report)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -261,6 +393,22 @@ mod test {
         );
     }
 
+    #[sqlx::test(fixtures("users", "projects", "two_owners"))]
+    async fn get_owner_suggestions_ok(pool: Pool) {
+        assert_eq!(
+            get_owner_suggestions(&pool, "B", 10).await.unwrap(),
+            ["bob".to_string()]
+        );
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "two_owners"))]
+    async fn get_owner_suggestions_not_owner(pool: Pool) {
+        assert_eq!(
+            get_owner_suggestions(&pool, "ch", 10).await.unwrap(),
+            [] as [String; 0]
+        );
+    }
+
     #[sqlx::test(fixtures("users", "projects", "one_owner"))]
     async fn user_is_owner_true(pool: Pool) {
         assert!(user_is_owner(&pool, User(1), Project(42)).await.unwrap());
@@ -380,4 +528,86 @@ mod test {
         // However, it's not an error if it does.
         assert!(!has_owner(&pool, Project(0)).await.unwrap());
     }
+
+    #[sqlx::test(fixtures("users", "projects", "two_owners", "players"))]
+    async fn merge_users_duplicates(pool: Pool) {
+        let report = merge_users(&pool, User(2), User(1)).await.unwrap();
+
+        assert_eq!(report.rewritten["owners.user_id"], 0);
+        assert_eq!(report.dropped["owners.user_id"], 2);
+        assert_eq!(report.rewritten["players.user_id"], 0);
+        assert_eq!(report.dropped["players.user_id"], 1);
+
+        assert_eq!(
+            get_owners(&pool, Project(42)).await.unwrap(),
+            Users { users: vec!["bob".into()] }
+        );
+        assert_eq!(get_user_id(&pool, "alice").await.unwrap(), None);
+    }
+
+    #[sqlx::test(fixtures("users", "projects", "one_owner", "packages"))]
+    async fn merge_users_rewrite(pool: Pool) {
+        let report = merge_users(&pool, User(1), User(3)).await.unwrap();
+
+        assert_eq!(report.rewritten["owners.user_id"], 1);
+        assert_eq!(report.dropped["owners.user_id"], 0);
+        assert_eq!(report.rewritten["packages.created_by"], 3);
+        assert_eq!(report.rewritten["releases.published_by"], 1);
+        assert_eq!(report.rewritten["releases_revisions.modified_by"], 1);
+        assert_eq!(report.rewritten["files_history.published_by"], 1);
+        assert_eq!(report.rewritten["files.published_by"], 1);
+        assert_eq!(report.rewritten["projects.modified_by"], 2);
+        assert_eq!(report.rewritten["projects_revisions.modified_by"], 3);
+
+        assert_eq!(
+            get_owners(&pool, Project(42)).await.unwrap(),
+            Users { users: vec!["chuck".into()] }
+        );
+        assert_eq!(get_user_id(&pool, "bob").await.unwrap(), None);
+    }
+
+    #[sqlx::test(fixtures("users", "projects"))]
+    async fn merge_users_not_found(pool: Pool) {
+        assert_eq!(
+            merge_users(&pool, User(99), User(1)).await.unwrap_err(),
+            DatabaseError::NotFound
+        );
+    }
+
+    #[sqlx::test]
+    async fn merge_users_all_references(pool: Pool) {
+        // every foreign key to users must be rewritten by merging
+        let tables: Vec<String> = sqlx::query_scalar(
+            "SELECT name FROM sqlite_schema WHERE type = 'table'"
+        )
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+
+        let mut act = vec![];
+        for table in tables {
+            let fks: Vec<(String, String)> = sqlx::query_as(
+                "SELECT \"table\", \"from\" FROM pragma_foreign_key_list(?)"
+            )
+            .bind(&table)
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+
+            act.extend(
+                fks.into_iter()
+                    .filter(|(target, _)| target == "users")
+                    .map(|(_, column)| (table.clone(), column))
+            );
+        }
+        act.sort();
+
+        let mut exp = USER_UNIQUE_COLUMNS.iter()
+            .chain(USER_COLUMNS)
+            .map(|(t, c)| (t.to_string(), c.to_string()))
+            .collect::<Vec<_>>();
+        exp.sort();
+
+        assert_eq!(act, exp);
+    }
 }
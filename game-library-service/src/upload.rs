@@ -1,4 +1,5 @@
-use http::header::{HeaderMap, HeaderName, HeaderValue};
+use chrono::{DateTime, Utc};
+use http::header::{CONTENT_DISPOSITION, HeaderMap, HeaderName, HeaderValue};
 use regex::Regex;
 use s3::{
     bucket::Bucket,
@@ -11,20 +12,29 @@ use s3::{
 };
 use std::{
     future::Future,
+    io,
+    path::{Path, PathBuf},
     sync::LazyLock
 };
 use thiserror::Error;
-use tokio::io::AsyncRead;
+use tokio::{
+    fs::{self, File},
+    io::{AsyncRead, AsyncWriteExt}
+};
 
 #[derive(Debug, Error)]
 pub enum UploadError {
     #[error("Bucket error: {0}")]
     S3Error(#[from] S3Error),
+    #[error("{0}")]
+    IOError(#[from] io::Error),
+    #[error("{0}")]
+    BadTimestamp(#[from] chrono::format::ParseError)
 }
 
 impl PartialEq for UploadError {
     fn eq(&self, _other: &Self) -> bool {
-        // S3Error is not PartialEq, so we must exclude it
+        // S3Error, io::Error are not PartialEq, so we must exclude them
         false
     }
 }
@@ -61,6 +71,27 @@ pub fn safe_filename(path: &str) -> Result<&str, InvalidFilename> {
     }
 }
 
+// Objects stored under their hash are downloaded under their filename;
+// the ASCII fallback is for clients which don't understand RFC 5987
+pub fn content_disposition(filename: &str) -> String {
+    let ascii = filename.chars()
+        .map(|c| if c.is_ascii() { c } else { '_' })
+        .collect::<String>();
+
+    format!(
+        "attachment; filename=\"{ascii}\"; filename*=UTF-8''{0}",
+        urlencoding::encode(filename)
+    )
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct StoredObject {
+    pub path: String,
+    pub url: String,
+    pub size: u64,
+    pub last_modified: DateTime<Utc>
+}
+
 pub trait Uploader {
     fn upload<R>(
         &self,
@@ -78,27 +109,65 @@ pub trait Uploader {
     ) -> impl Future<Output = Result<String, UploadError>> + Send
     where
         R: AsyncRead + Unpin + Send;
+
+    // store an object to be downloaded under a name other than its path
+    fn upload_with_filename<R>(
+        &self,
+        path: &str,
+        reader: R,
+        content_type: &str,
+        filename: &str
+    ) -> impl Future<Output = Result<String, UploadError>> + Send
+    where
+        R: AsyncRead + Unpin + Send;
+
+    fn list(
+        &self
+    ) -> impl Future<Output = Result<Vec<StoredObject>, UploadError>> + Send;
+
+    fn delete(
+        &self,
+        path: &str
+    ) -> impl Future<Output = Result<(), UploadError>> + Send;
+}
+
+// Stored objects are kept in their own subdirectory, apart from temporary
+// files and unfinished uploads, so that only they are served or collected.
+pub fn local_objects_dir(uploads_directory: &Path) -> PathBuf {
+    uploads_directory.join("objects")
 }
 
 pub struct LocalUploader {
-    pub uploads_directory: String
+    pub uploads_directory: PathBuf,
+    pub base_url: String
 }
 
 impl Uploader for LocalUploader {
     async fn upload<R>(
         &self,
         filename: &str,
-        _reader: R
+        mut reader: R
     ) -> Result<String, UploadError>
     where
         R: AsyncRead + Unpin + Send
     {
-        Ok(
-            format!(
-                "http://localhost:3000/{0}/{filename}",
-                self.uploads_directory
-            )
-        )
+        let path = local_objects_dir(&self.uploads_directory).join(filename);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+
+        // write to a temporary file so that a partial upload is never served
+        let tmp_path = self.uploads_directory.join(
+            format!("{:032x}.part", rand::random::<u128>())
+        );
+
+        let mut file = File::create(&tmp_path).await?;
+        tokio::io::copy(&mut reader, &mut file).await?;
+        file.flush().await?;
+        file.sync_all().await?;
+        fs::rename(&tmp_path, &path).await?;
+
+        Ok(format!("{0}/{filename}", self.base_url))
     }
 
     async fn upload_with_content_type<R>(
@@ -112,6 +181,69 @@ impl Uploader for LocalUploader {
     {
         self.upload(filename, reader).await
     }
+
+    async fn upload_with_filename<R>(
+        &self,
+        path: &str,
+        reader: R,
+        _content_type: &str,
+        _filename: &str
+    ) -> Result<String, UploadError>
+    where
+        R: AsyncRead + Unpin + Send
+    {
+        // local uploads are served with no Content-Disposition
+        self.upload(path, reader).await
+    }
+
+    async fn list(&self) -> Result<Vec<StoredObject>, UploadError> {
+        let objects_dir = local_objects_dir(&self.uploads_directory);
+        if !fs::try_exists(&objects_dir).await? {
+            return Ok(vec![]);
+        }
+
+        let mut objects = vec![];
+        let mut dirs = vec![objects_dir.clone()];
+
+        while let Some(dir) = dirs.pop() {
+            let mut entries = fs::read_dir(&dir).await?;
+            while let Some(entry) = entries.next_entry().await? {
+                let meta = entry.metadata().await?;
+                if meta.is_dir() {
+                    dirs.push(entry.path());
+                    continue;
+                }
+
+                let path = entry.path()
+                    .strip_prefix(&objects_dir)
+                    .map_err(io::Error::other)?
+                    .iter()
+                    .map(|c| c.to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+
+                objects.push(
+                    StoredObject {
+                        url: format!("{0}/{path}", self.base_url),
+                        path,
+                        size: meta.len(),
+                        last_modified: meta.modified()?.into()
+                    }
+                );
+            }
+        }
+
+        objects.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(objects)
+    }
+
+    async fn delete(&self, path: &str) -> Result<(), UploadError> {
+        Ok(
+            fs::remove_file(
+                local_objects_dir(&self.uploads_directory).join(path)
+            ).await?
+        )
+    }
 }
 
 #[derive(Debug, Error)]
@@ -203,12 +335,244 @@ impl Uploader for BucketUploader {
         ).await?;
         Ok(format!("{0}/{path}", self.base_url))
     }
+
+    async fn upload_with_filename<R>(
+        &self,
+        path: &str,
+        mut reader: R,
+        content_type: &str,
+        filename: &str
+    ) -> Result<String, UploadError>
+    where
+        R: AsyncRead + Unpin + Send
+    {
+        let path = format!("{0}/{path}", self.base_dir);
+
+        // extra headers are sent with each part of a multipart upload,
+        // unlike those set on a put request
+        let mut headers = self.bucket.extra_headers().clone();
+        headers.insert(
+            CONTENT_DISPOSITION,
+            HeaderValue::from_str(&content_disposition(filename))
+                .map_err(S3Error::InvalidHeaderValue)?
+        );
+        let bucket = self.bucket.with_extra_headers(headers)?;
+
+// TODO: check return code?
+        bucket.put_object_stream_with_content_type(
+            &mut reader,
+            &path,
+            content_type
+        ).await?;
+        Ok(format!("{0}/{path}", self.base_url))
+    }
+
+    async fn list(&self) -> Result<Vec<StoredObject>, UploadError> {
+        let prefix = format!("{0}/", self.base_dir);
+
+        self.bucket.list(prefix, None)
+            .await?
+            .into_iter()
+            .flat_map(|r| r.contents)
+            .map(|o| Ok(
+                StoredObject {
+                    url: format!("{0}/{1}", self.base_url, o.key),
+                    last_modified: o.last_modified.parse()?,
+                    path: o.key,
+                    size: o.size
+                }
+            ))
+            .collect()
+    }
+
+    async fn delete(&self, path: &str) -> Result<(), UploadError> {
+        self.bucket.delete_object(path).await?;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
 
+    use crate::fake_s3::{BASE_URL, FakeS3};
+
+    #[tokio::test]
+    async fn local_uploader_upload() {
+        let dir = std::env::temp_dir().join(
+            format!("gls-local-uploader-{}", std::process::id())
+        );
+
+        let uploader = LocalUploader {
+            uploads_directory: dir.clone(),
+            base_url: "http://localhost:3000/api/v1/uploads".into()
+        };
+
+        assert_eq!(
+            uploader.upload_with_content_type(
+                "a/b/test.txt",
+                b"hello".as_slice(),
+                "text/plain"
+            ).await.unwrap(),
+            "http://localhost:3000/api/v1/uploads/a/b/test.txt"
+        );
+
+        assert_eq!(
+            fs::read(dir.join("objects/a/b/test.txt")).await.unwrap(),
+            b"hello"
+        );
+
+        // only the object remains
+        let mut entries = fs::read_dir(&dir).await.unwrap();
+        let mut names = vec![];
+        while let Some(entry) = entries.next_entry().await.unwrap() {
+            names.push(entry.file_name());
+        }
+        assert_eq!(names, ["objects"]);
+
+        fs::remove_dir_all(&dir).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn local_uploader_list_delete() {
+        let dir = std::env::temp_dir().join(
+            format!("gls-local-uploader-list-{}", std::process::id())
+        );
+
+        let uploader = LocalUploader {
+            uploads_directory: dir.clone(),
+            base_url: "http://localhost:3000/api/v1/uploads".into()
+        };
+
+        uploader.upload("a/b/x.txt", b"hello".as_slice()).await.unwrap();
+        uploader.upload("y.txt", b"hi".as_slice()).await.unwrap();
+
+        // files outside the objects directory, such as unfinished uploads,
+        // are not stored objects
+        fs::write(dir.join("tmp123"), b"partial").await.unwrap();
+
+        let objects = uploader.list().await.unwrap();
+        assert_eq!(
+            objects.iter()
+                .map(|o| (o.path.as_str(), o.url.as_str(), o.size))
+                .collect::<Vec<_>>(),
+            [
+                ("a/b/x.txt", "http://localhost:3000/api/v1/uploads/a/b/x.txt", 5),
+                ("y.txt", "http://localhost:3000/api/v1/uploads/y.txt", 2)
+            ]
+        );
+
+        uploader.delete("a/b/x.txt").await.unwrap();
+
+        assert_eq!(
+            uploader.list()
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|o| o.path)
+                .collect::<Vec<_>>(),
+            ["y.txt"]
+        );
+
+        fs::remove_dir_all(&dir).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn local_uploader_list_none() {
+        let uploader = LocalUploader {
+            uploads_directory: std::env::temp_dir().join(
+                format!("gls-local-uploader-none-{}", std::process::id())
+            ),
+            base_url: "http://localhost:3000/api/v1/uploads".into()
+        };
+
+        assert_eq!(uploader.list().await.unwrap(), []);
+    }
+
+    #[tokio::test]
+    async fn bucket_uploader_upload() {
+        let (s3, endpoint) = FakeS3::start().await;
+        let uploader = FakeS3::uploader(&endpoint, "dir");
+
+        assert_eq!(
+            uploader.upload("test.txt", b"hello".as_slice()).await.unwrap(),
+            format!("{BASE_URL}/dir/test.txt")
+        );
+
+        assert_eq!(s3.keys(), ["dir/test.txt"]);
+    }
+
+    #[tokio::test]
+    async fn bucket_uploader_upload_with_filename() {
+        let (s3, endpoint) = FakeS3::start().await;
+        let uploader = FakeS3::uploader(&endpoint, "dir");
+
+        assert_eq!(
+            uploader.upload_with_filename(
+                "a/b/abcdef",
+                b"hello".as_slice(),
+                "text/plain",
+                "tést.txt"
+            ).await.unwrap(),
+            format!("{BASE_URL}/dir/a/b/abcdef")
+        );
+
+        assert_eq!(s3.keys(), ["dir/a/b/abcdef"]);
+        assert_eq!(
+            s3.content_disposition("dir/a/b/abcdef").unwrap(),
+            "attachment; filename=\"t_st.txt\"; filename*=UTF-8''t%C3%A9st.txt"
+        );
+    }
+
+    #[test]
+    fn content_disposition_ascii() {
+        assert_eq!(
+            content_disposition("a_module 1.0.vmod"),
+            "attachment; filename=\"a_module 1.0.vmod\"; filename*=UTF-8''a_module%201.0.vmod"
+        );
+    }
+
+    #[tokio::test]
+    async fn bucket_uploader_list() {
+        let (s3, endpoint) = FakeS3::start().await;
+        let uploader = FakeS3::uploader(&endpoint, "dir");
+
+        let t = "2024-02-03T04:05:06.789Z".parse::<DateTime<Utc>>().unwrap();
+        s3.insert("dir/a.txt", 5, t);
+        s3.insert("dir/b/c.png", 7, t);
+        s3.insert("other/d.txt", 9, t);
+
+        assert_eq!(
+            uploader.list().await.unwrap(),
+            [
+                StoredObject {
+                    path: "dir/a.txt".into(),
+                    url: format!("{BASE_URL}/dir/a.txt"),
+                    size: 5,
+                    last_modified: t
+                },
+                StoredObject {
+                    path: "dir/b/c.png".into(),
+                    url: format!("{BASE_URL}/dir/b/c.png"),
+                    size: 7,
+                    last_modified: t
+                }
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn bucket_uploader_delete() {
+        let (s3, endpoint) = FakeS3::start().await;
+        let uploader = FakeS3::uploader(&endpoint, "dir");
+
+        s3.insert("dir/a.txt", 5, Utc::now());
+        s3.insert("dir/b.txt", 5, Utc::now());
+
+        uploader.delete("dir/a.txt").await.unwrap();
+        assert_eq!(s3.keys(), ["dir/b.txt"]);
+    }
+
     #[track_caller]
     fn assert_invalid_filename(filename: &str) {
         assert_eq!(safe_filename(filename).unwrap_err(), InvalidFilename);